@@ -0,0 +1,64 @@
+/*!
+  Number of heap allocations, and of allocated bytes, for parsing, and writing, profiles, as
+  counted by a global allocator, with `from_buffer_in` reusing the buffers of a `ParseScratch`; run
+  with `cargo bench --bench allocations`.
+*/
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+use cmx::profile::{borrowed::ProfileRef, ParseScratch, Profile};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Relaxed);
+        BYTES.fetch_add(layout.size(), Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+const PROFILES: [(&str, &[u8]); 2] = [
+    ("sRGB", include_bytes!("../examples/test_profiles/sRGB.icc")),
+    ("Generic CMYK", include_bytes!("../examples/test_profiles/Generic CMYK Profile.icc")),
+];
+
+// Allocations, and allocated bytes, of a single run of a function.
+fn count<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    let (allocations, bytes) = (ALLOCATIONS.load(Relaxed), BYTES.load(Relaxed));
+    let result = f();
+    let counts = (ALLOCATIONS.load(Relaxed) - allocations, BYTES.load(Relaxed) - bytes);
+    drop(result);
+    counts
+}
+
+fn main() {
+    println!("{:<14} {:<15} {:>12} {:>12}", "profile", "operation", "allocations", "bytes");
+    for (name, icc) in PROFILES {
+        let profile = Profile::from_buffer(icc).unwrap();
+        // the scratch buffers, after parsing, and recycling, a first profile
+        let mut scratch = ParseScratch::new();
+        let first = Profile::from_buffer_in(&mut scratch, icc).unwrap();
+        scratch.recycle(first);
+        let rows = [
+            ("from_buffer", count(|| Profile::from_buffer(icc).unwrap())),
+            ("from_buffer_in", count(|| Profile::from_buffer_in(&mut scratch, icc).unwrap())),
+            ("borrowed", count(|| ProfileRef::from_buffer(icc).unwrap())),
+            ("to_buffer", count(|| profile.to_buffer().unwrap())),
+        ];
+        for (operation, (allocations, bytes)) in rows {
+            println!("{:<14} {:<15} {:>12} {:>12}", name, operation, allocations, bytes);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+/*!
+  Benchmarks of parsing, writing, and creating profiles, and of color transforms, run with
+  `cargo bench`; compare runs with `cargo bench -- --save-baseline main` and `--baseline main`.
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use cmx::profile::{borrowed::ProfileRef, standards, Profile, RenderingIntent};
+use cmx::transform::Transform;
+
+const PROFILES: [(&str, &[u8]); 2] = [
+    ("sRGB", include_bytes!("../examples/test_profiles/sRGB.icc")),
+    ("Generic CMYK", include_bytes!("../examples/test_profiles/Generic CMYK Profile.icc")),
+];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for (name, icc) in PROFILES {
+        group.throughput(Throughput::Bytes(icc.len() as u64));
+        group.bench_with_input(BenchmarkId::new("from_buffer", name), icc, |b, icc| b.iter(|| Profile::from_buffer(black_box(icc)).unwrap()));
+        group.bench_with_input(BenchmarkId::new("borrowed", name), icc, |b, icc| b.iter(|| ProfileRef::from_buffer(black_box(icc)).unwrap()));
+    }
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    for (name, icc) in PROFILES {
+        let profile = Profile::from_buffer(icc).unwrap();
+        group.throughput(Throughput::Bytes(icc.len() as u64));
+        group.bench_function(name, |b| b.iter(|| black_box(&profile).to_buffer().unwrap()));
+    }
+    group.finish();
+}
+
+fn build(c: &mut Criterion) {
+    c.bench_function("build/srgb", |b| b.iter(standards::srgb));
+    c.bench_function("build/display_p3", |b| b.iter(standards::display_p3));
+}
+
+fn transform(c: &mut Criterion) {
+    let transform = Transform::new(&standards::srgb(), &standards::display_p3(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    let pixels: Vec<[f64; 3]> = (0..4096).map(|i| [(i % 16) as f64 / 15.0, (i / 16 % 16) as f64 / 15.0, (i / 256) as f64 / 15.0]).collect();
+    let mut group = c.benchmark_group("transform");
+    group.throughput(Throughput::Elements(pixels.len() as u64));
+    group.bench_function("srgb_to_display_p3", |b| b.iter(|| {
+        pixels.iter().for_each(|p| {
+            black_box(transform.apply(black_box(p)));
+        })
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, parse, write, build, transform);
+criterion_main!(benches);
@@ -0,0 +1,94 @@
+/*
+  C interface of the cmx library, for parsing ICC profiles; generated by cbindgen from src/ffi.rs.
+  Signatures are returned as big-endian four character codes, e.g. 0x6D6E7472 for 'mntr', and zero
+  for absent values, and invalid handles, or indices.
+*/
+
+#ifndef CMX_H
+#define CMX_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque profile handle.
+typedef struct CmxProfile CmxProfile;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parses a profile from a buffer of `len` bytes; returns null if the profile could not be parsed.
+//
+// # Safety
+// `data` must point to `len` readable bytes.
+CmxProfile *cmx_profile_from_bytes(const uint8_t *data, size_t len);
+
+// Releases a profile handle; null handles are ignored.
+//
+// # Safety
+// `profile` must be null, or a handle returned by [cmx_profile_from_bytes], not released before.
+void cmx_profile_free(CmxProfile *profile);
+
+// Profile version, encoded as in the profile header, e.g. `0x04300000` for version 4.3.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_version(const CmxProfile *profile);
+
+// Profile class signature, e.g. `'mntr'` for display profiles.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_class(const CmxProfile *profile);
+
+// Data color space signature, e.g. `'RGB '`.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_color_space(const CmxProfile *profile);
+
+// Profile connection space signature, `'XYZ '` or `'Lab '`.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_pcs(const CmxProfile *profile);
+
+// Rendering intent from the header, from 0 (perceptual) to 3 (absolute colorimetric).
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_rendering_intent(const CmxProfile *profile);
+
+// Number of tags in the profile.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+size_t cmx_profile_tag_count(const CmxProfile *profile);
+
+// Signature of the tag at `index`, in the order of the tag table.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_tag_signature(const CmxProfile *profile, size_t index);
+
+// Type signature of the tag at `index`, e.g. `'curv'`.
+//
+// # Safety
+// `profile` must be null, or a valid handle.
+uint32_t cmx_profile_tag_type(const CmxProfile *profile, size_t index);
+
+// Encoded data of the tag at `index`, starting with its type signature, with its length written
+// to `len`; returns null, and a length of zero, if the tag is absent, or could not be encoded.
+//
+// # Safety
+// `profile` must be null, or a valid handle, and `len` null, or a valid pointer.
+const uint8_t *cmx_profile_tag_data(const CmxProfile *profile, size_t index, size_t *len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CMX_H */
@@ -0,0 +1,137 @@
+/*!
+  Chromatic adaptation, of XYZ tristimulus values from one white point to another, using a von Kries
+  type transform: the values are scaled in a cone response like space, by the ratios of the
+  responses to the destination and source white points.
+
+  The ICC recommends the Bradford transform, which is used by default; CAT02 and CAT16 are the
+  transforms of the CIECAM02 and CAM16 color appearance models.
+
+  ```
+  use cmx::adaptation::{matrix, Method};
+  use cmx::illuminants::{D65_XYZ, PCS_ILLUMINANT};
+  let chad = matrix(Method::Bradford, D65_XYZ, PCS_ILLUMINANT);
+  let white = cmx::adaptation::mul_vec(chad, D65_XYZ);
+  assert!((white[2] - PCS_ILLUMINANT[2]).abs() < 1E-9);
+  ```
+*/
+
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::{TagData, XYZ};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Method {
+    #[default]
+    Bradford,
+    Cat02,
+    Cat16,
+    /// Hunt-Pointer-Estevez cone fundamentals.
+    VonKries,
+    /// Scaling of the XYZ values.
+    XyzScaling,
+}
+
+impl Method {
+    /// Matrix transforming XYZ values to the cone response like space of the method.
+    pub fn cone_response(&self) -> [[f64; 3]; 3] {
+        match self {
+            Method::Bradford => [[0.8951, 0.2664, -0.1614], [-0.7502, 1.7135, 0.0367], [0.0389, -0.0685, 1.0296]],
+            Method::Cat02 => [[0.7328, 0.4296, -0.1624], [-0.7036, 1.6975, 0.0061], [0.0030, 0.0136, 0.9834]],
+            Method::Cat16 => [
+                [0.401288, 0.650173, -0.051461],
+                [-0.250268, 1.204414, 0.045854],
+                [-0.002079, 0.048952, 0.953127],
+            ],
+            Method::VonKries => [[0.40024, 0.70760, -0.08081], [-0.22630, 1.16532, 0.04570], [0.0, 0.0, 0.91822]],
+            Method::XyzScaling => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        }
+    }
+}
+
+/// Chromatic adaptation matrix, from a source to a destination white point, given as XYZ values.
+pub fn matrix(method: Method, src: [f64; 3], dst: [f64; 3]) -> [[f64; 3]; 3] {
+    let m = method.cone_response();
+    let s = mul_vec(m, src);
+    let d = mul_vec(m, dst);
+    let scale = [[d[0] / s[0], 0.0, 0.0], [0.0, d[1] / s[1], 0.0], [0.0, 0.0, d[2] / s[2]]];
+    mul(inverse(m), mul(scale, m))
+}
+
+/// Product of two 3x3 matrices.
+pub fn mul(a: [[f64; 3]; 3], b: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    m
+}
+
+/// Product of a 3x3 matrix and a vector.
+pub fn mul_vec(a: [[f64; 3]; 3], v: [f64; 3]) -> [f64; 3] {
+    a.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+/// Inverse of a 3x3 matrix.
+pub fn inverse(m: [[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    [
+        [
+            (m[1][1] * m[2][2] - m[1][2] * m[2][1]) / det,
+            (m[0][2] * m[2][1] - m[0][1] * m[2][2]) / det,
+            (m[0][1] * m[1][2] - m[0][2] * m[1][1]) / det,
+        ],
+        [
+            (m[1][2] * m[2][0] - m[1][0] * m[2][2]) / det,
+            (m[0][0] * m[2][2] - m[0][2] * m[2][0]) / det,
+            (m[0][2] * m[1][0] - m[0][0] * m[1][2]) / det,
+        ],
+        [
+            (m[1][0] * m[2][1] - m[1][1] * m[2][0]) / det,
+            (m[0][1] * m[2][0] - m[0][0] * m[2][1]) / det,
+            (m[0][0] * m[1][1] - m[0][1] * m[1][0]) / det,
+        ],
+    ]
+}
+
+impl Profile {
+    /// Adapts the colorant tags, holding XYZ values for the source white point, to the destination
+    /// white point, typically the PCS illuminant, using the Bradford transform; the adaptation matrix
+    /// is stored in the 'chad' tag, and the media white point set to the destination white point.
+    pub fn with_chad_for_whitepoint(mut self, src: [f64; 3], dst: [f64; 3]) -> Self {
+        let chad = matrix(Method::Bradford, src, dst);
+        for signature in [TagSignature::RedMatrixColumnTag, TagSignature::GreenMatrixColumnTag, TagSignature::BlueMatrixColumnTag] {
+            if let Some(TagData::XYZ(XYZ(v))) = self.tag_data(&signature) {
+                let adapted = v.iter().map(|xyz| mul_vec(chad, *xyz)).collect();
+                self = self.with_tag(signature, TagData::XYZ(XYZ(adapted)));
+            }
+        }
+        self.with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![dst])))
+            .with_tag(TagSignature::ChromaticAdaptationTag, TagData::s15_fixed16_array(&chad))
+    }
+}
+
+#[test]
+fn test_adaptation() {
+    use crate::illuminants::{A_XYZ, D65_XYZ, PCS_ILLUMINANT};
+    for method in [Method::Bradford, Method::Cat02, Method::Cat16, Method::VonKries, Method::XyzScaling] {
+        let m = matrix(method, A_XYZ, D65_XYZ);
+        let white = mul_vec(m, A_XYZ);
+        assert!(white.iter().zip(D65_XYZ).all(|(a, b)| (a - b).abs() < 1E-12), "{method:?}");
+        let back = mul(matrix(method, D65_XYZ, A_XYZ), m);
+        assert!((0..3).all(|i| (0..3).all(|j| (back[i][j] - if i == j { 1.0 } else { 0.0 }).abs() < 1E-12)));
+    }
+
+    let srgb = crate::profile::standards::srgb();
+    let column = |p: &Profile, sig: TagSignature| match p.tag_data(&sig).unwrap() {
+        TagData::XYZ(XYZ(v)) => v[0],
+        _ => unreachable!(),
+    };
+    let adapted = crate::profile::standards::srgb().with_chad_for_whitepoint(PCS_ILLUMINANT, D65_XYZ);
+    let red = mul_vec(matrix(Method::Bradford, PCS_ILLUMINANT, D65_XYZ), column(&srgb, TagSignature::RedMatrixColumnTag));
+    assert_eq!(column(&adapted, TagSignature::RedMatrixColumnTag), red);
+    assert_eq!(column(&adapted, TagSignature::MediaWhitePointTag), D65_XYZ);
+}
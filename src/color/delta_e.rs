@@ -0,0 +1,100 @@
+/*!
+  Color differences of CIELAB values: ΔE*ab (CIE76), ΔE*94 (CIE94), CIEDE2000, and CMC l:c.
+
+  The CIE94, and CMC, differences are not symmetric: the first argument is the reference, or standard,
+  color, and the second the sample.
+*/
+
+/// CIE76 color difference: the Euclidean distance of the CIELAB values.
+pub fn cie76(lab1: [f64; 3], lab2: [f64; 3]) -> f64 {
+    (0..3).map(|i| (lab1[i] - lab2[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+/// CIE94 color difference, with the weighting factors for graphic arts.
+pub fn cie94(reference: [f64; 3], sample: [f64; 3]) -> f64 {
+    let (dl, dc, dh) = differences(reference, sample);
+    let c1 = reference[1].hypot(reference[2]);
+    let (sc, sh) = (1.0 + 0.045 * c1, 1.0 + 0.015 * c1);
+    (dl.powi(2) + (dc / sc).powi(2) + (dh / sh).powi(2)).sqrt()
+}
+
+/// CMC l:c color difference, with a lightness weight `l`, and a chroma weight `c`; usually 2:1 for
+/// acceptability, and 1:1 for perceptibility.
+pub fn cmc(reference: [f64; 3], sample: [f64; 3], l: f64, c: f64) -> f64 {
+    let (dl, dc, dh) = differences(reference, sample);
+    let [l1, a1, b1] = reference;
+    let c1 = a1.hypot(b1);
+    let h1 = b1.atan2(a1).to_degrees().rem_euclid(360.0);
+    let sl = if l1 < 16.0 { 0.511 } else { 0.040975 * l1 / (1.0 + 0.01765 * l1) };
+    let sc = 0.0638 * c1 / (1.0 + 0.0131 * c1) + 0.638;
+    let f = (c1.powi(4) / (c1.powi(4) + 1900.0)).sqrt();
+    let t = if (164.0..=345.0).contains(&h1) {
+        0.56 + (0.2 * (h1 + 168.0).to_radians().cos()).abs()
+    } else {
+        0.36 + (0.4 * (h1 + 35.0).to_radians().cos()).abs()
+    };
+    let sh = sc * (f * t + 1.0 - f);
+    ((dl / (l * sl)).powi(2) + (dc / (c * sc)).powi(2) + (dh / sh).powi(2)).sqrt()
+}
+
+/// CIEDE2000 color difference.
+pub fn ciede2000(lab1: [f64; 3], lab2: [f64; 3]) -> f64 {
+    let [l1, a1, b1] = lab1;
+    let [l2, a2, b2] = lab2;
+    let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+    let g = 0.5 * (1.0 - (c_mean.powi(7) / (c_mean.powi(7) + 25.0f64.powi(7))).sqrt());
+    let (a1, a2) = (a1 * (1.0 + g), a2 * (1.0 + g));
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let hue = |b: f64, a: f64| if a == 0.0 && b == 0.0 { 0.0 } else { b.atan2(a).to_degrees().rem_euclid(360.0) };
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+    let dh = match h2 - h1 {
+        _ if c1 * c2 == 0.0 => 0.0,
+        d if d > 180.0 => d - 360.0,
+        d if d < -180.0 => d + 360.0,
+        d => d,
+    };
+    let (dl, dc) = (l2 - l1, c2 - c1);
+    let dh = 2.0 * (c1 * c2).sqrt() * (dh / 2.0).to_radians().sin();
+    let l_mean = (l1 + l2) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = match (h1 + h2, (h1 - h2).abs()) {
+        _ if c1 * c2 == 0.0 => h1 + h2,
+        (s, d) if d <= 180.0 => s / 2.0,
+        (s, _) if s < 360.0 => (s + 360.0) / 2.0,
+        (s, _) => (s - 360.0) / 2.0,
+    };
+    let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos() + 0.24 * (2.0 * h_mean).to_radians().cos()
+        + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+    let sl = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let sc = 1.0 + 0.045 * c_mean;
+    let sh = 1.0 + 0.015 * c_mean * t;
+    let rt = -2.0 * (c_mean.powi(7) / (c_mean.powi(7) + 25.0f64.powi(7))).sqrt()
+        * (60.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp()).to_radians().sin();
+    ((dl / sl).powi(2) + (dc / sc).powi(2) + (dh / sh).powi(2) + rt * (dc / sc) * (dh / sh)).sqrt()
+}
+
+// Lightness, chroma, and hue differences.
+fn differences(lab1: [f64; 3], lab2: [f64; 3]) -> (f64, f64, f64) {
+    let dc = lab1[1].hypot(lab1[2]) - lab2[1].hypot(lab2[2]);
+    let dh2 = (lab1[1] - lab2[1]).powi(2) + (lab1[2] - lab2[2]).powi(2) - dc.powi(2);
+    (lab1[0] - lab2[0], dc, dh2.max(0.0).sqrt())
+}
+
+#[test]
+fn test_delta_e() {
+    // test data from Sharma, Wu, and Dalal, The CIEDE2000 color-difference formula, 2005
+    assert!((ciede2000([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485]) - 2.0425).abs() < 1E-4);
+    assert!((ciede2000([50.0, 2.5, 0.0], [73.0, 25.0, -18.0]) - 27.1492).abs() < 1E-4);
+    assert!((ciede2000([2.0776, 0.0795, -1.1350], [0.9033, -0.0636, -0.5514]) - 0.9082).abs() < 1E-4);
+
+    assert_eq!(cie76([50.0, 0.0, 0.0], [53.0, 4.0, 0.0]), 5.0);
+    // lightness differences only
+    assert!((cie94([50.0, 20.0, 10.0], [52.0, 20.0, 10.0]) - 2.0).abs() < 1E-12);
+    let sl = 0.040975 * 50.0 / (1.0 + 0.01765 * 50.0);
+    assert!((cmc([50.0, 20.0, 10.0], [52.0, 20.0, 10.0], 2.0, 1.0) - 1.0 / sl).abs() < 1E-12);
+    // chroma weighted less than lightness, and not symmetric
+    let (a, b) = ([50.0, 40.0, 10.0], [50.0, 45.0, 10.0]);
+    assert!(cie94(a, b) < cie76(a, b) && cie94(a, b) != cie94(b, a));
+    assert!(cmc(a, b, 2.0, 1.0) < cie76(a, b));
+}
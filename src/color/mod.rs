@@ -0,0 +1,6 @@
+/*!
+  Color science calculations on colorimetric values, independent of profiles.
+*/
+
+pub mod delta_e;
+pub mod pcs;
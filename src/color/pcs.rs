@@ -0,0 +1,117 @@
+/*!
+  Integer encodings of profile connection space values, as used in lookup tables, and named colors.
+
+  CIELAB values have three encodings:
+  - 8-bit: L* from 0 to 100 maps to 0 to 255, and a* and b* from -128 to 127 map to 0 to 255;
+  - 16-bit, version 4: L* from 0 to 100 maps to 0 to 65535, and a* and b* from -128 to 127 map to
+    0 to 65535;
+  - 16-bit, legacy: L* from 0 to 100 maps to 0 to 65280 (0xFF00), and a* and b* from -128 to 127
+    map to 0 to 65280, with the values up to 65535 just above the range. This is the encoding of
+    version 2 profiles, and of 'mft2' (lut16Type) tags in all versions.
+
+  CIEXYZ values are encoded as u1Fixed15Number values, from 0 to 1 + 32767/32768.
+
+  Encoding clips values to the encoding range, and rounds them to the nearest integer.
+*/
+
+/// Largest CIEXYZ value of the 16-bit encoding.
+pub const XYZ_ENCODING_MAX: f64 = 1.0 + 32767.0 / 32768.0;
+
+fn quantize(v: f64, max: f64) -> f64 {
+    v.clamp(0.0, max).round()
+}
+
+pub fn lab_to_u8([l, a, b]: [f64; 3]) -> [u8; 3] {
+    [l * 255.0 / 100.0, a + 128.0, b + 128.0].map(|v| quantize(v, 255.0) as u8)
+}
+
+pub fn u8_to_lab([l, a, b]: [u8; 3]) -> [f64; 3] {
+    [l as f64 * 100.0 / 255.0, a as f64 - 128.0, b as f64 - 128.0]
+}
+
+/// Version 4 16-bit encoding.
+pub fn lab_to_u16(lab: [f64; 3]) -> [u16; 3] {
+    lab_to_normalized(lab).map(|v| quantize(v * 65535.0, 65535.0) as u16)
+}
+
+pub fn u16_to_lab(lab: [u16; 3]) -> [f64; 3] {
+    normalized_to_lab(lab.map(|v| v as f64 / 65535.0))
+}
+
+/// Version 4 encoding, as values from 0.0 to 1.0, which are not clipped, or quantized, as used for
+/// sampling color lookup tables.
+pub fn lab_to_normalized([l, a, b]: [f64; 3]) -> [f64; 3] {
+    [l / 100.0, (a + 128.0) / 255.0, (b + 128.0) / 255.0]
+}
+
+pub fn normalized_to_lab([l, a, b]: [f64; 3]) -> [f64; 3] {
+    [l * 100.0, a * 255.0 - 128.0, b * 255.0 - 128.0]
+}
+
+/// Legacy 16-bit encoding, of version 2 profiles, and of 'mft2' tags.
+pub fn lab_to_u16_legacy([l, a, b]: [f64; 3]) -> [u16; 3] {
+    [l * 65280.0 / 100.0, (a + 128.0) * 256.0, (b + 128.0) * 256.0].map(|v| quantize(v, 65535.0) as u16)
+}
+
+pub fn u16_legacy_to_lab([l, a, b]: [u16; 3]) -> [f64; 3] {
+    [l as f64 * 100.0 / 65280.0, a as f64 / 256.0 - 128.0, b as f64 / 256.0 - 128.0]
+}
+
+/// Legacy 16-bit encoding, as values from 0.0 to 1.0, which are not clipped, or quantized, as used
+/// for evaluating lut16Type tags.
+pub fn lab_to_normalized_legacy([l, a, b]: [f64; 3]) -> [f64; 3] {
+    [l * 65280.0 / 100.0, (a + 128.0) * 256.0, (b + 128.0) * 256.0].map(|v| v / 65535.0)
+}
+
+pub fn normalized_legacy_to_lab(v: [f64; 3]) -> [f64; 3] {
+    let [l, a, b] = v.map(|v| v * 65535.0);
+    [l * 100.0 / 65280.0, a / 256.0 - 128.0, b / 256.0 - 128.0]
+}
+
+/// Converts a legacy 16-bit CIELAB value to the version 4 encoding; values above 65280 clip.
+pub fn u16_legacy_to_v4(v: u16) -> u16 {
+    quantize(v as f64 * 65535.0 / 65280.0, 65535.0) as u16
+}
+
+/// Converts a version 4 16-bit CIELAB value to the legacy encoding.
+pub fn u16_v4_to_legacy(v: u16) -> u16 {
+    quantize(v as f64 * 65280.0 / 65535.0, 65280.0) as u16
+}
+
+/// u1Fixed15Number encoding; 1.0 encodes as 32768.
+pub fn xyz_to_u16(xyz: [f64; 3]) -> [u16; 3] {
+    xyz.map(|v| quantize(v * 32768.0, 65535.0) as u16)
+}
+
+pub fn u16_to_xyz(xyz: [u16; 3]) -> [f64; 3] {
+    xyz.map(|v| v as f64 / 32768.0)
+}
+
+/// u1Fixed15Number encoding, as values from 0.0 to 1.0, which are not clipped, or quantized.
+pub fn xyz_to_normalized(xyz: [f64; 3]) -> [f64; 3] {
+    xyz.map(|v| v * 32768.0 / 65535.0)
+}
+
+pub fn normalized_to_xyz(xyz: [f64; 3]) -> [f64; 3] {
+    xyz.map(|v| v * 65535.0 / 32768.0)
+}
+
+#[test]
+fn test_pcs_encodings() {
+    assert_eq!(lab_to_u8([100.0, -128.0, 127.0]), [255, 0, 255]);
+    assert_eq!(lab_to_u16([100.0, 0.0, 127.0]), [65535, 32896, 65535]);
+    assert_eq!(lab_to_u16_legacy([100.0, 0.0, 127.0]), [0xFF00, 0x8000, 0xFF00]);
+    assert_eq!(lab_to_u16_legacy([120.0, -200.0, 127.9]), [0xFFFF, 0, 0xFFE6]);
+    assert_eq!(u16_legacy_to_lab([0xFF00, 0x8000, 0]), [100.0, 0.0, -128.0]);
+    assert_eq!((u16_legacy_to_v4(0xFF00), u16_legacy_to_v4(0xFFFF), u16_v4_to_legacy(65535)), (65535, 65535, 0xFF00));
+    for lab in [[0.0, -128.0, -128.0], [50.0, 12.5, -64.25], [100.0, 127.0, 127.0]] {
+        let back = u16_to_lab(lab_to_u16(lab));
+        assert!((0..3).all(|i| (back[i] - lab[i]).abs() < 0.002), "{back:?}");
+        assert_eq!(u16_legacy_to_lab(lab_to_u16_legacy(lab)), lab);
+        assert!(normalized_to_lab(lab_to_normalized(lab)).iter().zip(lab).all(|(a, b)| (a - b).abs() < 1E-12));
+        assert!(normalized_legacy_to_lab(lab_to_normalized_legacy(lab)).iter().zip(lab).all(|(a, b)| (a - b).abs() < 1E-9));
+    }
+    assert_eq!(xyz_to_u16([0.9642, 1.0, 2.5]), [31595, 32768, 65535]);
+    assert_eq!(u16_to_xyz([65535, 32768, 0]), [XYZ_ENCODING_MAX, 1.0, 0.0]);
+    assert_eq!(normalized_to_xyz([1.0, 0.0, 0.0])[0], XYZ_ENCODING_MAX);
+}
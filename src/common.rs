@@ -5,7 +5,7 @@ use chrono::{DateTime, Datelike, Timelike, Utc};
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + 'static>>;
 //pub type DError = Box<dyn std::error::Error + 'static>;
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub enum Lut {
     Bit8(Vec<u8>),
     Bit16(Vec<u16>),
@@ -20,62 +20,62 @@ pub fn zero_as_none<T: num::Num + num::Zero>(v: T) -> Option<T> {
 }
 
 pub fn read_be_f16(input: &mut &[u8]) -> Result<half::f16> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<half::f16>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<half::f16>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(half::f16::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_be_f32(input: &mut &[u8]) -> Result<f32> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<f32>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<f32>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(f32::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_be_f64(input: &mut &[u8]) -> Result<f64> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<f64>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<f64>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(f64::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_u8(input: &mut &[u8]) -> Result<u8> {
-    let (byte, rest) = input.split_at(std::mem::size_of::<u8>());
+    let (byte, rest) = input.split_at_checked(std::mem::size_of::<u8>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(byte[0])
 }
 
 pub fn read_be_u16(input: &mut &[u8]) -> Result<u16> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<u16>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<u16>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(u16::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_be_u32(input: &mut &[u8]) -> Result<u32> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<u32>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<u32>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(u32::from_be_bytes(int_bytes.try_into()?))
 }
 
 
 pub fn read_be_i32(input: &mut &[u8]) -> Result<i32> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<i32>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<i32>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(i32::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_be_u64(input: &mut &[u8]) -> Result<u64> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<u64>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<u64>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(u64::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_be_u128(input: &mut &[u8]) -> Result<u128> {
-    let (int_bytes, rest) = input.split_at(std::mem::size_of::<u128>());
+    let (int_bytes, rest) = input.split_at_checked(std::mem::size_of::<u128>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok(u128::from_be_bytes(int_bytes.try_into()?))
 }
 
 pub fn read_version(input: &mut &[u8]) -> Result<[u8;3]> {
-    let (version, rest) = input.split_at(std::mem::size_of::<[u8;4]>());
+    let (version, rest) = input.split_at_checked(std::mem::size_of::<[u8;4]>()).ok_or("unexpected end of data")?;
     *input = rest;
     Ok([version[0], version[1]>>4_u8, version[1]&0x0F_u8])
 }
@@ -91,10 +91,10 @@ pub fn read_date_time(icc_buf: &mut &[u8]) -> Result <Option<chrono::DateTime<ch
     if year == 0 && month == 0 && day == 0 {
         Ok(None)
     } else {
-        let d = chrono::NaiveDate::from_ymd(year as i32, month as u32, day as u32);
-        let t = chrono::NaiveTime::from_hms(hour as u32, minute as u32, second as u32);
+        let d = chrono::NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32).ok_or("invalid date")?;
+        let t = chrono::NaiveTime::from_hms_opt(hour as u32, minute as u32, second as u32).ok_or("invalid time")?;
         let dt = chrono::NaiveDateTime::new(d,t);
-        Ok(Some(chrono::DateTime::from_utc(dt, chrono::Utc)))
+        Ok(Some(chrono::DateTime::from_naive_utc_and_offset(dt, chrono::Utc)))
     }
 }
 
@@ -102,9 +102,9 @@ pub fn datetime_to_be_bytes(dt: Option<DateTime<Utc>>) -> [u8;12] {
     match dt {
         None => [0;12],
         Some(dt) => {
-            let year = dt.date().year() as u16;
-            let month = dt.date().month() as u16;
-            let day = dt.date().day() as u16;
+            let year = dt.date_naive().year() as u16;
+            let month = dt.date_naive().month() as u16;
+            let day = dt.date_naive().day() as u16;
             let hour = dt.time().hour() as u16;
             let minute = dt.time().minute() as u16;
             let second = dt.time().second() as u16;
@@ -122,7 +122,7 @@ pub fn datetime_to_be_bytes(dt: Option<DateTime<Utc>>) -> [u8;12] {
 
 
 pub fn read_signature(icc_buf: &mut &[u8]) -> Result<Option<String>>{
-    let (s, rest) = icc_buf.split_at(std::mem::size_of::<[u8;4]>());
+    let (s, rest) = icc_buf.split_at_checked(std::mem::size_of::<[u8;4]>()).ok_or("unexpected end of data")?;
     *icc_buf = rest;
     if s[0]!=0 && s[1]!=0 && s[2]!=0 && s[3]!=0 {
         Ok(Some(std::str::from_utf8(s)?.to_owned()))
@@ -164,10 +164,9 @@ pub fn xyz_to_be_bytes(xyz: Option<[f64;3]>) -> [u8;12] {
        None =>  [0;12],
        Some([x,y,z]) => {
         let mut v: Vec<u8> = Vec::with_capacity(12);
-        v.extend(((x * 655536.0) as i32).to_be_bytes());
-        v.extend(((y * 655536.0) as i32).to_be_bytes());
-        v.extend(((z * 655536.0) as i32).to_be_bytes());
-        v.truncate(12);
+        v.extend(s15fixed16_to_be_bytes(x));
+        v.extend(s15fixed16_to_be_bytes(y));
+        v.extend(s15fixed16_to_be_bytes(z));
         v.try_into().unwrap()
 
        }
@@ -189,7 +188,7 @@ pub fn mcs_to_be_bytes(n_mcs: Option<u16>) -> [u8;4] {
     if n == 0 {
         [0, 0, 0, 0]
     } else {
-        (0x6d63u32 << 2 | n).to_be_bytes()
+        (0x6d63u32 << 16 | n).to_be_bytes()
     }
 }
 
@@ -203,6 +202,18 @@ pub fn read_vec(input: &mut &[u8], n: usize) -> Result<Vec<u8>> {
     Ok(bytes.to_vec())
 }
 
+/// Decompresses zlib compressed data, returning an error if the decompressed data would exceed
+/// `limit` bytes, as compressed data in untrusted input can expand to any size.
+pub fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    use std::io::Read;
+    let mut v = Vec::new();
+    flate2::read::ZlibDecoder::new(data).take(limit as u64 + 1).read_to_end(&mut v)?;
+    if v.len() > limit {
+        return Err("decompressed data exceeds the maximum profile size".into());
+    }
+    Ok(v)
+}
+
 pub fn read_vec_u16(input: &mut &[u8], n: usize) -> Result<Vec<u16>> {
     if n>input.len() {
         return Err("request exceeds buffer length".into())
@@ -221,8 +232,9 @@ pub fn read_ascii_string(buf: &mut &[u8], n: usize) -> Result<String> {
     Ok(std::str::from_utf8(&v)?.trim_end_matches(char::from(0)).to_owned())
 }
 
+// Reads a string of n UTF-16 code units.
 pub fn read_unicode_string(buf: &mut &[u8], n: usize) -> Result<String> {
-    let v = read_vec_u16(buf,n/2)?;
+    let v = read_vec_u16(buf, n.checked_mul(2).ok_or("unicode string length error")?)?;
     Ok(String::from_utf16(&v)?.trim_end_matches(char::from(0)).to_owned())
 }
 
@@ -250,3 +262,46 @@ pub fn read_s15fixed16_array(buf: &mut &[u8], n: Option<usize>) -> Result<Vec<f3
     Ok(v)
 
 }
+
+pub fn s15fixed16_to_be_bytes(v: f64) -> [u8;4] {
+    ((v * 65536.0).round() as i32).to_be_bytes()
+}
+
+pub fn u16fixed16_to_be_bytes(v: f64) -> [u8;4] {
+    ((v * 65536.0).round() as u32).to_be_bytes()
+}
+
+// Fixed length, zero padded, ascii string field; longer strings are truncated, keeping at least one
+// terminating zero.
+pub fn ascii_to_be_bytes(s: &str, n: usize) -> Vec<u8> {
+    let mut v: Vec<u8> = s.bytes().take(n.saturating_sub(1)).collect();
+    v.resize(n, 0);
+    v
+}
+
+// Four character signature field, such as used for the platform, manufacturer, and creator in the profile header.
+pub fn signature_to_be_bytes(s: &Option<String>) -> [u8;4] {
+    match s {
+        Some(s) => {
+            let mut sig = [b' '; 4];
+            for (c, b) in sig.iter_mut().zip(s.bytes()) {
+                *c = b;
+            }
+            sig
+        }
+        None => [0;4],
+    }
+}
+
+// Hexadecimal representation of binary data, as used for raw tag data in profile dumps.
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(s: &str) -> Result<Vec<u8>> {
+    let s: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !s.len().is_multiple_of(2) {
+        return Err("hexadecimal data with an odd number of digits".into());
+    }
+    s.chunks(2).map(|c| Ok(u8::from_str_radix(std::str::from_utf8(c)?, 16)?)).collect()
+}
@@ -0,0 +1,462 @@
+/*!
+  ICC profiles in HEIF and AVIF images.
+
+  Both formats use the ISO Base Media File Format, and store color information of an image item in
+  'colr' item property boxes, in the 'ipco' container of the 'iprp' box, in the top level 'meta' box.
+  A 'colr' box with colour type 'prof' (or 'rICC', the restricted variant) contains a complete ICC
+  profile. The 'ipma' box associates properties with image items.
+
+  When a profile is written, all boxes are kept as they are, except the 'colr' box of the primary
+  image item, which is replaced or added, and the sizes of its parent boxes. As the 'meta' box
+  usually precedes the 'mdat' box with the image data, the file offsets in the 'iloc' box are
+  updated too.
+*/
+
+use crate::common::Result;
+#[cfg(feature = "fs")]
+use crate::profile::Profile;
+
+const META: [u8; 4] = *b"meta";
+const IPRP: [u8; 4] = *b"iprp";
+const IPCO: [u8; 4] = *b"ipco";
+const IPMA: [u8; 4] = *b"ipma";
+const ILOC: [u8; 4] = *b"iloc";
+const PITM: [u8; 4] = *b"pitm";
+const COLR: [u8; 4] = *b"colr";
+const FTYP: [u8; 4] = *b"ftyp";
+
+// A box in the source buffer: `start` is the location of its size field, `body` of its payload
+// (after the version and flags for full boxes), and `end` is the first byte after the box.
+#[derive(Debug, Clone, Copy)]
+struct BoxRange {
+    kind: [u8; 4],
+    start: usize,
+    body: usize,
+    end: usize,
+    large: bool,
+}
+
+fn be_uint(buf: &[u8], pos: usize, n: usize) -> Result<u64> {
+    if n > 8 {
+        return Err("HEIF field size exceeds 8 bytes".into());
+    }
+    let bytes = pos.checked_add(n).and_then(|end| buf.get(pos..end)).ok_or("unexpected end of HEIF data")?;
+    Ok(bytes.iter().fold(0u64, |v, &b| v << 8 | b as u64))
+}
+
+fn put_be_uint(v: u64, n: usize) -> Result<Vec<u8>> {
+    if n > 8 {
+        return Err("HEIF field size exceeds 8 bytes".into());
+    }
+    if n < 8 && v >> (8 * n) != 0 {
+        return Err("HEIF offset does not fit its field size".into());
+    }
+    Ok((0..n).rev().map(|i| (v >> (8 * i)) as u8).collect())
+}
+
+fn children(buf: &[u8], start: usize, end: usize) -> Result<Vec<BoxRange>> {
+    let mut v = Vec::new();
+    let mut pos = start;
+    while pos < end {
+        let size = be_uint(buf, pos, 4)?;
+        let kind: [u8; 4] = buf.get(pos + 4..pos + 8).ok_or("unexpected end of HEIF data")?.try_into()?;
+        let (size, header, large) = match size {
+            0 => ((end - pos) as u64, 8, false),
+            1 => (be_uint(buf, pos + 8, 8)?, 16, true),
+            n => (n, 8, false),
+        };
+        let box_end = usize::try_from(size).ok().and_then(|s| pos.checked_add(s)).ok_or("HEIF box size error")?;
+        if box_end > end || size < header as u64 {
+            return Err("HEIF box size error".into());
+        }
+        let body = if &kind == b"uuid" { pos + header + 16 } else { pos + header };
+        v.push(BoxRange { kind, start: pos, body, end: box_end, large });
+        pos = box_end;
+    }
+    Ok(v)
+}
+
+fn find(boxes: &[BoxRange], kind: [u8; 4]) -> Option<BoxRange> {
+    boxes.iter().find(|b| b.kind == kind).copied()
+}
+
+// Full boxes have a one byte version and three bytes of flags before their payload.
+fn full_box(buf: &[u8], b: BoxRange) -> Result<(u8, u32, usize)> {
+    let vf = be_uint(buf, b.body, 4)? as u32;
+    Ok(((vf >> 24) as u8, vf & 0xFFFFFF, b.body + 4))
+}
+
+#[derive(Debug)]
+struct ItemProperties {
+    version: u8,
+    flags: u32,
+    // item id, and its (essential, property index) associations, with 1-based property indices.
+    entries: Vec<(u32, Vec<(bool, u16)>)>,
+}
+
+impl ItemProperties {
+    fn parse(buf: &[u8], ipma: BoxRange) -> Result<Self> {
+        let (version, flags, mut pos) = full_box(buf, ipma)?;
+        let count = be_uint(buf, pos, 4)?;
+        pos += 4;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let id_size = if version < 1 { 2 } else { 4 };
+            let id = be_uint(buf, pos, id_size)? as u32;
+            pos += id_size;
+            let n = be_uint(buf, pos, 1)?;
+            pos += 1;
+            let mut associations = Vec::new();
+            for _ in 0..n {
+                let a = if flags & 1 == 1 {
+                    let a = be_uint(buf, pos, 2)? as u16;
+                    pos += 2;
+                    (a & 0x8000 != 0, a & 0x7FFF)
+                } else {
+                    let a = be_uint(buf, pos, 1)? as u16;
+                    pos += 1;
+                    (a & 0x80 != 0, a & 0x7F)
+                };
+                associations.push(a);
+            }
+            entries.push((id, associations));
+        }
+        if pos > ipma.end {
+            return Err("HEIF ipma box size error".into());
+        }
+        Ok(Self { version, flags, entries })
+    }
+
+    fn properties(&self, item: u32) -> Vec<u16> {
+        self.entries.iter()
+            .filter(|(id, _)| *id == item)
+            .flat_map(|(_, a)| a.iter().map(|(_, i)| *i))
+            .collect()
+    }
+
+    fn to_box(&self) -> Result<Vec<u8>> {
+        let mut body = Vec::new();
+        body.push(self.version);
+        body.extend(&self.flags.to_be_bytes()[1..]);
+        body.extend((self.entries.len() as u32).to_be_bytes());
+        for (id, associations) in &self.entries {
+            if self.version < 1 {
+                body.extend(u16::try_from(*id)?.to_be_bytes());
+            } else {
+                body.extend(id.to_be_bytes());
+            }
+            body.push(u8::try_from(associations.len())?);
+            for &(essential, index) in associations {
+                if self.flags & 1 == 1 {
+                    body.extend((index | (essential as u16) << 15).to_be_bytes());
+                } else {
+                    body.push(index as u8 | (essential as u8) << 7);
+                }
+            }
+        }
+        Ok(new_box(IPMA, &body))
+    }
+}
+
+fn new_box(kind: [u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(body.len() + 8);
+    v.extend((body.len() as u32 + 8).to_be_bytes());
+    v.extend(kind);
+    v.extend(body);
+    v
+}
+
+fn colr_box(icc: &[u8]) -> Vec<u8> {
+    let mut body = b"prof".to_vec();
+    body.extend(icc);
+    new_box(COLR, &body)
+}
+
+// The 'colr' box holding an ICC profile, if any, and its property index.
+fn icc_property(buf: &[u8], properties: &[BoxRange], indices: &[u16]) -> Option<(u16, BoxRange)> {
+    indices.iter()
+        .filter_map(|&i| properties.get((i as usize).checked_sub(1)?).map(|b| (i, *b)))
+        .find(|(_, b)| {
+            b.kind == COLR && b.body + 4 <= b.end && matches!(buf.get(b.body..b.body + 4), Some(b"prof") | Some(b"rICC"))
+        })
+}
+
+struct Layout {
+    meta: BoxRange,
+    iprp: BoxRange,
+    ipco: BoxRange,
+    ipma: BoxRange,
+    iloc: Option<BoxRange>,
+    properties: Vec<BoxRange>,
+    associations: ItemProperties,
+    primary: Option<u32>,
+}
+
+impl Layout {
+    fn parse(buf: &[u8]) -> Result<Self> {
+        let top = children(buf, 0, buf.len())?;
+        if top.first().map(|b| b.kind) != Some(FTYP) {
+            return Err("not a HEIF/AVIF file: no ftyp box found".into());
+        }
+        let meta = find(&top, META).ok_or("no meta box found in HEIF file")?;
+        let (_, _, meta_body) = full_box(buf, meta)?;
+        let in_meta = children(buf, meta_body, meta.end)?;
+        let iprp = find(&in_meta, IPRP).ok_or("no iprp box found in HEIF file")?;
+        let in_iprp = children(buf, iprp.body, iprp.end)?;
+        let ipco = find(&in_iprp, IPCO).ok_or("no ipco box found in HEIF file")?;
+        let ipma = find(&in_iprp, IPMA).ok_or("no ipma box found in HEIF file")?;
+        let properties = children(buf, ipco.body, ipco.end)?;
+        let associations = ItemProperties::parse(buf, ipma)?;
+        let primary = match find(&in_meta, PITM) {
+            Some(pitm) => {
+                let (version, _, pos) = full_box(buf, pitm)?;
+                Some(be_uint(buf, pos, if version == 0 { 2 } else { 4 })? as u32)
+            }
+            None => None,
+        };
+        Ok(Self { meta, iprp, ipco, ipma, iloc: find(&in_meta, ILOC), properties, associations, primary })
+    }
+
+    // Property indices of the primary image item, or of all items if there is no primary item.
+    fn primary_properties(&self) -> Vec<u16> {
+        match self.primary {
+            Some(id) => self.associations.properties(id),
+            None => (1..=self.properties.len() as u16).collect(),
+        }
+    }
+}
+
+/// Returns the ICC profile data of the primary image in a HEIF or AVIF file, if present.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    let layout = Layout::parse(buf)?;
+    Ok(icc_property(buf, &layout.properties, &layout.primary_properties())
+        .map(|(_, b)| buf[b.body + 4..b.end].to_vec()))
+}
+
+// A replacement of `remove` bytes at position `pos` in the source buffer.
+struct Edit {
+    pos: usize,
+    remove: usize,
+    insert: Vec<u8>,
+}
+
+impl Edit {
+    fn delta(&self) -> i64 {
+        self.insert.len() as i64 - self.remove as i64
+    }
+}
+
+fn size_edit(b: BoxRange, delta: i64) -> Result<Edit> {
+    let size = (b.end - b.start) as i64 + delta;
+    Ok(if b.large {
+        Edit { pos: b.start + 8, remove: 8, insert: put_be_uint(size as u64, 8)? }
+    } else {
+        Edit { pos: b.start, remove: 4, insert: put_be_uint(size as u64, 4)? }
+    })
+}
+
+// Updates the file offsets of items stored with construction method 0 (file offset), which refer to data
+// located after the edits.
+fn iloc_edits(buf: &[u8], iloc: BoxRange, shift: impl Fn(u64) -> i64) -> Result<Vec<Edit>> {
+    let (version, _, mut pos) = full_box(buf, iloc)?;
+    let sizes = be_uint(buf, pos, 2)? as usize;
+    pos += 2;
+    let (offset_size, length_size, base_offset_size) = (sizes >> 12, sizes >> 8 & 0xF, sizes >> 4 & 0xF);
+    let index_size = if version == 1 || version == 2 { sizes & 0xF } else { 0 };
+    if [offset_size, length_size, base_offset_size, index_size].iter().any(|n| ![0, 4, 8].contains(n)) {
+        return Err("HEIF iloc field sizes other than 0, 4, or 8 bytes".into());
+    }
+    let count_size = if version < 2 { 2 } else { 4 };
+    let count = be_uint(buf, pos, count_size)?;
+    pos += count_size;
+    let mut edits = Vec::new();
+    for _ in 0..count {
+        pos += if version < 2 { 2 } else { 4 }; // item id
+        let method = if version == 1 || version == 2 {
+            pos += 2;
+            be_uint(buf, pos - 2, 2)? & 0xF
+        } else {
+            0
+        };
+        pos += 2; // data reference index
+        let base_pos = pos;
+        let base = be_uint(buf, pos, base_offset_size)?;
+        pos += base_offset_size;
+        let extents = be_uint(buf, pos, 2)?;
+        pos += 2;
+        let mut shift_base = false;
+        for _ in 0..extents {
+            pos += index_size;
+            let offset = be_uint(buf, pos, offset_size)?;
+            let delta = shift(base.checked_add(offset).ok_or("HEIF iloc offset overflow")?);
+            if method == 0 && delta != 0 {
+                if base_offset_size > 0 && base > 0 {
+                    shift_base = true;
+                } else {
+                    let shifted = i64::try_from(offset)?.checked_add(delta).ok_or("HEIF iloc offset overflow")?;
+                    let shifted = u64::try_from(shifted)?;
+                    edits.push(Edit { pos, remove: offset_size, insert: put_be_uint(shifted, offset_size)? });
+                }
+            }
+            pos += offset_size + length_size;
+        }
+        if shift_base {
+            let shifted = i64::try_from(base)?.checked_add(shift(base)).ok_or("HEIF iloc offset overflow")?;
+            let shifted = u64::try_from(shifted)?;
+            edits.push(Edit { pos: base_pos, remove: base_offset_size, insert: put_be_uint(shifted, base_offset_size)? });
+        }
+    }
+    Ok(edits)
+}
+
+/// Stores an ICC profile in a HEIF or AVIF file, for its primary image.
+///
+/// An existing ICC 'colr' property of the primary image is replaced, else a new property is added.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    let layout = Layout::parse(buf)?;
+    let mut edits = Vec::new();
+    match icc_property(buf, &layout.properties, &layout.primary_properties()) {
+        Some((_, colr)) => {
+            edits.push(Edit { pos: colr.start, remove: colr.end - colr.start, insert: colr_box(icc) });
+        }
+        None => {
+            let index = u16::try_from(layout.properties.len() + 1)?;
+            if index > 0x7FFF {
+                return Err("too many HEIF item properties".into());
+            }
+            let mut associations = layout.associations;
+            if index > 0x7F {
+                associations.flags |= 1;
+            }
+            let item = layout.primary.ok_or("HEIF file without a primary item")?;
+            match associations.entries.iter_mut().find(|(id, _)| *id == item) {
+                Some((_, a)) => a.push((false, index)),
+                None => associations.entries.push((item, vec![(false, index)])),
+            }
+            edits.push(Edit { pos: layout.ipco.end, remove: 0, insert: colr_box(icc) });
+            edits.push(Edit {
+                pos: layout.ipma.start,
+                remove: layout.ipma.end - layout.ipma.start,
+                insert: associations.to_box()?,
+            });
+        }
+    }
+
+    // Sizes of the parent boxes, each box only contains the edits within its range
+    let within = |b: BoxRange, edits: &[Edit]| -> i64 {
+        edits.iter().filter(|e| e.pos >= b.body && e.pos + e.remove <= b.end).map(Edit::delta).sum()
+    };
+    let mut sizes = vec![
+        size_edit(layout.ipco, within(layout.ipco, &edits))?,
+        size_edit(layout.iprp, within(layout.iprp, &edits))?,
+    ];
+    if layout.meta.end > layout.meta.start + 8 || layout.meta.large {
+        sizes.push(size_edit(layout.meta, within(layout.meta, &edits))?);
+    }
+    if let Some(iloc) = layout.iloc {
+        let shift = |offset: u64| -> i64 {
+            edits.iter().filter(|e| ((e.pos + e.remove) as u64) <= offset).map(Edit::delta).sum()
+        };
+        sizes.extend(iloc_edits(buf, iloc, shift)?);
+    }
+    edits.extend(sizes);
+    edits.sort_by_key(|e| e.pos);
+
+    let mut out = Vec::with_capacity(buf.len() + icc.len() + 16);
+    let mut pos = 0;
+    for e in edits {
+        if e.pos < pos {
+            return Err("overlapping HEIF edits".into());
+        }
+        out.extend(&buf[pos..e.pos]);
+        out.extend(e.insert);
+        pos = e.pos + e.remove;
+    }
+    out.extend(&buf[pos..]);
+    Ok(out)
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a HEIF (.heic, .heif) or AVIF (.avif) file.
+    pub fn from_heif(path: &str) -> Result<Profile> {
+        let buf = std::fs::read(path)?;
+        let icc = read_icc(&buf)?.ok_or("no ICC profile found in HEIF file")?;
+        Ok(Profile::from_buffer(&icc)?)
+    }
+}
+
+#[test]
+fn test_heif_icc_roundtrip() {
+    fn full(kind: &[u8; 4], version: u8, body: &[u8]) -> Vec<u8> {
+        let mut v = vec![version, 0, 0, 0];
+        v.extend(body);
+        new_box(*kind, &v)
+    }
+    let ftyp = new_box(*b"ftyp", b"avifmif1");
+    let pitm = full(b"pitm", 0, &[0, 1]);
+    // one item, 4 byte offsets and lengths, no base offset, one extent
+    let iloc_len = 12 + 2 + 2 + 2 + 2 + 2 + 4 + 4;
+    let ispe = full(b"ispe", 0, &[0, 0, 0, 1, 0, 0, 0, 1]);
+    let ipco = new_box(IPCO, &ispe);
+    let ipma = full(b"ipma", 0, &[0, 0, 0, 1, 0, 1, 1, 0x81]);
+    let iprp = new_box(IPRP, &[ipco, ipma].concat());
+    let meta_len = 12 + pitm.len() + iloc_len + iprp.len();
+    let data_offset = (ftyp.len() + meta_len + 8) as u32;
+    let mut iloc_body = vec![0x44, 0x00, 0, 1, 0, 1, 0, 0, 0, 1];
+    iloc_body.extend(data_offset.to_be_bytes());
+    iloc_body.extend(4u32.to_be_bytes());
+    let iloc = full(b"iloc", 0, &iloc_body);
+    assert_eq!(iloc.len(), iloc_len);
+    let meta = full(b"meta", 0, &[pitm, iloc, iprp].concat());
+    let heif = [ftyp, meta, new_box(*b"mdat", b"data")].concat();
+    assert_eq!(read_icc(&heif).unwrap(), None);
+
+    let added = write_icc(&heif, b"first profile").unwrap();
+    assert_eq!(read_icc(&added).unwrap().unwrap(), b"first profile");
+    let replaced = write_icc(&added, b"second").unwrap();
+    assert_eq!(read_icc(&replaced).unwrap().unwrap(), b"second");
+
+    // the item data offset in the iloc box follows the mdat payload
+    let layout = Layout::parse(&replaced).unwrap();
+    let iloc = layout.iloc.unwrap();
+    let offset = be_uint(&replaced, iloc.end - 8, 4).unwrap() as usize;
+    assert_eq!(&replaced[offset..offset + 4], b"data");
+}
+
+#[test]
+fn test_heif_truncated_colr() {
+    // a 'colr' box with a body of two bytes, followed by bytes which read as a colour type
+    let buf = b"\0\0\0\x0Acolrprof";
+    let colr = BoxRange { kind: COLR, start: 0, body: 8, end: 10, large: false };
+    assert!(icc_property(buf, &[colr], &[1]).is_none());
+    let complete = BoxRange { end: 12, ..colr };
+    assert_eq!(icc_property(buf, &[complete], &[1]).map(|(i, _)| i), Some(1));
+}
+
+#[test]
+fn test_heif_corrupt_iloc() {
+    // version 0, one item, with item id 1, and data reference index 0
+    fn iloc(sizes: [u8; 2], fields: &[u8]) -> Vec<u8> {
+        let mut body = vec![0, 0, 0, 0, sizes[0], sizes[1], 0, 1, 0, 1, 0, 0];
+        body.extend(fields);
+        new_box(ILOC, &body)
+    }
+    let range = |buf: &[u8]| BoxRange { kind: ILOC, start: 0, body: 8, end: buf.len(), large: false };
+    // an offset size of 15 bytes, from the upper nibble of the sizes field
+    let buf = iloc([0xF4, 0x00], &[0; 40]);
+    assert!(iloc_edits(&buf, range(&buf), |_| 16).is_err());
+    // 8 byte base offset and extent offset, which overflow when added
+    let mut fields = vec![0xFF; 8];
+    fields.extend([0, 1]);
+    fields.extend([0xFF; 8]);
+    fields.extend([0; 8]);
+    let buf = iloc([0x88, 0x80], &fields);
+    assert!(iloc_edits(&buf, range(&buf), |_| 16).is_err());
+    // an extent offset beyond the range of i64
+    let mut fields = vec![0, 1];
+    fields.extend([0xFF; 8]);
+    fields.extend([0; 8]);
+    let buf = iloc([0x88, 0x00], &fields);
+    assert!(iloc_edits(&buf, range(&buf), |_| 16).is_err());
+}
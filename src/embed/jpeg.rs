@@ -0,0 +1,156 @@
+/*!
+  ICC profiles in JPEG files.
+
+  A JPEG segment can hold at most 65533 bytes of data, so profiles are split over one or more APP2
+  segments, each starting with the `ICC_PROFILE\0` identifier, followed by the 1-based sequence number
+  of the segment, and the total number of segments.
+*/
+
+use crate::common::Result;
+#[cfg(feature = "fs")]
+use crate::profile::Profile;
+
+const SOI: u8 = 0xD8;
+const SOS: u8 = 0xDA;
+const APP0: u8 = 0xE0;
+const APP1: u8 = 0xE1;
+const APP2: u8 = 0xE2;
+const ICC_PROFILE: &[u8; 12] = b"ICC_PROFILE\0";
+
+// Maximum number of profile bytes in a segment: the segment length field includes its own two bytes,
+// and the identifier is followed by the sequence number and segment count.
+const MAX_CHUNK: usize = 0xFFFF - 2 - ICC_PROFILE.len() - 2;
+
+// A marker segment in the source buffer, with `start` the location of its 0xFF marker prefix, and `end`
+// the first byte after its data.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    marker: u8,
+    start: usize,
+    end: usize,
+}
+
+impl Segment {
+    fn data<'a>(&self, buf: &'a [u8]) -> &'a [u8] {
+        &buf[self.start + 4..self.end]
+    }
+
+    fn is_icc(&self, buf: &[u8]) -> bool {
+        self.marker == APP2 && self.data(buf).starts_with(ICC_PROFILE)
+    }
+}
+
+// The marker segments of the header of a JPEG file, up to and excluding the start of scan segment,
+// and the position of the start of scan marker.
+fn segments(buf: &[u8]) -> Result<(Vec<Segment>, usize)> {
+    if !buf.starts_with(&[0xFF, SOI]) {
+        return Err("not a JPEG file".into());
+    }
+    let mut v = Vec::new();
+    let mut pos = 2;
+    loop {
+        // markers may be preceded by any number of 0xFF fill bytes
+        while buf.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        let header = buf.get(pos..pos + 4).ok_or("unexpected end of JPEG data")?;
+        if header[0] != 0xFF {
+            return Err("JPEG marker error".into());
+        }
+        let marker = header[1];
+        if marker == SOS {
+            return Ok((v, pos));
+        }
+        let length = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let end = pos + 2 + length;
+        if length < 2 || end > buf.len() {
+            return Err("JPEG segment length error".into());
+        }
+        v.push(Segment { marker, start: pos, end });
+        pos = end;
+    }
+}
+
+/// Returns the ICC profile of a JPEG file, reassembled from its APP2 `ICC_PROFILE` segments, if present.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    let (segments, _) = segments(buf)?;
+    let mut chunks: Vec<(u8, u8, &[u8])> = segments
+        .iter()
+        .filter(|s| s.is_icc(buf))
+        .map(|s| {
+            let data = &s.data(buf)[ICC_PROFILE.len()..];
+            match data {
+                [seq, count, icc @ ..] => Ok((*seq, *count, icc)),
+                _ => Err("ICC_PROFILE segment length error"),
+            }
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    if chunks.is_empty() {
+        return Ok(None);
+    }
+    chunks.sort_by_key(|&(seq, _, _)| seq);
+    let count = chunks[0].1;
+    if chunks.len() != count as usize || chunks.iter().enumerate().any(|(i, &(seq, n, _))| seq as usize != i + 1 || n != count) {
+        return Err("missing or duplicate ICC_PROFILE segments".into());
+    }
+    Ok(Some(chunks.into_iter().flat_map(|(_, _, icc)| icc).copied().collect()))
+}
+
+/// Embeds an ICC profile in a JPEG file, replacing an existing one. The profile segments are placed
+/// after the JFIF (APP0) and Exif (APP1) segments at the start of the file, if any.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    let (segments, _) = segments(buf)?;
+    let count: u8 = icc.len().div_ceil(MAX_CHUNK).try_into().map_err(|_| "ICC profile too large for a JPEG file")?;
+    let mut app2 = Vec::with_capacity(icc.len() + count as usize * 18);
+    for (i, chunk) in icc.chunks(MAX_CHUNK).enumerate() {
+        app2.extend([0xFF, APP2]);
+        app2.extend(((chunk.len() + ICC_PROFILE.len() + 4) as u16).to_be_bytes());
+        app2.extend(ICC_PROFILE);
+        app2.extend([i as u8 + 1, count]);
+        app2.extend(chunk);
+    }
+    let insert = segments.iter().take_while(|s| s.marker == APP0 || s.marker == APP1).last().map_or(2, |s| s.end);
+
+    let mut out = Vec::with_capacity(buf.len() + app2.len());
+    out.extend(&buf[..insert]);
+    out.extend(app2);
+    for s in segments.iter().filter(|s| s.start >= insert && !s.is_icc(buf)) {
+        out.extend(&buf[s.start..s.end]);
+    }
+    let image_data = segments.last().map_or(2, |s| s.end).max(insert);
+    out.extend(&buf[image_data..]);
+    Ok(out)
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a JPEG file.
+    pub fn from_jpeg(path: &str) -> Result<Profile> {
+        let icc = read_icc(&std::fs::read(path)?)?.ok_or("no ICC profile found in JPEG file")?;
+        Ok(Profile::from_buffer(&icc)?)
+    }
+
+    /// Writes a copy of a JPEG file, with this profile embedded.
+    pub fn embed_in_jpeg(&self, path: &str, out: &str) -> Result<()> {
+        let jpeg = write_icc(&std::fs::read(path)?, &self.to_buffer()?)?;
+        Ok(std::fs::write(out, jpeg)?)
+    }
+}
+
+#[test]
+fn test_jpeg_icc() {
+    let jfif = [&[0xFF, SOI, 0xFF, APP0, 0, 16][..], b"JFIF\0", &[1, 1, 0, 0, 1, 0, 1, 0, 0]].concat();
+    let jpeg = [&jfif[..], &[0xFF, SOS, 0, 2, 0xAB, 0xFF, 0xD9]].concat();
+    assert_eq!(read_icc(&jpeg).unwrap(), None);
+
+    let icc = crate::profile::standards::srgb().to_buffer().unwrap();
+    let tagged = write_icc(&jpeg, &icc).unwrap();
+    assert_eq!(read_icc(&tagged).unwrap().as_deref(), Some(&icc[..]));
+    assert!(tagged.starts_with(&jfif) && tagged.ends_with(&jpeg[jfif.len()..]));
+
+    // a profile larger than a single segment, replacing the existing profile
+    let large: Vec<u8> = (0..150_000u32).map(|i| i as u8).collect();
+    let retagged = write_icc(&tagged, &large).unwrap();
+    assert_eq!(segments(&retagged).unwrap().0.iter().filter(|s| s.is_icc(&retagged)).count(), 3);
+    assert_eq!(read_icc(&retagged).unwrap(), Some(large));
+}
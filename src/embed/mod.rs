@@ -0,0 +1,92 @@
+/*!
+  Reading and writing of ICC profiles embedded in image files.
+
+  The format specific modules work on file buffers; [read_icc], [write_icc], and the `Profile`
+  methods `from_image` and `embed_in_image` detect the image format from the file's leading bytes.
+  The [pdf] module reads the profiles embedded in PDF documents.
+*/
+
+use crate::common::Result;
+#[cfg(feature = "fs")]
+use crate::profile::Profile;
+
+pub mod heif;
+pub mod jpeg;
+pub mod pdf;
+pub mod png;
+pub mod tiff;
+pub mod webp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Heif,
+    Jpeg,
+    Png,
+    Tiff,
+    WebP,
+}
+
+impl ImageFormat {
+    /// Detects the format of an image file from its leading bytes.
+    pub fn detect(buf: &[u8]) -> Option<Self> {
+        match buf {
+            [0x89, b'P', b'N', b'G', ..] => Some(Self::Png),
+            [0xFF, 0xD8, ..] => Some(Self::Jpeg),
+            [b'I', b'I', b'*', 0, ..] | [b'M', b'M', 0, b'*', ..] => Some(Self::Tiff),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::WebP),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::Heif),
+            _ => None,
+        }
+    }
+}
+
+fn detect(buf: &[u8]) -> Result<ImageFormat> {
+    ImageFormat::detect(buf).ok_or_else(|| "unsupported image file format".into())
+}
+
+/// Returns the ICC profile embedded in an image file.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    match detect(buf)? {
+        ImageFormat::Heif => heif::read_icc(buf),
+        ImageFormat::Jpeg => jpeg::read_icc(buf),
+        ImageFormat::Png => png::read_icc(buf),
+        ImageFormat::Tiff => tiff::read_icc(buf),
+        ImageFormat::WebP => webp::read_icc(buf),
+    }
+}
+
+/// Embeds an ICC profile in an image file, replacing an existing one.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    match detect(buf)? {
+        ImageFormat::Heif => heif::write_icc(buf, icc),
+        ImageFormat::Jpeg => jpeg::write_icc(buf, icc),
+        ImageFormat::Png => png::write_icc(buf, icc),
+        ImageFormat::Tiff => tiff::write_icc(buf, icc),
+        ImageFormat::WebP => webp::write_icc(buf, icc),
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a HEIF, AVIF, JPEG, PNG, TIFF, or WebP file.
+    pub fn from_image(path: &str) -> Result<Profile> {
+        let icc = read_icc(&std::fs::read(path)?)?.ok_or("no ICC profile found in image file")?;
+        Ok(Profile::from_buffer(&icc)?)
+    }
+
+    /// Reads the ICC profiles embedded in a PDF file, in file order; see [pdf::read_icc_profiles]
+    /// for the object numbers, and the output intent profiles.
+    pub fn from_pdf(path: &str) -> Result<Vec<Profile>> {
+        pdf::read_icc_profiles(&std::fs::read(path)?)?.iter().map(|p| Ok(Profile::from_buffer(&p.icc)?)).collect()
+    }
+
+    /// Writes a copy of an image file, with this profile embedded; see also [Profile::embed_in_png].
+    pub fn embed_in_image(&self, path: &str, out: &str) -> Result<()> {
+        let buf = std::fs::read(path)?;
+        let image = match detect(&buf)? {
+            ImageFormat::Png => png::write_profile(&buf, self)?,
+            _ => write_icc(&buf, &self.to_buffer()?)?,
+        };
+        Ok(std::fs::write(out, image)?)
+    }
+}
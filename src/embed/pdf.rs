@@ -0,0 +1,120 @@
+/*!
+  ICC profiles embedded in PDF documents, for auditing the profiles of prepress deliverables.
+
+  Profiles are stored in PDF files as streams, referenced by ICCBased color spaces, and by the
+  'DestOutputProfile' entries of output intents. This module does not parse the document structure:
+  it scans the file for stream objects, decodes them if they are uncompressed, or Flate compressed,
+  and keeps the streams which have an ICC profile header. Streams inside compressed object streams
+  are not found, but PDF does not allow streams in object streams.
+*/
+
+use crate::common::{inflate, Result};
+use crate::profile::MAX_PROFILE_SIZE;
+
+#[derive(Debug, Clone)]
+pub struct PdfProfile {
+    /// Object number of the stream.
+    pub object: u32,
+    /// The profile is the destination profile of an output intent.
+    pub output_intent: bool,
+    pub icc: Vec<u8>,
+}
+
+/// Returns the ICC profiles of a PDF file, in file order.
+pub fn read_icc_profiles(buf: &[u8]) -> Result<Vec<PdfProfile>> {
+    if !buf.starts_with(b"%PDF") {
+        return Err("not a PDF file".into());
+    }
+    let output_intents: Vec<u32> = find_all(buf, b"/DestOutputProfile")
+        .filter_map(|i| object_reference(&buf[i + b"/DestOutputProfile".len()..]))
+        .collect();
+    let mut profiles = Vec::new();
+    let mut position = 0;
+    while let Some(start) = find(&buf[position..], b" obj").map(|i| position + i) {
+        position = start + 4;
+        let Some(object) = object_number(&buf[..start]) else { continue };
+        // the dictionary, followed by the 'stream' keyword, ends the object header
+        let Some(header_end) = find(&buf[position..], b"endobj").map(|i| position + i) else { break };
+        let Some(stream) = find(&buf[position..header_end], b"stream").map(|i| position + i) else { continue };
+        let data_start = stream + 6 + match &buf[stream + 6..] {
+            [b'\r', b'\n', ..] => 2,
+            [b'\n', ..] | [b'\r', ..] => 1,
+            _ => continue,
+        };
+        let Some(data_end) = find(&buf[data_start..], b"endstream").map(|i| data_start + i) else { break };
+        position = data_end;
+        if let Some(icc) = decode(&buf[start..stream], &buf[data_start..data_end]) {
+            profiles.push(PdfProfile { object, output_intent: output_intents.contains(&object), icc });
+        }
+    }
+    Ok(profiles)
+}
+
+// Stream data with an ICC profile header, decoded, and truncated to the profile size.
+fn decode(dictionary: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    let mut icc = if find(dictionary, b"/FlateDecode").is_some() {
+        inflate(data, MAX_PROFILE_SIZE).ok()?
+    } else if find(dictionary, b"/Filter").is_none() {
+        data.to_vec()
+    } else {
+        return None;
+    };
+    let size = u32::from_be_bytes(icc.get(0..4)?.try_into().ok()?) as usize;
+    if icc.get(36..40) != Some(b"acsp") || !(128..=icc.len()).contains(&size) {
+        return None;
+    }
+    icc.truncate(size);
+    Some(icc)
+}
+
+fn find(buf: &[u8], pattern: &[u8]) -> Option<usize> {
+    buf.windows(pattern.len()).position(|w| w == pattern)
+}
+
+fn find_all<'a>(buf: &'a [u8], pattern: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    buf.windows(pattern.len()).enumerate().filter(move |(_, w)| *w == pattern).map(|(i, _)| i)
+}
+
+// The object number of the object definition ending the buffer, as in "12 0".
+fn object_number(buf: &[u8]) -> Option<u32> {
+    let digits = |buf: &[u8]| buf.iter().rev().take_while(|b| b.is_ascii_digit()).count();
+    let generation = digits(buf);
+    let rest = buf[..buf.len() - generation].strip_suffix(b" ")?;
+    let n = digits(rest);
+    if generation == 0 || n == 0 {
+        return None;
+    }
+    std::str::from_utf8(&rest[rest.len() - n..]).ok()?.parse().ok()
+}
+
+// The object number of an indirect reference, as in " 12 0 R", starting the buffer.
+fn object_reference(buf: &[u8]) -> Option<u32> {
+    let text = std::str::from_utf8(&buf[..buf.len().min(32)]).ok()?;
+    let mut words = text.split_ascii_whitespace();
+    let object = words.next()?.parse().ok()?;
+    words.next()?.parse::<u16>().ok()?;
+    words.next()?.starts_with('R').then_some(object)
+}
+
+#[test]
+fn test_pdf_profiles() {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let srgb = crate::profile::standards::srgb().to_buffer().unwrap();
+    let gray = crate::profile::standards::gray_gamma22().to_buffer().unwrap();
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&gray).unwrap();
+    let compressed = encoder.finish().unwrap();
+    let pdf = [
+        &b"%PDF-1.7\n1 0 obj\n<< /Type /Catalog /OutputIntents [<< /S /GTS_PDFX /DestOutputProfile 3 0 R >>] >>\nendobj\n"[..],
+        format!("2 0 obj\n<< /N 3 /Length {} >>\nstream\n", srgb.len()).as_bytes(), &srgb, b"\nendstream\nendobj\n",
+        format!("3 0 obj\n<< /N 1 /Filter /FlateDecode /Length {} >>\nstream\r\n", compressed.len()).as_bytes(), &compressed,
+        b"\r\nendstream\nendobj\n4 0 obj\n<< /Length 5 >>\nstream\nhello\nendstream\nendobj\n%%EOF\n",
+    ].concat();
+    let profiles = read_icc_profiles(&pdf).unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert!(profiles[0].object == 2 && !profiles[0].output_intent && profiles[0].icc == srgb);
+    assert!(profiles[1].object == 3 && profiles[1].output_intent && profiles[1].icc == gray);
+    assert!(read_icc_profiles(&srgb).is_err());
+}
@@ -0,0 +1,270 @@
+/*!
+  Color information chunks in PNG images.
+
+  A PNG file can carry its color encoding as an ICC profile in an 'iCCP' chunk, or as ITU-T H.273
+  code points in a 'cICP' chunk. Browsers give the 'cICP' chunk precedence, so when both are present
+  they should describe the same encoding; [cicp_mismatch] compares a 'cICP' chunk with the 'cicp'
+  tag of a profile, and [write_profile_with_cicp] keeps, or replaces, the chunk when embedding a
+  profile.
+
+  The profile in an 'iCCP' chunk is zlib compressed, and is preceded by a profile name.
+*/
+
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::common::{inflate, Result};
+use crate::profile::{ParseOptions, Profile};
+use crate::tags::cicp::Cicp;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+const IHDR: [u8; 4] = *b"IHDR";
+const CICP: [u8; 4] = *b"cICP";
+const ICCP: [u8; 4] = *b"iCCP";
+const SRGB: [u8; 4] = *b"sRGB";
+
+// Profile name used for the 'iCCP' chunk; the name is required, but is not used by readers.
+const PROFILE_NAME: &[u8] = b"ICC Profile";
+
+// A chunk in the source buffer, with `start` the location of its length field, and `end` the first
+// byte after its crc.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    kind: [u8; 4],
+    start: usize,
+    end: usize,
+}
+
+impl Chunk {
+    fn data<'a>(&self, buf: &'a [u8]) -> &'a [u8] {
+        &buf[self.start + 8..self.end - 4]
+    }
+}
+
+fn chunks(buf: &[u8]) -> Result<Vec<Chunk>> {
+    if !buf.starts_with(&SIGNATURE) {
+        return Err("not a PNG file".into());
+    }
+    let mut v = Vec::new();
+    let mut pos = SIGNATURE.len();
+    while pos < buf.len() {
+        let header = buf.get(pos..pos + 8).ok_or("unexpected end of PNG data")?;
+        let length = u32::from_be_bytes(header[0..4].try_into()?) as usize;
+        let end = length.checked_add(pos + 12).filter(|&end| end <= buf.len()).ok_or("PNG chunk length error")?;
+        let kind: [u8; 4] = header[4..8].try_into()?;
+        v.push(Chunk { kind, start: pos, end });
+        pos = end;
+        if &kind == b"IEND" {
+            break;
+        }
+    }
+    if v.first().map(|c| c.kind) != Some(IHDR) {
+        return Err("PNG file does not start with an IHDR chunk".into());
+    }
+    Ok(v)
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFFu32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { crc >> 1 ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn new_chunk(kind: [u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(data.len() + 12);
+    v.extend((data.len() as u32).to_be_bytes());
+    v.extend(kind);
+    v.extend(data);
+    v.extend(crc32(&v[4..]).to_be_bytes());
+    v
+}
+
+fn find_chunk(buf: &[u8], kind: [u8; 4]) -> Result<Option<&[u8]>> {
+    Ok(chunks(buf)?.into_iter().find(|c| c.kind == kind).map(|c| c.data(buf)))
+}
+
+// Replaces all chunks of a kind by a single new chunk, placed directly after the image header, or removes
+// them if `data` is None.
+fn replace_chunk(buf: &[u8], kind: [u8; 4], data: Option<&[u8]>) -> Result<Vec<u8>> {
+    let chunks = chunks(buf)?;
+    let mut out = Vec::with_capacity(buf.len() + data.map_or(0, |d| d.len() + 12));
+    out.extend(SIGNATURE);
+    for c in chunks {
+        if c.kind != kind {
+            out.extend(&buf[c.start..c.end]);
+        }
+        if c.kind == IHDR {
+            if let Some(data) = data {
+                out.extend(new_chunk(kind, data));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Returns the decompressed ICC profile of the 'iCCP' chunk of a PNG file, if present.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    read_icc_with_options(buf, &ParseOptions::default())
+}
+
+/// Returns the ICC profile of the 'iCCP' chunk of a PNG file, as [read_icc], with an error if the
+/// decompressed profile exceeds the maximum profile size of the options.
+pub fn read_icc_with_options(buf: &[u8], options: &ParseOptions) -> Result<Option<Vec<u8>>> {
+    let Some(data) = find_chunk(buf, ICCP)? else {
+        return Ok(None);
+    };
+    let name_end = data.iter().take(80).position(|&b| b == 0).ok_or("iCCP profile name error")?;
+    let (method, compressed) = data[name_end + 1..].split_first().ok_or("unexpected end of iCCP chunk")?;
+    if *method != 0 {
+        return Err("unknown iCCP compression method".into());
+    }
+    Ok(Some(inflate(compressed, options.max_profile_size)?))
+}
+
+/// Adds, or replaces, the 'iCCP' chunk of a PNG file, and removes its 'sRGB' chunk, as a PNG file can
+/// not have both.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    let mut data = [PROFILE_NAME, &[0, 0]].concat();
+    let mut encoder = ZlibEncoder::new(&mut data, Compression::best());
+    encoder.write_all(icc)?;
+    encoder.finish()?;
+    replace_chunk(&replace_chunk(buf, SRGB, None)?, ICCP, Some(&data))
+}
+
+/// Handling of the 'cICP' chunk of a PNG file, when embedding a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CicpChunk {
+    /// Writes the code points of the 'cicp' tag of the profile, or removes the chunk if the profile
+    /// has no 'cicp' tag, as the chunk would take precedence over the profile.
+    #[default]
+    FromProfile,
+    /// Keeps the chunk, if present, and returns an error if it differs from the 'cicp' tag of the
+    /// profile, as found by [cicp_mismatch].
+    Keep,
+}
+
+/// Embeds a profile in a PNG file, as described for [Profile::embed_in_png].
+pub fn write_profile(buf: &[u8], profile: &Profile) -> Result<Vec<u8>> {
+    write_profile_with_cicp(buf, profile, CicpChunk::FromProfile)
+}
+
+/// Embeds a profile in a PNG file, with the 'cICP' chunk handled as set by `cicp`.
+pub fn write_profile_with_cicp(buf: &[u8], profile: &Profile, cicp: CicpChunk) -> Result<Vec<u8>> {
+    if cicp == CicpChunk::Keep {
+        if let Some(warning) = read_cicp(buf)?.and_then(|chunk| cicp_mismatch(chunk, profile)) {
+            return Err(warning.into());
+        }
+    }
+    let png = write_icc(buf, &profile.to_buffer()?)?;
+    match (cicp, cicp_from_profile(profile)) {
+        (CicpChunk::Keep, _) => Ok(png),
+        (CicpChunk::FromProfile, Some(cicp)) => write_cicp(&png, cicp),
+        (CicpChunk::FromProfile, None) => replace_chunk(&png, CICP, None),
+    }
+}
+
+/// Reads the coding-independent code points of the 'cICP' chunk of a PNG file, if present.
+pub fn read_cicp(buf: &[u8]) -> Result<Option<Cicp>> {
+    match find_chunk(buf, CICP)? {
+        Some(mut data) => Ok(Some(Cicp::try_new(&mut data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Adds, or replaces, the 'cICP' chunk of a PNG file.
+pub fn write_cicp(buf: &[u8], cicp: Cicp) -> Result<Vec<u8>> {
+    replace_chunk(buf, CICP, Some(&cicp.to_be_bytes()))
+}
+
+/// The code points of the 'cicp' tag of a profile, as used for the 'cICP' chunk of a PNG file.
+pub fn cicp_from_profile(profile: &Profile) -> Option<Cicp> {
+    profile.cicp()
+}
+
+/// Returns a warning if the code points of a 'cICP' chunk differ from the 'cicp' tag of the profile
+/// embedded in the same file; browsers use the chunk, other applications often the profile.
+pub fn cicp_mismatch(chunk: Cicp, profile: &Profile) -> Option<String> {
+    match cicp_from_profile(profile) {
+        Some(tag) if tag != chunk => Some(format!(
+            "cICP chunk {:?} differs from the cicp tag {:?} of the embedded profile", chunk.to_be_bytes(), tag.to_be_bytes()
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a PNG file.
+    pub fn from_png(path: &str) -> Result<Profile> {
+        Self::from_png_with_options(path, &ParseOptions::default())
+    }
+
+    /// Reads the ICC profile embedded in a PNG file, decompressing, and parsing, it with the options.
+    pub fn from_png_with_options(path: &str, options: &ParseOptions) -> Result<Profile> {
+        let icc = read_icc_with_options(&std::fs::read(path)?, options)?.ok_or("no ICC profile found in PNG file")?;
+        Ok(Profile::from_buffer_with_options(&icc, options)?)
+    }
+
+    /// Writes a copy of a PNG file, with this profile embedded. If the profile has a 'cicp' tag, its code
+    /// points are also written to a 'cICP' chunk, and otherwise an existing 'cICP' chunk is removed, as
+    /// it would take precedence over the profile.
+    pub fn embed_in_png(&self, path: &str, out: &str) -> Result<()> {
+        Ok(std::fs::write(out, write_profile(&std::fs::read(path)?, self)?)?)
+    }
+}
+
+#[test]
+fn test_png_cicp() {
+    let ihdr = new_chunk(IHDR, &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]);
+    let png = [&SIGNATURE[..], &ihdr, &new_chunk(*b"IDAT", &[0]), &new_chunk(*b"IEND", &[])].concat();
+    // crc of an empty IEND chunk, as found in any png file
+    assert_eq!(&png[png.len() - 4..], &[0xAE, 0x42, 0x60, 0x82]);
+    assert_eq!(read_cicp(&png).unwrap(), None);
+
+    let hlg = Cicp { color_primaries: 9, transfer_characteristics: 18, matrix_coefficients: 0, video_full_range: true };
+    let tagged = write_cicp(&png, hlg).unwrap();
+    assert_eq!(read_cicp(&tagged).unwrap(), Some(hlg));
+    let pq = Cicp { transfer_characteristics: 16, ..hlg };
+    let retagged = write_cicp(&tagged, pq).unwrap();
+    assert_eq!(retagged.len(), tagged.len());
+    assert_eq!(read_cicp(&retagged).unwrap(), Some(pq));
+}
+
+#[test]
+fn test_png_icc() {
+    let ihdr = new_chunk(IHDR, &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 0]);
+    let png = [&SIGNATURE[..], &ihdr, &new_chunk(SRGB, &[0]), &new_chunk(*b"IDAT", &[0]), &new_chunk(*b"IEND", &[])].concat();
+    assert_eq!(read_icc(&png).unwrap(), None);
+
+    let icc = crate::profile::standards::display_p3().to_buffer().unwrap();
+    let tagged = write_icc(&png, &icc).unwrap();
+    assert_eq!(read_icc(&tagged).unwrap(), Some(icc.clone()));
+    assert!(find_chunk(&tagged, SRGB).unwrap().is_none());
+    assert_eq!(chunks(&tagged).unwrap()[1].kind, ICCP);
+
+    // a 'cICP' chunk is removed for a profile without a 'cicp' tag, unless kept, and a kept chunk
+    // should match the 'cicp' tag of the profile
+    let hlg = Cicp { color_primaries: 9, transfer_characteristics: 18, matrix_coefficients: 0, video_full_range: true };
+    let with_cicp = write_cicp(&png, hlg).unwrap();
+    let p3 = crate::profile::standards::display_p3();
+    assert_eq!(read_cicp(&write_profile(&with_cicp, &p3).unwrap()).unwrap(), None);
+    assert_eq!(read_cicp(&write_profile_with_cicp(&with_cicp, &p3, CicpChunk::Keep).unwrap()).unwrap(), Some(hlg));
+    let pq = p3.with_tag(crate::signatures::tag::TagSignature::CicpTag, crate::tags::TagData::Cicp(Cicp { transfer_characteristics: 16, ..hlg }));
+    assert!(write_profile_with_cicp(&with_cicp, &pq, CicpChunk::Keep).is_err());
+
+    // compressed data is decompressed up to a limit
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&icc).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(inflate(&compressed, icc.len()).unwrap(), icc);
+    assert!(inflate(&compressed, icc.len() - 1).is_err());
+    let options = |max_profile_size| ParseOptions { max_profile_size, ..Default::default() };
+    assert_eq!(read_icc_with_options(&tagged, &options(icc.len())).unwrap(), Some(icc.clone()));
+    assert!(read_icc_with_options(&tagged, &options(icc.len() - 1)).is_err());
+}
@@ -0,0 +1,169 @@
+/*!
+  ICC profiles in TIFF files, stored in the ICC Profile tag (34675) of the first image file directory.
+
+  When embedding a profile, the profile data is appended to the file. An existing ICC Profile tag is
+  updated in place; otherwise a copy of the first image file directory, with the tag added, is
+  appended as well, so none of the existing data has to move.
+*/
+
+use crate::common::Result;
+#[cfg(feature = "fs")]
+use crate::profile::Profile;
+
+const ICC_PROFILE_TAG: u16 = 34675;
+const UNDEFINED: u16 = 7;
+
+#[derive(Debug, Clone, Copy)]
+struct Endian {
+    little: bool,
+}
+
+impl Endian {
+    fn u16(&self, buf: &[u8], pos: usize) -> Result<u16> {
+        let b: [u8; 2] = buf.get(pos..pos + 2).ok_or("unexpected end of TIFF data")?.try_into()?;
+        Ok(if self.little { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32(&self, buf: &[u8], pos: usize) -> Result<u32> {
+        let b: [u8; 4] = buf.get(pos..pos + 4).ok_or("unexpected end of TIFF data")?.try_into()?;
+        Ok(if self.little { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn u16_bytes(&self, v: u16) -> [u8; 2] {
+        if self.little { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+
+    fn u32_bytes(&self, v: u32) -> [u8; 4] {
+        if self.little { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+}
+
+// The first image file directory: its location, and the location of each 12 byte entry.
+struct Directory {
+    endian: Endian,
+    offset: usize,
+    entries: Vec<usize>,
+}
+
+impl Directory {
+    fn parse(buf: &[u8]) -> Result<Self> {
+        let endian = match buf.get(0..4) {
+            Some(b"II*\0") => Endian { little: true },
+            Some(b"MM\0*") => Endian { little: false },
+            _ => return Err("not a (classic) TIFF file".into()),
+        };
+        let offset = endian.u32(buf, 4)? as usize;
+        let n = endian.u16(buf, offset)? as usize;
+        if offset + 2 + 12 * n + 4 > buf.len() {
+            return Err("TIFF image file directory length error".into());
+        }
+        Ok(Self { endian, offset, entries: (0..n).map(|i| offset + 2 + 12 * i).collect() })
+    }
+
+    fn tag(&self, buf: &[u8], entry: usize) -> Result<u16> {
+        self.endian.u16(buf, entry)
+    }
+
+    fn icc_entry(&self, buf: &[u8]) -> Result<Option<usize>> {
+        for &entry in &self.entries {
+            if self.tag(buf, entry)? == ICC_PROFILE_TAG {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the ICC profile of the first image of a TIFF file, if present.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    let dir = Directory::parse(buf)?;
+    let Some(entry) = dir.icc_entry(buf)? else {
+        return Ok(None);
+    };
+    let count = dir.endian.u32(buf, entry + 4)? as usize;
+    let start = if count <= 4 { entry + 8 } else { dir.endian.u32(buf, entry + 8)? as usize };
+    let icc = start.checked_add(count).and_then(|end| buf.get(start..end)).ok_or("TIFF ICC profile length error")?;
+    Ok(Some(icc.to_vec()))
+}
+
+/// Embeds an ICC profile in the first image of a TIFF file, replacing an existing one.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    let dir = Directory::parse(buf)?;
+    let e = dir.endian;
+    let mut out = buf.to_vec();
+    // TIFF data offsets are word aligned
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    let icc_offset: u32 = out.len().try_into()?;
+    out.extend(icc);
+    if out.len() % 2 == 1 {
+        out.push(0);
+    }
+    let mut entry = [0u8; 12];
+    entry[0..2].copy_from_slice(&e.u16_bytes(ICC_PROFILE_TAG));
+    entry[2..4].copy_from_slice(&e.u16_bytes(UNDEFINED));
+    entry[4..8].copy_from_slice(&e.u32_bytes(icc.len().try_into()?));
+    entry[8..12].copy_from_slice(&e.u32_bytes(icc_offset));
+
+    match dir.icc_entry(buf)? {
+        Some(pos) => out[pos..pos + 12].copy_from_slice(&entry),
+        None => {
+            // new directory, with its entries sorted by tag, and the same next directory offset
+            let mut entries: Vec<&[u8]> = dir.entries.iter().map(|&pos| &buf[pos..pos + 12]).collect();
+            let at = dir.entries.iter().take_while(|&&pos| dir.tag(buf, pos).is_ok_and(|t| t < ICC_PROFILE_TAG)).count();
+            entries.insert(at, &entry);
+            let next = &buf[dir.offset + 2 + 12 * dir.entries.len()..][..4];
+            let dir_offset: u32 = out.len().try_into()?;
+            out.extend(e.u16_bytes(entries.len() as u16));
+            out.extend(entries.concat());
+            out.extend(next);
+            out[4..8].copy_from_slice(&e.u32_bytes(dir_offset));
+        }
+    }
+    u32::try_from(out.len()).map_err(|_| "TIFF file too large")?;
+    Ok(out)
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a TIFF file.
+    pub fn from_tiff(path: &str) -> Result<Profile> {
+        let icc = read_icc(&std::fs::read(path)?)?.ok_or("no ICC profile found in TIFF file")?;
+        Ok(Profile::from_buffer(&icc)?)
+    }
+}
+
+#[test]
+fn test_tiff_icc() {
+    // a 1x1 pixel gray image, with ImageWidth, ImageLength, and StripOffsets entries
+    fn tiff(little: bool) -> Vec<u8> {
+        let e = Endian { little };
+        let mut v = if little { b"II*\0".to_vec() } else { b"MM\0*".to_vec() };
+        v.extend(e.u32_bytes(8));
+        v.extend(e.u16_bytes(3));
+        for (tag, value) in [(256u16, 1u32), (257, 1), (273, 8 + 2 + 36 + 4)] {
+            v.extend(e.u16_bytes(tag));
+            v.extend(e.u16_bytes(4));
+            v.extend(e.u32_bytes(1));
+            v.extend(e.u32_bytes(value));
+        }
+        v.extend([0, 0, 0, 0, 0x80]);
+        v
+    }
+    let icc = crate::profile::standards::gray_gamma22().to_buffer().unwrap();
+    for little in [true, false] {
+        let image = tiff(little);
+        assert_eq!(read_icc(&image).unwrap(), None);
+        let tagged = write_icc(&image, &icc).unwrap();
+        assert_eq!(read_icc(&tagged).unwrap().as_deref(), Some(&icc[..]));
+        let dir = Directory::parse(&tagged).unwrap();
+        let tags: Vec<u16> = dir.entries.iter().map(|&pos| dir.tag(&tagged, pos).unwrap()).collect();
+        assert_eq!(tags, [256, 257, 273, ICC_PROFILE_TAG]);
+
+        // replacing the profile does not add another directory
+        let retagged = write_icc(&tagged, &icc[..200]).unwrap();
+        assert_eq!(read_icc(&retagged).unwrap().as_deref(), Some(&icc[..200]));
+        assert_eq!(Directory::parse(&retagged).unwrap().offset, dir.offset);
+    }
+}
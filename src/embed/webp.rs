@@ -0,0 +1,142 @@
+/*!
+  ICC profiles in WebP files.
+
+  A profile is stored in an 'ICCP' chunk, which is only allowed in the extended file format, directly
+  after the 'VP8X' chunk, with its ICC flag set. A simple format file, with only a 'VP8 ' or 'VP8L'
+  image chunk, is converted to the extended format when a profile is embedded.
+*/
+
+use crate::common::Result;
+#[cfg(feature = "fs")]
+use crate::profile::Profile;
+
+const VP8X: [u8; 4] = *b"VP8X";
+const ICCP: [u8; 4] = *b"ICCP";
+const ICC_FLAG: u8 = 0x20;
+const ALPHA_FLAG: u8 = 0x10;
+
+// A chunk in the source buffer, with `start` the location of its fourcc, and `end` the first byte after
+// its (padded) data.
+#[derive(Debug, Clone, Copy)]
+struct Chunk {
+    kind: [u8; 4],
+    start: usize,
+    end: usize,
+}
+
+impl Chunk {
+    fn data<'a>(&self, buf: &'a [u8]) -> &'a [u8] {
+        let size = u32::from_le_bytes(buf[self.start + 4..self.start + 8].try_into().unwrap()) as usize;
+        &buf[self.start + 8..self.start + 8 + size]
+    }
+}
+
+fn chunks(buf: &[u8]) -> Result<Vec<Chunk>> {
+    if buf.len() < 12 || &buf[0..4] != b"RIFF" || &buf[8..12] != b"WEBP" {
+        return Err("not a WebP file".into());
+    }
+    let riff_end = (u32::from_le_bytes(buf[4..8].try_into()?) as usize).saturating_add(8).min(buf.len());
+    let mut v = Vec::new();
+    let mut pos = 12;
+    while pos + 8 <= riff_end {
+        let kind: [u8; 4] = buf[pos..pos + 4].try_into()?;
+        let size = u32::from_le_bytes(buf[pos + 4..pos + 8].try_into()?) as usize;
+        let end = (pos + 8).checked_add(size + size % 2).ok_or("WebP chunk size error")?;
+        if pos + 8 + size > riff_end {
+            return Err("WebP chunk size error".into());
+        }
+        v.push(Chunk { kind, start: pos, end: end.min(riff_end) });
+        pos = end;
+    }
+    Ok(v)
+}
+
+fn new_chunk(kind: [u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(data.len() + 9);
+    v.extend(kind);
+    v.extend((data.len() as u32).to_le_bytes());
+    v.extend(data);
+    if data.len() % 2 == 1 {
+        v.push(0);
+    }
+    v
+}
+
+// 'VP8X' chunk data for a simple format image, with the canvas size, and the alpha flag, taken from
+// the image's bitstream header.
+fn vp8x_for(image: Chunk, buf: &[u8]) -> Result<[u8; 10]> {
+    let data = image.data(buf);
+    let (width, height, alpha) = match &image.kind {
+        b"VP8 " if data.len() >= 10 => (
+            u16::from_le_bytes([data[6], data[7]]) as u32 & 0x3FFF,
+            u16::from_le_bytes([data[8], data[9]]) as u32 & 0x3FFF,
+            false,
+        ),
+        b"VP8L" if data.len() >= 5 && data[0] == 0x2F => {
+            let bits = u32::from_le_bytes(data[1..5].try_into()?);
+            ((bits & 0x3FFF) + 1, (bits >> 14 & 0x3FFF) + 1, bits >> 28 & 1 == 1)
+        }
+        _ => return Err("unsupported WebP image chunk".into()),
+    };
+    let mut vp8x = [0u8; 10];
+    vp8x[0] = if alpha { ALPHA_FLAG } else { 0 };
+    vp8x[4..7].copy_from_slice(&(width - 1).to_le_bytes()[..3]);
+    vp8x[7..10].copy_from_slice(&(height - 1).to_le_bytes()[..3]);
+    Ok(vp8x)
+}
+
+/// Returns the ICC profile of a WebP file, if present.
+pub fn read_icc(buf: &[u8]) -> Result<Option<Vec<u8>>> {
+    Ok(chunks(buf)?.into_iter().find(|c| c.kind == ICCP).map(|c| c.data(buf).to_vec()))
+}
+
+/// Embeds an ICC profile in a WebP file, replacing an existing one.
+pub fn write_icc(buf: &[u8], icc: &[u8]) -> Result<Vec<u8>> {
+    let chunks = chunks(buf)?;
+    let mut vp8x: [u8; 10] = match chunks.iter().find(|c| c.kind == VP8X) {
+        Some(c) => c.data(buf).try_into().map_err(|_| "VP8X chunk size error")?,
+        None => vp8x_for(*chunks.first().ok_or("WebP file without image data")?, buf)?,
+    };
+    vp8x[0] |= ICC_FLAG;
+
+    let mut out = Vec::with_capacity(buf.len() + icc.len() + 40);
+    out.extend(b"RIFF\0\0\0\0WEBP");
+    out.extend(new_chunk(VP8X, &vp8x));
+    out.extend(new_chunk(ICCP, icc));
+    for c in chunks.iter().filter(|c| c.kind != VP8X && c.kind != ICCP) {
+        out.extend(&buf[c.start..c.end]);
+    }
+    let riff_size: u32 = (out.len() - 8).try_into()?;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(out)
+}
+
+#[cfg(feature = "fs")]
+impl Profile {
+    /// Reads the ICC profile embedded in a WebP file.
+    pub fn from_webp(path: &str) -> Result<Profile> {
+        let icc = read_icc(&std::fs::read(path)?)?.ok_or("no ICC profile found in WebP file")?;
+        Ok(Profile::from_buffer(&icc)?)
+    }
+}
+
+#[test]
+fn test_webp_icc() {
+    // a lossless 3x2 image header, with alpha
+    let bits: u32 = 2 | 1 << 14 | 1 << 28;
+    let vp8l = [&[0x2F][..], &bits.to_le_bytes(), &[0]].concat();
+    let image = new_chunk(*b"VP8L", &vp8l);
+    let webp = [b"RIFF", &((4 + image.len()) as u32).to_le_bytes(), b"WEBP", &image[..]].concat();
+    assert_eq!(read_icc(&webp).unwrap(), None);
+
+    let icc = crate::profile::standards::srgb().to_buffer().unwrap();
+    let tagged = write_icc(&webp, &icc[..icc.len() - 1]).unwrap();
+    assert_eq!(read_icc(&tagged).unwrap().as_deref(), Some(&icc[..icc.len() - 1]));
+    let kinds: Vec<[u8; 4]> = chunks(&tagged).unwrap().iter().map(|c| c.kind).collect();
+    assert_eq!(kinds, [VP8X, ICCP, *b"VP8L"]);
+    assert_eq!(&tagged[20..30], &[ICC_FLAG | ALPHA_FLAG, 0, 0, 0, 2, 0, 0, 1, 0, 0]);
+    assert_eq!(u32::from_le_bytes(tagged[4..8].try_into().unwrap()) as usize, tagged.len() - 8);
+
+    let retagged = write_icc(&tagged, &icc).unwrap();
+    assert_eq!(read_icc(&retagged).unwrap(), Some(icc));
+}
@@ -0,0 +1,45 @@
+/*!
+  Errors of reading and writing profiles, for callers which handle failures by their cause.
+
+  Other operations return the boxed errors of [crate::common::Result], into which these errors
+  convert with the `?` operator.
+*/
+
+use crate::signatures::tag::TagSignature;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a profile file failed.
+    Io(std::io::Error),
+    /// The profile header is malformed, or the profile size is invalid.
+    Header(String),
+    /// The tag table is malformed, with a tag count, offset, or length outside of the profile.
+    TagTable(String),
+    /// The data of a tag could not be read or written.
+    TagData { signature: TagSignature, reason: String },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Header(reason) | Self::TagTable(reason) => f.write_str(reason),
+            Self::TagData { signature, reason } => write!(f, "{signature:?} tag: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
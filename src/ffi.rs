@@ -0,0 +1,186 @@
+/*!
+  C interface, for parsing profiles, and inspecting their header fields and tags, from other
+  languages; the declarations are in `include/cmx.h`, generated with cbindgen, as configured in
+  `cbindgen.toml`.
+
+  Available with the `ffi` feature; build a shared, or static, library with
+
+  ```text
+  cargo rustc --release --lib --features ffi --crate-type cdylib
+  cargo rustc --release --lib --features ffi --crate-type staticlib
+  ```
+
+  A profile is parsed from a byte buffer into an opaque handle, which owns the parsed profile, and
+  the encoded data of its tags; pointers to tag data remain valid until the handle is released with
+  `cmx_profile_free`. Signatures are returned as their big-endian four character codes, and zero is
+  returned for absent values, and invalid handles, or indices. Panics do not unwind into the
+  calling language: a function which panics returns null, or zero, instead.
+*/
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::profile::Profile;
+
+/// Opaque profile handle.
+pub struct CmxProfile {
+    profile: Profile,
+    // encoded tags, including their type signature, and reserved bytes, or none if they could not
+    // be encoded
+    tags: Vec<Option<Vec<u8>>>,
+}
+
+// Result of the body of an entry point, or `default` if it panics.
+fn guard<T>(default: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// Parses a profile from a buffer of `len` bytes; returns null if the profile could not be parsed.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_from_bytes(data: *const u8, len: usize) -> *mut CmxProfile {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    guard(ptr::null_mut(), || match Profile::from_buffer(std::slice::from_raw_parts(data, len)) {
+        Ok(profile) => {
+            let tags = profile.iter_tags().map(|(_, t)| t.to_be_bytes().ok()).collect();
+            Box::into_raw(Box::new(CmxProfile { profile, tags }))
+        }
+        Err(_) => ptr::null_mut(),
+    })
+}
+
+/// Releases a profile handle; null handles are ignored.
+///
+/// # Safety
+/// `profile` must be null, or a handle returned by [cmx_profile_from_bytes], not released before.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_free(profile: *mut CmxProfile) {
+    if !profile.is_null() {
+        guard((), || drop(Box::from_raw(profile)));
+    }
+}
+
+/// Profile version, encoded as in the profile header, e.g. `0x04300000` for version 4.3.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_version(profile: *const CmxProfile) -> u32 {
+    guard(0, || {
+        profile.as_ref().map_or(0, |p| {
+            let [major, minor, bugfix] = p.profile.version;
+            u32::from_be_bytes([major, minor << 4 | (bugfix & 0x0F), 0, 0])
+        })
+    })
+}
+
+/// Profile class signature, e.g. `'mntr'` for display profiles.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_class(profile: *const CmxProfile) -> u32 {
+    guard(0, || profile.as_ref().map_or(0, |p| p.profile.class as u32))
+}
+
+/// Data color space signature, e.g. `'RGB '`.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_color_space(profile: *const CmxProfile) -> u32 {
+    guard(0, || profile.as_ref().and_then(|p| p.profile.colorspace.as_ref()).map_or(0, |c| c.signature() as u32))
+}
+
+/// Profile connection space signature, `'XYZ '` or `'Lab '`.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_pcs(profile: *const CmxProfile) -> u32 {
+    guard(0, || profile.as_ref().and_then(|p| p.profile.pcs.as_ref()).map_or(0, |c| c.signature() as u32))
+}
+
+/// Rendering intent from the header, from 0 (perceptual) to 3 (absolute colorimetric).
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_rendering_intent(profile: *const CmxProfile) -> u32 {
+    guard(0, || profile.as_ref().map_or(0, |p| p.profile.rendering_intent as u32))
+}
+
+/// Number of tags in the profile.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_tag_count(profile: *const CmxProfile) -> usize {
+    guard(0, || profile.as_ref().map_or(0, |p| p.profile.tag_count()))
+}
+
+/// Signature of the tag at `index`, in the order of the tag table.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_tag_signature(profile: *const CmxProfile, index: usize) -> u32 {
+    guard(0, || profile.as_ref().and_then(|p| p.profile.iter_tags().nth(index)).map_or(0, |(s, _)| s.to_u32()))
+}
+
+/// Type signature of the tag at `index`, e.g. `'curv'`.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_tag_type(profile: *const CmxProfile, index: usize) -> u32 {
+    guard(0, || profile.as_ref().and_then(|p| p.profile.iter_tags().nth(index)).map_or(0, |(_, t)| t.type_signature() as u32))
+}
+
+/// Encoded data of the tag at `index`, starting with its type signature, with its length written
+/// to `len`; returns null, and a length of zero, if the tag is absent, or could not be encoded.
+///
+/// # Safety
+/// `profile` must be null, or a valid handle, and `len` null, or a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn cmx_profile_tag_data(profile: *const CmxProfile, index: usize, len: *mut usize) -> *const u8 {
+    guard(ptr::null(), || {
+        let data = profile.as_ref().and_then(|p| p.tags.get(index)).and_then(|t| t.as_deref());
+        if let Some(len) = len.as_mut() {
+            *len = data.map_or(0, |d| d.len());
+        }
+        data.map_or(ptr::null(), |d| d.as_ptr())
+    })
+}
+
+#[test]
+fn test_ffi() {
+    let icc = crate::profile::standards::srgb().to_buffer().unwrap();
+    unsafe {
+        let profile = cmx_profile_from_bytes(icc.as_ptr(), icc.len());
+        assert!(!profile.is_null());
+        assert_eq!(cmx_profile_version(profile), 0x04300000);
+        assert_eq!(&cmx_profile_class(profile).to_be_bytes(), b"mntr");
+        assert_eq!(&cmx_profile_color_space(profile).to_be_bytes(), b"RGB ");
+        assert_eq!(&cmx_profile_pcs(profile).to_be_bytes(), b"XYZ ");
+
+        let n = cmx_profile_tag_count(profile);
+        assert!(n > 0);
+        let mut len = 0;
+        let data = cmx_profile_tag_data(profile, 0, &mut len);
+        let bytes = std::slice::from_raw_parts(data, len);
+        assert_eq!(u32::from_be_bytes(bytes[0..4].try_into().unwrap()), cmx_profile_tag_type(profile, 0));
+        assert!(icc.windows(len).any(|w| w == bytes));
+        assert!(cmx_profile_tag_data(profile, n, &mut len).is_null() && len == 0);
+        assert_eq!(cmx_profile_tag_signature(profile, n), 0);
+        cmx_profile_free(profile);
+
+        assert!(cmx_profile_from_bytes(icc.as_ptr(), 10).is_null());
+        assert_eq!(cmx_profile_version(ptr::null()), 0);
+    }
+    assert!(guard(ptr::null::<u8>(), || panic!("caught")).is_null());
+}
@@ -0,0 +1,207 @@
+/*!
+  Gamut boundary descriptors, using the segment maxima method: the CIELAB color space is divided into
+  segments, by the polar and hue angles of spherical coordinates centered at L* 50, and the largest
+  distance from the center of the colors of a device in each segment describes its gamut boundary.
+
+  The colors on the surface of the device space are sampled with the profile's
+  [transform](crate::transform) to the PCS, for the media-relative colorimetric intent, through its
+  matrix/TRC, gray TRC, lookup table, or multi process element, tags. For devices with more than
+  three channels, such as CMYK printers, the two dimensional faces of the device space are sampled,
+  with fewer samples for each face. A descriptor can be stored as a triangle mesh in a version 5 'gbd ' tag, and used to calculate, and compare,
+  gamut volumes.
+*/
+
+use std::f64::consts::PI;
+
+use crate::common::Result;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::{Profile, RenderingIntent};
+use crate::progress::{Counter, NoProgress, Progress};
+use crate::tags::TagData;
+use crate::transform::DeviceTransform;
+
+// Number of segments along the polar angle, from the L* axis, and along the hue angle.
+const POLAR_SEGMENTS: usize = 16;
+const HUE_SEGMENTS: usize = 32;
+// Number of samples along each edge of the faces of the device space.
+const SAMPLES: usize = 65;
+const CENTER: [f64; 3] = [50.0, 0.0, 0.0];
+
+#[derive(Debug, Clone)]
+pub struct GamutBoundary {
+    /// CIELAB value with the largest distance from the center, in each segment, by polar angle, and
+    /// hue angle, segment; the center for segments without colors.
+    pub maxima: Vec<[[f64; 3]; HUE_SEGMENTS]>,
+    /// Lightness range, of the colors on the L* axis.
+    pub lightness: [f64; 2],
+}
+
+impl GamutBoundary {
+    pub fn from_profile(profile: &Profile) -> Result<Self> {
+        Self::from_profile_with_progress(profile, &mut NoProgress)
+    }
+
+    /// Gamut boundary of a profile, as [GamutBoundary::from_profile], reporting a step for each row
+    /// of samples, and stopping with an error if the operation is cancelled.
+    pub fn from_profile_with_progress(profile: &Profile, progress: &mut dyn Progress) -> Result<Self> {
+        let model = DeviceTransform::new(profile, RenderingIntent::MediaRelativeColorimetric, true)?;
+        let mut boundary = Self { maxima: vec![[CENTER; HUE_SEGMENTS]; POLAR_SEGMENTS], lightness: [100.0, 0.0] };
+        let channels = model.channels();
+        let lab = |device: &[f64]| model.to_xyz(device).map(xyz_to_lab).ok_or("device values not matching the transform");
+        if channels == 1 {
+            let mut counter = Counter::new(progress, SAMPLES);
+            for i in 0..SAMPLES {
+                boundary.add(lab(&[i as f64 / (SAMPLES - 1) as f64])?);
+                counter.step()?;
+            }
+            return Ok(boundary);
+        }
+        // faces of the device space, with two free channels, and the others at 0.0 or 1.0, sampled with
+        // about as many values in total as the six faces of an RGB cube
+        let pairs: Vec<(usize, usize)> = (0..channels).flat_map(|a| (a + 1..channels).map(move |b| (a, b))).collect();
+        let corners = 1usize << (channels - 2);
+        let faces = pairs.len() * corners;
+        let samples = (((6 * SAMPLES * SAMPLES) as f64 / faces as f64).sqrt() as usize).clamp(5, SAMPLES);
+        let steps: Vec<f64> = (0..samples).map(|i| i as f64 / (samples - 1) as f64).collect();
+        let mut counter = Counter::new(progress, faces * samples);
+        for &(a, b) in &pairs {
+            for corner in 0..corners {
+                // the fixed channels, in order, take the bits of the corner index
+                let mut device = vec![0.0; channels];
+                for (bit, c) in (0..channels).filter(|&c| c != a && c != b).enumerate() {
+                    device[c] = (corner >> bit & 1) as f64;
+                }
+                for &s in &steps {
+                    for &t in &steps {
+                        (device[a], device[b]) = (s, t);
+                        boundary.add(lab(&device)?);
+                    }
+                    counter.step()?;
+                }
+            }
+        }
+        Ok(boundary)
+    }
+
+    fn add(&mut self, lab: [f64; 3]) {
+        let (r, polar, hue) = spherical(lab);
+        let i = ((polar / PI * POLAR_SEGMENTS as f64) as usize).min(POLAR_SEGMENTS - 1);
+        let j = ((hue / (2.0 * PI) * HUE_SEGMENTS as f64) as usize).min(HUE_SEGMENTS - 1);
+        if r > spherical(self.maxima[i][j]).0 {
+            self.maxima[i][j] = lab;
+        }
+        self.lightness = [self.lightness[0].min(lab[0]), self.lightness[1].max(lab[0])];
+    }
+
+    /// Approximate volume of the gamut, in cubic CIELAB units, as the volume enclosed by its mesh.
+    pub fn volume(&self) -> f64 {
+        mesh_volume(&self.mesh())
+    }
+
+    /// Approximate volume of the intersection of two gamuts, using the maximum closest to the center
+    /// in each segment.
+    pub fn intersection_volume(&self, other: &GamutBoundary) -> f64 {
+        let mut intersection = self.clone();
+        for (a, b) in intersection.maxima.iter_mut().flatten().zip(other.maxima.iter().flatten()) {
+            if spherical(*b).0 < spherical(*a).0 {
+                *a = *b;
+            }
+        }
+        intersection.lightness = [self.lightness[0].max(other.lightness[0]), self.lightness[1].min(other.lightness[1])];
+        intersection.volume()
+    }
+
+    /// Triangle mesh of the boundary, with the maxima of the segments, and the ends of the lightness
+    /// range, as vertices.
+    pub fn mesh(&self) -> (Vec<[f64; 3]>, Vec<[u32; 3]>) {
+        let mut vertices = vec![[self.lightness[1], 0.0, 0.0]];
+        vertices.extend(self.maxima.iter().flatten());
+        vertices.push([self.lightness[0], 0.0, 0.0]);
+
+        let bottom = vertices.len() as u32 - 1;
+        let vertex = |i: usize, j: usize| (1 + i * HUE_SEGMENTS + j % HUE_SEGMENTS) as u32;
+        let mut triangles = Vec::new();
+        for j in 0..HUE_SEGMENTS {
+            triangles.push([0, vertex(0, j), vertex(0, j + 1)]);
+            for i in 0..POLAR_SEGMENTS - 1 {
+                triangles.push([vertex(i, j), vertex(i + 1, j), vertex(i + 1, j + 1)]);
+                triangles.push([vertex(i, j), vertex(i + 1, j + 1), vertex(i, j + 1)]);
+            }
+            triangles.push([vertex(POLAR_SEGMENTS - 1, j), bottom, vertex(POLAR_SEGMENTS - 1, j + 1)]);
+        }
+        (vertices, triangles)
+    }
+
+    /// Gamut boundary description tag data, in the version 5 'gbd ' type encoding, with CIELAB PCS
+    /// values, and without device values, for the vertices.
+    pub fn to_tag_data(&self) -> TagData {
+        let (vertices, triangles) = self.mesh();
+        let mut v = Vec::new();
+        v.extend(3u16.to_be_bytes()); // PCS channels
+        v.extend(0u16.to_be_bytes()); // device channels
+        v.extend((vertices.len() as u32).to_be_bytes());
+        v.extend((triangles.len() as u32).to_be_bytes());
+        v.extend(triangles.iter().flatten().flat_map(|i| i.to_be_bytes()));
+        v.extend(vertices.iter().flatten().flat_map(|&x| (x as f32).to_be_bytes()));
+        TagData::GamutBoundaryDescription(v)
+    }
+}
+
+// Sum of the volumes of the tetrahedra formed by the triangles and the center.
+fn mesh_volume((vertices, triangles): &(Vec<[f64; 3]>, Vec<[u32; 3]>)) -> f64 {
+    let v = |i: u32| {
+        let p = vertices[i as usize];
+        [p[0] - CENTER[0], p[1], p[2]]
+    };
+    let det = |[a, b, c]: [[f64; 3]; 3]| {
+        a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0]) + a[2] * (b[0] * c[1] - b[1] * c[0])
+    };
+    triangles.iter().map(|t| det(t.map(v)).abs() / 6.0).sum()
+}
+
+// Distance from the center, polar angle from the L* axis, and hue angle, of a CIELAB value.
+fn spherical([l, a, b]: [f64; 3]) -> (f64, f64, f64) {
+    let l = l - CENTER[0];
+    let r = (l * l + a * a + b * b).sqrt();
+    let polar = if r > 0.0 { (l / r).clamp(-1.0, 1.0).acos() } else { 0.0 };
+    (r, polar, b.atan2(a).rem_euclid(2.0 * PI))
+}
+
+pub(crate) fn xyz_to_lab(xyz: [f64; 3]) -> [f64; 3] {
+    let f = |t: f64| if t > (6.0f64 / 29.0).powi(3) { t.cbrt() } else { t / (3.0 * (6.0f64 / 29.0).powi(2)) + 4.0 / 29.0 };
+    let [x, y, z] = [0, 1, 2].map(|i| f(xyz[i] / PCS_ILLUMINANT[i]));
+    [116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z)]
+}
+
+#[test]
+fn test_gamut_boundary() {
+    use crate::profile::standards;
+    let srgb = GamutBoundary::from_profile(&standards::srgb()).unwrap();
+    let p3 = GamutBoundary::from_profile(&standards::display_p3()).unwrap();
+    // sRGB has a gamut volume of about 833,000 cubic CIELAB units
+    assert!((srgb.volume() / 833_000.0 - 1.0).abs() < 0.05, "{}", srgb.volume());
+    assert!(p3.volume() > srgb.volume() * 1.2);
+    assert!(p3.intersection_volume(&srgb) > 0.95 * srgb.volume());
+    assert!((srgb.lightness[1] - 100.0).abs() < 1E-3 && srgb.lightness[0].abs() < 1E-3);
+
+    // the colorimetric lookup table, and not the matrix/TRC tags, defines the gamut
+    use crate::tags::lut::{lut_atob, ClutBuilder};
+    use crate::tags::Curve;
+    let model = crate::transform::MatrixTrc::from_profile(&standards::display_p3()).unwrap();
+    let clut = ClutBuilder::new(3, 3, 17).fill(|rgb| crate::color::pcs::xyz_to_normalized(model.to_xyz(rgb)).to_vec()).unwrap();
+    let curves = || (0..3).map(|_| TagData::Curve(Curve(Vec::new()))).collect();
+    let atob = lut_atob(curves(), clut, Vec::new(), None, curves()).unwrap();
+    let lut = standards::srgb().with_tag(crate::signatures::tag::TagSignature::AToB1Tag, TagData::LutAToB(atob));
+    let lut = GamutBoundary::from_profile(&Profile::from_buffer(&lut.to_buffer().unwrap()).unwrap()).unwrap();
+    assert!((lut.volume() / p3.volume() - 1.0).abs() < 0.02, "{} {}", lut.volume(), p3.volume());
+
+    let gray = GamutBoundary::from_profile(&standards::gray_gamma22()).unwrap();
+    assert!(gray.volume() < 1000.0);
+
+    let TagData::GamutBoundaryDescription(data) = srgb.to_tag_data() else { unreachable!() };
+    let (vertices, triangles) = srgb.mesh();
+    assert_eq!(data.len(), 12 + 12 * triangles.len() + 12 * vertices.len());
+    assert!(triangles.iter().flatten().all(|&i| (i as usize) < vertices.len()));
+    let profile = standards::srgb().with_tag(crate::signatures::tag::TagSignature::GamutBoundaryDescription0Tag, srgb.to_tag_data());
+    assert!(Profile::from_buffer(&profile.to_buffer().unwrap()).is_ok());
+}
@@ -0,0 +1,37 @@
+/*!
+  White points of the CIE standard illuminants, for the CIE 1931 standard observer, as XYZ tristimulus
+  values, normalized to a luminance Y of 1.0, and as xy chromaticity coordinates; and the profile
+  connection space illuminant.
+
+  The PCS illuminant differs slightly from the CIE D50 white point: it is the value specified by the
+  ICC, and stored in the header of each profile, which should be used for PCS values, and for the
+  media white point of version 4 profiles.
+*/
+
+/// PCS illuminant, the ICC's D50 white point.
+pub const PCS_ILLUMINANT: [f64; 3] = [0.9642, 1.0, 0.8249];
+
+pub const D50_XYZ: [f64; 3] = [0.96422, 1.0, 0.82521];
+pub const D50_XY: [f64; 2] = [0.3457, 0.3585];
+
+pub const D65_XYZ: [f64; 3] = [0.95047, 1.0, 1.08883];
+pub const D65_XY: [f64; 2] = [0.3127, 0.3290];
+
+pub const A_XYZ: [f64; 3] = [1.09850, 1.0, 0.35585];
+pub const A_XY: [f64; 2] = [0.44757, 0.40745];
+
+pub const E_XYZ: [f64; 3] = [1.0, 1.0, 1.0];
+pub const E_XY: [f64; 2] = [1.0 / 3.0, 1.0 / 3.0];
+
+/// XYZ tristimulus values, with a luminance Y of 1.0, for xy chromaticity coordinates.
+pub fn xy_to_xyz([x, y]: [f64; 2]) -> [f64; 3] {
+    [x / y, 1.0, (1.0 - x - y) / y]
+}
+
+#[test]
+fn test_white_points() {
+    for (xyz, xy) in [(D50_XYZ, D50_XY), (D65_XYZ, D65_XY), (A_XYZ, A_XY), (E_XYZ, E_XY)] {
+        let v = xy_to_xyz(xy);
+        assert!(xyz.iter().zip(v).all(|(a, b)| (a - b).abs() < 5E-4), "{xyz:?} {v:?}");
+    }
+}
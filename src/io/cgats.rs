@@ -0,0 +1,264 @@
+/*!
+  CGATS.17, and IT8.7, measurement files, as written by ArgyllCMS, i1Profiler, and other profiling
+  tools.
+
+  A file holds one or more tables, each with a file type identifier, keywords with values, the names
+  of its data fields, and the data, as rows of values. [CgatsTable::measurements] collects the
+  device values, the XYZ and CIELAB values, and the spectral values of the patches of a table, using
+  the standard field names, such as `RGB_R`, `CMYK_C`, `XYZ_X`, `LAB_L`, and `SPEC_380` or
+  `SPECTRAL_NM380`.
+
+  ```
+  let ti3 = "CTI3\nNUMBER_OF_FIELDS 4\nBEGIN_DATA_FORMAT\nSAMPLE_ID RGB_R XYZ_X LAB_L\nEND_DATA_FORMAT\n\
+             NUMBER_OF_SETS 1\nBEGIN_DATA\n1 100.0 96.42 100.0\nEND_DATA\n";
+  let cgats = cmx::io::cgats::Cgats::parse(ti3).unwrap();
+  assert_eq!(cgats.tables[0].file_type, "CTI3");
+  ```
+*/
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::common::Result;
+
+#[derive(Debug, Clone, Default)]
+pub struct Cgats {
+    pub tables: Vec<CgatsTable>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CgatsTable {
+    pub file_type: String,
+    /// Keywords, with their values, without quotes, in the order of the file.
+    pub keywords: Vec<(String, String)>,
+    pub fields: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Device, colorimetric, and spectral values of a measured patch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    pub id: String,
+    /// Device values, as in the file, typically in the range from 0 to 100.
+    pub device: Vec<f64>,
+    pub xyz: Option<[f64; 3]>,
+    pub lab: Option<[f64; 3]>,
+    /// Spectral values, at the wavelengths of the measurement set.
+    pub spectral: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeasurementSet {
+    /// Device color space, from the device field names, such as `RGB`, or `CMYK`.
+    pub device_space: Option<String>,
+    /// Wavelengths of the spectral values, in nanometers.
+    pub wavelengths: Vec<f64>,
+    pub patches: Vec<Patch>,
+}
+
+// Device field name prefixes, with their channel names.
+const DEVICE_SPACES: &[(&str, &[&str])] = &[
+    ("RGB", &["R", "G", "B"]),
+    ("CMYK", &["C", "M", "Y", "K"]),
+    ("CMY", &["C", "M", "Y"]),
+    ("GRAY", &["G"]),
+];
+
+// Splits a line in tokens, separated by whitespace, keeping quoted strings, without their quotes, as
+// single tokens; comments, starting with '#', are removed.
+fn tokens(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '#' {
+            break;
+        } else if c == '"' {
+            chars.next();
+            tokens.push(chars.by_ref().take_while(|&c| c != '"').collect());
+        } else {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+impl Cgats {
+    #[cfg(feature = "fs")]
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        enum Section {
+            Header,
+            Format,
+            Data,
+        }
+        let mut tables: Vec<CgatsTable> = Vec::new();
+        let mut section = Section::Header;
+        let mut data: Vec<String> = Vec::new();
+        for line in text.lines() {
+            let t = tokens(line);
+            let Some(first) = t.first() else { continue };
+            match section {
+                Section::Format if first == "END_DATA_FORMAT" => section = Section::Header,
+                Section::Format => tables.last_mut().ok_or("data format without table")?.fields.extend(t),
+                Section::Data if first == "END_DATA" => {
+                    let table = tables.last_mut().ok_or("data without table")?;
+                    if table.fields.is_empty() || !data.len().is_multiple_of(table.fields.len()) {
+                        return Err(format!("{} data values for {} fields", data.len(), table.fields.len()).into());
+                    }
+                    table.rows = data.chunks(table.fields.len()).map(|r| r.to_vec()).collect();
+                    data.clear();
+                    section = Section::Header;
+                }
+                Section::Data => data.extend(t),
+                Section::Header => match first.as_str() {
+                    "BEGIN_DATA_FORMAT" => section = Section::Format,
+                    "BEGIN_DATA" => section = Section::Data,
+                    // the first line of a table, with only the file type identifier
+                    _ if t.len() == 1 && tables.last().is_none_or(|table| !table.rows.is_empty()) => {
+                        tables.push(CgatsTable { file_type: first.clone(), ..Default::default() })
+                    }
+                    "KEYWORD" => (),
+                    _ => {
+                        let table = tables.last_mut().ok_or("missing file type identifier")?;
+                        table.keywords.push((first.clone(), t[1..].join(" ")));
+                    }
+                },
+            }
+        }
+        if !matches!(section, Section::Header) {
+            return Err("unexpected end of CGATS data".into());
+        }
+        for table in &tables {
+            if let Some(n) = table.keyword("NUMBER_OF_SETS").and_then(|n| n.parse::<usize>().ok()) {
+                if n != table.rows.len() {
+                    return Err(format!("{} data sets, NUMBER_OF_SETS is {}", table.rows.len(), n).into());
+                }
+            }
+        }
+        Ok(Self { tables })
+    }
+}
+
+impl CgatsTable {
+    /// Value of the first occurrence of a keyword.
+    pub fn keyword(&self, name: &str) -> Option<&str> {
+        self.keywords.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn column(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|f| f.eq_ignore_ascii_case(field))
+    }
+
+    /// Values of a field, as numbers.
+    pub fn values(&self, field: &str) -> Result<Option<Vec<f64>>> {
+        let Some(i) = self.column(field) else { return Ok(None) };
+        let values = self.rows.iter().map(|r| r[i].parse::<f64>().map_err(|_| format!("invalid {} value '{}'", field, r[i])));
+        Ok(Some(values.collect::<std::result::Result<_, _>>()?))
+    }
+
+    pub fn measurements(&self) -> Result<MeasurementSet> {
+        let mut set = MeasurementSet::default();
+        let columns = |names: &[String]| -> Result<Option<Vec<Vec<f64>>>> {
+            let values: Vec<Option<Vec<f64>>> = names.iter().map(|n| self.values(n)).collect::<Result<_>>()?;
+            Ok(values.into_iter().collect())
+        };
+
+        let mut device = None;
+        for (space, channels) in DEVICE_SPACES {
+            let names: Vec<String> = channels.iter().map(|c| format!("{}_{}", space, c)).collect();
+            if let Some(values) = columns(&names)? {
+                set.device_space = Some(space.to_string());
+                device = Some(values);
+                break;
+            }
+        }
+        let triple = |v: Option<Vec<Vec<f64>>>| v.map(|v| (0..self.rows.len()).map(|i| [v[0][i], v[1][i], v[2][i]]).collect::<Vec<_>>());
+        let xyz = triple(columns(&["XYZ_X", "XYZ_Y", "XYZ_Z"].map(String::from))?);
+        let lab = triple(columns(&["LAB_L", "LAB_A", "LAB_B"].map(String::from))?);
+
+        let mut spectral: Vec<(f64, usize)> = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let upper = field.to_ascii_uppercase();
+            let nm = ["SPECTRAL_NM", "SPECTRAL_", "SPEC_", "NM_", "NM"].iter().find_map(|p| upper.strip_prefix(p)?.parse::<f64>().ok());
+            if let Some(nm) = nm {
+                spectral.push((nm, i));
+            }
+        }
+        spectral.sort_by(|a, b| a.0.total_cmp(&b.0));
+        set.wavelengths = spectral.iter().map(|(nm, _)| *nm).collect();
+
+        let id = ["SAMPLE_ID", "SAMPLE_NAME", "SAMPLEID"].iter().find_map(|f| self.column(f));
+        for (i, row) in self.rows.iter().enumerate() {
+            let spectral = spectral.iter().map(|&(_, c)| row[c].parse::<f64>().map_err(|_| format!("invalid spectral value '{}'", row[c])));
+            set.patches.push(Patch {
+                id: id.map_or_else(|| (i + 1).to_string(), |c| row[c].clone()),
+                device: device.as_ref().map_or_else(Vec::new, |d| d.iter().map(|c| c[i]).collect()),
+                xyz: xyz.as_ref().map(|v| v[i]),
+                lab: lab.as_ref().map(|v| v[i]),
+                spectral: spectral.collect::<std::result::Result<_, _>>()?,
+            });
+        }
+        Ok(set)
+    }
+}
+
+#[test]
+fn test_cgats() {
+    let ti3 = r#"CTI3   # ArgyllCMS
+DESCRIPTOR "Argyll Calibration Target chart information 3"
+ORIGINATOR "Argyll target"
+KEYWORD "DEVICE_CLASS"
+DEVICE_CLASS "DISPLAY"
+NUMBER_OF_FIELDS 10
+BEGIN_DATA_FORMAT
+SAMPLE_ID RGB_R RGB_G RGB_B XYZ_X XYZ_Y XYZ_Z
+SPEC_380 SPEC_390 SPEC_400
+END_DATA_FORMAT
+NUMBER_OF_SETS 2
+BEGIN_DATA
+1 100.00 100.00 100.00 95.106 100.00 108.84 0.1 0.2 0.3
+2 0.0000 0.0000 0.0000 0.3 0.31
+ 0.33 0.0 0.0 0.0
+END_DATA
+
+CAL
+NUMBER_OF_FIELDS 2
+BEGIN_DATA_FORMAT
+RGB_I RGB_R
+END_DATA_FORMAT
+NUMBER_OF_SETS 1
+BEGIN_DATA
+0.0 0.0
+END_DATA
+"#;
+    let cgats = Cgats::parse(ti3).unwrap();
+    assert_eq!(cgats.tables.len(), 2);
+    let table = &cgats.tables[0];
+    assert_eq!(table.keyword("DESCRIPTOR"), Some("Argyll Calibration Target chart information 3"));
+    assert_eq!(table.keyword("DEVICE_CLASS"), Some("DISPLAY"));
+    let set = table.measurements().unwrap();
+    assert_eq!(set.device_space.as_deref(), Some("RGB"));
+    assert_eq!(set.wavelengths, [380.0, 390.0, 400.0]);
+    assert_eq!(set.patches[1].id, "2");
+    assert_eq!(set.patches[1].xyz, Some([0.3, 0.31, 0.33]));
+    assert_eq!(set.patches[0].device, [100.0; 3]);
+    assert_eq!(set.patches[0].spectral, [0.1, 0.2, 0.3]);
+    assert!(set.patches[0].lab.is_none());
+    assert_eq!(cgats.tables[1].file_type, "CAL");
+
+    assert!(Cgats::parse("CTI3\nNUMBER_OF_SETS 2\nBEGIN_DATA_FORMAT\nA B\nEND_DATA_FORMAT\nBEGIN_DATA\n1 2\nEND_DATA\n").is_err());
+    assert!(Cgats::parse("CTI3\nBEGIN_DATA_FORMAT\nA B\nEND_DATA_FORMAT\nBEGIN_DATA\n1 2 3\nEND_DATA\n").is_err());
+}
@@ -0,0 +1,304 @@
+/*!
+  CxF3, Color Exchange Format (ISO 17972-1), files, as used for spot color libraries, and the
+  payload of the 'CxF ' tag of version 5 profiles.
+
+  Only the parts of the format used for named colors are read: the objects of the object collection,
+  with their names, CIELAB values, reflectance spectra, and CMYK, or RGB, device values. The
+  wavelength increment of a spectrum is taken from the `WavelengthRange` element of its color
+  specification, and is 10 nm if not specified.
+
+  Named colors with CIELAB values can be converted to a [NamedColor2] tag; colors with only spectral
+  values are skipped, as their CIELAB values depend on the observer and illuminant used.
+*/
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::{
+    named_color2::{NamedColor2, NamedColorPcs},
+    TagData,
+};
+
+const NAMESPACE: &str = "http://colorexchangeformat.com/CxF3-core";
+const DEFAULT_INCREMENT: f64 = 10.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cxf {
+    pub objects: Vec<CxfObject>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CxfObject {
+    pub name: String,
+    pub id: String,
+    /// CIELAB values, for the D50 illuminant and the CIE 1931 observer.
+    pub lab: Option<[f64; 3]>,
+    pub spectrum: Option<CxfSpectrum>,
+    /// CMYK, or RGB, device values, in the range from 0.0 to 1.0.
+    pub device: Vec<f64>,
+}
+
+/// Reflectance spectrum, with values in the range from 0.0 to 1.0, starting at a wavelength, with a
+/// fixed increment, in nanometers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CxfSpectrum {
+    pub start: f64,
+    pub increment: f64,
+    pub values: Vec<f64>,
+}
+
+// Element of an XML document, with its name without namespace prefix.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|e| e.name == name)
+    }
+
+    fn descendants<'a>(&'a self, name: &'a str) -> Box<dyn Iterator<Item = &'a Element> + 'a> {
+        Box::new(self.children.iter().flat_map(move |e| {
+            let this = (e.name == name).then_some(e).into_iter();
+            this.chain(e.descendants(name))
+        }))
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>> {
+        match self.child(name) {
+            Some(e) => Ok(Some(e.text.trim().parse().map_err(|_| format!("invalid {} value '{}'", name, e.text.trim()))?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_owned()
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Parses an XML document, ignoring its declaration, comments, and processing instructions.
+fn parse_xml(xml: &str) -> Result<Element> {
+    let mut stack = vec![Element::default()];
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let text = &rest[..start];
+        if !text.trim().is_empty() {
+            stack.last_mut().ok_or("invalid XML")?.text.push_str(&unescape(text));
+        }
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = &comment[comment.find("-->").ok_or("unterminated XML comment")? + 3..];
+            continue;
+        }
+        let end = rest.find('>').ok_or("unterminated XML tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let element = stack.pop().ok_or("invalid XML")?;
+            if element.name != local_name(name.trim()) {
+                return Err(format!("unexpected closing XML tag '{}'", name).into());
+            }
+            stack.last_mut().ok_or("unbalanced XML tags")?.children.push(element);
+            continue;
+        }
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(t) => (t, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut element = Element { name: local_name(&tag[..name_end]), ..Default::default() };
+        let mut attributes = tag[name_end..].trim();
+        while let Some(eq) = attributes.find('=') {
+            let key = attributes[..eq].trim();
+            let value = attributes[eq + 1..].trim_start();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'').ok_or("unquoted XML attribute")?;
+            let close = value[1..].find(quote).ok_or("unterminated XML attribute")? + 1;
+            element.attributes.push((local_name(key), unescape(&value[1..close])));
+            attributes = value[close + 1..].trim_start();
+        }
+        if empty {
+            stack.last_mut().ok_or("invalid XML")?.children.push(element);
+        } else {
+            stack.push(element);
+        }
+    }
+    match (stack.pop(), stack.is_empty()) {
+        (Some(root), true) => Ok(root),
+        _ => Err("unbalanced XML tags".into()),
+    }
+}
+
+impl Cxf {
+    #[cfg(feature = "fs")]
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(xml: &str) -> Result<Self> {
+        let root = parse_xml(xml)?;
+        let cxf = root.child("CxF").ok_or("not a CxF3 document")?;
+        // wavelength increments, by color specification identifier
+        let increments: Vec<(&str, f64)> = cxf
+            .descendants("ColorSpecification")
+            .filter_map(|spec| {
+                let range = spec.descendants("WavelengthRange").next()?;
+                Some((spec.attribute("Id")?, range.attribute("Increment")?.parse().ok()?))
+            })
+            .collect();
+
+        let mut objects = Vec::new();
+        for object in cxf.descendants("Object") {
+            let mut o = CxfObject {
+                name: object.attribute("Name").unwrap_or_default().to_owned(),
+                id: object.attribute("Id").unwrap_or_default().to_owned(),
+                ..Default::default()
+            };
+            if let Some(lab) = object.descendants("ColorCIELab").next() {
+                if let (Some(l), Some(a), Some(b)) = (lab.number("L")?, lab.number("A")?, lab.number("B")?) {
+                    o.lab = Some([l, a, b]);
+                }
+            }
+            if let Some(spectrum) = object.descendants("ReflectanceSpectrum").next() {
+                let values = spectrum.text.split_whitespace().map(|v| v.parse::<f64>().map_err(|_| format!("invalid spectral value '{}'", v)));
+                let spec = spectrum.attribute("ColorSpecification").unwrap_or_default();
+                o.spectrum = Some(CxfSpectrum {
+                    start: spectrum.attribute("StartWL").and_then(|v| v.parse().ok()).ok_or("reflectance spectrum without a start wavelength")?,
+                    increment: increments.iter().find(|(id, _)| *id == spec).map_or(DEFAULT_INCREMENT, |(_, inc)| *inc),
+                    values: values.collect::<std::result::Result<_, _>>()?,
+                });
+            }
+            if let Some(cmyk) = object.descendants("ColorCMYK").next() {
+                let channels = ["Cyan", "Magenta", "Yellow", "Black"].map(|c| cmyk.number(c));
+                o.device = channels.into_iter().map(|v| Ok(v?.unwrap_or(0.0) / 100.0)).collect::<Result<_>>()?;
+            } else if let Some(rgb) = object.descendants("ColorSRGB").chain(object.descendants("ColorRGB")).next() {
+                let max = rgb.attribute("MaxValue").and_then(|v| v.parse().ok()).unwrap_or(255.0);
+                let channels = ["R", "G", "B"].map(|c| rgb.number(c));
+                o.device = channels.into_iter().map(|v| Ok(v?.unwrap_or(0.0) / max)).collect::<Result<_>>()?;
+            }
+            objects.push(o);
+        }
+        Ok(Self { objects })
+    }
+
+    /// CxF3 document, with a single color specification for the spectra, using the increment of
+    /// the first spectrum.
+    pub fn to_xml(&self) -> String {
+        let increment = self.objects.iter().find_map(|o| o.spectrum.as_ref()).map_or(DEFAULT_INCREMENT, |s| s.increment);
+        let mut xml = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<cc:CxF xmlns:cc=\"{}\">\n  <cc:Resources>\n    <cc:ObjectCollection>\n", NAMESPACE);
+        for o in &self.objects {
+            xml += &format!("      <cc:Object ObjectType=\"Standard\" Name=\"{}\" Id=\"{}\">\n        <cc:ColorValues>\n", escape(&o.name), escape(&o.id));
+            if let Some([l, a, b]) = o.lab {
+                xml += &format!("          <cc:ColorCIELab ColorSpecification=\"CSD50-2\"><cc:L>{}</cc:L><cc:A>{}</cc:A><cc:B>{}</cc:B></cc:ColorCIELab>\n", l, a, b);
+            }
+            if let Some(s) = &o.spectrum {
+                let values: Vec<String> = s.values.iter().map(f64::to_string).collect();
+                xml += &format!("          <cc:ReflectanceSpectrum ColorSpecification=\"CSSpectral\" StartWL=\"{}\">{}</cc:ReflectanceSpectrum>\n", s.start, values.join(" "));
+            }
+            xml += "        </cc:ColorValues>\n";
+            let device = match o.device.len() {
+                4 => Some(("ColorCMYK", ["Cyan", "Magenta", "Yellow", "Black"].as_slice(), 100.0)),
+                3 => Some(("ColorSRGB", ["R", "G", "B"].as_slice(), 255.0)),
+                _ => None,
+            };
+            if let Some((element, channels, max)) = device {
+                let values: String = channels.iter().zip(&o.device).map(|(c, v)| format!("<cc:{c}>{}</cc:{c}>", v * max)).collect();
+                xml += &format!("        <cc:DeviceColorValues><cc:{element}>{values}</cc:{element}></cc:DeviceColorValues>\n");
+            }
+            xml += "      </cc:Object>\n";
+        }
+        xml += "    </cc:ObjectCollection>\n    <cc:ColorSpecificationCollection>\n";
+        xml += "      <cc:ColorSpecification Id=\"CSD50-2\"><cc:TristimulusSpec><cc:Illuminant>D50</cc:Illuminant><cc:Observer>2_Degree</cc:Observer></cc:TristimulusSpec></cc:ColorSpecification>\n";
+        xml += &format!("      <cc:ColorSpecification Id=\"CSSpectral\"><cc:MeasurementSpec><cc:WavelengthRange Increment=\"{}\"/></cc:MeasurementSpec></cc:ColorSpecification>\n", increment);
+        xml += "    </cc:ColorSpecificationCollection>\n  </cc:Resources>\n</cc:CxF>\n";
+        xml
+    }
+
+    /// Named color tag, with the colors which have CIELAB values, for a profile with a CIELAB PCS.
+    pub fn to_named_color2(&self, prefix: &str, suffix: &str) -> NamedColor2 {
+        self.objects
+            .iter()
+            .filter_map(|o| Some((o, o.lab?)))
+            .fold(NamedColor2::new(prefix, suffix), |nc, (o, lab)| nc.with_color(&o.name, NamedColorPcs::Lab, lab, &o.device))
+    }
+}
+
+impl Profile {
+    /// Adds a 'CxF ' tag, with the document compressed in a zipUtf8Type tag.
+    pub fn with_cxf(self, cxf: &Cxf) -> Self {
+        self.with_tag(TagSignature::CxFTag, TagData::Utf8Zip(vec![cxf.to_xml()]))
+    }
+
+    /// The document of the profile's 'CxF ' tag, if present.
+    pub fn cxf(&self) -> Result<Option<Cxf>> {
+        match self.tag_data(&TagSignature::CxFTag) {
+            Some(TagData::Utf8(v) | TagData::Utf8Zip(v)) => Ok(Some(Cxf::parse(&v.concat())?)),
+            Some(_) => Err("unsupported 'CxF ' tag type".into()),
+            None => Ok(None),
+        }
+    }
+}
+
+#[test]
+fn test_cxf() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<cc:CxF xmlns:cc="http://colorexchangeformat.com/CxF3-core">
+  <!-- spot colors -->
+  <cc:Resources>
+    <cc:ObjectCollection>
+      <cc:Object ObjectType="Standard" Name="Red &amp; Warm" Id="1">
+        <cc:ColorValues>
+          <cc:ColorCIELab ColorSpecification="CS1"><cc:L>48.5</cc:L><cc:A>68.2</cc:A><cc:B>47.0</cc:B></cc:ColorCIELab>
+          <cc:ReflectanceSpectrum ColorSpecification="CS2" StartWL="400">0.05 0.04 0.6</cc:ReflectanceSpectrum>
+        </cc:ColorValues>
+        <cc:DeviceColorValues><cc:ColorCMYK><cc:Cyan>0</cc:Cyan><cc:Magenta>90</cc:Magenta><cc:Yellow>80</cc:Yellow><cc:Black>0</cc:Black></cc:ColorCMYK></cc:DeviceColorValues>
+      </cc:Object>
+      <cc:Object ObjectType="Standard" Name="Spectral only" Id="2">
+        <cc:ColorValues><cc:ReflectanceSpectrum ColorSpecification="CS2" StartWL="400">0.5 0.5 0.5</cc:ReflectanceSpectrum></cc:ColorValues>
+      </cc:Object>
+    </cc:ObjectCollection>
+    <cc:ColorSpecificationCollection>
+      <cc:ColorSpecification Id="CS2"><cc:MeasurementSpec><cc:WavelengthRange StartWL="400" Increment="20"/></cc:MeasurementSpec></cc:ColorSpecification>
+    </cc:ColorSpecificationCollection>
+  </cc:Resources>
+</cc:CxF>"#;
+    let cxf = Cxf::parse(xml).unwrap();
+    assert_eq!(cxf.objects.len(), 2);
+    let red = &cxf.objects[0];
+    assert_eq!(red.name, "Red & Warm");
+    assert_eq!(red.lab, Some([48.5, 68.2, 47.0]));
+    assert_eq!(red.spectrum, Some(CxfSpectrum { start: 400.0, increment: 20.0, values: vec![0.05, 0.04, 0.6] }));
+    assert_eq!(red.device, [0.0, 0.9, 0.8, 0.0]);
+    assert_eq!(Cxf::parse(&cxf.to_xml()).unwrap(), cxf);
+
+    let named = cxf.to_named_color2("", "");
+    assert_eq!(named.colors.len(), 1);
+    let color = named.colors(NamedColorPcs::Lab).next().unwrap();
+    assert!((color.pcs[1] - 68.2).abs() < 0.01);
+
+    let profile = crate::profile::standards::srgb().with_cxf(&cxf);
+    let parsed = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    assert_eq!(parsed.cxf().unwrap(), Some(cxf));
+    assert!(Cxf::parse("<cc:CxF><cc:Object></cc:CxF>").is_err());
+}
@@ -0,0 +1,6 @@
+/*!
+  Reading of measurement data, and other color data files, used to create profiles.
+*/
+
+pub mod cgats;
+pub mod cxf;
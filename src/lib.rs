@@ -16,7 +16,26 @@
   limitations under the License.
 */
 
+pub mod adaptation;
+pub mod color;
 pub mod common;
+pub mod embed;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gamut;
+pub mod illuminants;
+pub mod io;
+#[cfg(feature = "colorimetry")]
+pub mod metamerism;
 pub mod profile;
+pub mod progress;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "system")]
+pub mod system;
 pub mod tags;
+pub mod transform;
 pub mod signatures;
+
+pub use error::Error;
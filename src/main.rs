@@ -0,0 +1,449 @@
+/*!
+  Commandline utility for the inspection, creation, and manipulation of ICC profiles.
+*/
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use cmx::common::Result;
+use cmx::gamut::GamutBoundary;
+use cmx::profile::{dump::DumpOptions, standards, Profile, RenderingIntent};
+use cmx::signatures::tag::TagSignature;
+
+#[derive(Parser)]
+#[command(name = "cmx", version, about = "Inspect, create, and edit ICC profiles")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prints the content of a profile as JSON
+    Dump {
+        /// Profile file, or '-' to read the standard input
+        profile: PathBuf,
+        /// Add this number of samples of the output values of parametric curves
+        #[arg(long, default_value_t = 0)]
+        curve_samples: usize,
+        /// Add the encoded tag data, which allows reading the dump back with 'cmx load'
+        #[arg(long)]
+        raw: bool,
+        /// Write to a file instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes a profile from a JSON dump, created with 'cmx dump --raw'
+    Load {
+        json: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Reject dumps with unknown fields, or without the current schema version
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Creates one of the built-in standard profiles
+    Create {
+        #[arg(value_enum)]
+        standard: Standard,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Changes the description, copyright, or rendering intent of a profile, or removes tags
+    Edit {
+        profile: PathBuf,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        copyright: Option<String>,
+        /// Rendering intent, such as 'perceptual' or 'relative'
+        #[arg(long)]
+        intent: Option<RenderingIntent>,
+        /// Signature of a tag to remove, such as 'dscm'; can be repeated
+        #[arg(long, value_parser = parse_tag_signature)]
+        remove: Vec<TagSignature>,
+        /// Output file, if not given, the input profile is overwritten
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Shows a summary of a profile: class, color spaces, white point, primaries, gamma, and tags
+    Info {
+        profile: PathBuf,
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Plots the tone response curves, and the gamut, of a profile as SVG
+    #[cfg(feature = "plot")]
+    Plot {
+        profile: PathBuf,
+        /// Write to a file instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Shows the differences between two profiles
+    Diff {
+        left: PathBuf,
+        right: PathBuf,
+        /// Print the differences as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Writes the tone response curves and colorant matrices of a profile as CSV
+    ExportCurves {
+        profile: PathBuf,
+        /// Number of samples of each curve
+        #[arg(short, long, default_value_t = 256)]
+        samples: usize,
+        /// Write to a file instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Shows the gamut volume of a profile, and its intersection with the gamut of another profile
+    Gamut {
+        profile: PathBuf,
+        other: Option<PathBuf>,
+    },
+    /// Checks if a profile can be parsed, and written again
+    Validate { profiles: Vec<PathBuf> },
+    /// Parses all profiles in a directory, and its subdirectories, and lists their summaries, or errors
+    Scan {
+        dir: PathBuf,
+        /// Include the profiles embedded in image files
+        #[arg(long)]
+        images: bool,
+        /// Print the reports as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Times parsing, and writing, of a profile; see the benches directory for the full benchmarks
+    #[command(hide = true)]
+    Bench {
+        profile: PathBuf,
+        #[arg(short = 'n', long, default_value_t = 1000)]
+        iterations: u32,
+    },
+    /// Extracts the ICC profile embedded in an image file, or the profiles embedded in a PDF file,
+    /// which, if there are more than one, are written with their object numbers added to the file name
+    Extract {
+        image: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Lists, removes, or copies tags
+    Tag {
+        #[command(subcommand)]
+        command: TagCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommand {
+    /// Lists the tags of a profile, with their type and encoded size
+    Ls { profile: PathBuf },
+    /// Removes tags from a profile
+    Rm {
+        profile: PathBuf,
+        #[arg(required = true, value_parser = parse_tag_signature)]
+        tags: Vec<TagSignature>,
+        /// Output file, if not given, the input profile is overwritten
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Copies tags from one profile to another, replacing existing tags
+    Cp {
+        source: PathBuf,
+        destination: PathBuf,
+        #[arg(required = true, value_parser = parse_tag_signature)]
+        tags: Vec<TagSignature>,
+        /// Output file, if not given, the destination profile is overwritten
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes the encoded data of a tag, with its type signature, to a file
+    Export {
+        profile: PathBuf,
+        #[arg(value_parser = parse_tag_signature)]
+        tag: TagSignature,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Adds, or replaces, a tag with encoded data, as written by the export command
+    Import {
+        profile: PathBuf,
+        #[arg(value_parser = parse_tag_signature)]
+        tag: TagSignature,
+        data: PathBuf,
+        /// Output file, if not given, the input profile is overwritten
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Standard {
+    Srgb,
+    DisplayP3,
+    AdobeRgb,
+    Rec2020,
+    ProphotoRgb,
+    GrayGamma22,
+}
+
+fn parse_tag_signature(s: &str) -> std::result::Result<TagSignature, String> {
+    let bytes: [u8; 4] = format!("{:<4}", s).into_bytes().try_into().map_err(|_| "tag signatures have four characters")?;
+    Ok(TagSignature::new(u32::from_be_bytes(bytes)))
+}
+
+fn four_cc(v: u32) -> String {
+    v.to_be_bytes().iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect()
+}
+
+// Reads a profile from a file, or from the standard input for the path '-'.
+fn read_profile(path: &Path) -> Result<Profile> {
+    if path == Path::new("-") {
+        Ok(Profile::from_reader(std::io::stdin().lock())?)
+    } else {
+        Ok(Profile::from_buffer(&std::fs::read(path)?)?)
+    }
+}
+
+// Writes a profile to a file, or to the standard output for the path '-'.
+fn write_profile(path: &Path, profile: &Profile) -> Result<()> {
+    if path == Path::new("-") {
+        Ok(profile.write_to(std::io::stdout().lock())?)
+    } else {
+        Ok(std::fs::write(path, profile.to_buffer()?)?)
+    }
+}
+
+fn main() -> Result<()> {
+    run(Cli::parse())
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Dump { profile, curve_samples, raw, output } => {
+            let profile = read_profile(&profile)?;
+            let json = serde_json::to_string_pretty(&profile.to_json(DumpOptions { curve_samples, raw_tags: raw })?)?;
+            match output {
+                Some(path) => std::fs::write(path, json)?,
+                None => println!("{}", json),
+            }
+        }
+        Command::Load { json, output, strict } => {
+            let json = std::fs::read_to_string(json)?;
+            let profile = if strict { Profile::from_json_strict(&json)? } else { Profile::from_json(&json)? };
+            write_profile(&output, &profile)?;
+        }
+        Command::Create { standard, output } => {
+            let profile = match standard {
+                Standard::Srgb => standards::srgb(),
+                Standard::DisplayP3 => standards::display_p3(),
+                Standard::AdobeRgb => standards::adobe_rgb(),
+                Standard::Rec2020 => standards::rec2020(),
+                Standard::ProphotoRgb => standards::prophoto_rgb(),
+                Standard::GrayGamma22 => standards::gray_gamma22(),
+            };
+            write_profile(&output, &profile)?;
+        }
+        Command::Edit { profile: path, description, copyright, intent, remove, output } => {
+            let mut profile = read_profile(&path)?;
+            if let Some(description) = description {
+                profile = profile.with_description(&description);
+            }
+            if let Some(copyright) = copyright {
+                profile = profile.with_copyright(&copyright);
+            }
+            if let Some(intent) = intent {
+                profile.rendering_intent = intent;
+            }
+            for signature in remove {
+                profile = profile.without_tag(signature);
+            }
+            write_profile(&output.unwrap_or(path), &profile)?;
+        }
+        Command::Info { profile, json } => {
+            let summary = read_profile(&profile)?.summary();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+            } else {
+                print!("{}", summary);
+            }
+        }
+        #[cfg(feature = "plot")]
+        Command::Plot { profile, output } => {
+            let svg = read_profile(&profile)?.plot_svg()?;
+            match output {
+                Some(path) => std::fs::write(path, svg)?,
+                None => print!("{}", svg),
+            }
+        }
+        Command::Diff { left, right, json } => {
+            let left = read_profile(&left)?;
+            let diff = left.diff(&read_profile(&right)?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print!("{}", diff);
+            }
+        }
+        Command::ExportCurves { profile, samples, output } => {
+            let profile = read_profile(&profile)?;
+            let csv = format!("{}\n{}", profile.trc_table(samples).to_csv(), profile.matrix_table().to_csv());
+            match output {
+                Some(path) => std::fs::write(path, csv)?,
+                None => print!("{}", csv),
+            }
+        }
+        Command::Gamut { profile, other } => {
+            let gamut = |path: &PathBuf| GamutBoundary::from_profile(&read_profile(path)?);
+            let a = gamut(&profile)?;
+            println!("{}: {:.0}", profile.display(), a.volume());
+            if let Some(other) = other {
+                let b = gamut(&other)?;
+                let intersection = a.intersection_volume(&b);
+                println!("{}: {:.0}", other.display(), b.volume());
+                println!("intersection: {:.0} ({:.1}% of {})", intersection, 100.0 * intersection / a.volume(), profile.display());
+            }
+        }
+        Command::Validate { profiles } => {
+            let mut failed = 0;
+            for path in profiles {
+                match std::fs::read(&path).map_err(Into::into).and_then(|buf| Profile::from_buffer(&buf)).and_then(|p| p.to_buffer()) {
+                    Ok(_) => println!("{}: ok", path.display()),
+                    Err(e) => {
+                        failed += 1;
+                        println!("{}: {}", path.display(), e);
+                    }
+                }
+            }
+            if failed > 0 {
+                return Err(format!("{} invalid profile(s)", failed).into());
+            }
+        }
+        Command::Scan { dir, images, json } => {
+            let options = cmx::profile::batch::BatchOptions { images, ..Default::default() };
+            let reports = cmx::profile::batch::analyze_dir(dir, &options, &mut cmx::progress::NoProgress)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&reports)?);
+            } else {
+                for report in &reports {
+                    match (&report.summary, &report.error) {
+                        (Some(s), _) => {
+                            let space = s.colorspace.map_or_else(|| String::from("-"), |c| format!("{:?}", c));
+                            println!("{}: {:?} {} {}", report.path.display(), s.class, space, s.description.as_deref().unwrap_or_default())
+                        }
+                        (_, error) => println!("{}: {}", report.path.display(), error.as_deref().unwrap_or_default()),
+                    }
+                    for warning in &report.warnings {
+                        println!("  {}", warning);
+                    }
+                }
+            }
+            let failed = reports.iter().filter(|r| r.error.is_some()).count();
+            if failed > 0 {
+                return Err(format!("{} of {} profile(s) could not be parsed", failed, reports.len()).into());
+            }
+        }
+        Command::Bench { profile, iterations } => {
+            let icc = std::fs::read(profile)?;
+            let profile = Profile::from_buffer(&icc)?;
+            let time = |name: &str, f: &dyn Fn() -> Result<()>| -> Result<()> {
+                let start = std::time::Instant::now();
+                for _ in 0..iterations {
+                    f()?;
+                }
+                println!("{:<10} {:>12.3?}", name, start.elapsed() / iterations.max(1));
+                Ok(())
+            };
+            time("parse", &|| Ok(Profile::from_buffer(&icc).map(drop)?))?;
+            time("borrowed", &|| cmx::profile::borrowed::ProfileRef::from_buffer(&icc).map(drop))?;
+            time("write", &|| Ok(profile.to_buffer().map(drop)?))?;
+            time("summary", &|| {
+                profile.summary();
+                Ok(())
+            })?;
+        }
+        Command::Extract { image, output } => {
+            let buf = std::fs::read(image)?;
+            if !buf.starts_with(b"%PDF") {
+                let icc = cmx::embed::read_icc(&buf)?.ok_or("no embedded ICC profile found")?;
+                std::fs::write(output, icc)?;
+                return Ok(());
+            }
+            let profiles = cmx::embed::pdf::read_icc_profiles(&buf)?;
+            if profiles.is_empty() {
+                return Err("no embedded ICC profile found".into());
+            }
+            for profile in &profiles {
+                let path = if profiles.len() == 1 {
+                    output.clone()
+                } else {
+                    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+                    let extension = output.extension().map_or(String::new(), |e| format!(".{}", e.to_string_lossy()));
+                    output.with_file_name(format!("{stem}-{}{extension}", profile.object))
+                };
+                let intent = if profile.output_intent { ", output intent" } else { "" };
+                println!("object {}{intent}: {}", profile.object, path.display());
+                std::fs::write(path, &profile.icc)?;
+            }
+        }
+        Command::Tag { command: TagCommand::Ls { profile } } => {
+            let profile = read_profile(&profile)?;
+            for (_, tag) in profile.iter_tags() {
+                let length = tag.to_be_bytes().map_or_else(|e| e.to_string(), |b| b.len().to_string());
+                println!("{}  {}  {}", four_cc(tag.signature().to_u32()), four_cc(tag.type_signature() as u32), length);
+            }
+        }
+        Command::Tag { command: TagCommand::Rm { profile: path, tags, output } } => {
+            let mut profile = read_profile(&path)?;
+            for signature in tags {
+                if !profile.contains_tag(&signature) {
+                    return Err(format!("tag {} not found", four_cc(signature.to_u32())).into());
+                }
+                profile = profile.without_tag(signature);
+            }
+            write_profile(&output.unwrap_or(path), &profile)?;
+        }
+        Command::Tag { command: TagCommand::Cp { source, destination, tags, output } } => {
+            let source = read_profile(&source)?;
+            let profile = read_profile(&destination)?.with_tags_from(source, &tags)?;
+            write_profile(&output.unwrap_or(destination), &profile)?;
+        }
+        Command::Tag { command: TagCommand::Export { profile, tag, output } } => {
+            std::fs::write(output, read_profile(&profile)?.export_tag(&tag)?)?;
+        }
+        Command::Tag { command: TagCommand::Import { profile: path, tag, data, output } } => {
+            let mut profile = read_profile(&path)?;
+            profile.import_tag(tag, &std::fs::read(data)?)?;
+            write_profile(&output.unwrap_or(path), &profile)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_edit() {
+    use cmx::tags::TagData;
+    let dir = std::env::temp_dir().join(format!("cmx-edit-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for version in [[2, 4, 0], [4, 4, 0]] {
+        let path = dir.join(format!("v{}.icc", version[0]));
+        let mut profile = standards::srgb();
+        profile.version = version;
+        write_profile(&path, &profile).unwrap();
+        let path_arg = path.to_str().unwrap();
+        run(Cli::try_parse_from(["cmx", "edit", path_arg, "--description", "edited", "--copyright", "none", "--intent", "relative"]).unwrap()).unwrap();
+        let edited = read_profile(&path).unwrap();
+        assert_eq!(edited.description(None).as_deref(), Some("edited"));
+        assert_eq!(edited.rendering_intent, RenderingIntent::MediaRelativeColorimetric);
+        let (desc, cprt) = (edited.tag_data(&TagSignature::ProfileDescriptionTag), edited.tag_data(&TagSignature::CopyrightTag));
+        if version[0] >= 4 {
+            assert!(matches!((desc, cprt), (Some(TagData::MultiLocalizedUnicode(_)), Some(TagData::MultiLocalizedUnicode(_)))));
+        } else {
+            assert!(matches!((desc, cprt), (Some(TagData::TextDescription(_)), Some(TagData::Text(t))) if t == "none"));
+        }
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
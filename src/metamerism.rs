@@ -0,0 +1,165 @@
+/*!
+  Observer metamerism: how well color matches between pairs of spectral reflectance samples, made
+  for the CIE 1931 standard observer, hold up for other observers.
+
+  For each pair, the CIEDE2000 color difference is calculated for the reference CIE 1931 2° observer,
+  and for the CIE 1964 10°, CIE 2015 2°, and CIE 2015 10° observers; the observer metamerism index of a
+  pair is the largest color difference found for these test observers. Requires the `colorimetry`
+  feature, which provides the color matching functions.
+
+  The samples are given by their reflectance spectra, or, for version 5 profiles with a spectral
+  reflectance, or transmittance, connection space, by device values, with their spectra calculated
+  by the spectral pipeline of the profile.
+*/
+
+use colorimetry::{colorant::Colorant, illuminant::Illuminant, observer::Observer, spectrum::Spectrum};
+
+use crate::common::Result;
+use crate::profile::{Profile, SpectralColorSpace, WavelengthRange};
+use crate::signatures::tag::TagSignature;
+use crate::tags::TagData;
+
+pub const TEST_OBSERVERS: [Observer; 3] = [Observer::Cie1964, Observer::Cie2015, Observer::Cie2015_10];
+
+/// A spectral reflectance sample, with reflectance values, in the range from 0.0 to 1.0, at the
+/// wavelengths of the measurement set it belongs to.
+#[derive(Debug, Clone)]
+pub struct Reflectance {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PairMetamerism {
+    pub reference: String,
+    pub sample: String,
+    /// Color difference for the CIE 1931 observer.
+    pub reference_delta_e: f64,
+    /// Color differences for the test observers, in the order of [TEST_OBSERVERS].
+    pub test_delta_e: [f64; 3],
+}
+
+impl PairMetamerism {
+    pub fn index(&self) -> f64 {
+        self.test_delta_e.iter().copied().fold(0.0, f64::max)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MetamerismReport {
+    pub pairs: Vec<PairMetamerism>,
+}
+
+impl MetamerismReport {
+    /// The pair with the largest observer metamerism index.
+    pub fn worst(&self) -> Option<&PairMetamerism> {
+        self.pairs.iter().max_by(|a, b| a.index().total_cmp(&b.index()))
+    }
+}
+
+fn colorant(wavelengths: &[f64], sample: &Reflectance) -> Result<Colorant> {
+    if sample.values.len() != wavelengths.len() {
+        return Err(format!("sample {} does not match the number of wavelengths", sample.name).into());
+    }
+    let spectrum = Spectrum::linear_interpolate(wavelengths, &sample.values).map_err(|e| e.to_string())?;
+    Ok(Colorant::new(spectrum).map_err(|e| e.to_string())?)
+}
+
+fn delta_e(a: &Colorant, b: &Colorant, illuminant: &Illuminant, observer: Observer) -> Result<f64> {
+    let (lab_a, lab_b) = (a.cielab(Some(illuminant), Some(observer)), b.cielab(Some(illuminant), Some(observer)));
+    Ok(lab_a.ciede2000(&lab_b).map_err(|e| e.to_string())?)
+}
+
+/// Calculates the observer metamerism of pairs of samples, given by their indices in `samples`, for
+/// a measurement set with the given wavelengths, in nanometers, viewed under `illuminant`, or D50 if
+/// not given, as used for ICC profiles.
+pub fn observer_metamerism(
+    wavelengths: &[f64],
+    samples: &[Reflectance],
+    pairs: &[(usize, usize)],
+    illuminant: Option<&Illuminant>,
+) -> Result<MetamerismReport> {
+    let d50 = Illuminant::d50();
+    let illuminant = illuminant.unwrap_or(&d50);
+    let colorants = samples.iter().map(|s| colorant(wavelengths, s)).collect::<Result<Vec<_>>>()?;
+    let mut report = MetamerismReport::default();
+    for &(i, j) in pairs {
+        let (a, b) = (colorants.get(i).ok_or("sample index error")?, colorants.get(j).ok_or("sample index error")?);
+        let mut test_delta_e = [0.0; 3];
+        for (de, observer) in test_delta_e.iter_mut().zip(TEST_OBSERVERS) {
+            *de = delta_e(a, b, illuminant, observer)?;
+        }
+        report.pairs.push(PairMetamerism {
+            reference: samples[i].name.clone(),
+            sample: samples[j].name.clone(),
+            reference_delta_e: delta_e(a, b, illuminant, Observer::Cie1931)?,
+            test_delta_e,
+        });
+    }
+    Ok(report)
+}
+
+/// Calculates the observer metamerism of pairs of device colors, given by their indices in `colors`,
+/// of a version 5 profile with a spectral reflectance, or transmittance, connection space, as
+/// [observer_metamerism]; the spectra of the colors are calculated by the 'D2B0' multi process
+/// element pipeline, or an 'A2B0' tag of this type, at the wavelengths of the spectral connection
+/// space of the header.
+pub fn profile_observer_metamerism(
+    profile: &Profile,
+    colors: &[Vec<f64>],
+    pairs: &[(usize, usize)],
+    illuminant: Option<&Illuminant>,
+) -> Result<MetamerismReport> {
+    if !matches!(profile.spectral_pcs, Some(SpectralColorSpace::Reflectance(_) | SpectralColorSpace::Transmission(_))) {
+        return Err("observer metamerism requires a spectral reflectance, or transmittance, connection space".into());
+    }
+    let wavelengths = profile.spectral_pcs_wavelength_range.as_ref().map(WavelengthRange::wavelengths).unwrap_or_default();
+    let pipeline = [TagSignature::DToB0Tag, TagSignature::AToB0Tag]
+        .iter()
+        .find_map(|signature| match profile.tag_data(signature) {
+            Some(TagData::MultiProcessElements(m)) if m.outputs as usize == wavelengths.len() => Some(m),
+            _ => None,
+        })
+        .ok_or("profile without a spectral 'D2B0', or 'A2B0', pipeline")?;
+    let mut samples = Vec::with_capacity(colors.len());
+    for (i, device) in colors.iter().enumerate() {
+        if device.len() != pipeline.inputs as usize {
+            return Err(format!("color {i} does not match the number of device channels").into());
+        }
+        let input: Vec<f32> = device.iter().map(|&v| v as f32).collect();
+        let values = pipeline.eval(&input).into_iter().map(f64::from).collect();
+        samples.push(Reflectance { name: format!("color {i}"), values });
+    }
+    observer_metamerism(&wavelengths, &samples, pairs, illuminant)
+}
+
+#[test]
+fn test_observer_metamerism() {
+    let wavelengths: Vec<f64> = (0..=30).map(|i| 400.0 + 10.0 * i as f64).collect();
+    let gray = Reflectance { name: "gray".into(), values: vec![0.5; wavelengths.len()] };
+    let same = Reflectance { name: "same".into(), values: vec![0.5; wavelengths.len()] };
+    // a sample with a spiky reflectance spectrum
+    let spiky = Reflectance {
+        name: "spiky".into(),
+        values: (0..wavelengths.len()).map(|i| if i % 3 == 0 { 0.9 } else { 0.3 }).collect(),
+    };
+    let report = observer_metamerism(&wavelengths, &[gray, same, spiky], &[(0, 1), (0, 2)], None).unwrap();
+    assert!(report.pairs[0].index() < 1E-6);
+    assert!(report.pairs[1].index() > 0.0);
+    assert_eq!(report.worst().unwrap().sample, "spiky");
+}
+
+#[test]
+fn test_profile_observer_metamerism() {
+    use crate::tags::multi_process_elements::{MultiProcessElements, ProcessElement};
+    // a single channel device, with flat reflectance spectra
+    let pipeline = MultiProcessElements::new(1, 31).with_element(ProcessElement::Matrix { inputs: 1, outputs: 31, matrix: vec![1.0; 31], offsets: vec![0.0; 31] });
+    let mut profile = crate::profile::standards::srgb().with_tag(TagSignature::DToB0Tag, TagData::MultiProcessElements(pipeline));
+    assert!(profile_observer_metamerism(&profile, &[vec![0.5]], &[], None).is_err());
+    profile.version = [5, 0, 0];
+    let profile = profile.with_spectral_pcs(SpectralColorSpace::Reflectance(31), WavelengthRange::new(400.0, 700.0, 31)).unwrap();
+    let report = profile_observer_metamerism(&profile, &[vec![0.5], vec![0.5], vec![0.8]], &[(0, 1), (0, 2)], None).unwrap();
+    assert!(report.pairs[0].index() < 1E-6);
+    assert!(report.pairs[1].reference_delta_e > 1.0);
+    assert!(profile_observer_metamerism(&profile, &[vec![0.5, 0.5]], &[], None).is_err());
+}
@@ -1,5 +1,47 @@
 
 #![allow(unused)]
+
+pub mod abstract_lab;
+#[cfg(feature = "batch")]
+pub mod batch;
+pub mod borrowed;
+#[cfg(feature = "fs")]
+pub mod cache;
+pub mod class;
+pub mod compare;
+pub mod diff;
+pub mod dimming;
+pub mod display;
+pub mod dump;
+pub mod edid;
+pub mod equivalence;
+pub mod fit;
+pub mod id;
+pub mod id_cache;
+pub mod input;
+pub mod lazy;
+pub mod link;
+pub mod macros;
+pub mod metadata;
+pub mod minimal;
+pub mod optimize;
+pub mod output;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod postscript;
+pub mod report;
+pub mod roundtrip;
+pub mod scrub;
+pub mod standards;
+pub mod summary;
+pub mod synthetic;
+pub mod tables;
+pub mod validation;
+pub mod version;
+
+pub use compare::compare;
+pub use roundtrip::roundtrip_check;
+
 use chrono::{DateTime, Datelike, Timelike, Utc};
 use serde::Serializer;
 use serde::ser::SerializeStruct;
@@ -8,23 +50,26 @@ use std::convert::TryInto;
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
 use half::f16;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::common::*;
+use crate::error::Error;
 use crate::tags::{
-    Tag
+    Tag, TagData
 };
 use crate::signatures::{
-    tag::TagSignature, 
-    colorspace::ColorSpaceSignature
+    tag::TagSignature, tagtype::TagTypeSignature,
+    colorspace::ColorSpaceSignature,
+    image_state::ColorimetricIntentImageState, technology::TechnologySignature,
 };
 
 // ICC profile file signature, used at location 36..40 in the profile header
 const ACSP: u32 = 0x61637370; 
-const SIG_NONE: &str = "\0\0\0\0";
 
-#[derive(Default, Debug, Serialize)]
-#[serde(default)]
+// the derived implementations are inherent functions, with `remote = "Self"`, used by the Serialize,
+// and Deserialize, implementations below
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default, remote = "Self")]
 pub struct Profile {
     pub cmm: Option<crate::signatures::cmm::CmmSignature>,
     pub version: [u8;3],
@@ -51,7 +96,7 @@ pub struct Profile {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub creator: Option<String>, // a manufacturer signature
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_profile_id")]
     pub profile_id: Option<u128>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -68,121 +113,811 @@ pub struct Profile {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub profile_device_sub_class: Option<u32>,
-    // tags list
-    pub tags: Vec<crate::tags::Tag>,
+    // Tags, in the order of the tag table, with a single tag for each signature; read with
+    // [Profile::get_tag], and [Profile::iter_tags], and edited with [Profile::with_tag],
+    // [Profile::insert_tag], and [Profile::remove_tag], which keep it that way.
+    pub(crate) tags: Vec<crate::tags::Tag>,
+}
+
+impl Serialize for Profile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Profile::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Profile {
+    // the device attributes have the version of the profile
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut profile = Profile::deserialize(deserializer)?;
+        profile.attributes.version = profile.version[0];
+        Ok(profile)
+    }
+}
+
+/// Options for parsing profiles.
+///
+/// Parsing never panics, and returns an error for malformed data, which, with a limited profile
+/// size, makes it safe to use for untrusted input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseOptions {
+    /// Returns an error for tags with malformed data, or, if not set, keeps their data unparsed,
+    /// as [TagData::Custom].
+    pub strict: bool,
+    /// Largest size of a profile, in bytes, which is [MAX_PROFILE_SIZE] by default.
+    pub max_profile_size: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { strict: true, max_profile_size: MAX_PROFILE_SIZE }
+    }
+}
+
+/// Default largest size of a profile, in bytes, for parsing, which also limits the size of
+/// decompressed profiles embedded in images, and documents, and of compressed tag data.
+pub const MAX_PROFILE_SIZE: usize = 256 << 20;
+
+/// Buffers reused by [Profile::from_buffer_in]: the tag table, and the tag list of a profile which is
+/// no longer used, as returned with [ParseScratch::recycle].
+#[derive(Debug, Default)]
+pub struct ParseScratch {
+    tag_table: Vec<TagTableRow>,
+    tags: Vec<Tag>,
+}
+
+impl ParseScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the tag list of a profile which is no longer used, for the next profile parsed.
+    pub fn recycle(&mut self, profile: Profile) {
+        let mut tags = profile.tags;
+        if tags.capacity() > self.tags.capacity() {
+            tags.clear();
+            self.tags = tags;
+        }
+    }
+}
+
+/// Options for writing profiles.
+///
+/// The defaults give the smallest profile which conforms to the specification. The other settings
+/// are for reproducing the layout of profiles written by other tools, such as for byte comparisons.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WriteOptions {
+    /// Writes a single copy of the data of tags with identical data.
+    pub share_tags: bool,
+    /// Writes the tags in their order in the profile, or, if not set, sorted by signature.
+    pub preserve_order: bool,
+    /// Alignment of the tag data, in bytes; the specification requires four.
+    pub align: usize,
+    /// Calculates, and writes, the profile ID if set, writes a zero ID if not set, or, if `None`,
+    /// writes an ID only if the profile has one.
+    pub update_profile_id: Option<bool>,
+    /// Pads the profile with zero bytes to a multiple of this size, in bytes.
+    pub pad_to: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self { share_tags: true, preserve_order: true, align: 4, update_profile_id: None, pad_to: 1 }
+    }
 }
 
 impl Profile {
-    pub fn from_buffer(mut icc_buf: &[u8]) -> Result<Profile> {
-        let buf_len = icc_buf.len();
-        let size = read_be_u32(&mut icc_buf)? as usize;
-        if size<132 || buf_len!=size {return Err("ICC profile size error".into())}; // 128 header + 4 byte number of tags
-      //  let cmm = read_signature(&mut icc_buf)?;
-        let cmm = crate::signatures::cmm::CmmSignature::new(read_be_u32(&mut icc_buf)?);
-        let version = read_version(&mut icc_buf)?;
-        let class = Class::read(&mut icc_buf)?;
-        let colorspace = ColorSpace::read(&mut icc_buf)?;
-        //let (colorspace, colorspace_channels) = ColorSpaceSignature::read(&mut icc_buf)?;
-        let pcs= ColorSpace::read(&mut icc_buf)?;
-        let date_time = read_date_time(&mut icc_buf)?;
-        let profile_file_signature = read_be_u32(&mut icc_buf)?;
-        if profile_file_signature!= ACSP { return Err("Profile file signature error".into())};
-        let platform = read_signature(&mut icc_buf)?;
-        let flags = ProfileFlags::new(&mut icc_buf)?;
-        let manufacturer = read_signature(&mut icc_buf)?;
-        let device= read_signature(&mut icc_buf)?;
-        let attributes = DeviceAttributes::new(&mut icc_buf, version[0])?;
-        let rendering_intent = RenderingIntent::read(&mut icc_buf)?;
-        let pcs_illuminant = read_xyz(&mut icc_buf)?;
-        let creator= read_signature(&mut icc_buf)?;
-        let profile_id = zero_as_none(read_be_u128(&mut icc_buf)?);
-        let spectral_pcs = SpectralColorSpace::read(&mut icc_buf)?;
-        let spectral_pcs_wavelength_range = WavelengthRange::read(&mut icc_buf)?;
-        let bi_spectral_pcs_wavelength_range = WavelengthRange::read(&mut icc_buf)?;
-        let mcs = read_mcs(&mut icc_buf)?;
-        let profile_device_sub_class = zero_as_none(read_be_u32(&mut icc_buf)?);
-        let _reserved = read_be_u32(&mut &mut icc_buf);
+    pub fn from_buffer(buf: &[u8]) -> std::result::Result<Profile, Error> {
+        Self::from_buffer_with_options(buf, &ParseOptions::default())
+    }
+
+    pub fn from_buffer_with_options(buf: &[u8], options: &ParseOptions) -> std::result::Result<Profile, Error> {
+        Self::parse(&mut ParseScratch::default(), buf, options)
+    }
+
+    /// Parses a profile, as [Profile::from_buffer], reusing the tag table, and tag list, buffers of a
+    /// [ParseScratch], for parsing many profiles with fewer allocations.
+    pub fn from_buffer_in(scratch: &mut ParseScratch, buf: &[u8]) -> std::result::Result<Profile, Error> {
+        Self::parse(scratch, buf, &ParseOptions::default())
+    }
+
+    fn parse(scratch: &mut ParseScratch, buf: &[u8], options: &ParseOptions) -> std::result::Result<Profile, Error> {
+        if buf.len() > options.max_profile_size {
+            return Err(Error::Header("ICC profile exceeds the maximum profile size".into()));
+        }
+        let mut profile = Self::read_header_into(buf, buf.len(), &mut scratch.tag_table)?;
+        profile.tags = std::mem::take(&mut scratch.tags);
+        profile.tags.reserve(scratch.tag_table.len());
+        for tag_record in &scratch.tag_table {
+            let data = &buf[tag_record.offset..tag_record.offset + tag_record.length];
+            let tag = match crate::tags::Tag::try_new_with_limit(tag_record.sig.clone(), &mut &data[..], options.max_profile_size) {
+                Ok(tag) => tag,
+                Err(e) if options.strict || data.len() < 8 => {
+                    return Err(Error::TagData { signature: tag_record.sig.clone(), reason: e.to_string() })
+                }
+                Err(_) => Tag::new(tag_record.sig.clone(), TagData::Custom([data[0], data[1], data[2], data[3]], data[8..].to_vec())),
+            };
+            profile.tags.push(tag);
+        }
+        Ok(profile)
+    }
+
+    // Reads the header, and the tag table, from the start of a profile of `file_size` bytes, with the tag
+    // offsets and lengths checked against the profile size, and returns the profile without its tags.
+    pub(crate) fn read_header(buf: &[u8], file_size: usize) -> std::result::Result<(Profile, Vec<TagTableRow>), Error> {
+        let mut tag_table = Vec::new();
+        let mut profile = Self::read_header_into(buf, file_size, &mut tag_table)?;
+        profile.tags = Vec::with_capacity(tag_table.len());
+        Ok((profile, tag_table))
+    }
+
+    // Reads the header, as [Profile::read_header], into a tag table which is cleared first.
+    fn read_header_into(buf: &[u8], file_size: usize, tag_table: &mut Vec<TagTableRow>) -> std::result::Result<Profile, Error> {
+        let mut icc_buf = buf;
+        let size = read_be_u32(&mut icc_buf).map_err(|e| Error::Header(e.to_string()))? as usize;
+        if size<132 || file_size!=size {return Err(Error::Header("ICC profile size error".into()))}; // 128 header + 4 byte number of tags
+        let mut profile = Self::read_header_fields(&mut icc_buf).map_err(|e| Error::Header(e.to_string()))?;
 
         // read tags pass 1
         // this will fill the `sig`, `offset`, and `length` fields.
 
-        let tags_length = read_be_u32(&mut icc_buf)? as usize;
-        let data_start = 128 + 4 + 12 * tags_length;
+        // All offsets and lengths are taken from the profile itself, and are checked against the
+        // profile size before use, as profiles are often untrusted input.
+        let tag_table_error = |e: Box<dyn std::error::Error>| Error::TagTable(e.to_string());
+        let tags_length = read_be_u32(&mut icc_buf).map_err(tag_table_error)? as usize;
+        let data_start = tags_length.checked_mul(12).and_then(|n| n.checked_add(128 + 4))
+            .filter(|&n| n <= size).ok_or(Error::TagTable("ICC tag count error".into()))?;
 
-        let mut tag_table = Vec::with_capacity(tags_length);
-        for i in 0..tags_length {
-            let sig = read_tag_signature(&mut icc_buf)?;
-            let offset = read_be_u32(&mut icc_buf)? as usize - data_start; // offset
-            let length = read_be_u32(&mut icc_buf)? as usize;
+        tag_table.clear();
+        tag_table.reserve(tags_length);
+        for _ in 0..tags_length {
+            let sig = read_tag_signature(&mut icc_buf).map_err(tag_table_error)?;
+            let offset = read_be_u32(&mut icc_buf).map_err(tag_table_error)? as usize; // offset from the start of the profile
+            let length = read_be_u32(&mut icc_buf).map_err(tag_table_error)? as usize;
+            if offset < data_start || offset.checked_add(length).filter(|&end| end <= size).is_none() {
+                return Err(Error::TagTable("ICC tag offset or length error".into()));
+            }
             tag_table.push(TagTableRow::new(sig, offset, length));
         }
+        Ok(profile)
+    }
 
-        let mut tags = Vec::with_capacity(tags_length);
-        for tag_record in tag_table {
-            let start = tag_record.offset;
-            let end = start + tag_record.length;
-            // tags[i].data = Some(icc_buf[start..end].to_vec());
-            tags.push(crate::tags::Tag::try_new(tag_record.sig, &mut &icc_buf[start..end])?);
-        }
-        
+    // Reads the header fields following the profile size, up to the tag table.
+    fn read_header_fields(icc_buf: &mut &[u8]) -> Result<Profile> {
+      //  let cmm = read_signature(icc_buf)?;
+        let cmm = crate::signatures::cmm::CmmSignature::new(read_be_u32(icc_buf)?);
+        let version = read_version(icc_buf)?;
+        let class = Class::read(icc_buf)?;
+        let colorspace = ColorSpace::read(icc_buf)?;
+        //let (colorspace, colorspace_channels) = ColorSpaceSignature::read(icc_buf)?;
+        let pcs= ColorSpace::read(icc_buf)?;
+        let date_time = read_date_time(icc_buf)?;
+        let profile_file_signature = read_be_u32(icc_buf)?;
+        if profile_file_signature!= ACSP { return Err("Profile file signature error".into())};
+        let platform = read_signature(icc_buf)?;
+        let flags = ProfileFlags::new(icc_buf)?;
+        let manufacturer = read_signature(icc_buf)?;
+        let device= read_signature(icc_buf)?;
+        let attributes = DeviceAttributes::new(icc_buf, version[0])?;
+        let rendering_intent = RenderingIntent::read(icc_buf)?;
+        let pcs_illuminant = read_xyz(icc_buf)?;
+        let creator= read_signature(icc_buf)?;
+        let profile_id = zero_as_none(read_be_u128(icc_buf)?);
+        let spectral_pcs = SpectralColorSpace::read(icc_buf)?;
+        let spectral_pcs_wavelength_range = WavelengthRange::read(icc_buf)?;
+        let bi_spectral_pcs_wavelength_range = WavelengthRange::read(icc_buf)?;
+        let mcs = read_mcs(icc_buf)?;
+        let profile_device_sub_class = zero_as_none(read_be_u32(icc_buf)?);
+        let _reserved = read_be_u32(icc_buf)?;
         Ok(Profile {
             cmm, version, class, colorspace, pcs, date_time,
             platform, flags, 
             manufacturer, device, attributes,
             rendering_intent, pcs_illuminant, creator, profile_id, spectral_pcs, spectral_pcs_wavelength_range,
-            bi_spectral_pcs_wavelength_range, mcs, profile_device_sub_class, tags,
+            bi_spectral_pcs_wavelength_range, mcs, profile_device_sub_class, tags: Vec::new(),
         })
-
     }
 
-    pub fn from_file(iccfile: &str) -> Result<Profile>  {
+    #[cfg(feature = "fs")]
+    pub fn from_file(iccfile: &str) -> std::result::Result<Profile, Error>  {
         let icc_data = std::fs::read(iccfile)?;
         Self::from_buffer(icc_data.as_slice())
     }
 
+    #[cfg(feature = "fs")]
+    pub fn from_file_with_options(iccfile: &str, options: &ParseOptions) -> std::result::Result<Profile, Error>  {
+        if std::fs::metadata(iccfile)?.len() > options.max_profile_size as u64 {
+            return Err(Error::Header("ICC profile exceeds the maximum profile size".into()));
+        }
+        let icc_data = std::fs::read(iccfile)?;
+        Self::from_buffer_with_options(icc_data.as_slice(), options)
+    }
+
+    /// Reads a profile from a reader, such as a network stream, an archive entry, or the standard
+    /// input, until its end.
+    pub fn from_reader(reader: impl std::io::Read) -> std::result::Result<Profile, Error> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Reads a profile from a reader, reading no more than the maximum profile size of the options.
+    pub fn from_reader_with_options(reader: impl std::io::Read, options: &ParseOptions) -> std::result::Result<Profile, Error> {
+        use std::io::Read;
+        let mut reader = reader;
+        // the profile size, in the first four bytes, gives the number of bytes to read
+        let mut size = [0u8; 4];
+        reader.read_exact(&mut size)?;
+        let size = u32::from_be_bytes(size) as usize;
+        if size > options.max_profile_size {
+            return Err(Error::Header("ICC profile exceeds the maximum profile size".into()));
+        }
+        if size < 128 {
+            return Err(Error::Header("ICC profile size is smaller than its header".into()));
+        }
+        let mut icc_data = vec![0u8; size];
+        icc_data[..4].copy_from_slice(&(size as u32).to_be_bytes());
+        reader.read_exact(&mut icc_data[4..])?;
+        Self::from_buffer_with_options(&icc_data, options)
+    }
+
     pub fn new(version: [u8;3], class: Class) -> Self {
-        let mut profile = Profile::default();
-        profile.version = version;
-        profile.class = class;
-        profile.date_time = Some(chrono::Utc::now());
-        profile
+        Profile {
+            version,
+            class,
+            date_time: Some(chrono::Utc::now()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a tag to the profile, or replaces the data of an existing tag with the same signature.
+    pub fn with_tag(mut self, signature: TagSignature, data: TagData) -> Self {
+        self.insert_tag(Tag::new(signature, data));
+        self
+    }
+
+    /// Adds a tag, or replaces the tag with the same signature, in place, returning the replaced tag.
+    pub fn insert_tag(&mut self, tag: Tag) -> Option<Tag> {
+        match self.tags.iter_mut().find(|t| t.signature() == tag.signature()) {
+            Some(t) => Some(std::mem::replace(t, tag)),
+            None => {
+                self.tags.push(tag);
+                None
+            }
+        }
+    }
+
+    /// Encoded data of a tag, with its type signature, and reserved field, as stored in a profile,
+    /// such as for copying private tags between profiles with [Profile::import_tag].
+    pub fn export_tag(&self, signature: &TagSignature) -> Result<Vec<u8>> {
+        self.get_tag(signature).ok_or_else(|| format!("tag {:?} not found", signature))?.to_be_bytes()
+    }
+
+    /// Adds, or replaces, a tag from its encoded data, as returned by [Profile::export_tag],
+    /// returning the replaced tag. The data is kept unparsed, with its type signature, if parsing,
+    /// and encoding, it again would change it, as for private types of vendor tags; data with a
+    /// reserved field which is not zero, which can not be written unchanged, is rejected.
+    pub fn import_tag(&mut self, signature: TagSignature, bytes: &[u8]) -> Result<Option<Tag>> {
+        let unchanged = |tag: &Tag| tag.to_be_bytes().is_ok_and(|b| b == bytes);
+        let tag = match Tag::try_new(signature.clone(), &mut &bytes[..]) {
+            Ok(tag) if unchanged(&tag) => tag,
+            Err(e) if bytes.len() < 8 => return Err(e),
+            _ => Tag::new(signature.clone(), TagData::Custom([bytes[0], bytes[1], bytes[2], bytes[3]], bytes[8..].to_vec())),
+        };
+        if !unchanged(&tag) {
+            return Err(format!("tag {:?} has a reserved field which is not zero, and can not be written unchanged", signature).into());
+        }
+        Ok(self.insert_tag(tag))
+    }
+
+    /// Removes, and returns, the tag with the given signature.
+    pub fn remove_tag(&mut self, signature: &TagSignature) -> Option<Tag> {
+        let i = self.tags.iter().position(|t| t.signature() == signature)?;
+        Some(self.tags.remove(i))
+    }
+
+    pub fn get_tag(&self, signature: &TagSignature) -> Option<&Tag> {
+        self.tags.iter().find(|t| t.signature() == signature)
+    }
+
+    pub fn contains_tag(&self, signature: &TagSignature) -> bool {
+        self.get_tag(signature).is_some()
+    }
+
+    pub fn tag_count(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Edits the dictionary tag with the given signature, which is added if not present, or replaced
+    /// if it holds another type of data.
+    pub fn with_dict(mut self, signature: TagSignature, f: impl FnOnce(&mut crate::tags::dict::Dict)) -> Self {
+        let pos = self.tags.iter().position(|t| t.signature() == &signature && matches!(t.data(), TagData::Dict(_)));
+        let pos = match pos {
+            Some(i) => i,
+            None => {
+                self = self.with_tag(signature.clone(), TagData::Dict(Default::default()));
+                self.tags.iter().position(|t| t.signature() == &signature).unwrap()
+            }
+        };
+        if let TagData::Dict(d) = self.tags[pos].data_mut() {
+            f(d);
+        }
+        self
+    }
+
+    /// Tags, in the order of the profile, with their signatures.
+    pub fn iter_tags(&self) -> impl Iterator<Item = (&TagSignature, &crate::tags::Tag)> {
+        self.tags.iter().map(|t| (t.signature(), t))
+    }
+
+    /// Tags, with their data as parsed, or their encoded data, for tags which were not parsed.
+    pub fn iter_parsed(&self) -> impl Iterator<Item = (&TagSignature, ParsedTag<'_>)> {
+        self.tags.iter().map(|t| {
+            let parsed = match t.data() {
+                TagData::Custom(_, data) => ParsedTag::Unparsed(t.type_signature(), data),
+                data => ParsedTag::Parsed(data),
+            };
+            (t.signature(), parsed)
+        })
+    }
+
+    /// Data of the tag with the given signature, if present.
+    pub fn tag_data(&self, signature: &TagSignature) -> Option<&TagData> {
+        self.get_tag(signature).map(|t| t.data())
+    }
+
+    // Data of the tag with the given signature, for editing.
+    fn tag_data_mut(&mut self, signature: &TagSignature) -> Option<&mut TagData> {
+        self.tags.iter_mut().find(|t| t.signature() == signature).map(|t| t.data_mut())
+    }
+
+    /// The media white point, of the 'wtpt' tag.
+    pub fn white_point(&self) -> Option<[f64; 3]> {
+        match self.tag_data(&TagSignature::MediaWhitePointTag) {
+            Some(TagData::XYZ(crate::tags::XYZ(v))) => v.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// The XYZ values of the red, green, and blue colorants of a matrix/TRC profile, as the columns
+    /// of its matrix, from the 'rXYZ', 'gXYZ', and 'bXYZ' tags.
+    pub fn matrix_columns(&self) -> Option<[[f64; 3]; 3]> {
+        let column = |signature| match self.tag_data(&signature) {
+            Some(TagData::XYZ(crate::tags::XYZ(v))) => v.first().copied(),
+            _ => None,
+        };
+        Some([column(TagSignature::RedMatrixColumnTag)?, column(TagSignature::GreenMatrixColumnTag)?, column(TagSignature::BlueMatrixColumnTag)?])
+    }
+
+    /// The tone response curve of a channel of a matrix/TRC, or gray TRC, profile.
+    pub fn trc(&self, channel: Channel) -> Option<crate::transform::Trc> {
+        let signature = match channel {
+            Channel::Red => TagSignature::RedTRCTag,
+            Channel::Green => TagSignature::GreenTRCTag,
+            Channel::Blue => TagSignature::BlueTRCTag,
+            Channel::Gray => TagSignature::GrayTRCTag,
+        };
+        self.tag_data(&signature).and_then(crate::transform::Trc::from_tag_data)
+    }
+
+    /// The description in the given language, from the 'desc' tag, or Apple's localized 'dscm' tag,
+    /// or else the first description of the 'desc' tag.
+    pub fn description(&self, language: Option<isolang::Language>) -> Option<String> {
+        let localized = |signature| match (self.tag_data(&signature), language) {
+            (Some(TagData::MultiLocalizedUnicode(m)), Some(language)) => m.0.iter().find(|e| e.1 == language).map(|e| e.2.clone()),
+            _ => None,
+        };
+        localized(TagSignature::ProfileDescriptionTag)
+            .or_else(|| localized(TagSignature::MultilocalizedDescriptionStringTag))
+            .or_else(|| text(self.tag_data(&TagSignature::ProfileDescriptionTag)?))
+    }
+
+    /// The copyright text, of the 'cprt' tag, in its first language.
+    pub fn copyright(&self) -> Option<String> {
+        text(self.tag_data(&TagSignature::CopyrightTag)?)
+    }
+
+    /// The device technology, of the 'tech' tag, if present, and registered; the signature of other
+    /// technologies is available with [TagData::signature].
+    pub fn technology(&self) -> Option<TechnologySignature> {
+        match self.tag_data(&TagSignature::TechnologyTag)? {
+            TagData::Technology(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    pub fn with_technology(self, technology: TechnologySignature) -> Self {
+        self.with_tag(TagSignature::TechnologyTag, TagData::Technology(technology))
+    }
+
+    /// The colorimetric intent image state, of the 'ciis' tag, if present, and registered.
+    pub fn image_state(&self) -> Option<ColorimetricIntentImageState> {
+        let signature = self.tag_data(&TagSignature::ColorimetricIntentImageStateTag)?.signature()?;
+        ColorimetricIntentImageState::from_u32(u32::from_be_bytes(signature))
+    }
+
+    pub fn with_image_state(self, state: ColorimetricIntentImageState) -> Self {
+        self.with_tag(TagSignature::ColorimetricIntentImageStateTag, TagData::Signature((state as u32).to_be_bytes()))
+    }
+
+    /// Sets the spectral profile connection space, and its wavelength range, of the header of a
+    /// version 5 (iccMAX) profile.
+    pub fn with_spectral_pcs(mut self, space: SpectralColorSpace, range: WavelengthRange) -> Result<Self> {
+        self.require_version_5()?;
+        self.spectral_pcs = Some(space);
+        self.spectral_pcs_wavelength_range = Some(range);
+        Ok(self)
+    }
+
+    /// Sets the second wavelength range of a bi-spectral profile connection space, of a version 5
+    /// profile.
+    pub fn with_bi_spectral_range(mut self, range: WavelengthRange) -> Result<Self> {
+        self.require_version_5()?;
+        self.bi_spectral_pcs_wavelength_range = Some(range);
+        Ok(self)
+    }
+
+    /// Sets the number of channels of the multiplex connection space, of a version 5 profile.
+    pub fn with_mcs(mut self, channels: u16) -> Result<Self> {
+        self.require_version_5()?;
+        self.mcs = (channels > 0).then_some(channels);
+        Ok(self)
+    }
+
+    /// Sets the profile, or device, subclass signature, of a version 5 profile.
+    pub fn with_device_sub_class(mut self, signature: u32) -> Result<Self> {
+        self.require_version_5()?;
+        self.profile_device_sub_class = (signature != 0).then_some(signature);
+        Ok(self)
+    }
+
+    /// The device attributes of the header, as named in the specification.
+    pub fn attributes_typed(&self) -> &DeviceAttributes {
+        &self.attributes
+    }
+
+    /// Edits the device attributes of the header; the version 5 attributes are written for all
+    /// versions, but only shown in dumps of version 5 profiles.
+    pub fn with_attributes_typed(mut self, f: impl FnOnce(&mut DeviceAttributes)) -> Self {
+        f(&mut self.attributes);
+        self.attributes.version = self.version[0];
+        self
+    }
+
+    /// The 64-bit device attributes header field, including the reserved, and vendor, bits.
+    pub fn attributes(&self) -> u64 {
+        self.attributes.to_u64()
+    }
+
+    // The spectral, and multiplex, header fields are reserved, and zero, before version 5.
+    fn require_version_5(&self) -> Result<()> {
+        if self.version[0] < 5 {
+            return Err("the spectral, and multiplex, header fields require a version 5 profile".into());
+        }
+        Ok(())
+    }
+
+    /// The coding-independent code points of the 'cicp' tag, if present.
+    pub fn cicp(&self) -> Option<crate::tags::cicp::Cicp> {
+        match self.tag_data(&TagSignature::CicpTag)? {
+            TagData::Cicp(cicp) => Some(*cicp),
+            _ => None,
+        }
+    }
+
+    /// Edits the code points of the 'cicp' tag, which is added, with unspecified code points, if not
+    /// present.
+    pub fn with_cicp(self, f: impl FnOnce(&mut crate::tags::cicp::Cicp)) -> Self {
+        let mut cicp = self.cicp().unwrap_or_default();
+        f(&mut cicp);
+        self.with_tag(TagSignature::CicpTag, TagData::Cicp(cicp))
+    }
+
+    /// The video card gamma table, if present.
+    pub fn vcgt(&self) -> Option<&crate::tags::vcgt::Vcgt> {
+        match self.tag_data(&TagSignature::VcgtTag)? {
+            TagData::Vcgt(vcgt) => Some(vcgt),
+            _ => None,
+        }
+    }
+
+    /// Edits the video card gamma table, which is added, as an identity formula, if not present.
+    pub fn with_vcgt(mut self, f: impl FnOnce(&mut crate::tags::vcgt::Vcgt)) -> Self {
+        if self.vcgt().is_none() {
+            self = self.with_tag(TagSignature::VcgtTag, TagData::Vcgt(crate::tags::vcgt::Vcgt::identity()));
+        }
+        if let Some(TagData::Vcgt(vcgt)) = self.tag_data_mut(&TagSignature::VcgtTag) {
+            f(vcgt);
+        }
+        self
+    }
+
+    /// The viewing conditions of the 'view' tag, if present.
+    pub fn viewing_conditions(&self) -> Option<&crate::tags::viewing_conditions::ViewingConditions> {
+        match self.tag_data(&TagSignature::ViewingConditionsTag)? {
+            TagData::ViewingConditions(vc) => Some(vc),
+            _ => None,
+        }
+    }
+
+    /// Edits the viewing conditions of the 'view' tag, which is added, with zero XYZ values and an
+    /// unknown illuminant, if not present.
+    pub fn with_viewing_conditions(mut self, f: impl FnOnce(&mut crate::tags::viewing_conditions::ViewingConditions)) -> Self {
+        if self.viewing_conditions().is_none() {
+            self = self.with_tag(TagSignature::ViewingConditionsTag, TagData::ViewingConditions(Default::default()));
+        }
+        if let Some(TagData::ViewingConditions(vc)) = self.tag_data_mut(&TagSignature::ViewingConditionsTag) {
+            f(vc);
+        }
+        self
+    }
+
+    /// The measurement conditions of the 'meas' tag, if present.
+    pub fn measurement(&self) -> Option<&crate::tags::measurement::Measurement> {
+        match self.tag_data(&TagSignature::MeasurementTag)? {
+            TagData::Measurement(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Edits the measurement conditions of the 'meas' tag, which is added, with unknown conditions,
+    /// if not present.
+    pub fn with_measurement(mut self, f: impl FnOnce(&mut crate::tags::measurement::Measurement)) -> Self {
+        if self.measurement().is_none() {
+            self = self.with_tag(TagSignature::MeasurementTag, TagData::Measurement(Default::default()));
+        }
+        if let Some(TagData::Measurement(m)) = self.tag_data_mut(&TagSignature::MeasurementTag) {
+            f(m);
+        }
+        self
+    }
+
+    /// The phosphor or colorant chromaticities of the 'chrm' tag, if present.
+    pub fn chromaticity(&self) -> Option<&crate::tags::chromaticity::Chromaticity> {
+        match self.tag_data(&TagSignature::ChromaticityTag)? {
+            TagData::Chromaticity(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Edits the phosphor or colorant chromaticities of the 'chrm' tag, which is added, without
+    /// channels, if not present.
+    pub fn with_chromaticity(mut self, f: impl FnOnce(&mut crate::tags::chromaticity::Chromaticity)) -> Self {
+        if self.chromaticity().is_none() {
+            self = self.with_tag(TagSignature::ChromaticityTag, TagData::Chromaticity(Default::default()));
+        }
+        if let Some(TagData::Chromaticity(c)) = self.tag_data_mut(&TagSignature::ChromaticityTag) {
+            f(c);
+        }
+        self
+    }
+
+    /// The colorant names, and PCS values, of the 'clrt' tag, if present.
+    pub fn colorant_table(&self) -> Option<&crate::tags::colorant_table::ColorantTable> {
+        match self.tag_data(&TagSignature::ColorantTableTag)? {
+            TagData::ColorantTable(c) => Some(c),
+            _ => None,
+        }
     }
 
-    pub fn to_file(&self, iccfile: &str) -> Result<()>  {
+    /// Edits the colorant names, and PCS values, of the 'clrt' tag, which is added, without
+    /// colorants, if not present.
+    pub fn with_colorant_table(mut self, f: impl FnOnce(&mut crate::tags::colorant_table::ColorantTable)) -> Self {
+        if self.colorant_table().is_none() {
+            self = self.with_tag(TagSignature::ColorantTableTag, TagData::ColorantTable(Default::default()));
+        }
+        if let Some(TagData::ColorantTable(c)) = self.tag_data_mut(&TagSignature::ColorantTableTag) {
+            f(c);
+        }
+        self
+    }
+
+    /// Apple's native display information, of the 'ndin' tag, if present.
+    pub fn native_display_info(&self) -> Option<&crate::tags::native_display_info::NativeDisplayInfo> {
+        match self.tag_data(&TagSignature::NativeDisplayInfoTag)? {
+            TagData::NativeDisplayInfo(ndin) => Some(ndin),
+            _ => None,
+        }
+    }
+
+    /// Edits Apple's native display information, of the 'ndin' tag, which is added, with zero
+    /// values, if not present.
+    pub fn with_native_display_info(mut self, f: impl FnOnce(&mut crate::tags::native_display_info::NativeDisplayInfo)) -> Self {
+        if self.native_display_info().is_none() {
+            self = self.with_tag(TagSignature::NativeDisplayInfoTag, TagData::NativeDisplayInfo(Default::default()));
+        }
+        if let Some(TagData::NativeDisplayInfo(ndin)) = self.tag_data_mut(&TagSignature::NativeDisplayInfoTag) {
+            f(ndin);
+        }
+        self
+    }
+
+    /// Apple's make and model, of the 'mmod' tag, if present.
+    pub fn make_and_model(&self) -> Option<&crate::tags::make_model::MakeAndModel> {
+        match self.tag_data(&TagSignature::MakeAndModelTag)? {
+            TagData::MakeAndModel(mmod) => Some(mmod),
+            _ => None,
+        }
+    }
+
+    /// Edits Apple's make and model, of the 'mmod' tag, which is added if not present; the result
+    /// should have a valid manufacturer ID, and a model.
+    pub fn with_make_and_model(mut self, f: impl FnOnce(&mut crate::tags::make_model::MakeAndModel)) -> Result<Self> {
+        let mut mmod = self.make_and_model().cloned().unwrap_or_default();
+        f(&mut mmod);
+        mmod.validate()?;
+        Ok(self.with_tag(TagSignature::MakeAndModelTag, TagData::MakeAndModel(mmod)))
+    }
+
+    /// Sets the profile description, in several languages, and optionally countries, as a 'desc'
+    /// tag, and as Apple's 'dscm' tag. Version 4 profiles have all entries in both tags, while the
+    /// 'desc' tag of version 2 profiles, as a textDescriptionType, has the first entry only.
+    pub fn with_description_localized(self, entries: &[(isolang::Language, Option<isocountry::CountryCode>, &str)]) -> Result<Self> {
+        use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, text_description::TextDescription};
+        let mluc = || MultiLocalizedUnicode(entries.iter().map(|&(language, country, text)| (country, language, text.to_owned())).collect());
+        let (_, _, first) = entries.first().ok_or("description without entries")?;
+        let desc = if self.version[0] >= 4 {
+            TagData::MultiLocalizedUnicode(mluc())
+        } else {
+            TagData::TextDescription(TextDescription::new(first))
+        };
+        Ok(self
+            .with_tag(TagSignature::ProfileDescriptionTag, desc)
+            .with_tag(TagSignature::MultilocalizedDescriptionStringTag, TagData::MultiLocalizedUnicode(mluc())))
+    }
+
+    /// Sets the profile description, as a 'desc' tag, with the tag type of the profile version: a
+    /// multiLocalizedUnicodeType, in US English, for version 4, and later, profiles, and a
+    /// textDescriptionType for version 2 profiles.
+    pub fn with_description(self, description: &str) -> Self {
+        use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, text_description::TextDescription};
+        let desc = if self.version[0] >= 4 {
+            TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(description))
+        } else {
+            TagData::TextDescription(TextDescription::new(description))
+        };
+        self.with_tag(TagSignature::ProfileDescriptionTag, desc)
+    }
+
+    /// Sets the copyright, as a 'cprt' tag, with a multiLocalizedUnicodeType, in US English, for
+    /// version 4, and later, profiles, and a textType for version 2 profiles.
+    pub fn with_copyright(self, copyright: &str) -> Self {
+        use crate::tags::multi_localized_unicode::MultiLocalizedUnicode;
+        let cprt = if self.version[0] >= 4 {
+            TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(copyright))
+        } else {
+            TagData::Text(copyright.to_owned())
+        };
+        self.with_tag(TagSignature::CopyrightTag, cprt)
+    }
+
+    /// Adds, or replaces, the tags with the given signatures from another profile.
+    pub fn with_tags_from(mut self, mut other: Profile, signatures: &[TagSignature]) -> Result<Self> {
+        for signature in signatures {
+            let i = other.tags.iter().position(|t| t.signature() == signature)
+                .ok_or_else(|| format!("tag {:?} not found in source profile", signature))?;
+            let tag = other.tags.remove(i);
+            match self.tags.iter_mut().find(|t| t.signature() == signature) {
+                Some(t) => *t = tag,
+                None => self.tags.push(tag),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Removes the tag with the given signature, if present.
+    pub fn without_tag(mut self, signature: TagSignature) -> Self {
+        self.remove_tag(&signature);
+        self
+    }
+
+    /// Keeps only the tags for which the predicate returns true, such as to remove private tags.
+    pub fn retain_tags(mut self, mut f: impl FnMut(&TagSignature) -> bool) -> Self {
+        self.tags.retain(|t| f(t.signature()));
+        self
+    }
+
+    /// Moves the tags with the given signatures, in the given order, to the start of the tag table;
+    /// the other tags follow, in their current order. Tags with identical data are still shared when
+    /// written.
+    pub fn reorder_tags(mut self, order: &[TagSignature]) -> Self {
+        self.tags.sort_by_key(|t| order.iter().position(|s| s == t.signature()).unwrap_or(order.len()));
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    pub fn to_file(&self, iccfile: &str) -> std::result::Result<(), Error>  {
         let icc_buf = self.to_buffer()?;
         Ok(std::fs::write(iccfile, icc_buf)?)
     }
 
-    pub fn to_buffer(&self) -> Result<Vec<u8>> {
-        let length = 128 + 4 + self.tags.len() * 100;
-        let mut buf: Vec<u8> = Vec::with_capacity(length); // actual length might be smaller, correct at end
-        buf.extend((length as u32).to_be_bytes());
+    /// Writes the encoded profile to a writer, such as a network stream, or the standard output.
+    pub fn write_to(&self, mut writer: impl std::io::Write) -> std::result::Result<(), Error> {
+        Ok(writer.write_all(&self.to_buffer()?)?)
+    }
+
+    pub fn to_buffer(&self) -> std::result::Result<Vec<u8>, Error> {
+        self.to_buffer_with_options(&WriteOptions::default())
+    }
+
+    pub fn to_buffer_with_options(&self, options: &WriteOptions) -> std::result::Result<Vec<u8>, Error> {
+        if options.align == 0 || options.pad_to == 0 {
+            return Err(Error::Header("alignment, and padding, sizes should be at least one byte".into()));
+        }
+        let mut tags: Vec<&crate::tags::Tag> = self.tags.iter().collect();
+        if !options.preserve_order {
+            tags.sort_by_key(|tag| tag.signature().to_u32());
+        }
+        // tag data, aligned on `options.align` byte boundaries, starting after the tag table
+        let data_start = (128 + 4 + 12 * tags.len()).next_multiple_of(options.align);
+        let mut tag_table: Vec<TagTableRow> = Vec::with_capacity(tags.len());
+        let mut tag_data: Vec<u8> = Vec::new();
+        for tag in tags {
+            let bytes = tag.to_be_bytes().map_err(|e| Error::TagData { signature: tag.signature().clone(), reason: e.to_string() })?;
+            let shared = tag_table.iter()
+                .filter(|_| options.share_tags)
+                .find(|row| tag_data[row.offset..row.offset+row.length] == bytes[..])
+                .map(|row| row.offset);
+            let offset = match shared {
+                Some(offset) => offset,
+                None => {
+                    let offset = tag_data.len();
+                    tag_data.extend(&bytes);
+                    tag_data.resize((offset + bytes.len()).next_multiple_of(options.align), 0);
+                    offset
+                }
+            };
+            tag_table.push(TagTableRow::new(tag.signature().clone(), offset, bytes.len()));
+        }
+        let length = (data_start + tag_data.len()).next_multiple_of(options.pad_to);
+        // the profile size, and the tag offsets and sizes, are 32 bit fields
+        let size = u32::try_from(length)
+            .map_err(|_| Error::Header(format!("ICC profile size of {length} bytes exceeds the 4 GiB maximum")))?;
+
+        let mut buf: Vec<u8> = Vec::with_capacity(length);
+        buf.extend(size.to_be_bytes());
+        buf.extend(self.cmm.as_ref().map_or(0, |c| c.to_u32()).to_be_bytes());
         buf.extend([self.version[0], self.version[1]<<4_u8 | self.version[2], 0, 0]);
         buf.extend((self.class as u32).to_be_bytes());
         buf.extend(self.colorspace.unwrap_or_default().to_be_bytes());
         buf.extend(self.pcs.unwrap_or_default().to_be_bytes());
         buf.extend(datetime_to_be_bytes(self.date_time));
         buf.extend(ACSP.to_be_bytes());
-        buf.extend(self.platform.clone().unwrap_or(SIG_NONE.to_string()).as_bytes());
+        buf.extend(signature_to_be_bytes(&self.platform));
         buf.extend(self.flags.to_be_bytes());
-        buf.extend(self.manufacturer.clone().unwrap_or(SIG_NONE.to_string()).as_bytes());
-        buf.extend(self.device.clone().unwrap_or(SIG_NONE.to_string()).as_bytes());
+        buf.extend(signature_to_be_bytes(&self.manufacturer));
+        buf.extend(signature_to_be_bytes(&self.device));
         buf.extend(self.attributes.to_be_bytes());
         buf.extend((self.rendering_intent as u32).to_be_bytes());
         buf.extend(xyz_to_be_bytes(self.pcs_illuminant));
-        buf.extend(self.creator.clone().unwrap_or(SIG_NONE.to_string()).as_bytes());
-        buf.extend(0u32.to_be_bytes()); // profile id
+        buf.extend(signature_to_be_bytes(&self.creator));
+        buf.extend(0u128.to_be_bytes()); // profile id, calculated when the profile is complete
         buf.extend(self.spectral_pcs.unwrap_or(SpectralColorSpace::None).to_be_bytes());
         buf.extend(self.spectral_pcs_wavelength_range.clone().unwrap_or_default().to_be_bytes());
         buf.extend(self.bi_spectral_pcs_wavelength_range.clone().unwrap_or_default().to_be_bytes());
         buf.extend(mcs_to_be_bytes(self.mcs));
-       
+        buf.extend(self.profile_device_sub_class.unwrap_or(0).to_be_bytes());
+        buf.extend([0u8; 4]); // reserved
+
+        buf.extend((tag_table.len() as u32).to_be_bytes());
+        for row in &tag_table {
+            let field = |n: usize| u32::try_from(n)
+                .map_err(|_| Error::TagData { signature: row.sig.clone(), reason: format!("tag offset, or size, of {n} bytes exceeds the 4 GiB maximum") });
+            buf.extend(row.sig.to_u32().to_be_bytes());
+            buf.extend(field(data_start + row.offset)?.to_be_bytes());
+            buf.extend(field(row.length)?.to_be_bytes());
+        }
+        buf.resize(data_start, 0);
+        buf.extend(tag_data);
+        buf.resize(length, 0);
+        if options.update_profile_id.unwrap_or(self.profile_id.is_some()) {
+            let id = id::profile_id(&buf);
+            buf[84..100].copy_from_slice(&id.to_be_bytes());
+        }
         Ok(buf)
     }
 }
 
-#[derive(FromPrimitive, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, Clone, Copy, Debug, Serialize, Deserialize, Default)]
 pub enum Class {
+    #[default]
     Input = 0x73636E72,
     Display = 0x6D6E7472,
     Output = 0x70727472,
@@ -197,12 +932,6 @@ pub enum Class {
     MultiplexVisualization = 0x6d766973,
 }
 
-impl Default for Class {
-    fn default() -> Self {
-        Class::Input
-    }
-}
-
 impl Class {
     fn read(icc_buf: &mut &[u8]) -> Result<Class> {
         match FromPrimitive::from_u32(read_be_u32(icc_buf)?) {
@@ -212,7 +941,8 @@ impl Class {
     }
 }
 
-#[derive(Default, Debug, Serialize)]
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProfileFlags{
     pub embedded_profile: bool,
     pub use_with_embedded_data_only: bool,
@@ -248,6 +978,8 @@ pub struct DeviceAttributes{ // u64!
     pub textured: bool,
     pub non_isotropic: bool,
     pub self_luminous: bool,
+    /// Bits 8 to 31, reserved by the ICC, kept as read.
+    pub reserved: u32,
     pub vendor: u32,
     pub version: u8,
 
@@ -256,8 +988,12 @@ pub struct DeviceAttributes{ // u64!
 impl DeviceAttributes {
 
     fn new(icc_buf: &mut &[u8], version: u8) -> Result<Self> {
-        let v = read_be_u64(icc_buf)?;
-        Ok(Self{
+        Ok(Self::from_u64(read_be_u64(icc_buf)?, version))
+    }
+
+    /// Attributes of the 64-bit header field, for a profile with the given major version.
+    pub fn from_u64(v: u64, version: u8) -> Self {
+        Self{
             transparency: (v & (1<<0)) !=0,
             matte: (v & (1<<1)) !=0,
             media_negative: (v & (1<<2)) !=0,
@@ -266,23 +1002,28 @@ impl DeviceAttributes {
             textured: (v & (1<<5)) !=0,
             non_isotropic: (v & (1<<6)) !=0,
             self_luminous: (v & (1<<7)) !=0,
+            reserved: (v >> 8) as u32 & 0xFF_FFFF,
             vendor: (v>>32) as u32,
             version,
-        })
+        }
     }
 
-    fn to_be_bytes(&self) -> [u8;8] {
-        let v = (self.vendor as u64) << 32;
-        v
-        | (self.transparency as u64) << 0
+    /// The 64-bit header field, including the reserved, and vendor, bits.
+    pub fn to_u64(&self) -> u64 {
+        (self.vendor as u64) << 32
+        | ((self.reserved & 0xFF_FFFF) as u64) << 8
+        | (self.transparency as u64)
         | (self.matte as u64) << 1
         | (self.media_negative as u64) << 2
         | (self.media_black_and_white as u64) << 3
         | (self.non_paper_based as u64) << 4
         | (self.textured as u64) << 5
         | (self.non_isotropic as u64) << 6
-        | (self.self_luminous as u64) << 7;
-        v.to_be_bytes()
+        | (self.self_luminous as u64) << 7
+    }
+
+    fn to_be_bytes(&self) -> [u8;8] {
+        self.to_u64().to_be_bytes()
     }
 }
 
@@ -291,12 +1032,7 @@ impl Serialize for DeviceAttributes {
     where
         S: Serializer,
     {
-        let n: usize = match (self.version, self.vendor) {
-            (5..,0) => 8,
-            (5..,_) => 9,
-            (_, 0) => 4,
-            _ => 5,
-        };
+        let n = if self.version >= 5 { 8 } else { 4 } + (self.reserved != 0) as usize + (self.vendor != 0) as usize;
         let mut state = serializer.serialize_struct("attributes", n)?;
         state.serialize_field("transparancy", &self.transparency)?;
         state.serialize_field("matte", &self.matte)?;
@@ -308,6 +1044,9 @@ impl Serialize for DeviceAttributes {
             state.serialize_field("non_isotropic", &self.non_isotropic)?;
             state.serialize_field("self_luminous", &self.self_luminous)?;
         }
+        if self.reserved != 0 {
+            state.serialize_field("reserved", &self.reserved)?;
+        }
         if self.vendor!=0 {
             state.serialize_field("vendor", &self.vendor)?;
         }
@@ -315,7 +1054,74 @@ impl Serialize for DeviceAttributes {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug, Serialize)]
+impl<'de> Deserialize<'de> for DeviceAttributes {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // the fields as serialized; the version is set from the profile version by the Deserialize
+        // implementation of Profile
+        #[derive(Deserialize, Default)]
+        #[serde(default)]
+        struct Fields {
+            #[serde(alias = "transparency")]
+            transparancy: bool,
+            matte: bool,
+            media_negative: bool,
+            media_black_and_white: bool,
+            non_paper_based: bool,
+            textured: bool,
+            non_isotropic: bool,
+            self_luminous: bool,
+            reserved: u32,
+            vendor: u32,
+        }
+        let f = Fields::deserialize(deserializer)?;
+        Ok(Self {
+            transparency: f.transparancy,
+            matte: f.matte,
+            media_negative: f.media_negative,
+            media_black_and_white: f.media_black_and_white,
+            non_paper_based: f.non_paper_based,
+            textured: f.textured,
+            non_isotropic: f.non_isotropic,
+            self_luminous: f.self_luminous,
+            reserved: f.reserved & 0xFF_FFFF,
+            vendor: f.vendor,
+            version: 0,
+        })
+    }
+}
+
+// Text of a text, text description, or multi-localized Unicode tag, in its first language.
+fn text(data: &TagData) -> Option<String> {
+    match data {
+        TagData::Text(s) => Some(s.clone()),
+        TagData::TextDescription(t) => Some(t.ascii.clone()),
+        TagData::MultiLocalizedUnicode(m) => m.0.first().map(|e| e.2.clone()),
+        _ => None,
+    }
+}
+
+// The profile ID, as a number, or as a hexadecimal string, as used for dumps.
+fn deserialize_profile_id<'de, D>(deserializer: D) -> std::result::Result<Option<u128>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        Number(u128),
+        Hex(String),
+    }
+    match Option::<Id>::deserialize(deserializer)? {
+        Some(Id::Number(n)) => Ok(zero_as_none(n)),
+        Some(Id::Hex(s)) => u128::from_str_radix(&s, 16).map(zero_as_none).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ColorSpace {
     space: ColorSpaceSignature,
@@ -325,6 +1131,14 @@ pub struct ColorSpace {
 }
 
 impl ColorSpace {
+    pub fn new(space: ColorSpaceSignature) -> Self {
+        Self { space, channels: None }
+    }
+
+    pub fn signature(&self) -> ColorSpaceSignature {
+        self.space
+    }
+
     fn read(icc_buf: &mut &[u8]) -> Result<Option<ColorSpace>> {
         let (signature, channels) = ColorSpaceSignature::read(icc_buf)?;
         match signature {
@@ -333,7 +1147,7 @@ impl ColorSpace {
         }
     }
 
-    fn to_be_bytes(&self) -> [u8;4] {
+    fn to_be_bytes(self) -> [u8;4] {
         match self.channels {
             Some(n) => (ColorSpaceSignature::NC as u32 + n as u32).to_be_bytes(),
             None => (self.space as u32).to_be_bytes()
@@ -349,20 +1163,36 @@ impl Default for ColorSpace {
 
 
 
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+/// Device channel of a matrix/TRC, or gray TRC, profile.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Channel {
+    Red,
+    Green,
+    Blue,
+    Gray,
+}
+
+/// Data of a tag, as parsed, or, for unknown tag types, or malformed data kept by
+/// [ParseOptions], its type signature, and encoded data.
+#[derive(Debug, Clone, Copy)]
+pub enum ParsedTag<'a> {
+    Parsed(&'a TagData),
+    Unparsed(TagTypeSignature, &'a [u8]),
+}
+
+/// Rendering intent, with the canonical names `perceptual`, `media-relative-colorimetric`,
+/// `saturation`, and `absolute-colorimetric`, as used for display, parsing, and serialization; parsing
+/// also accepts `relative` and `absolute`, and the intent numbers, and is case insensitive.
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
 pub enum RenderingIntent {
+    #[default]
     Perceptual = 0,
     MediaRelativeColorimetric = 1,
     Saturation = 2,
     AbsoluteColorimetric = 3,
 }
 
-impl Default for RenderingIntent {
-    fn default() -> Self {
-        RenderingIntent::Perceptual
-    }
-}
-
 impl RenderingIntent {
     fn read(icc_buf: &mut &[u8]) -> Result<Self> {
         let sig =read_be_u32(icc_buf)?;
@@ -370,8 +1200,33 @@ impl RenderingIntent {
     }
 }
 
+impl std::fmt::Display for RenderingIntent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Perceptual => "perceptual",
+            Self::MediaRelativeColorimetric => "media-relative-colorimetric",
+            Self::Saturation => "saturation",
+            Self::AbsoluteColorimetric => "absolute-colorimetric",
+        })
+    }
+}
+
+impl std::str::FromStr for RenderingIntent {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "perceptual" | "0" => Ok(Self::Perceptual),
+            "media-relative-colorimetric" | "relative-colorimetric" | "relative" | "1" => Ok(Self::MediaRelativeColorimetric),
+            "saturation" | "2" => Ok(Self::Saturation),
+            "absolute-colorimetric" | "absolute" | "3" => Ok(Self::AbsoluteColorimetric),
+            _ => Err(format!("unknown rendering intent '{s}'")),
+        }
+    }
+}
+
 // V5 BToDx/DToBx or brdfBToDx/brdfDToBx or directionalBToDx/directionalDToBx spectral colour space signatures
-#[derive(Clone, Copy, Debug, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SpectralColorSpace {
     None,
     Reflectance(u16),
@@ -382,11 +1237,11 @@ pub enum SpectralColorSpace {
 }
 
 impl SpectralColorSpace {
-    fn read(icc_buf: &mut &[u8]) -> Result<Option<Self>> {
+    pub(crate) fn read(icc_buf: &mut &[u8]) -> Result<Option<Self>> {
         let sig = read_be_u16(icc_buf)?;
         let ch = read_be_u16(icc_buf)?;
         match sig {
-            0 => Ok((None)),
+            0 => Ok(None),
             0x7273 => Ok(Some(SpectralColorSpace::Reflectance(ch))),
             0x7473 => Ok(Some(SpectralColorSpace::Transmission(ch))),
             0x6573 => Ok(Some(SpectralColorSpace::RadiantEmission(ch))),
@@ -396,44 +1251,70 @@ impl SpectralColorSpace {
         }
     }
 
-    fn to_be_bytes(&self) -> [u8;4] {
+    pub(crate) fn to_be_bytes(self) -> [u8;4] {
         match self {
-            &SpectralColorSpace::Reflectance(ch) => (0x7273u32 << 2 | ch as u32).to_be_bytes(),
-            &SpectralColorSpace::Transmission(ch) => (0x7473u32 << 2 | ch as u32).to_be_bytes(),
-            &SpectralColorSpace::RadiantEmission(ch) => (0x6573u32 << 2 | ch as u32).to_be_bytes(),
-            &SpectralColorSpace::BiSpectralReflectance(ch) => (0x6273u32 << 2 | ch as u32).to_be_bytes(),
-            &SpectralColorSpace::BiSpectralReflectanceSparse(ch) => (0x736du32 << 2 | ch as u32).to_be_bytes(),
+            SpectralColorSpace::Reflectance(ch) => (0x7273u32 << 16 | ch as u32).to_be_bytes(),
+            SpectralColorSpace::Transmission(ch) => (0x7473u32 << 16 | ch as u32).to_be_bytes(),
+            SpectralColorSpace::RadiantEmission(ch) => (0x6573u32 << 16 | ch as u32).to_be_bytes(),
+            SpectralColorSpace::BiSpectralReflectance(ch) => (0x6273u32 << 16 | ch as u32).to_be_bytes(),
+            SpectralColorSpace::BiSpectralReflectanceSparse(ch) => (0x736du32 << 16 | ch as u32).to_be_bytes(),
             _ => [0,0,0,0],
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WavelengthRange ( RangeInclusive<f64>, usize);
 
 impl WavelengthRange {
+    /// Range of wavelengths, in nanometers, with the given number of equally spaced steps; the
+    /// wavelengths are encoded as half precision floating point numbers.
+    pub fn new(start: f64, end: f64, steps: usize) -> Self {
+        Self(start..=end, steps)
+    }
+
+    pub fn start(&self) -> f64 {
+        *self.0.start()
+    }
+
+    pub fn end(&self) -> f64 {
+        *self.0.end()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.1
+    }
+
+    /// Wavelengths of the steps.
+    pub fn wavelengths(&self) -> Vec<f64> {
+        match self.1 {
+            0 => Vec::new(),
+            1 => vec![self.start()],
+            n => (0..n).map(|i| self.start() + (self.end() - self.start()) * i as f64 / (n - 1) as f64).collect(),
+        }
+    }
 
     fn read(icc_buf: &mut &[u8]) -> Result<Option<Self>> {
+        let range = Self::try_new(icc_buf)?;
+        Ok(if range.1 == 0 { None } else { Some(range) })
+    }
+
+    pub(crate) fn try_new(icc_buf: &mut &[u8]) -> Result<Self> {
         let start = read_be_f16(icc_buf)?.to_f64();
         let end = read_be_f16(icc_buf)?.to_f64();
         let length = read_be_u16(icc_buf)? as usize;
-        if length == 0 {
-            Ok(None)
-        } else {
-            Ok(Some(Self(start..=end, length)))
-        }
+        Ok(Self(start..=end, length))
     }
 
-    fn to_be_bytes(&self) -> [u8;12] {
+    pub(crate) fn to_be_bytes(&self) -> [u8;6] {
         if self.1>0 {
             let mut v : Vec<u8> = Vec::new();
-            v.extend(self.0.start().to_be_bytes());
-            v.extend(self.0.end().to_be_bytes());
-            v.extend(self.1.to_be_bytes());
-            v.truncate(12);
+            v.extend(f16::from_f64(*self.0.start()).to_be_bytes());
+            v.extend(f16::from_f64(*self.0.end()).to_be_bytes());
+            v.extend((self.1 as u16).to_be_bytes());
             v.try_into().unwrap()
         } else {
-            [0u8;12]
+            [0u8;6]
         }
     }
 }
@@ -465,3 +1346,327 @@ impl TagTableRow {
         }
     }
 }
+
+#[test]
+fn test_malformed_profiles() {
+    let icc = standards::srgb().to_buffer().unwrap();
+    let patched = |pos: usize, value: u32| {
+        let mut v = icc.clone();
+        v[pos..pos + 4].copy_from_slice(&value.to_be_bytes());
+        v
+    };
+    assert!(Profile::from_buffer(&icc).is_ok());
+    assert!(Profile::from_buffer(&icc[..icc.len() - 4]).is_err());
+    assert!(matches!(Profile::from_buffer(&patched(128, u32::MAX)), Err(Error::TagTable(_)))); // tag count
+    assert!(Profile::from_buffer(&patched(136, 0)).is_err()); // tag offset in header
+    assert!(Profile::from_buffer(&patched(136, u32::MAX)).is_err()); // tag offset beyond end
+    assert!(Profile::from_buffer(&patched(140, u32::MAX)).is_err()); // tag length
+
+    // string offset of the first record in the 'desc' tag, before the end of the records
+    let desc = u32::from_be_bytes(icc[136..140].try_into().unwrap()) as usize;
+    assert!(matches!(Profile::from_buffer(&patched(desc + 24, 0)), Err(Error::TagData { signature: TagSignature::ProfileDescriptionTag, .. })));
+    assert!(Profile::from_buffer(&patched(desc + 20, u32::MAX)).is_err()); // string length
+    let lenient = ParseOptions { strict: false, ..Default::default() };
+    let profile = Profile::from_buffer_with_options(&patched(desc + 24, 0), &lenient).unwrap();
+    assert!(matches!(profile.iter_tags().next().unwrap().1.data(), TagData::Custom(t, _) if t == b"mluc"));
+    assert_eq!(profile.to_buffer().unwrap(), patched(desc + 24, 0));
+    assert!(Profile::from_buffer_with_options(&icc, &ParseOptions { max_profile_size: icc.len() - 1, ..lenient }).is_err());
+
+    // a creation date with a month of 13
+    let mut date = icc.clone();
+    date[26..28].copy_from_slice(&13u16.to_be_bytes());
+    assert!(matches!(Profile::from_buffer(&date), Err(Error::Header(_))));
+    #[cfg(feature = "fs")]
+    assert!(matches!(Profile::from_file("does not exist.icc"), Err(Error::Io(_))));
+
+    // a lut8 with a grid of 255^15 points
+    let lut = [&[15, 3, 255, 0][..], &[0; 36 + 15 * 256]].concat();
+    assert!(crate::tags::lut8::Lut8::try_new(&mut lut.as_slice()).is_err());
+}
+
+#[test]
+fn test_parse_scratch() {
+    let (srgb, lab) = (standards::srgb().to_buffer().unwrap(), standards::lab_d50().to_buffer().unwrap());
+    let mut scratch = ParseScratch::new();
+    let profile = Profile::from_buffer_in(&mut scratch, &srgb).unwrap();
+    assert_eq!(profile.to_buffer().unwrap(), srgb);
+    scratch.recycle(profile);
+    // the tag list of the recycled profile is reused, also for profiles with fewer tags
+    let profile = Profile::from_buffer_in(&mut scratch, &lab).unwrap();
+    assert_eq!(profile.to_buffer().unwrap(), lab);
+    assert!(Profile::from_buffer_in(&mut scratch, &srgb[..srgb.len() - 4]).is_err());
+}
+
+#[test]
+fn test_rendering_intent_names() {
+    for intent in [
+        RenderingIntent::Perceptual,
+        RenderingIntent::MediaRelativeColorimetric,
+        RenderingIntent::Saturation,
+        RenderingIntent::AbsoluteColorimetric,
+    ] {
+        assert_eq!(intent.to_string().parse::<RenderingIntent>(), Ok(intent));
+        let json = serde_json::to_string(&intent).unwrap();
+        assert_eq!(json, format!("\"{intent}\""));
+        assert_eq!(serde_json::from_str::<RenderingIntent>(&json).unwrap(), intent);
+    }
+    assert_eq!("Relative".parse(), Ok(RenderingIntent::MediaRelativeColorimetric));
+    assert_eq!("3".parse(), Ok(RenderingIntent::AbsoluteColorimetric));
+    assert!("colorimetric".parse::<RenderingIntent>().is_err());
+}
+
+#[test]
+fn test_with_tags_from() {
+    let gray = standards::gray_gamma22();
+    let profile = standards::srgb().with_tags_from(gray, &[TagSignature::GrayTRCTag, TagSignature::ProfileDescriptionTag]).unwrap();
+    assert!(profile.contains_tag(&TagSignature::GrayTRCTag));
+    assert_eq!(profile.tags.len(), standards::srgb().tags.len() + 1);
+    assert!(standards::srgb().with_tags_from(standards::srgb(), &[TagSignature::GrayTRCTag]).is_err());
+}
+
+#[test]
+fn test_retain_and_reorder_tags() {
+    use TagSignature::*;
+    let srgb = standards::srgb();
+    let size = srgb.to_buffer().unwrap().len();
+    let profile = srgb.with_tag(VendorTag("ndin".to_owned()), TagData::UInt32Array(vec![1]))
+        .retain_tags(|s| !matches!(s, VendorTag(_)))
+        .reorder_tags(&[BlueTRCTag, CopyrightTag]);
+    let signatures: Vec<&TagSignature> = profile.tags.iter().map(|t| t.signature()).collect();
+    assert_eq!(signatures[..3], [&BlueTRCTag, &CopyrightTag, &ProfileDescriptionTag]);
+    assert_eq!(profile.to_buffer().unwrap().len(), size);
+}
+
+#[test]
+fn test_viewing_conditions() {
+    use crate::tags::measurement::StandardIlluminant;
+    let profile = standards::srgb().with_viewing_conditions(|vc| {
+        vc.xyz_illuminant = [76.036, 80.0, 87.125];
+        vc.xyz_surround = [3.802, 4.0, 4.356];
+        vc.illuminant = StandardIlluminant::D50;
+    });
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let vc = profile.viewing_conditions().unwrap();
+    assert_eq!(vc.illuminant, StandardIlluminant::D50);
+    assert!((vc.xyz_illuminant[1] - 80.0).abs() < 1E-4 && (vc.xyz_surround[2] - 4.356).abs() < 1E-4);
+    let profile = profile.with_viewing_conditions(|vc| vc.illuminant = StandardIlluminant::D65);
+    assert_eq!(profile.viewing_conditions().unwrap().illuminant, StandardIlluminant::D65);
+    assert_eq!(profile.tags.iter().filter(|t| t.signature() == &TagSignature::ViewingConditionsTag).count(), 1);
+}
+
+#[test]
+fn test_measurement() {
+    use crate::tags::measurement::*;
+    let profile = Profile::new([4, 3, 0], Class::Output).with_measurement(|m| {
+        m.standard_observer = StandardObserver::Cie1931TwoDegrees;
+        m.xyz = [0.0, 0.0, 0.0];
+        m.geometry = Geometry::Normal45;
+        m.flare = Flare::Flare0;
+        m.illuminant = StandardIlluminant::D50;
+    });
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let m = profile.measurement().unwrap();
+    assert_eq!((m.standard_observer, m.geometry, m.illuminant), (StandardObserver::Cie1931TwoDegrees, Geometry::Normal45, StandardIlluminant::D50));
+    let profile = profile.with_measurement(|m| m.flare = Flare::Flare100);
+    assert_eq!(profile.measurement().unwrap().flare, Flare::Flare100);
+    assert_eq!(profile.measurement().unwrap().geometry, Geometry::Normal45);
+}
+
+#[test]
+fn test_chromaticity_tag() {
+    use crate::tags::chromaticity::Primaries;
+    let profile = standards::srgb().with_chromaticity(|c| c.set_primaries(Primaries::ITU));
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let c = profile.chromaticity().unwrap();
+    assert_eq!((c.primaries(), c.channels()), (Primaries::ITU, 3));
+    assert!((c.chromaticities()[0][0] - 0.64).abs() < 1E-4);
+}
+
+#[test]
+fn test_description_localized() {
+    use isocountry::CountryCode;
+    use isolang::Language;
+    let entries = [(Language::Eng, Some(CountryCode::USA), "Display"), (Language::Deu, None, "Bildschirm, groß")];
+    for version in [[2, 4, 0], [4, 3, 0]] {
+        let profile = Profile::new(version, Class::Display).with_description_localized(&entries).unwrap();
+        let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+        let tag = |signature: TagSignature| profile.tag_data(&signature);
+        let Some(TagData::MultiLocalizedUnicode(dscm)) = tag(TagSignature::MultilocalizedDescriptionStringTag) else { panic!("no dscm tag") };
+        assert_eq!(dscm.0[1], (None, Language::Deu, "Bildschirm, groß".to_owned()));
+        match tag(TagSignature::ProfileDescriptionTag) {
+            Some(TagData::MultiLocalizedUnicode(desc)) => assert_eq!((version[0], desc.0.len()), (4, 2)),
+            Some(TagData::TextDescription(desc)) => assert_eq!((version[0], desc.ascii.as_str()), (2, "Display")),
+            _ => panic!("no desc tag"),
+        }
+    }
+    assert!(Profile::new([4, 3, 0], Class::Display).with_description_localized(&[]).is_err());
+}
+
+#[test]
+fn test_accessors() {
+    let srgb = standards::srgb();
+    let white = srgb.white_point().unwrap();
+    assert!((white[1] - 1.0).abs() < 1e-3);
+    let columns = srgb.matrix_columns().unwrap();
+    let y: f64 = columns.iter().map(|c| c[1]).sum();
+    assert!((y - 1.0).abs() < 1e-3);
+    let trc = srgb.trc(Channel::Red).unwrap();
+    assert!((trc.eval(1.0) - 1.0).abs() < 1e-3 && trc.eval(0.5) < 0.5);
+    assert!(srgb.trc(Channel::Gray).is_none());
+    assert!(srgb.description(Some(isolang::Language::Eng)).is_some());
+    assert!(srgb.copyright().is_some());
+
+    // tags are found by their signature, not by their type
+    let vendor = TagSignature::VendorTag("zchr".to_owned());
+    let profile = srgb.with_tag(vendor.clone(), TagData::Chromaticity(Default::default()));
+    assert!(profile.chromaticity().is_none());
+    let profile = profile.with_chromaticity(|c| c.set_chromaticities(vec![[0.64, 0.33]]));
+    assert_eq!(profile.chromaticity().unwrap().channels(), 1);
+    assert!(matches!(profile.tag_data(&vendor), Some(TagData::Chromaticity(c)) if c.channels() == 0));
+}
+
+#[test]
+fn test_iter_parsed() {
+    let profile = standards::srgb().with_tag(TagSignature::VendorTag(String::from("priv")), TagData::Custom(*b"priv", vec![1, 2]));
+    assert_eq!(profile.iter_tags().count(), profile.tags.len());
+    assert!(profile.iter_tags().all(|(s, t)| s == t.signature()));
+    let unparsed: Vec<_> = profile.iter_parsed().filter_map(|(s, t)| matches!(t, ParsedTag::Unparsed(..)).then_some(s)).collect();
+    assert_eq!(unparsed, [&TagSignature::VendorTag(String::from("priv"))]);
+    assert!(matches!(profile.iter_parsed().next(), Some((_, ParsedTag::Parsed(TagData::MultiLocalizedUnicode(_))))));
+}
+
+#[test]
+fn test_tag_mutation() {
+    let mut profile = standards::srgb();
+    let count = profile.tag_count();
+    let old = profile.insert_tag(Tag::new(TagSignature::CopyrightTag, TagData::Text(String::from("CC0"))));
+    assert!(old.is_some() && profile.tag_count() == count);
+    assert_eq!(profile.copyright().as_deref(), Some("CC0"));
+    assert!(profile.remove_tag(&TagSignature::CopyrightTag).is_some());
+    assert!(!profile.contains_tag(&TagSignature::CopyrightTag) && profile.remove_tag(&TagSignature::CopyrightTag).is_none());
+    assert!(profile.insert_tag(Tag::new(TagSignature::CopyrightTag, TagData::Text(String::from("CC0")))).is_none());
+    assert_eq!(profile.iter_tags().last().unwrap().0, &TagSignature::CopyrightTag);
+}
+
+#[test]
+fn test_reader_writer() {
+    let srgb = standards::srgb();
+    let mut icc = Vec::new();
+    srgb.write_to(&mut icc).unwrap();
+    assert_eq!(icc, srgb.to_buffer().unwrap());
+    // only the profile is read, and the data following it is left in the reader
+    let mut stream = std::io::Cursor::new([&icc[..], b"next"].concat());
+    let profile = Profile::from_reader(&mut stream).unwrap();
+    assert_eq!((profile.tag_count(), stream.position() as usize), (10, icc.len()));
+    assert!(Profile::from_reader(&icc[..100]).is_err());
+    let options = ParseOptions { max_profile_size: icc.len() - 1, ..Default::default() };
+    assert!(matches!(Profile::from_reader_with_options(icc.as_slice(), &options), Err(Error::Header(_))));
+}
+
+#[test]
+fn test_signature_tags() {
+    let profile = standards::srgb()
+        .with_technology(TechnologySignature::FilmScanner)
+        .with_image_state(ColorimetricIntentImageState::SceneColorimetryEstimates);
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    assert_eq!(profile.technology(), Some(TechnologySignature::FilmScanner));
+    assert_eq!(profile.image_state(), Some(ColorimetricIntentImageState::SceneColorimetryEstimates));
+    assert_eq!(profile.tag_data(&TagSignature::ColorimetricIntentImageStateTag).unwrap().signature(), Some(*b"scoe"));
+
+    // unregistered signatures are kept
+    let raw = standards::srgb().with_tag(TagSignature::TechnologyTag, TagData::Signature(*b"abcd"));
+    let raw = Profile::from_buffer(&raw.to_buffer().unwrap()).unwrap();
+    assert_eq!(raw.technology(), None);
+    assert_eq!(raw.tag_data(&TagSignature::TechnologyTag).unwrap().signature(), Some(*b"abcd"));
+}
+
+#[test]
+fn test_v5_header_fields() {
+    let profile = Profile::new([5, 0, 0], Class::ColorSpace)
+        .with_spectral_pcs(SpectralColorSpace::Reflectance(31), WavelengthRange::new(400.0, 700.0, 31))
+        .and_then(|p| p.with_bi_spectral_range(WavelengthRange::new(300.0, 400.0, 11)))
+        .and_then(|p| p.with_mcs(6))
+        .and_then(|p| p.with_device_sub_class(u32::from_be_bytes(*b"test")))
+        .unwrap();
+    let icc = profile.to_buffer().unwrap();
+    assert_eq!(&icc[100..104], &[0x72, 0x73, 0, 31]);
+    let parsed = Profile::from_buffer(&icc).unwrap();
+    assert_eq!(parsed.spectral_pcs, Some(SpectralColorSpace::Reflectance(31)));
+    assert_eq!(parsed.spectral_pcs_wavelength_range.as_ref().map(|r| (r.start(), r.end(), r.steps())), Some((400.0, 700.0, 31)));
+    assert_eq!(parsed.bi_spectral_pcs_wavelength_range.as_ref().map(WavelengthRange::steps), Some(11));
+    assert_eq!((parsed.mcs, parsed.profile_device_sub_class), (Some(6), Some(u32::from_be_bytes(*b"test"))));
+    assert!(standards::srgb().with_mcs(3).is_err());
+}
+
+#[test]
+fn test_device_attributes() {
+    let v = 0x1234_5678_0000_0105u64;
+    let attributes = DeviceAttributes::from_u64(v, 4);
+    assert!(attributes.transparency && !attributes.matte && attributes.media_negative);
+    assert_eq!((attributes.reserved, attributes.vendor), (1, 0x1234_5678));
+    assert_eq!(attributes.to_u64(), v);
+    let mut profile = standards::srgb();
+    profile.attributes = attributes;
+    let parsed = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    assert_eq!(parsed.attributes.to_u64(), v);
+
+    // the reserved bits are kept in JSON, and the version is taken from the profile
+    let json = parsed.to_json(dump::DumpOptions { raw_tags: true, ..Default::default() }).unwrap();
+    assert_eq!(json["attributes"]["reserved"], 1);
+    let read: Profile = serde_json::from_value(json).unwrap();
+    assert_eq!((read.attributes(), read.attributes_typed().version), (v, 4));
+    let edited = read.with_attributes_typed(|a| a.matte = true);
+    assert!(edited.attributes_typed().matte);
+    assert_eq!(edited.attributes(), v | 2);
+}
+
+#[test]
+fn test_write_options() {
+    let profile = standards::srgb();
+    let shared = profile.to_buffer().unwrap();
+    let copies = profile.to_buffer_with_options(&WriteOptions { share_tags: false, ..Default::default() }).unwrap();
+    assert!(copies.len() > shared.len());
+    assert_eq!(Profile::from_buffer(&copies).unwrap().to_buffer().unwrap(), shared);
+
+    let options = WriteOptions { preserve_order: false, pad_to: 1024, update_profile_id: Some(true), ..Default::default() };
+    let icc = profile.to_buffer_with_options(&options).unwrap();
+    assert_eq!(icc.len() % 1024, 0);
+    assert_eq!(u32::from_be_bytes(icc[0..4].try_into().unwrap()) as usize, icc.len());
+    assert!(id::has_valid_profile_id(&icc));
+    let parsed = Profile::from_buffer(&icc).unwrap();
+    let signatures: Vec<u32> = parsed.iter_tags().map(|(s, _)| s.to_u32()).collect();
+    assert!(signatures.is_sorted());
+
+    let unaligned = profile.to_buffer_with_options(&WriteOptions { align: 1, update_profile_id: Some(false), ..Default::default() }).unwrap();
+    assert!(unaligned.len() <= shared.len() && unaligned[84..100] == [0; 16]);
+    assert!(profile.to_buffer_with_options(&WriteOptions { align: 0, ..Default::default() }).is_err());
+}
+
+#[test]
+fn test_export_import_tag() {
+    let source = standards::display_p3();
+    let desc = source.export_tag(&TagSignature::ProfileDescriptionTag).unwrap();
+    assert_eq!(&desc[..4], b"mluc");
+    let mut profile = standards::srgb();
+    let replaced = profile.import_tag(TagSignature::ProfileDescriptionTag, &desc).unwrap();
+    assert!(replaced.is_some());
+    assert_eq!(profile.description(None), source.description(None));
+
+    // a private tag, of a registered type, with data which does not re-encode unchanged, is kept as is
+    let mut private = b"text\0\0\0\0vendor data".to_vec();
+    private.extend([0, 1, 2]);
+    assert!(profile.import_tag(TagSignature::new(u32::from_be_bytes(*b"vndr")), &private).unwrap().is_none());
+    let icc = profile.to_buffer().unwrap();
+    assert!(icc.windows(private.len()).any(|w| w == private));
+
+    // as is a tag of an unregistered, private, type
+    let vendor = b"zzzz\0\0\0\0data";
+    assert!(profile.import_tag(TagSignature::new(u32::from_be_bytes(*b"zvnd")), vendor).unwrap().is_none());
+    assert_eq!(profile.export_tag(&TagSignature::new(u32::from_be_bytes(*b"zvnd"))).unwrap(), vendor);
+    let icc = profile.to_buffer().unwrap();
+    assert!(icc.windows(vendor.len()).any(|w| w == vendor));
+    assert!(Profile::from_buffer(&icc).unwrap().export_tag(&TagSignature::new(u32::from_be_bytes(*b"zvnd"))).unwrap() == vendor);
+    assert!(profile.import_tag(TagSignature::CopyrightTag, b"zzzz\0\0\0\x01data").is_err());
+    assert!(profile.import_tag(TagSignature::CopyrightTag, b"mluc").is_err());
+    assert!(profile.export_tag(&TagSignature::AToB0Tag).is_err());
+}
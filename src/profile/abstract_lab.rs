@@ -0,0 +1,69 @@
+/*!
+  Abstract profiles, for effects such as contrast, or saturation, adjustments, from a function mapping
+  CIELAB values to CIELAB values, which is sampled into the color lookup table of an 'A2B0' tag, with
+  a Lab profile connection space.
+
+  The lookup table uses the version 4 16-bit CIELAB encoding, with L* from 0 to 100, and a* and b*
+  from -128 to 127, linearly mapped to the range from 0 to 65535.
+*/
+
+use crate::color::pcs::{lab_to_normalized, normalized_to_lab};
+use crate::common::Result;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{
+    lut::{lut_atob, ClutBuilder},
+    multi_localized_unicode::MultiLocalizedUnicode,
+    Curve, TagData, XYZ,
+};
+
+/// CIELAB values of normalized 16-bit PCS values.
+pub(crate) fn decode(v: &[f64]) -> [f64; 3] {
+    normalized_to_lab([v[0], v[1], v[2]])
+}
+
+/// Normalized 16-bit PCS values of CIELAB values.
+pub(crate) fn encode(lab: [f64; 3]) -> Vec<f64> {
+    lab_to_normalized(lab).to_vec()
+}
+
+impl Profile {
+    /// Abstract profile, with the given description, sampling a CIELAB to CIELAB function on a grid
+    /// with `grid_points` points, from 2 to 255, along each axis; the output values are clipped to
+    /// the CIELAB encoding range.
+    pub fn abstract_from_lab_fn(description: &str, grid_points: usize, f: impl Fn([f64; 3]) -> [f64; 3] + Sync) -> Result<Profile> {
+        let clut = ClutBuilder::new(3, 3, grid_points).fill(|v| encode(f(decode(v))))?;
+        let identity = || vec![TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new()))];
+        let atob = lut_atob(identity(), clut, Vec::new(), None, identity())?;
+
+        let mut profile = Profile::new([4, 3, 0], Class::Abstract);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::Lab));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::Lab));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        Ok(profile
+            .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(description)))
+            .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us("No copyright, use freely")))
+            .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![PCS_ILLUMINANT])))
+            .with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob)))
+    }
+}
+
+#[test]
+fn test_abstract_from_lab_fn() {
+    // saturation increase
+    let profile = Profile::abstract_from_lab_fn("Saturation", 9, |[l, a, b]| [l, a * 1.2, b * 1.2]).unwrap();
+    assert!(profile.missing_tags().is_empty());
+    let parsed = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let TagData::LutAToB(data) = parsed.tag_data(&TagSignature::AToB0Tag).unwrap() else {
+        panic!("expected a lutAtoBType tag");
+    };
+    assert_eq!(data[..2], [3, 3]);
+    // the grid point at L* 50, a* -128, and b* -128+255/8
+    let clut = u32::from_be_bytes(data[16..20].try_into().unwrap()) as usize - 8;
+    assert_eq!(data[clut..clut + 3], [9, 9, 9]);
+    let entry = clut + 20 + 6 * (4 * 81 + 1);
+    let lab: Vec<u16> = data[entry..entry + 6].chunks(2).map(|c| u16::from_be_bytes(c.try_into().unwrap())).collect();
+    assert_eq!(lab, [0.5 * 65535.0, 0.0, ((255.0 / 8.0 - 128.0) * 1.2 + 128.0) / 255.0 * 65535.0].map(|v: f64| v.round() as u16));
+    assert!(Profile::abstract_from_lab_fn("", 1, |lab| lab).is_err());
+}
@@ -0,0 +1,158 @@
+/*!
+  Analysis of directories of profiles, for audits of large collections of system profiles, or of
+  profiles embedded in image assets.
+
+  Files are parsed in parallel, using all available cores; each file gives a [FileReport], with a
+  [Summary] of the profile, or the reason it could not be parsed, and warnings, such as tags which
+  could not be encoded again. Results are returned in the order of the file paths. Progress is
+  reported, and cancellation checked, on the calling thread.
+
+  ```no_run
+  use cmx::profile::batch::{analyze_dir, BatchOptions};
+  let reports = analyze_dir("/usr/share/color/icc", &BatchOptions::default(), &mut cmx::progress::NoProgress).unwrap();
+  for report in reports.iter().filter(|r| r.error.is_some()) {
+      println!("{}: {}", report.path.display(), report.error.as_deref().unwrap_or_default());
+  }
+  ```
+*/
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::common::Result;
+use crate::profile::summary::Summary;
+use crate::profile::Profile;
+use crate::progress::{Counter, Progress};
+
+const PROFILE_EXTENSIONS: [&str; 2] = ["icc", "icm"];
+const IMAGE_EXTENSIONS: [&str; 9] = ["avif", "heic", "heif", "jpeg", "jpg", "png", "tif", "tiff", "webp"];
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatchOptions {
+    /// Include the files in subdirectories.
+    pub recursive: bool,
+    /// Include the profiles embedded in image files; images without a profile are skipped.
+    pub images: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self { recursive: true, images: false }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub summary: Option<Summary>,
+    /// Reason the file, or its embedded profile, could not be read.
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Parses the profiles, and optionally the images, in a directory, in parallel.
+pub fn analyze_dir(path: impl AsRef<Path>, options: &BatchOptions, progress: &mut dyn Progress) -> Result<Vec<FileReport>> {
+    let mut files = Vec::new();
+    collect(path.as_ref(), options, &mut files)?;
+    files.sort();
+
+    let cancelled = AtomicBool::new(false);
+    let mut reports: Vec<Option<FileReport>> = files.iter().map(|_| None).collect();
+    let mut result = Ok(());
+    let (tx, rx) = mpsc::channel();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            files.par_iter().enumerate().for_each_with(tx, |tx, (i, path)| {
+                if !cancelled.load(Ordering::Relaxed) {
+                    let _ = tx.send((i, analyze_file(path)));
+                }
+            })
+        });
+        let mut counter = Counter::new(progress, files.len());
+        for (i, report) in rx {
+            reports[i] = report;
+            if let (Err(e), true) = (counter.step(), result.is_ok()) {
+                cancelled.store(true, Ordering::Relaxed);
+                result = Err(e);
+            }
+        }
+    });
+    result?;
+    Ok(reports.into_iter().flatten().collect())
+}
+
+fn collect(dir: &Path, options: &BatchOptions, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if options.recursive {
+                collect(&path, options, files)?;
+            }
+        } else if is_profile(&path) || (options.images && has_extension(&path, &IMAGE_EXTENSIONS)) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| extensions.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+fn is_profile(path: &Path) -> bool {
+    has_extension(path, &PROFILE_EXTENSIONS)
+}
+
+/// Report for a single file, or none for an image without an embedded profile.
+fn analyze_file(path: &Path) -> Option<FileReport> {
+    let report = |summary, error: Option<String>, warnings| Some(FileReport { path: path.to_owned(), summary, error, warnings });
+    let buf = match std::fs::read(path) {
+        Ok(buf) => buf,
+        Err(e) => return report(None, Some(e.to_string()), Vec::new()),
+    };
+    let icc = if is_profile(path) {
+        buf
+    } else {
+        match crate::embed::read_icc(&buf) {
+            Ok(Some(icc)) => icc,
+            Ok(None) => return None,
+            Err(e) => return report(None, Some(e.to_string()), Vec::new()),
+        }
+    };
+    match Profile::from_buffer(&icc) {
+        Ok(profile) => {
+            let warnings = profile.to_buffer().err().map(|e| format!("profile can not be written: {}", e)).into_iter().collect();
+            report(Some(profile.summary()), None, warnings)
+        }
+        Err(e) => report(None, Some(e.to_string()), Vec::new()),
+    }
+}
+
+#[test]
+fn test_analyze_dir() {
+    use crate::profile::standards;
+    let dir = std::env::temp_dir().join("cmx-batch");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("srgb.icc"), standards::srgb().to_buffer().unwrap()).unwrap();
+    std::fs::write(dir.join("sub/p3.ICM"), standards::display_p3().to_buffer().unwrap()).unwrap();
+    std::fs::write(dir.join("broken.icc"), b"not a profile").unwrap();
+    std::fs::write(dir.join("notes.txt"), b"not a profile either").unwrap();
+
+    let mut updates = Vec::new();
+    let mut progress = |done: usize, total: usize| updates.push((done, total));
+    let reports = analyze_dir(&dir, &BatchOptions::default(), &mut progress).unwrap();
+    assert_eq!(updates.last(), Some(&(3, 3)));
+    let names: Vec<_> = reports.iter().map(|r| r.path.strip_prefix(&dir).unwrap().to_str().unwrap()).collect();
+    assert_eq!(names, ["broken.icc", "srgb.icc", "sub/p3.ICM"]);
+    assert!(reports[0].error.is_some() && reports[0].summary.is_none());
+    assert_eq!(reports[2].summary.as_ref().unwrap().description.as_deref(), Some("Display P3"));
+
+    let reports = analyze_dir(&dir, &BatchOptions { recursive: false, ..Default::default() }, &mut crate::progress::NoProgress).unwrap();
+    assert_eq!(reports.len(), 2);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
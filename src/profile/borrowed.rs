@@ -0,0 +1,72 @@
+/*!
+  Read-only profiles borrowing their tag data from the profile buffer, for analysis of large numbers
+  of profiles without copying, or parsing, the data of tags which are not used.
+*/
+
+use num::FromPrimitive;
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::{tag::TagSignature, tagtype::TagTypeSignature};
+use crate::tags::Tag;
+
+/// A tag, with its encoded data, starting with its type signature, as a slice of the profile buffer.
+#[derive(Debug, Clone)]
+pub struct TagRef<'a> {
+    pub signature: TagSignature,
+    pub data: &'a [u8],
+}
+
+impl TagRef<'_> {
+    /// Type signature of the tag data, `UndefinedType` for unknown types.
+    pub fn type_signature(&self) -> TagTypeSignature {
+        self.data
+            .get(..4)
+            .and_then(|b| FromPrimitive::from_u32(u32::from_be_bytes(b.try_into().unwrap())))
+            .unwrap_or(TagTypeSignature::UndefinedType)
+    }
+
+    pub fn parse(&self) -> Result<Tag> {
+        Tag::try_new(self.signature.clone(), &mut &self.data[..])
+    }
+}
+
+#[derive(Debug)]
+pub struct ProfileRef<'a> {
+    /// The profile header; its tags are empty.
+    pub header: Profile,
+    pub tags: Vec<TagRef<'a>>,
+}
+
+impl<'a> ProfileRef<'a> {
+    pub fn from_buffer(buf: &'a [u8]) -> Result<Self> {
+        let (header, table) = Profile::read_header(buf, buf.len())?;
+        let tags = table.into_iter().map(|row| TagRef { data: &buf[row.offset..row.offset + row.length], signature: row.sig }).collect();
+        Ok(Self { header, tags })
+    }
+
+    pub fn tag(&self, signature: &TagSignature) -> Option<&TagRef<'a>> {
+        self.tags.iter().find(|t| &t.signature == signature)
+    }
+
+    /// Parses all tags, and returns the complete profile.
+    pub fn into_profile(self) -> Result<Profile> {
+        let mut profile = self.header;
+        profile.tags = self.tags.iter().map(TagRef::parse).collect::<Result<_>>()?;
+        Ok(profile)
+    }
+}
+
+
+#[test]
+fn test_profile_ref() {
+    let buf = crate::profile::standards::srgb().to_buffer().unwrap();
+    let profile = ProfileRef::from_buffer(&buf).unwrap();
+    assert_eq!(profile.tags.len(), 10);
+    // the red, green, and blue curves share their data
+    let (r, b) = (profile.tag(&TagSignature::RedTRCTag).unwrap(), profile.tag(&TagSignature::BlueTRCTag).unwrap());
+    assert_eq!(r.data.as_ptr(), b.data.as_ptr());
+    assert_eq!(r.type_signature(), TagTypeSignature::ParametricCurveType);
+    assert!(profile.tag(&TagSignature::GrayTRCTag).is_none());
+    assert_eq!(profile.into_profile().unwrap().to_buffer().unwrap(), buf);
+}
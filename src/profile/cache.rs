@@ -0,0 +1,85 @@
+/*!
+  A cache of parsed profiles, for long running processes which use a pool of profiles on disk.
+
+  Profiles are keyed by their path, and are parsed again only when the modification time of their
+  file has changed, so profiles can be replaced on disk while a server is running.
+
+  ```no_run
+  let cache = cmx::profile::cache::ProfileCache::new();
+  let profile = cache.get("profiles/sRGB.icc").unwrap();
+  ```
+*/
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::common::Result;
+use crate::profile::Profile;
+
+#[derive(Debug, Default)]
+pub struct ProfileCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, Arc<Profile>)>>,
+}
+
+impl ProfileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the profile stored at `path`, parsing it only if it is not in the cache yet, or if its
+    /// file has been modified since it was parsed.
+    pub fn get(&self, path: impl AsRef<Path>) -> Result<Arc<Profile>> {
+        let path = path.as_ref();
+        let modified = std::fs::metadata(path)?.modified()?;
+        if let Some((mtime, profile)) = self.entries.lock().unwrap().get(path) {
+            if *mtime == modified {
+                return Ok(Arc::clone(profile));
+            }
+        }
+        // parse without holding the lock, so other requests are not blocked by a slow file
+        let profile = Arc::new(Profile::from_buffer(&std::fs::read(path)?)?);
+        self.entries.lock().unwrap().insert(path.to_path_buf(), (modified, Arc::clone(&profile)));
+        Ok(profile)
+    }
+
+    /// Removes a profile from the cache, for example when its file has been deleted.
+    pub fn remove(&self, path: impl AsRef<Path>) -> Option<Arc<Profile>> {
+        self.entries.lock().unwrap().remove(path.as_ref()).map(|(_, profile)| profile)
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[test]
+fn test_profile_cache() {
+    let path = std::env::temp_dir().join(format!("cmx-cache-{}.icc", std::process::id()));
+    std::fs::write(&path, crate::profile::standards::srgb().to_buffer().unwrap()).unwrap();
+    let cache = ProfileCache::new();
+    let a = cache.get(&path).unwrap();
+    let b = cache.get(&path).unwrap();
+    assert!(Arc::ptr_eq(&a, &b));
+
+    std::fs::write(&path, crate::profile::standards::display_p3().to_buffer().unwrap()).unwrap();
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(SystemTime::now() + std::time::Duration::from_secs(10)).unwrap();
+    let c = cache.get(&path).unwrap();
+    assert!(!Arc::ptr_eq(&a, &c));
+    assert_eq!(cache.len(), 1);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(cache.get(&path).is_err());
+    assert!(cache.remove(&path).is_some());
+    assert!(cache.is_empty());
+}
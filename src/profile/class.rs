@@ -0,0 +1,83 @@
+/*!
+  Conversion of profiles to another device class, checking the tags required for the new class.
+
+  The required tags follow the ICC version 4 specification: a description, copyright, and media white
+  point, for all classes except device links, which have a profile sequence description instead, and
+  the tags holding the transform of the class. Input and display profiles can use a matrix/TRC, or
+  gray TRC, model instead of a lookup table.
+*/
+
+use crate::common::Result;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::{Class, Profile};
+use crate::signatures::tag::TagSignature;
+use crate::tags::{TagData, XYZ};
+
+use TagSignature::*;
+
+// Required tags of a class, as alternative sets of tags, one of which should be present.
+fn requirements(class: Class) -> Vec<Vec<Vec<TagSignature>>> {
+    let single = |sig: TagSignature| vec![vec![sig]];
+    let mut v = match class {
+        Class::DeviceLink => vec![single(ProfileSequenceDescTag), single(AToB0Tag)],
+        _ => vec![single(MediaWhitePointTag)],
+    };
+    v.push(single(ProfileDescriptionTag));
+    v.push(single(CopyrightTag));
+    match class {
+        Class::Input | Class::Display => v.push(vec![
+            vec![AToB0Tag],
+            vec![RedMatrixColumnTag, GreenMatrixColumnTag, BlueMatrixColumnTag, RedTRCTag, GreenTRCTag, BlueTRCTag],
+            vec![GrayTRCTag],
+        ]),
+        Class::Output => v.push(vec![vec![AToB0Tag, AToB1Tag, AToB2Tag, BToA0Tag, BToA1Tag, BToA2Tag, GamutTag], vec![GrayTRCTag]]),
+        Class::ColorSpace => v.push(vec![vec![AToB0Tag, BToA0Tag]]),
+        Class::Abstract => v.push(single(AToB0Tag)),
+        Class::NamedColor => v.push(single(NamedColor2Tag)),
+        _ => (),
+    }
+    v
+}
+
+impl Profile {
+    /// Tags required for the profile's class which are missing; for alternative sets of tags, the
+    /// missing tags of the first set are listed.
+    pub fn missing_tags(&self) -> Vec<TagSignature> {
+        let mut missing = Vec::new();
+        for alternatives in requirements(self.class) {
+            if !alternatives.iter().any(|set| set.iter().all(|s| self.contains_tag(s))) {
+                missing.extend(alternatives[0].iter().filter(|s| !self.contains_tag(s)).cloned());
+            }
+        }
+        missing
+    }
+
+    /// Changes the device class of the profile, adding a media white point, set to the PCS illuminant,
+    /// if required and not present, and failing with a list of the other required tags which are
+    /// missing.
+    pub fn convert_to(mut self, class: Class) -> Result<Profile> {
+        self.class = class;
+        if !matches!(class, Class::DeviceLink) && !self.contains_tag(&MediaWhitePointTag) {
+            self = self.with_tag(MediaWhitePointTag, TagData::XYZ(XYZ(vec![PCS_ILLUMINANT])));
+        }
+        let missing = self.missing_tags();
+        if !missing.is_empty() {
+            return Err(format!("profile is missing the tags {:?}, required for the {:?} class", missing, class).into());
+        }
+        Ok(self)
+    }
+}
+
+#[test]
+fn test_convert_to() {
+    use crate::profile::standards;
+    assert!(standards::srgb().missing_tags().is_empty());
+    let input = standards::srgb().without_tag(MediaWhitePointTag).convert_to(Class::Input).unwrap();
+    assert!(matches!(input.class, Class::Input));
+    assert!(input.contains_tag(&MediaWhitePointTag));
+
+    let e = standards::srgb().convert_to(Class::ColorSpace).unwrap_err().to_string();
+    assert!(e.contains("[AToB0Tag, BToA0Tag]"), "{e}");
+    let e = standards::gray_gamma22().without_tag(CopyrightTag).convert_to(Class::DeviceLink).unwrap_err().to_string();
+    assert!(e.contains("[ProfileSequenceDescTag, AToB0Tag, CopyrightTag]"), "{e}");
+}
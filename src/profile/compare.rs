@@ -0,0 +1,93 @@
+/*!
+  Color differences of two profiles for the same device, such as a profile and its rewritten, or
+  optimized, version.
+
+  A grid of device values is converted to CIELAB values with both profiles, and the color differences
+  are reported as ΔE*ab (CIE76), and CIEDE2000, statistics. Profiles are evaluated with their
+  [DeviceTransform] to the PCS, through the tone response curves and colorant matrices, lookup
+  tables, or multi process element tags, selected by [TransformPath::select](crate::transform::TransformPath::select)
+  for the intent.
+*/
+
+use crate::common::Result;
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::equivalence::device_grid;
+use crate::color::delta_e::{cie76, ciede2000};
+use crate::profile::fit::DeltaEStats;
+use crate::profile::{Profile, RenderingIntent};
+use crate::transform::DeviceTransform;
+
+/// Number of device values along each channel of a three channel grid; grids with more channels
+/// use fewer, and grids with fewer channels more, to keep the number of samples similar.
+const GRID: usize = 9;
+
+/// Color difference statistics of two profiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub delta_e76: DeltaEStats,
+    pub delta_e2000: DeltaEStats,
+    /// Number of compared device values.
+    pub samples: usize,
+}
+
+// Conversion of device values to CIELAB values, with the PCS XYZ values scaled by the media white
+// point for the absolute colorimetric intent.
+struct DeviceModel(DeviceTransform, [f64; 3]);
+
+impl DeviceModel {
+    fn new(profile: &Profile, intent: RenderingIntent) -> Result<Self> {
+        let transform = DeviceTransform::new(profile, intent, true)?;
+        let scale = match (intent, profile.white_point()) {
+            (RenderingIntent::AbsoluteColorimetric, Some(white)) => [0, 1, 2].map(|i| white[i] / PCS_ILLUMINANT[i]),
+            _ => [1.0; 3],
+        };
+        Ok(Self(transform, scale))
+    }
+
+    fn channels(&self) -> usize {
+        self.0.channels()
+    }
+
+    fn lab(&self, device: &[f64]) -> Result<[f64; 3]> {
+        let xyz = self.0.to_xyz(device).ok_or("device values not matching the transform")?;
+        Ok(xyz_to_lab([0, 1, 2].map(|i| xyz[i] * self.1[i])))
+    }
+}
+
+/// Compares the colors of two profiles, for the same device color space, and rendering intent, over
+/// a grid of device values.
+pub fn compare(a: &Profile, b: &Profile, intent: RenderingIntent) -> Result<Comparison> {
+    let (a, b) = (DeviceModel::new(a, intent)?, DeviceModel::new(b, intent)?);
+    let channels = a.channels();
+    if channels != b.channels() || channels == 0 {
+        return Err("profiles with a different number of device channels".into());
+    }
+    let n = ((GRID.pow(3) as f64).powf(1.0 / channels as f64).round() as usize).clamp(3, 256);
+    let pairs = device_grid(channels, n).iter().map(|d| Ok((a.lab(d)?, b.lab(d)?))).collect::<Result<Vec<([f64; 3], [f64; 3])>>>()?;
+    let de76: Vec<f64> = pairs.iter().map(|&(p, q)| cie76(p, q)).collect();
+    let de2000: Vec<f64> = pairs.iter().map(|&(p, q)| ciede2000(p, q)).collect();
+    Ok(Comparison { delta_e76: DeltaEStats::new(&de76), delta_e2000: DeltaEStats::new(&de2000), samples: pairs.len() })
+}
+
+#[test]
+fn test_compare() {
+    use crate::profile::standards;
+    let same = compare(&standards::srgb(), &standards::srgb(), RenderingIntent::Perceptual).unwrap();
+    assert_eq!((same.samples, same.delta_e2000.max), (729, 0.0));
+    let p3 = compare(&standards::srgb(), &standards::display_p3(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert!(p3.delta_e2000.max > 5.0 && p3.delta_e76.max > p3.delta_e2000.max);
+    assert!(p3.delta_e2000.mean <= p3.delta_e2000.percentile_95 && p3.delta_e2000.percentile_95 <= p3.delta_e2000.max);
+    assert!(compare(&standards::srgb(), &standards::gray_gamma22(), RenderingIntent::Perceptual).is_err());
+    // the lookup table, and not the matrix/TRC tags, defines the colors of the perceptual intent
+    use crate::signatures::tag::TagSignature;
+    use crate::tags::{lut::{lut_atob, ClutBuilder}, Curve, TagData};
+    let model = crate::transform::MatrixTrc::from_profile(&standards::display_p3()).unwrap();
+    let clut = ClutBuilder::new(3, 3, 17).fill(|rgb| crate::color::pcs::xyz_to_normalized(model.to_xyz(rgb)).to_vec()).unwrap();
+    let curves = || (0..3).map(|_| TagData::Curve(Curve(Vec::new()))).collect();
+    let lut = standards::srgb().with_tag(TagSignature::AToB0Tag, TagData::LutAToB(lut_atob(curves(), clut, Vec::new(), None, curves()).unwrap()));
+    let lut = Profile::from_buffer(&lut.to_buffer().unwrap()).unwrap();
+    let sampled = compare(&standards::display_p3(), &lut, RenderingIntent::Perceptual).unwrap();
+    assert!(sampled.delta_e2000.max < 1.0, "{:?}", sampled.delta_e2000);
+    assert!(compare(&standards::srgb(), &lut, RenderingIntent::Perceptual).unwrap().delta_e2000.max > 5.0);
+}
@@ -0,0 +1,170 @@
+/*!
+  Differences between two profiles.
+
+  Header fields, and parsed tag values, are compared using their serialized (JSON) representation,
+  as also used for the `cmx dump` output; changed tags are also compared byte by byte, in their
+  encoded form as written by [Profile::to_buffer].
+*/
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::Tag;
+
+#[derive(Debug, Default, Serialize)]
+pub struct ProfileDiff {
+    pub header: Vec<FieldDiff>,
+    pub added: Vec<TagSignature>,
+    pub removed: Vec<TagSignature>,
+    pub changed: Vec<TagDiff>,
+}
+
+/// A header field with different values in the two profiles, as JSON values; `Value::Null` for absent fields.
+#[derive(Debug, Serialize)]
+pub struct FieldDiff {
+    pub field: String,
+    pub left: Value,
+    pub right: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagDiff {
+    pub signature: TagSignature,
+    /// Length of the encoded tags, if they could be encoded.
+    pub length: (Option<usize>, Option<usize>),
+    /// Position of the first differing byte, and the number of differing bytes in the overlapping
+    /// part of the encoded tags.
+    pub first_difference: Option<usize>,
+    pub bytes_changed: usize,
+    /// Parsed tag values, if the tags differ in type or value.
+    pub value: Option<(Value, Value)>,
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool {
+        self.header.is_empty() && self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Profile {
+    /// Compares this profile with another, with additions and removals of tags as seen from this profile.
+    pub fn diff(&self, other: &Profile) -> ProfileDiff {
+        let mut diff = ProfileDiff::default();
+        let (left, right) = (header_fields(self), header_fields(other));
+        let mut fields: Vec<&String> = left.keys().chain(right.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        for field in fields {
+            let (l, r) = (left.get(field).unwrap_or(&Value::Null), right.get(field).unwrap_or(&Value::Null));
+            if l != r {
+                diff.header.push(FieldDiff { field: field.clone(), left: l.clone(), right: r.clone() });
+            }
+        }
+
+        for (signature, tag) in self.iter_tags() {
+            match other.get_tag(signature) {
+                Some(t) => diff.changed.extend(tag_diff(tag, t)),
+                None => diff.removed.push(signature.clone()),
+            }
+        }
+        for (signature, _) in other.iter_tags() {
+            if !self.contains_tag(signature) {
+                diff.added.push(signature.clone());
+            }
+        }
+        diff
+    }
+}
+
+fn header_fields(profile: &Profile) -> serde_json::Map<String, Value> {
+    // a JSON value can not hold the 128 bit profile ID, which is compared as a hexadecimal string instead
+    let value = serde_json::to_string(profile).and_then(|s| serde_json::from_str(&s));
+    match value {
+        Ok(Value::Object(mut map)) => {
+            map.remove("tags");
+            if let Some(id) = profile.profile_id {
+                map.insert("profile_id".to_owned(), Value::String(format!("{:032x}", id)));
+            }
+            map
+        }
+        _ => serde_json::Map::new(),
+    }
+}
+
+fn tag_diff(left: &Tag, right: &Tag) -> Option<TagDiff> {
+    let (lb, rb) = (left.to_be_bytes().ok(), right.to_be_bytes().ok());
+    let (lv, rv) = (serde_json::to_value(left).unwrap_or(Value::Null), serde_json::to_value(right).unwrap_or(Value::Null));
+    if lb == rb && lv == rv {
+        return None;
+    }
+    let (first_difference, bytes_changed) = match (&lb, &rb) {
+        (Some(l), Some(r)) => {
+            let differing: Vec<usize> = l.iter().zip(r).enumerate().filter(|(_, (a, b))| a != b).map(|(i, _)| i).collect();
+            let first = differing.first().copied().or_else(|| (l.len() != r.len()).then(|| l.len().min(r.len())));
+            (first, differing.len())
+        }
+        _ => (None, 0),
+    };
+    Some(TagDiff {
+        signature: left.signature().clone(),
+        length: (lb.as_ref().map(Vec::len), rb.as_ref().map(Vec::len)),
+        first_difference,
+        bytes_changed,
+        value: (lv != rv).then_some((lv, rv)),
+    })
+}
+
+impl fmt::Display for ProfileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.header {
+            writeln!(f, "header {}: {} -> {}", d.field, d.left, d.right)?;
+        }
+        for sig in &self.removed {
+            writeln!(f, "tag {:?}: removed", sig)?;
+        }
+        for sig in &self.added {
+            writeln!(f, "tag {:?}: added", sig)?;
+        }
+        for d in &self.changed {
+            write!(f, "tag {:?}: changed", d.signature)?;
+            if let (Some(l), Some(r)) = d.length {
+                write!(f, ", length {} -> {}", l, r)?;
+            }
+            if let Some(pos) = d.first_difference {
+                write!(f, ", {} byte(s) differ, first at offset {}", d.bytes_changed, pos)?;
+            }
+            writeln!(f)?;
+            if let Some((l, r)) = &d.value {
+                writeln!(f, "  - {}\n  + {}", l, r)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_profile_diff() {
+    use crate::profile::standards;
+    let srgb = standards::srgb();
+    assert!(srgb.diff(&standards::srgb()).header.iter().all(|d| d.field == "date_time"));
+
+    let diff = srgb.diff(&standards::display_p3());
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    let changed: Vec<&TagSignature> = diff.changed.iter().map(|d| &d.signature).collect();
+    assert_eq!(
+        changed,
+        [
+            &TagSignature::ProfileDescriptionTag,
+            &TagSignature::RedMatrixColumnTag,
+            &TagSignature::GreenMatrixColumnTag,
+            &TagSignature::BlueMatrixColumnTag
+        ]
+    );
+
+    let diff = srgb.diff(&standards::prophoto_rgb());
+    assert_eq!(diff.removed, [TagSignature::ChromaticAdaptationTag]);
+}
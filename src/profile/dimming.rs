@@ -0,0 +1,96 @@
+/*!
+  Display profiles for dimmed displays.
+
+  A display which is operated at a lower luminance than it was profiled for, such as a broadcast
+  reference monitor set to a fixed peak luminance, is dimmed through its video card gamma table, by
+  scaling the light output of all channels by the same factor. The gamma table values drive the
+  display, and are not linear in light, so they are scaled through the inverse of the profile's tone
+  response curves. As a display's primaries mix additively, this keeps the chromaticity of its white
+  point, and of all other colors, constant, and the profile's luminance tag is scaled by the same
+  factor.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::vcgt::Vcgt;
+use crate::tags::{TagData, XYZ};
+use crate::transform::{MatrixTrc, Trc};
+
+const VCGT_ENTRIES: usize = 256;
+
+// The light output of a channel is TRC(vcgt(x)); scaling it by `f` gives the drive value
+// TRC⁻¹(f·TRC(vcgt(x))), as the vcgt values are not linear in light.
+fn scaled_vcgt(vcgt: &Vcgt, trcs: &[Trc], f: f64) -> Result<Vcgt> {
+    let n = match vcgt {
+        Vcgt::Table(t) if t.entry_count >= 2 => t.entry_count as usize,
+        _ => VCGT_ENTRIES,
+    };
+    let channels: Vec<Vec<u16>> = (0..3)
+        .map(|c| {
+            let trc = &trcs[c.min(trcs.len() - 1)];
+            (0..n)
+                .map(|i| {
+                    let v = vcgt.value(c, i as f64 / (n - 1) as f64);
+                    (trc.eval_inverse(f * trc.eval(v)).clamp(0.0, 1.0) * 65535.0).round() as u16
+                })
+                .collect()
+        })
+        .collect();
+    Vcgt::table(&channels)
+}
+
+impl Profile {
+    /// Peak luminance of the display, in cd/m², from the luminance tag.
+    pub fn luminance(&self) -> Option<f64> {
+        match self.tag_data(&TagSignature::LuminanceTag)? {
+            TagData::XYZ(XYZ(v)) => v.first().map(|xyz| xyz[1]),
+            _ => None,
+        }
+    }
+
+    /// Derives the profile of the display dimmed to a peak luminance of `luminance` cd/m², by scaling
+    /// its video card gamma table, which is added if not present, through the inverse of its tone
+    /// response curves, and its luminance tag.
+    pub fn dimmed(self, luminance: f64) -> Result<Profile> {
+        let lumi = match self.tag_data(&TagSignature::LuminanceTag) {
+            Some(TagData::XYZ(XYZ(v))) if !v.is_empty() && v[0][1] > 0.0 => v[0],
+            _ => return Err("profile without a luminance tag".into()),
+        };
+        let f = luminance / lumi[1];
+        if !(f > 0.0 && f <= 1.0) {
+            return Err(format!("target luminance should be in the range from 0 to {} cd/m²", lumi[1]).into());
+        }
+        let model = MatrixTrc::from_profile(&self).map_err(|_| "dimming needs the tone response curves of a matrix/TRC profile")?;
+        let vcgt = match self.tag_data(&TagSignature::VcgtTag) {
+            Some(TagData::Vcgt(v)) => scaled_vcgt(v, &model.trcs, f)?,
+            _ => scaled_vcgt(&Vcgt::identity(), &model.trcs, f)?,
+        };
+        Ok(self
+            .with_tag(TagSignature::LuminanceTag, TagData::XYZ(XYZ(vec![lumi.map(|v| v * f)])))
+            .with_tag(TagSignature::VcgtTag, TagData::Vcgt(vcgt)))
+    }
+}
+
+#[test]
+fn test_dimmed() {
+    let profile = crate::profile::standards::srgb()
+        .with_tag(TagSignature::LuminanceTag, TagData::XYZ(XYZ(vec![[190.0, 200.0, 218.0]])));
+    assert!(crate::profile::standards::srgb().dimmed(100.0).is_err());
+    let model = MatrixTrc::from_profile(&profile).unwrap();
+    // relative light output of a channel, through the gamma table, and the tone response curve
+    let light = |p: &Profile, c: usize, x: f64| {
+        let Some(TagData::Vcgt(vcgt)) = p.tag_data(&TagSignature::VcgtTag) else { panic!("expected a vcgt tag") };
+        model.trcs[c].eval(vcgt.value(c, x))
+    };
+    let dimmed = profile.dimmed(100.0).unwrap();
+    assert_eq!(dimmed.luminance(), Some(100.0));
+    for (c, x) in [(0, 1.0), (1, 0.5), (2, 0.25)] {
+        let expected = 0.5 * model.trcs[c].eval(x);
+        assert!((light(&dimmed, c, x) - expected).abs() < 2E-3 * expected.max(0.01), "{c} {x}");
+    }
+    let dimmed = dimmed.dimmed(50.0).unwrap();
+    assert_eq!(dimmed.luminance(), Some(50.0));
+    assert!((light(&dimmed, 1, 1.0) - 0.25).abs() < 1E-3);
+    assert!(dimmed.dimmed(60.0).is_err());
+}
@@ -0,0 +1,153 @@
+/*!
+  Matrix/TRC display profiles from measured patches: pairs of RGB values, sent to the display, and the
+  XYZ values measured on its screen, in any unit, such as cd/m².
+
+  The white point is the color of the white patch, and the tone response curves are derived from the
+  ramps of the red, green, and blue patches, with the other channels at zero, as the projections of
+  the measured colors on the color of the channel's full value. The colorant matrix is then fitted to
+  all patches. The black level is subtracted from the measurements, and the colors are adapted to
+  the D50 profile connection space with the Bradford transform, recorded in the 'chad' tag.
+*/
+
+use crate::adaptation::{matrix, mul_vec, Method};
+use crate::common::Result;
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::fit::{fit_matrix, DeltaEStats};
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{curve_fit::fit_samples, multi_localized_unicode::MultiLocalizedUnicode, vcgt::Vcgt, Curve, TagData, XYZ};
+use crate::transform::Trc;
+
+// Number of entries of sampled tone response curves.
+const CURVE_ENTRIES: usize = 1024;
+// Largest lightness difference, in L* units, with the measured ramp values, for a fitted parametric
+// tone response curve.
+const PARAMETRIC_TOLERANCE: f64 = 0.2;
+
+#[derive(Debug, Default)]
+pub struct DisplayProfileOptions {
+    pub description: String,
+    /// Calibration curves, loaded in the video card while measuring, stored in a 'vcgt' tag.
+    pub calibration: Option<Vcgt>,
+}
+
+#[derive(Debug)]
+pub struct DisplayProfile {
+    pub profile: Profile,
+    /// Measured white point, with a luminance Y of 1.0.
+    pub white_point: [f64; 3],
+    /// Color differences of the patches, between their measured values, and the values of the profile.
+    pub delta_e: DeltaEStats,
+}
+
+impl DisplayProfile {
+    /// Display profile from pairs of RGB values, in the range from 0.0 to 1.0, and measured XYZ values;
+    /// the measurements should include black, white, and red, green, and blue ramps.
+    pub fn from_measurements(patches: &[([f64; 3], [f64; 3])], options: DisplayProfileOptions) -> Result<DisplayProfile> {
+        let find = |rgb: [f64; 3]| {
+            patches.iter().find(|(d, _)| d.iter().zip(rgb).all(|(a, b)| (a - b).abs() < 1E-6)).map(|(_, xyz)| *xyz)
+        };
+        let white = find([1.0; 3]).ok_or("measurements without a white patch")?;
+        let black = find([0.0; 3]).unwrap_or_default();
+        let scale = white[1] - black[1];
+        if scale <= 0.0 {
+            return Err("the white patch should be brighter than the black patch".into());
+        }
+        let relative = |xyz: [f64; 3]| [0, 1, 2].map(|i| (xyz[i] - black[i]) / scale);
+        let white_point = relative(white);
+        let chad = matrix(Method::Bradford, white_point, PCS_ILLUMINANT);
+        let adapted = |xyz: [f64; 3]| mul_vec(chad, relative(xyz));
+
+        let mut trcs = Vec::new();
+        for c in 0..3 {
+            let mut full = [0.0; 3];
+            full[c] = 1.0;
+            let primary = relative(find(full).ok_or("measurements without a red, green, or blue patch")?);
+            let norm: f64 = primary.iter().map(|v| v * v).sum();
+            let mut ramp: Vec<(f64, f64)> = patches
+                .iter()
+                .filter(|(d, _)| (0..3).all(|i| i == c || d[i] == 0.0))
+                .map(|(d, xyz)| (d[c], relative(*xyz).iter().zip(primary).map(|(a, b)| a * b).sum::<f64>() / norm))
+                .collect();
+            ramp.push((0.0, 0.0));
+            ramp.sort_by(|a, b| a.0.total_cmp(&b.0));
+            ramp.dedup_by(|a, b| a.0 == b.0);
+            trcs.push(tone_response_curve(&ramp));
+        }
+
+        let linear: Vec<[f64; 3]> = patches.iter().map(|(d, _)| [0, 1, 2].map(|i| trcs[i].eval(d[i]))).collect();
+        let xyz: Vec<[f64; 3]> = patches.iter().map(|(_, xyz)| adapted(*xyz)).collect();
+        let colorants = fit_matrix(&linear, &xyz);
+        let delta_e = DeltaEStats::from_xyz(linear.iter().zip(&xyz).map(|(l, x)| (mul_vec(colorants, *l), *x)));
+
+        let mut profile = Profile::new([4, 3, 0], Class::Display);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::RGB));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        let column = |i: usize| TagData::XYZ(XYZ(vec![[colorants[0][i], colorants[1][i], colorants[2][i]]]));
+        profile = profile
+            .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(&options.description)))
+            .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us("No copyright, use freely")))
+            .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![PCS_ILLUMINANT])))
+            .with_tag(TagSignature::ChromaticAdaptationTag, TagData::s15_fixed16_array(&chad))
+            .with_tag(TagSignature::RedMatrixColumnTag, column(0))
+            .with_tag(TagSignature::GreenMatrixColumnTag, column(1))
+            .with_tag(TagSignature::BlueMatrixColumnTag, column(2));
+        for (signature, trc) in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag].into_iter().zip(&trcs) {
+            profile = profile.with_tag(signature, trc.to_tag_data());
+        }
+        if let Some(vcgt) = options.calibration {
+            profile = profile.with_tag(TagSignature::VcgtTag, TagData::Vcgt(vcgt));
+        }
+        Ok(DisplayProfile { profile, white_point, delta_e })
+    }
+}
+
+// Tone response curve through the points of a ramp, sorted by device value, as a parametric curve
+// if one fits the points, or else as a sampled curve, linearly interpolating the points.
+fn tone_response_curve(ramp: &[(f64, f64)]) -> Trc {
+    let lightness = |y: f64| xyz_to_lab([y; 3])[0];
+    if let Ok(fit) = fit_samples(ramp) {
+        if ramp.iter().all(|&(x, y)| (lightness(fit.curve.eval(x)) - lightness(y)).abs() < PARAMETRIC_TOLERANCE) {
+            return Trc::Parametric(fit.curve);
+        }
+    }
+    let interpolate = |x: f64| {
+        let i = ramp.partition_point(|p| p.0 <= x).clamp(1, ramp.len() - 1);
+        let ((x0, y0), (x1, y1)) = (ramp[i - 1], ramp[i]);
+        if x1 > x0 { y0 + (y1 - y0) * (x - x0) / (x1 - x0) } else { y1 }
+    };
+    let n = CURVE_ENTRIES;
+    Trc::Curve(Curve((0..n).map(|i| (interpolate(i as f64 / (n - 1) as f64).clamp(0.0, 1.0) * 65535.0).round() as u16).collect()))
+}
+
+#[test]
+fn test_display_profile() {
+    use crate::transform::MatrixTrc;
+    // measurements of a Display P3 display, with a white luminance of 120 cd/m²
+    let p3 = crate::profile::standards::display_p3();
+    let model = MatrixTrc::from_profile(&p3).unwrap();
+    let d65 = crate::illuminants::D65_XYZ;
+    let to_d65 = matrix(Method::Bradford, PCS_ILLUMINANT, d65);
+    let mut patches = Vec::new();
+    for i in 0..=16 {
+        let v = i as f64 / 16.0;
+        for rgb in [[v, 0.0, 0.0], [0.0, v, 0.0], [0.0, 0.0, v], [v, v, v], [v, 1.0 - v, 0.5]] {
+            patches.push((rgb, mul_vec(to_d65, model.to_xyz(&rgb)).map(|x| x * 120.0)));
+        }
+    }
+    let calibration = Vcgt::identity();
+    let display = DisplayProfile::from_measurements(&patches, DisplayProfileOptions { description: String::from("P3"), calibration: Some(calibration) }).unwrap();
+    assert!(display.white_point.iter().zip(d65).all(|(a, b)| (a - b).abs() < 1E-3), "{:?}", display.white_point);
+    assert!(display.delta_e.max < 0.5, "{:?}", display.delta_e);
+    assert!(display.profile.missing_tags().is_empty());
+    let fitted = MatrixTrc::from_profile(&display.profile).unwrap();
+    assert!(fitted.matrix.iter().flatten().zip(model.matrix.iter().flatten()).all(|(a, b)| (a - b).abs() < 2E-3), "{:?}", fitted.matrix);
+    assert!((fitted.trcs[1].eval(0.5) - model.trcs[1].eval(0.5)).abs() < 1E-3);
+    // ramps of a gamma curve give parametric curves
+    let ramp: Vec<(f64, f64)> = (0..=16).map(|i| (i as f64 / 16.0, (i as f64 / 16.0).powf(2.2))).collect();
+    assert!(matches!(tone_response_curve(&ramp), Trc::Parametric(_)));
+    assert!(Profile::from_buffer(&display.profile.to_buffer().unwrap()).is_ok());
+    assert!(DisplayProfile::from_measurements(&patches[..3], DisplayProfileOptions::default()).is_err());
+}
@@ -0,0 +1,141 @@
+/*!
+  JSON representation of a profile, as used for the `cmx dump` output, with options for additional,
+  derived, values which make the dump easier to review.
+
+  Dumps with the raw tag data included can be read back with [Profile::from_json], for editing a
+  profile's header as text, and writing it as a binary profile again.
+
+  The format of a dump is versioned, with its version in a `schema` field, currently [SCHEMA]:
+
+  - the header fields have the names of the [Profile] fields, with fields which are not set left
+    out, and the profile ID as a hexadecimal string;
+  - `tags` is an array of tag objects, in the order of the profile's tags, with `tag_signature` and
+    `type_signature` fields, with the names of their signatures, a `data` object with a single field,
+    named after the data type, holding its parsed representation, and the optional `samples`, and
+    `raw` fields, as set by the [DumpOptions].
+
+  [Profile::from_json_strict] rejects dumps with other fields, or without a supported schema version.
+*/
+
+use serde_json::Value;
+
+use crate::common::{to_hex, Result};
+use crate::profile::Profile;
+use crate::tags::TagData;
+
+/// Version of the dump format.
+pub const SCHEMA: u64 = 1;
+
+const PROFILE_FIELDS: [&str; 22] = [
+    "schema", "cmm", "version", "class", "colorspace", "pcs", "date_time", "platform", "flags", "manufacturer", "device",
+    "attributes", "rendering_intent", "pcs_illuminant", "creator", "profile_id", "spectral_pcs",
+    "spectral_pcs_wavelength_range", "bi_spectral_pcs_wavelength_range", "mcs", "profile_device_sub_class", "tags",
+];
+
+const TAG_FIELDS: [&str; 5] = ["tag_signature", "type_signature", "data", "samples", "raw"];
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DumpOptions {
+    /// Number of samples of parametric curves, added as a `samples` array, with output values at
+    /// equally spaced input values from 0.0 to 1.0, to the curve tags; none if zero.
+    pub curve_samples: usize,
+    /// Add the encoded tag data, as a hexadecimal string, as a `raw` field to the tags, which is
+    /// required to read a dump back as a profile.
+    pub raw_tags: bool,
+}
+
+impl Profile {
+    pub fn to_json(&self, options: DumpOptions) -> Result<Value> {
+        // a JSON number can not hold the 128 bit profile ID without loss, which is shown as a hexadecimal string instead
+        let mut json: Value = serde_json::from_str(&serde_json::to_string(self)?)?;
+        if let Value::Object(map) = &mut json {
+            if let Some(id) = self.profile_id {
+                map.insert("profile_id".to_owned(), Value::String(format!("{:032x}", id)));
+            }
+            map.insert("schema".to_owned(), SCHEMA.into());
+        }
+        let n = options.curve_samples;
+        let tags = json.get_mut("tags").and_then(Value::as_array_mut).ok_or("profile without tags array")?;
+        for ((_, tag), value) in self.iter_tags().zip(tags) {
+            let Value::Object(map) = value else { continue };
+            if let (TagData::ParametricCurve(p), true) = (tag.data(), n > 0) {
+                let samples = (0..n).map(|i| p.value(if n > 1 { i as f32 / (n - 1) as f32 } else { 0.0 }) as f64);
+                map.insert("samples".to_owned(), samples.collect());
+            }
+            if options.raw_tags {
+                map.insert("raw".to_owned(), Value::String(to_hex(&tag.to_be_bytes()?)));
+            }
+        }
+        Ok(json)
+    }
+
+    /// Reads a profile from a JSON dump, written with the raw tags option; the tags are parsed from
+    /// their raw data, other tag fields are ignored, while the header is read from its fields.
+    /// Dumps without a schema version, written by earlier versions, are accepted, while dumps with a
+    /// newer schema version are not.
+    pub fn from_json(json: &str) -> Result<Profile> {
+        let value: Value = serde_json::from_str(json)?;
+        if let Some(schema) = value.get("schema") {
+            if schema.as_u64().filter(|&v| v <= SCHEMA).is_none() {
+                return Err(format!("unsupported dump schema version {schema}").into());
+            }
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Reads a profile from a JSON dump, as [Profile::from_json], but rejects dumps without the
+    /// current schema version, and dumps with unknown header, or tag, fields.
+    pub fn from_json_strict(json: &str) -> Result<Profile> {
+        let value: Value = serde_json::from_str(json)?;
+        if value.get("schema").and_then(Value::as_u64) != Some(SCHEMA) {
+            return Err(format!("dump without schema version {SCHEMA}").into());
+        }
+        let unknown = |value: &Value, fields: &[&str]| -> Option<String> {
+            value.as_object()?.keys().find(|k| !fields.contains(&k.as_str())).cloned()
+        };
+        if let Some(key) = unknown(&value, &PROFILE_FIELDS) {
+            return Err(format!("unknown field '{key}' in profile dump").into());
+        }
+        for tag in value.get("tags").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(key) = unknown(tag, &TAG_FIELDS) {
+                return Err(format!("unknown field '{key}' in tag {}", tag["tag_signature"]).into());
+            }
+        }
+        Self::from_json(json)
+    }
+}
+
+#[test]
+fn test_curve_samples() {
+    let json = crate::profile::standards::gray_gamma22().to_json(DumpOptions { curve_samples: 3, ..Default::default() }).unwrap();
+    let tags = json["tags"].as_array().unwrap();
+    let trc = tags.iter().find(|t| t["tag_signature"] == "GrayTRCTag").unwrap();
+    let samples: Vec<f64> = trc["samples"].as_array().unwrap().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(samples.len(), 3);
+    assert!((samples[1] - 0.5f64.powf(2.2)).abs() < 1E-3);
+    assert!(tags.iter().all(|t| t["tag_signature"] == "GrayTRCTag" || t.get("samples").is_none()));
+}
+
+#[test]
+fn test_from_json() {
+    for profile in [crate::profile::standards::srgb(), crate::profile::synthetic::many_tags(3)] {
+        let json = profile.to_json(DumpOptions { raw_tags: true, ..Default::default() }).unwrap().to_string();
+        assert_eq!(Profile::from_json(&json).unwrap().to_buffer().unwrap(), profile.to_buffer().unwrap());
+    }
+    let json = crate::profile::standards::srgb().to_json(DumpOptions::default()).unwrap().to_string();
+    assert!(Profile::from_json(&json).unwrap_err().to_string().contains("without raw data"));
+
+    let json = crate::profile::standards::srgb().to_json(DumpOptions { raw_tags: true, ..Default::default() }).unwrap();
+    assert_eq!(json["schema"], SCHEMA);
+    assert!(Profile::from_json_strict(&json.to_string()).is_ok());
+    let edited = |f: &dyn Fn(&mut Value)| {
+        let mut json = json.clone();
+        f(&mut json);
+        json.to_string()
+    };
+    assert!(Profile::from_json_strict(&edited(&|j| j["colour_space"] = "RGB".into())).is_err());
+    assert!(Profile::from_json_strict(&edited(&|j| j["tags"][0]["note"] = "".into())).is_err());
+    assert!(Profile::from_json(&edited(&|j| j["tags"][0]["note"] = "".into())).is_ok());
+    assert!(Profile::from_json_strict(&edited(&|j| { j.as_object_mut().unwrap().remove("schema"); })).is_err());
+    assert!(Profile::from_json(&edited(&|j| j["schema"] = (SCHEMA + 1).into())).is_err());
+}
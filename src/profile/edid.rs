@@ -0,0 +1,130 @@
+/*!
+  Default display profiles from the EDID (Extended Display Identification Data) block of a monitor,
+  such as read from `/sys/class/drm/<connector>/edid` on Linux.
+
+  The EDID base block records the chromaticities of the display's primaries, and of its white point,
+  with a precision of 1/1024, and its gamma. A profile made from these values is only as good as
+  the values the manufacturer recorded, but, for displays which were not measured, it is a better
+  default than sRGB. Gamma values defined in extension blocks are not read, and give a gamma of 2.2.
+*/
+
+use crate::common::Result;
+use crate::illuminants::{xy_to_xyz, PCS_ILLUMINANT};
+use crate::profile::display::DisplayProfile;
+use crate::profile::fit::DeltaEStats;
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, Curve, TagData};
+
+const HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+const DEFAULT_GAMMA: f64 = 2.2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edid {
+    /// Three letter manufacturer ID.
+    pub manufacturer: String,
+    pub product: u16,
+    /// Monitor name, and serial number, from the display descriptors.
+    pub name: Option<String>,
+    pub serial: Option<String>,
+    /// xy chromaticities of the red, green, and blue primaries.
+    pub primaries: [[f64; 2]; 3],
+    pub white: [f64; 2],
+    /// Gamma, or `None` if it is defined in an extension block.
+    pub gamma: Option<f64>,
+}
+
+impl Edid {
+    /// Parses the 128 byte base block of an EDID; extension blocks are ignored.
+    pub fn parse(bytes: &[u8]) -> Result<Edid> {
+        let block = bytes.get(..128).ok_or("EDID should have at least 128 bytes")?;
+        if block[..8] != HEADER {
+            return Err("EDID header error".into());
+        }
+        if block.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err("EDID checksum error".into());
+        }
+        let id = u16::from_be_bytes([block[8], block[9]]);
+        let manufacturer = [10, 5, 0].map(|shift| (b'A' - 1 + (id >> shift & 0x1F) as u8) as char).iter().collect();
+
+        // ten bit chromaticity values: the high bits in bytes 27 to 34, the low bits in bytes 25, and 26
+        let low = u16::from_be_bytes([block[25], block[26]]);
+        let value = |i: usize| ((block[27 + i] as u16) << 2 | (low >> (14 - 2 * i) & 0x03)) as f64 / 1024.0;
+        let xy = |i: usize| [value(2 * i), value(2 * i + 1)];
+
+        let descriptor = |tag: u8| {
+            block[54..126].chunks(18).find(|d| d[..3] == [0, 0, 0] && d[3] == tag).map(|d| {
+                let text = d[5..].split(|&b| b == 0x0A).next().unwrap_or_default();
+                String::from_utf8_lossy(text).trim_end().to_string()
+            })
+        };
+        Ok(Edid {
+            manufacturer,
+            product: u16::from_le_bytes([block[10], block[11]]),
+            name: descriptor(0xFC),
+            serial: descriptor(0xFF),
+            primaries: [xy(0), xy(1), xy(2)],
+            white: xy(3),
+            gamma: (block[23] != 0xFF).then(|| (block[23] as f64 + 100.0) / 100.0),
+        })
+    }
+}
+
+impl DisplayProfile {
+    /// Matrix/TRC display profile from the chromaticities, and gamma, of an EDID block.
+    pub fn from_edid(bytes: &[u8]) -> Result<DisplayProfile> {
+        let edid = Edid::parse(bytes)?;
+        if edid.white[1] <= 0.0 || edid.primaries.iter().any(|p| p[1] <= 0.0) {
+            return Err("EDID without valid chromaticities".into());
+        }
+        let description = edid.name.clone().unwrap_or_else(|| format!("{} {:04X}", edid.manufacturer, edid.product));
+        let text = |s: &str| TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(s));
+        let mut curve = Curve::default();
+        curve.set_gamma(edid.gamma.unwrap_or(DEFAULT_GAMMA));
+
+        let mut profile = Profile::new([4, 3, 0], Class::Display);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::RGB));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        let [red, green, blue] = edid.primaries;
+        let mut profile = profile
+            .with_tag(TagSignature::ProfileDescriptionTag, text(&description))
+            .with_tag(TagSignature::CopyrightTag, text("No copyright, use freely"))
+            .with_primaries(red, green, blue, edid.white);
+        for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+            profile = profile.with_tag(signature, TagData::Curve(curve.clone()));
+        }
+        Ok(DisplayProfile { profile, white_point: xy_to_xyz(edid.white), delta_e: DeltaEStats::default() })
+    }
+}
+
+#[test]
+fn test_edid() {
+    use crate::transform::MatrixTrc;
+    // base block of an sRGB monitor, with a gamma of 2.2, and a name descriptor
+    let mut edid = vec![0u8; 128];
+    edid[..8].copy_from_slice(&HEADER);
+    edid[8..10].copy_from_slice(&(4u16 << 10 | 5 << 5 | 12).to_be_bytes()); // "DEL"
+    edid[10..12].copy_from_slice(&0x40B0u16.to_le_bytes());
+    edid[23] = 120;
+    let xy = [0.640, 0.330, 0.300, 0.600, 0.150, 0.060, 0.3127, 0.3290].map(|v: f64| (v * 1024.0).round() as u16);
+    let low = xy.iter().fold(0u16, |low, v| low << 2 | (v & 0x03));
+    edid[25..27].copy_from_slice(&low.to_be_bytes());
+    xy.iter().enumerate().for_each(|(i, v)| edid[27 + i] = (v >> 2) as u8);
+    edid[54..72].copy_from_slice(&[&[0, 0, 0, 0xFC, 0][..], b"Test Monitor\n", &[0x20]].concat()[..18]);
+    edid[127] = 0u8.wrapping_sub(edid.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)));
+
+    let parsed = Edid::parse(&edid).unwrap();
+    assert_eq!((parsed.manufacturer.as_str(), parsed.product, parsed.name.as_deref()), ("DEL", 0x40B0, Some("Test Monitor")));
+    assert!((parsed.primaries[0][0] - 0.64).abs() < 1E-3 && (parsed.white[1] - 0.329).abs() < 1E-3, "{parsed:?}");
+    assert_eq!(parsed.gamma, Some(2.2));
+
+    let display = DisplayProfile::from_edid(&edid).unwrap();
+    assert!(display.profile.missing_tags().is_empty());
+    let model = MatrixTrc::from_profile(&display.profile).unwrap();
+    let srgb = MatrixTrc::from_profile(&crate::profile::standards::srgb()).unwrap();
+    assert!(model.matrix.iter().flatten().zip(srgb.matrix.iter().flatten()).all(|(a, b)| (a - b).abs() < 2E-3), "{:?}", model.matrix);
+    assert!((model.trcs[0].eval(0.5) - 0.5f64.powf(2.2)).abs() < 1E-3);
+    edid[127] ^= 1;
+    assert!(Edid::parse(&edid).is_err());
+}
@@ -0,0 +1,117 @@
+/*!
+  Equivalence of profiles, at three levels: identical encoding, equal content apart from the creation
+  date and profile ID, and equal colorimetry, within a tolerance.
+
+  Colorimetric equality is tested by converting a grid of device values to PCS XYZ values, with the
+  tone response curves and colorant matrices of matrix/TRC and gray profiles. Profiles using other
+  transforms, such as lookup tables, are colorimetrically equal only if they are semantically equal.
+*/
+
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::{Channel, Profile};
+use crate::signatures::tag::TagSignature;
+use crate::tags::TagData;
+
+// Number of samples along each device channel.
+const GRID: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EquivalenceLevel {
+    /// Profiles encode to the same bytes.
+    ByteIdentical,
+    /// Profiles have the same header fields and tags, apart from their creation date and profile ID.
+    SemanticallyEqual,
+    /// Profiles convert device values to PCS XYZ values which differ no more than `tolerance`, with
+    /// XYZ values scaled to a luminance Y of 1.0 for white.
+    ColorimetricallyEqual { tolerance: f64 },
+}
+
+impl Profile {
+    /// Compares two profiles at the given level of equivalence.
+    pub fn equivalent(&self, other: &Profile, level: EquivalenceLevel) -> bool {
+        match level {
+            EquivalenceLevel::ByteIdentical => match (self.to_buffer(), other.to_buffer()) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            },
+            EquivalenceLevel::SemanticallyEqual => {
+                let diff = self.diff(other);
+                diff.added.is_empty()
+                    && diff.removed.is_empty()
+                    && diff.changed.is_empty()
+                    && diff.header.iter().all(|f| f.field == "date_time" || f.field == "profile_id")
+            }
+            EquivalenceLevel::ColorimetricallyEqual { tolerance } => {
+                let channels = match (self.device_channels(), other.device_channels()) {
+                    (Some(a), Some(b)) if a == b => a,
+                    _ => return self.equivalent(other, EquivalenceLevel::SemanticallyEqual),
+                };
+                device_grid(channels, GRID).iter().all(|device| {
+                    match (self.device_to_xyz(device), other.device_to_xyz(device)) {
+                        (Some(a), Some(b)) => a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance),
+                        _ => false,
+                    }
+                })
+            }
+        }
+    }
+
+    // Number of device channels, for profiles with a matrix/TRC, or a gray TRC, model.
+    fn device_channels(&self) -> Option<usize> {
+        if self.matrix_columns().is_some() && self.trc(Channel::Red).is_some() {
+            Some(3)
+        } else if self.trc(Channel::Gray).is_some() {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn device_to_xyz(&self, device: &[f64]) -> Option<[f64; 3]> {
+        if let [k] = device {
+            let y = self.trc(Channel::Gray)?.eval(*k);
+            return Some(PCS_ILLUMINANT.map(|w| w * y));
+        }
+        let mut xyz = [0.0; 3];
+        let columns = self.matrix_columns()?;
+        for ((channel, column), &x) in [Channel::Red, Channel::Green, Channel::Blue].into_iter().zip(columns).zip(device) {
+            let v = self.trc(channel)?.eval(x);
+            xyz.iter_mut().zip(column).for_each(|(s, c)| *s += v * c);
+        }
+        Some(xyz)
+    }
+}
+
+// Device values of a grid with equally spaced values, from 0.0 to 1.0, along each channel.
+pub(crate) fn device_grid(channels: usize, n: usize) -> Vec<Vec<f64>> {
+    let steps: Vec<f64> = (0..n).map(|i| i as f64 / (n - 1) as f64).collect();
+    (0..channels).fold(vec![Vec::new()], |grid, _| {
+        grid.iter().flat_map(|v| steps.iter().map(move |&s| [&v[..], &[s]].concat())).collect()
+    })
+}
+
+#[test]
+fn test_equivalent() {
+    use crate::profile::standards;
+    use crate::tags::parametric_curve::ParametricCurve;
+    let (a, b) = (standards::srgb(), standards::srgb());
+    assert!(a.equivalent(&b, EquivalenceLevel::SemanticallyEqual));
+    assert!(!a.equivalent(&standards::display_p3(), EquivalenceLevel::SemanticallyEqual));
+
+    // a sampled curve instead of the parametric sRGB curve
+    let curve = match a.tag_data(&TagSignature::RedTRCTag) {
+        Some(TagData::ParametricCurve(p)) => *p,
+        _ => panic!("expected a parametric curve"),
+    };
+    let sampled = |p: ParametricCurve| {
+        TagData::Curve(crate::tags::Curve((0..1024).map(|i| (p.value(i as f32 / 1023.0) * 65535.0).round() as u16).collect()))
+    };
+    let c = standards::srgb()
+        .with_tag(TagSignature::RedTRCTag, sampled(curve))
+        .with_tag(TagSignature::GreenTRCTag, sampled(curve))
+        .with_tag(TagSignature::BlueTRCTag, sampled(curve));
+    assert!(!a.equivalent(&c, EquivalenceLevel::ByteIdentical));
+    assert!(!a.equivalent(&c, EquivalenceLevel::SemanticallyEqual));
+    assert!(a.equivalent(&c, EquivalenceLevel::ColorimetricallyEqual { tolerance: 1E-3 }));
+    assert!(!a.equivalent(&standards::adobe_rgb(), EquivalenceLevel::ColorimetricallyEqual { tolerance: 1E-3 }));
+}
@@ -0,0 +1,126 @@
+/*!
+  Helpers for profiles created from measurements: colorimetric values of measured patches, least
+  squares colorant matrices, and color difference statistics, used to report the accuracy of a fit.
+*/
+
+use crate::adaptation::{inverse, mul_vec};
+use crate::color::delta_e::cie76;
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::io::cgats::Patch;
+
+/// Statistics of CIELAB ΔE*ab color differences.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DeltaEStats {
+    pub mean: f64,
+    pub percentile_95: f64,
+    pub max: f64,
+}
+
+impl DeltaEStats {
+    pub fn new(differences: &[f64]) -> Self {
+        if differences.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = differences.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let i = ((sorted.len() - 1) as f64 * 0.95).round() as usize;
+        Self { mean: sorted.iter().sum::<f64>() / sorted.len() as f64, percentile_95: sorted[i], max: sorted[sorted.len() - 1] }
+    }
+
+    /// Statistics of the differences of pairs of XYZ values, relative to the PCS illuminant.
+    pub fn from_xyz(pairs: impl IntoIterator<Item = ([f64; 3], [f64; 3])>) -> Self {
+        let differences: Vec<f64> = pairs.into_iter().map(|(a, b)| delta_e(a, b)).collect();
+        Self::new(&differences)
+    }
+}
+
+pub(crate) fn delta_e(a: [f64; 3], b: [f64; 3]) -> f64 {
+    cie76(xyz_to_lab(a), xyz_to_lab(b))
+}
+
+pub(crate) fn lab_to_xyz([l, a, b]: [f64; 3]) -> [f64; 3] {
+    let f = |t: f64| if t > 6.0 / 29.0 { t.powi(3) } else { 3.0 * (6.0f64 / 29.0).powi(2) * (t - 4.0 / 29.0) };
+    let fy = (l + 16.0) / 116.0;
+    [f(fy + a / 500.0) * PCS_ILLUMINANT[0], f(fy) * PCS_ILLUMINANT[1], f(fy - b / 200.0) * PCS_ILLUMINANT[2]]
+}
+
+/// XYZ value of a patch, with a luminance of 1.0 for the perfect diffuser, from its XYZ values, in
+/// the range from 0 to 100, or its CIELAB values.
+pub(crate) fn patch_xyz(patch: &Patch) -> Option<[f64; 3]> {
+    patch.xyz.map(|xyz| xyz.map(|v| v / 100.0)).or(patch.lab.map(lab_to_xyz))
+}
+
+/// Scale of device values: 1, 100, or 255, from the largest value.
+pub(crate) fn device_scale(patches: &[Patch]) -> f64 {
+    match patches.iter().flat_map(|p| &p.device).fold(0.0, |a: f64, &b| a.max(b)) {
+        m if m <= 1.0 => 1.0,
+        m if m <= 100.0 => 100.0,
+        _ => 255.0,
+    }
+}
+
+/// Matrix with the smallest sum of squared differences of the XYZ values, and the matrix products
+/// of the linear device values.
+pub(crate) fn fit_matrix(linear: &[[f64; 3]], xyz: &[[f64; 3]]) -> [[f64; 3]; 3] {
+    // normal equations: M = (X^T L) (L^T L)^-1
+    let mut ltl = [[0.0; 3]; 3];
+    let mut xtl = [[0.0; 3]; 3];
+    for (l, x) in linear.iter().zip(xyz) {
+        for i in 0..3 {
+            for j in 0..3 {
+                ltl[i][j] += l[i] * l[j];
+                xtl[i][j] += x[i] * l[j];
+            }
+        }
+    }
+    let ltl_inv = inverse(ltl);
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        *row = mul_vec(ltl_inv, xtl[i]);
+    }
+    m
+}
+
+/// Solution of a system of linear equations, by Gaussian elimination with partial pivoting, or none
+/// for a singular system.
+pub(crate) fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for i in 0..n {
+        let pivot = (i..n).max_by(|&j, &k| a[j][i].abs().total_cmp(&a[k][i].abs()))?;
+        if a[pivot][i].abs() < 1E-300 {
+            return None;
+        }
+        a.swap(i, pivot);
+        b.swap(i, pivot);
+        for j in i + 1..n {
+            let f = a[j][i] / a[i][i];
+            let (top, bottom) = a.split_at_mut(j);
+            bottom[0][i..].iter_mut().zip(&top[i][i..]).for_each(|(x, y)| *x -= f * y);
+            b[j] -= f * b[i];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        x[i] = (b[i] - (i + 1..n).map(|k| a[i][k] * x[k]).sum::<f64>()) / a[i][i];
+    }
+    Some(x)
+}
+
+#[test]
+fn test_fit() {
+    let xyz = lab_to_xyz([50.0, 20.0, -30.0]);
+    let lab = xyz_to_lab(xyz);
+    assert!((lab[0] - 50.0).abs() < 1E-9 && (lab[1] - 20.0).abs() < 1E-9 && (lab[2] + 30.0).abs() < 1E-9);
+    let stats = DeltaEStats::new(&[1.0, 3.0, 2.0]);
+    assert_eq!((stats.mean, stats.max), (2.0, 3.0));
+
+    let m = [[0.4, 0.35, 0.15], [0.2, 0.7, 0.1], [0.02, 0.1, 0.7]];
+    let linear = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.5], [0.2, 0.3, 1.0], [0.5, 0.5, 0.5]];
+    let xyz: Vec<[f64; 3]> = linear.iter().map(|&l| mul_vec(m, l)).collect();
+    let fitted = fit_matrix(&linear, &xyz);
+    assert!(fitted.iter().flatten().zip(m.iter().flatten()).all(|(a, b)| (a - b).abs() < 1E-9));
+    let x = solve(vec![vec![0.0, 2.0], vec![1.0, 1.0]], vec![4.0, 3.0]).unwrap();
+    assert!((x[0] - 1.0).abs() < 1E-12 && (x[1] - 2.0).abs() < 1E-12);
+    assert!(solve(vec![vec![1.0, 1.0], vec![1.0, 1.0]], vec![1.0, 2.0]).is_none());
+}
@@ -0,0 +1,104 @@
+/*!
+  Profile IDs: the MD5 checksum of an encoded profile, with the profile flags, rendering intent,
+  and profile ID fields of its header set to zero, as defined in section 7.2.18 of the ICC
+  specification.
+
+  [Profile::to_buffer] writes the checksum of the encoded profile as its ID if the profile has an
+  ID, such as a parsed profile with an ID, or a profile after [Profile::with_profile_id]; the ID
+  is then always consistent with the written profile, also after edits.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+
+/// Profile ID of an encoded profile, calculated from its contents.
+pub fn profile_id(icc: &[u8]) -> u128 {
+    let mut buf = icc.to_vec();
+    for range in [44..48, 64..68, 84..100] {
+        if let Some(field) = buf.get_mut(range) {
+            field.fill(0);
+        }
+    }
+    u128::from_be_bytes(md5(&buf))
+}
+
+/// Checks if the ID in the header of an encoded profile is absent, or matches its contents.
+pub fn has_valid_profile_id(icc: &[u8]) -> bool {
+    match icc.get(84..100) {
+        Some(id) if id.iter().any(|&b| b != 0) => u128::from_be_bytes(id.try_into().unwrap()) == profile_id(icc),
+        _ => true,
+    }
+}
+
+impl Profile {
+    /// Sets the profile ID to the checksum of the encoded profile.
+    pub fn with_profile_id(mut self) -> Result<Self> {
+        self.profile_id = Some(0);
+        let icc = self.to_buffer()?;
+        self.profile_id = Some(u128::from_be_bytes(icc[84..100].try_into()?));
+        Ok(self)
+    }
+}
+
+/// MD5 message digest, as defined in RFC 1321.
+fn md5(data: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let k: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect();
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend(((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    for chunk in message.chunks(64) {
+        let m: Vec<u32> = chunk.chunks(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+            (a, d, c) = (d, c, b);
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+    let mut digest = [0; 16];
+    digest.iter_mut().zip(state.iter().flat_map(|s| s.to_le_bytes())).for_each(|(d, s)| *d = s);
+    digest
+}
+
+#[test]
+fn test_profile_id() {
+    assert_eq!(u128::from_be_bytes(md5(b"")), 0xd41d8cd98f00b204e9800998ecf8427e);
+    assert_eq!(u128::from_be_bytes(md5(b"The quick brown fox jumps over the lazy dog")), 0x9e107d9d372bb6826bd81d3542a419d6);
+    assert_eq!(u128::from_be_bytes(md5(b"abc")), 0x900150983cd24fb0d6963f7d28e17f72);
+
+    let srgb = crate::profile::standards::srgb();
+    assert!(srgb.profile_id.is_none());
+    let icc = srgb.with_profile_id().unwrap().to_buffer().unwrap();
+    assert!(icc[84..100].iter().any(|&b| b != 0));
+    assert!(has_valid_profile_id(&icc));
+    let mut edited = icc.clone();
+    edited[200] ^= 1;
+    assert!(!has_valid_profile_id(&edited));
+    // edits of the rendering intent do not change the ID
+    edited[200] ^= 1;
+    edited[67] = 3;
+    assert!(has_valid_profile_id(&edited));
+
+    let p3 = include_bytes!("../../examples/test_profiles/Display P3.icc");
+    assert!(p3[84..100].iter().any(|&b| b != 0) && has_valid_profile_id(p3));
+    assert!(has_valid_profile_id(&Profile::from_buffer(p3).unwrap().to_buffer().unwrap()));
+}
@@ -0,0 +1,214 @@
+/*!
+  A thread safe cache of parsed profiles, and transforms, keyed by profile ID, for image pipelines
+  which encounter the same embedded profiles over and over again.
+
+  The key is the profile ID calculated from the encoded profile, as defined in the [id](super::id)
+  module, and not the ID stored in its header, which may be absent, or wrong, together with the
+  profile flags, and rendering intent, header fields, which the profile ID does not include, but
+  which are parsed. Calculating the ID is much faster than parsing a profile. Profiles, and transforms, are each kept up to a configured
+  number of entries, after which the least recently used, or the oldest, entry is evicted.
+
+  ```
+  use cmx::profile::{id_cache::ProfileIdCache, standards, RenderingIntent};
+  let cache = ProfileIdCache::new();
+  let icc = standards::display_p3().to_buffer().unwrap();
+  let srgb = standards::srgb().to_buffer().unwrap();
+  let transform = cache.transform(&icc, &srgb, RenderingIntent::Perceptual).unwrap();
+  let again = cache.transform(&icc, &srgb, RenderingIntent::Perceptual).unwrap();
+  assert_eq!(cache.stats().hits, 1);
+  ```
+*/
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use crate::common::Result;
+use crate::profile::{id::profile_id, Profile, RenderingIntent};
+use crate::transform::Transform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Eviction {
+    /// Evicts the entry which was not used for the longest time.
+    #[default]
+    LeastRecentlyUsed,
+    /// Evicts the entry which was added first.
+    Oldest,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CacheOptions {
+    /// Largest number of profiles, and of transforms, kept in the cache.
+    pub capacity: usize,
+    pub eviction: Eviction,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self { capacity: 64, eviction: Eviction::LeastRecentlyUsed }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    value: Arc<T>,
+    added: u64,
+    used: u64,
+}
+
+// Profile ID, with the profile flags, and rendering intent, header fields, which it excludes.
+type ProfileKey = (u128, [u8; 4], [u8; 4]);
+
+fn profile_key(icc: &[u8]) -> ProfileKey {
+    let field = |offset: usize| icc.get(offset..offset + 4).and_then(|b| b.try_into().ok()).unwrap_or_default();
+    (profile_id(icc), field(44), field(64))
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    profiles: HashMap<ProfileKey, Entry<Profile>>,
+    transforms: HashMap<(ProfileKey, ProfileKey, u8), Entry<Transform>>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+#[derive(Debug, Default)]
+pub struct ProfileIdCache {
+    options: CacheOptions,
+    entries: Mutex<Entries>,
+}
+
+impl ProfileIdCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: CacheOptions) -> Self {
+        Self { options, entries: Mutex::default() }
+    }
+
+    /// Returns the parsed profile, parsing the encoded profile only if no profile with the same
+    /// ID is in the cache.
+    pub fn get(&self, icc: &[u8]) -> Result<Arc<Profile>> {
+        self.profile(profile_key(icc), icc)
+    }
+
+    /// Returns the transform between two encoded profiles, for a rendering intent, parsing the
+    /// profiles, and creating the transform, only if it is not in the cache.
+    pub fn transform(&self, src: &[u8], dst: &[u8], intent: RenderingIntent) -> Result<Arc<Transform>> {
+        let (src_id, dst_id) = (profile_key(src), profile_key(dst));
+        let key = (src_id, dst_id, intent as u8);
+        if let Some(transform) = self.lookup(|e| &mut e.transforms, &key) {
+            return Ok(transform);
+        }
+        let transform = Arc::new(Transform::new(&*self.profile(src_id, src)?, &*self.profile(dst_id, dst)?, intent)?);
+        self.insert(|e| &mut e.transforms, key, &transform);
+        Ok(transform)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.entries.lock().unwrap().stats
+    }
+
+    /// Number of profiles in the cache.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().profiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all profiles, and transforms, and resets the statistics.
+    pub fn clear(&self) {
+        *self.entries.lock().unwrap() = Entries::default();
+    }
+
+    fn profile(&self, id: ProfileKey, icc: &[u8]) -> Result<Arc<Profile>> {
+        if let Some(profile) = self.lookup(|e| &mut e.profiles, &id) {
+            return Ok(profile);
+        }
+        // parse without holding the lock, so other threads are not blocked
+        let profile = Arc::new(Profile::from_buffer(icc)?);
+        self.insert(|e| &mut e.profiles, id, &profile);
+        Ok(profile)
+    }
+
+    fn lookup<K: Hash + Eq, T>(&self, map: impl Fn(&mut Entries) -> &mut HashMap<K, Entry<T>>, key: &K) -> Option<Arc<T>> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+        let found = map(&mut entries).get_mut(key).map(|entry| {
+            entry.used = clock;
+            Arc::clone(&entry.value)
+        });
+        match found {
+            Some(_) => entries.stats.hits += 1,
+            None => entries.stats.misses += 1,
+        }
+        found
+    }
+
+    fn insert<K: Hash + Eq + Clone, T>(&self, map: impl Fn(&mut Entries) -> &mut HashMap<K, Entry<T>>, key: K, value: &Arc<T>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clock += 1;
+        let clock = entries.clock;
+        let eviction = self.options.eviction;
+        let capacity = self.options.capacity;
+        let target = map(&mut entries);
+        target.insert(key, Entry { value: Arc::clone(value), added: clock, used: clock });
+        let mut evicted = 0;
+        while target.len() > capacity {
+            let age = |e: &Entry<T>| if eviction == Eviction::Oldest { e.added } else { e.used };
+            let Some(oldest) = target.iter().min_by_key(|(_, e)| age(e)).map(|(k, _)| k.clone()) else { break };
+            target.remove(&oldest);
+            evicted += 1;
+        }
+        entries.stats.evictions += evicted;
+    }
+}
+
+#[test]
+fn test_profile_id_cache() {
+    use crate::profile::standards;
+    let [srgb, p3, gray] = [standards::srgb(), standards::display_p3(), standards::gray_gamma22()].map(|p| p.to_buffer().unwrap());
+
+    let cache = ProfileIdCache::with_options(CacheOptions { capacity: 2, ..Default::default() });
+    let a = cache.get(&srgb).unwrap();
+    assert!(Arc::ptr_eq(&a, &cache.get(&srgb).unwrap()));
+    // the rendering intent in the header is not part of the profile ID, but is part of the key
+    let mut relative = srgb.clone();
+    relative[67] = 1;
+    let b = cache.get(&relative).unwrap();
+    assert!(!Arc::ptr_eq(&a, &b) && b.rendering_intent != a.rendering_intent);
+    assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 2, evictions: 0 });
+    cache.clear();
+    let a = cache.get(&srgb).unwrap();
+
+    // sRGB is used last, and is kept
+    cache.get(&p3).unwrap();
+    cache.get(&srgb).unwrap();
+    cache.get(&gray).unwrap();
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.stats().evictions, 1);
+    assert!(Arc::ptr_eq(&a, &cache.get(&srgb).unwrap()));
+
+    let t = cache.transform(&p3, &srgb, RenderingIntent::Perceptual).unwrap();
+    assert!(Arc::ptr_eq(&t, &cache.transform(&p3, &srgb, RenderingIntent::Perceptual).unwrap()));
+    assert!(!Arc::ptr_eq(&t, &cache.transform(&p3, &srgb, RenderingIntent::AbsoluteColorimetric).unwrap()));
+    assert!(cache.get(b"not a profile").is_err());
+
+    let oldest = ProfileIdCache::with_options(CacheOptions { capacity: 1, eviction: Eviction::Oldest });
+    let a = oldest.get(&srgb).unwrap();
+    oldest.get(&p3).unwrap();
+    assert!(!Arc::ptr_eq(&a, &oldest.get(&srgb).unwrap()));
+    cache.clear();
+    assert!(cache.is_empty() && cache.stats() == CacheStats::default());
+}
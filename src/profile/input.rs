@@ -0,0 +1,161 @@
+/*!
+  Scanner, and camera, input profiles from measurements of an IT8.7/1, IT8.7/2, or similar, target:
+  the device RGB values of the patches, as scanned, and their reference XYZ, or CIELAB, values, as
+  read with the [CGATS](crate::io::cgats) parser.
+
+  A gamma curve for each channel, and a colorant matrix, are fitted to the measurements, minimizing
+  the CIELAB color differences of the patches. Optionally, the remaining differences are corrected
+  with a color lookup table in an 'A2B0' tag, using inverse distance weighting of the differences of
+  the patches, in linear device space.
+*/
+
+use crate::adaptation::mul_vec;
+use crate::color::pcs::XYZ_ENCODING_MAX;
+use crate::common::Result;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::io::cgats::MeasurementSet;
+use crate::profile::fit::{delta_e, device_scale, fit_matrix, patch_xyz, DeltaEStats};
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{
+    curve_fit::minimize,
+    lut::{lut_atob, ClutBuilder},
+    multi_localized_unicode::MultiLocalizedUnicode,
+    parametric_curve::ParametricCurve,
+    Curve, TagData, XYZ,
+};
+
+#[derive(Debug, Clone)]
+pub struct InputProfileOptions {
+    pub description: String,
+    /// Number of grid points of a color lookup table correcting the matrix/TRC model, or none for
+    /// a matrix/TRC profile only.
+    pub grid_points: Option<usize>,
+}
+
+impl Default for InputProfileOptions {
+    fn default() -> Self {
+        Self { description: String::from("Input profile"), grid_points: None }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputProfile {
+    pub profile: Profile,
+    /// Color differences of the patches, between their measured values, and the values of the profile.
+    pub delta_e: DeltaEStats,
+}
+
+impl InputProfile {
+    pub fn from_it8(measurements: &MeasurementSet, options: &InputProfileOptions) -> Result<InputProfile> {
+        if measurements.device_space.as_deref() != Some("RGB") {
+            return Err("input profiles require measurements with RGB device values".into());
+        }
+        let scale = device_scale(&measurements.patches);
+        let mut device = Vec::new();
+        let mut xyz = Vec::new();
+        for patch in &measurements.patches {
+            let v = patch_xyz(patch).ok_or_else(|| format!("patch {} without XYZ, or CIELAB, values", patch.id))?;
+            device.push([0, 1, 2].map(|i| (patch.device[i] / scale).clamp(0.0, 1.0)));
+            xyz.push(v);
+        }
+        if device.len() < 4 {
+            return Err("at least four patches are required".into());
+        }
+
+        let linear = |gammas: &[f64]| -> Vec<[f64; 3]> { device.iter().map(|d| [0, 1, 2].map(|i| d[i].powf(gammas[i]))).collect() };
+        let error = |gammas: &[f64]| {
+            if gammas.iter().any(|&g| !(0.1..=10.0).contains(&g)) {
+                return f64::MAX;
+            }
+            let linear = linear(gammas);
+            let m = fit_matrix(&linear, &xyz);
+            linear.iter().zip(&xyz).map(|(l, x)| delta_e(mul_vec(m, *l), *x).powi(2)).sum()
+        };
+        let gammas = minimize(error, vec![2.0; 3]);
+        let linear = linear(&gammas);
+        let matrix = fit_matrix(&linear, &xyz);
+        let residuals: Vec<[f64; 3]> = linear.iter().zip(&xyz).map(|(l, x)| {
+            let m = mul_vec(matrix, *l);
+            [0, 1, 2].map(|i| x[i] - m[i])
+        }).collect();
+
+        let mut profile = Profile::new([4, 3, 0], Class::Input);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::RGB));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        let column = |i: usize| TagData::XYZ(XYZ(vec![[matrix[0][i], matrix[1][i], matrix[2][i]]]));
+        let trc = |i: usize| TagData::ParametricCurve(ParametricCurve::ExponentGamma { g: gammas[i] as f32 });
+        profile = profile
+            .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(&options.description)))
+            .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us("No copyright, use freely")))
+            .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![PCS_ILLUMINANT])))
+            .with_tag(TagSignature::RedMatrixColumnTag, column(0))
+            .with_tag(TagSignature::GreenMatrixColumnTag, column(1))
+            .with_tag(TagSignature::BlueMatrixColumnTag, column(2))
+            .with_tag(TagSignature::RedTRCTag, trc(0))
+            .with_tag(TagSignature::GreenTRCTag, trc(1))
+            .with_tag(TagSignature::BlueTRCTag, trc(2));
+
+        let model = |l: &[f64]| mul_vec(matrix, [l[0], l[1], l[2]]);
+        let Some(grid_points) = options.grid_points else {
+            let delta_e = DeltaEStats::from_xyz(linear.iter().zip(&xyz).map(|(l, x)| (model(l), *x)));
+            return Ok(InputProfile { profile, delta_e });
+        };
+        // matrix/TRC model, with the residuals of the patches interpolated by inverse distance weighting
+        let corrected = |l: &[f64]| {
+            let (mut sum, mut weights) = ([0.0; 3], 0.0);
+            for (p, r) in linear.iter().zip(&residuals) {
+                let d2: f64 = (0..3).map(|i| (p[i] - l[i]).powi(2)).sum();
+                let w = 1.0 / (d2 + 1E-6).powi(2);
+                (0..3).for_each(|i| sum[i] += w * r[i]);
+                weights += w;
+            }
+            let m = model(l);
+            [0, 1, 2].map(|i| m[i] + sum[i] / weights)
+        };
+        let clut = ClutBuilder::new(3, 3, grid_points).fill(|l| corrected(l).iter().map(|v| v / XYZ_ENCODING_MAX).collect())?;
+        let identity = || vec![TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new()))];
+        // the patches as evaluated through the tag: the gamma curves, and the quantized lookup table
+        let curves = gammas.iter().map(|&g| ParametricCurve::ExponentGamma { g: g as f32 });
+        let evaluated = |d: &[f64; 3]| {
+            let l: Vec<f64> = curves.clone().zip(d).map(|(c, &v)| c.eval(v)).collect();
+            let v = clut.eval(&l);
+            [0, 1, 2].map(|i| v[i] * XYZ_ENCODING_MAX)
+        };
+        let delta_e = DeltaEStats::from_xyz(device.iter().zip(&xyz).map(|(d, x)| (evaluated(d), *x)));
+        let atob = lut_atob((0..3).map(trc).collect(), clut, Vec::new(), None, identity())?;
+        Ok(InputProfile { profile: profile.with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob)), delta_e })
+    }
+}
+
+#[test]
+fn test_input_profile() {
+    use crate::io::cgats::Patch;
+    use crate::transform::MatrixTrc;
+    // target values from the Adobe RGB profile, with a gamma of 563/256
+    let model = MatrixTrc::from_profile(&crate::profile::standards::adobe_rgb()).unwrap();
+    let mut set = MeasurementSet { device_space: Some(String::from("RGB")), ..Default::default() };
+    for i in 0..125 {
+        let device = [i / 25, i / 5 % 5, i % 5].map(|v| v as f64 * 255.0 / 4.0);
+        let xyz = model.to_xyz(&device.map(|v| v / 255.0)).map(|v| v * 100.0);
+        set.patches.push(Patch { id: i.to_string(), device: device.to_vec(), xyz: Some(xyz), ..Default::default() });
+    }
+    let input = InputProfile::from_it8(&set, &InputProfileOptions::default()).unwrap();
+    assert!(input.delta_e.max < 0.05, "{:?}", input.delta_e);
+    assert!(input.profile.missing_tags().is_empty());
+    let Some(TagData::ParametricCurve(ParametricCurve::ExponentGamma { g })) =
+        input.profile.tag_data(&TagSignature::GreenTRCTag)
+    else {
+        panic!("expected a gamma curve")
+    };
+    assert!((g - 563.0 / 256.0).abs() < 0.01, "{g}");
+
+    let options = InputProfileOptions { grid_points: Some(9), ..Default::default() };
+    let input = InputProfile::from_it8(&set, &options).unwrap();
+    assert!(Profile::from_buffer(&input.profile.to_buffer().unwrap()).is_ok());
+    // evaluated through the 16-bit lookup table, and its interpolation, with 9 grid points
+    assert!(input.delta_e.max < 0.04, "{:?}", input.delta_e);
+    set.device_space = Some(String::from("CMYK"));
+    assert!(InputProfile::from_it8(&set, &options).is_err());
+}
@@ -0,0 +1,101 @@
+/*!
+  Lazy reading of large profiles, such as camera and printer profiles with large lookup tables.
+
+  A [LazyProfile] reads only the header, and the tag table, when opened; the data of a tag is read
+  from the underlying reader when the tag is first accessed, and kept for later use. This allows
+  inspecting the header, or a few tags, of a profile of several megabytes, without reading it
+  completely.
+*/
+
+#[cfg(feature = "fs")]
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+use crate::common::Result;
+use crate::profile::{Profile, TagTableRow};
+use crate::signatures::tag::TagSignature;
+use crate::tags::Tag;
+
+pub struct LazyProfile<R> {
+    reader: R,
+    // profile header, with the tags loaded so far
+    profile: Profile,
+    table: Vec<TagTableRow>,
+}
+
+#[cfg(feature = "fs")]
+impl LazyProfile<BufReader<File>> {
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> LazyProfile<R> {
+    pub fn new(mut reader: R) -> Result<Self> {
+        let file_size: usize = reader.seek(SeekFrom::End(0))?.try_into()?;
+        reader.seek(SeekFrom::Start(0))?;
+        if file_size < 132 {
+            return Err("ICC profile size error".into());
+        }
+        let mut buf = vec![0u8; 132];
+        reader.read_exact(&mut buf)?;
+        // the tag count is the last field read, and determines the size of the tag table
+        let n = u32::from_be_bytes(buf[128..132].try_into()?) as usize;
+        let table_end = n.checked_mul(12).and_then(|t| t.checked_add(132)).filter(|&e| e <= file_size).ok_or("ICC tag count error")?;
+        buf.resize(table_end, 0);
+        reader.read_exact(&mut buf[132..])?;
+        let (profile, table) = Profile::read_header(&buf, file_size)?;
+        Ok(Self { reader, profile, table })
+    }
+
+    /// The profile header; the profile's tags are the tags loaded so far.
+    pub fn header(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Signatures of all tags in the profile, in the order of the tag table.
+    pub fn signatures(&self) -> impl Iterator<Item = &TagSignature> {
+        self.table.iter().map(|row| &row.sig)
+    }
+
+    /// Reads a tag, if not loaded yet; returns `None` if the profile has no tag with this signature.
+    pub fn tag(&mut self, signature: &TagSignature) -> Result<Option<&Tag>> {
+        if !self.profile.contains_tag(signature) {
+            let Some(row) = self.table.iter().find(|row| &row.sig == signature) else {
+                return Ok(None);
+            };
+            let mut data = vec![0u8; row.length];
+            self.reader.seek(SeekFrom::Start(row.offset as u64))?;
+            self.reader.read_exact(&mut data)?;
+            self.profile.tags.push(Tag::try_new(signature.clone(), &mut data.as_slice())?);
+        }
+        Ok(self.profile.get_tag(signature))
+    }
+
+    /// Reads all tags not loaded yet, and returns the complete profile, with its tags in the order of
+    /// the tag table.
+    pub fn load_all(mut self) -> Result<Profile> {
+        let signatures: Vec<TagSignature> = self.table.iter().map(|row| row.sig.clone()).collect();
+        for signature in &signatures {
+            self.tag(signature)?;
+        }
+        let mut profile = self.profile;
+        profile.tags.sort_by_key(|t| signatures.iter().position(|s| s == t.signature()));
+        Ok(profile)
+    }
+}
+
+#[test]
+fn test_lazy_profile() {
+    let buf = crate::profile::standards::srgb().to_buffer().unwrap();
+    let mut lazy = LazyProfile::new(std::io::Cursor::new(buf.clone())).unwrap();
+    assert_eq!(lazy.signatures().count(), 10);
+    assert_eq!(lazy.header().tag_count(), 0);
+    assert!(lazy.tag(&TagSignature::GreenTRCTag).unwrap().is_some());
+    assert!(lazy.tag(&TagSignature::GrayTRCTag).unwrap().is_none());
+    assert_eq!(lazy.header().tag_count(), 1);
+    assert_eq!(lazy.load_all().unwrap().to_buffer().unwrap(), buf);
+
+    assert!(LazyProfile::new(std::io::Cursor::new(&buf[..200])).is_err());
+    assert!(LazyProfile::new(std::io::Cursor::new(&buf[..100])).is_err());
+}
@@ -0,0 +1,143 @@
+/*!
+  Device link profiles, combining the transforms of a source and a destination profile into a single
+  transform, from source to destination device values, in an 'A2B0' tag.
+
+  Only matrix/TRC, and gray, profiles are supported, as used by a [Transform] without lookup tables.
+  Their transform is encoded exactly in a lutAtoBType pipeline: the source tone response curves as
+  'A' curves, a color lookup table with two grid points, the matrix combining both colorant matrices, and the
+  inverse of the destination tone response curves as 'B' curves. With black point compensation, the
+  matrix offsets map the source black point to the destination black point.
+*/
+
+use crate::adaptation::{inverse, mul, mul_vec};
+use crate::common::Result;
+use crate::profile::{Class, Profile, RenderingIntent};
+use crate::progress::{NoProgress, Progress};
+use crate::signatures::{tag::TagSignature, technology::TechnologySignature};
+use crate::tags::{
+    multi_localized_unicode::MultiLocalizedUnicode,
+    parametric_curve::ParametricCurve,
+    profile_sequence_desc::{ProfileDescription, ProfileSequenceDesc},
+    lut::{lut_atob, ClutBuilder},
+    TagData,
+};
+use crate::transform::{Transform, Trc};
+
+// Number of entries of sampled inverse tone response curves.
+const INVERSE_CURVE_ENTRIES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkOptions {
+    /// Apply black point compensation, see [Transform::with_black_point_compensation].
+    pub black_point_compensation: bool,
+}
+
+impl Profile {
+    /// Device link profile, from the source to the destination profile's device values, for the
+    /// given rendering intent.
+    pub fn device_link(src: &Profile, dst: &Profile, intent: RenderingIntent) -> Result<Profile> {
+        Self::device_link_with_options(src, dst, intent, &LinkOptions::default())
+    }
+
+    pub fn device_link_with_options(src: &Profile, dst: &Profile, intent: RenderingIntent, options: &LinkOptions) -> Result<Profile> {
+        Self::device_link_with_progress(src, dst, intent, options, &mut NoProgress)
+    }
+
+    /// Device link profile, as [Profile::device_link_with_options], reporting the progress of
+    /// sampling its color lookup table, and stopping with an error if the operation is cancelled.
+    pub fn device_link_with_progress(src: &Profile, dst: &Profile, intent: RenderingIntent, options: &LinkOptions, progress: &mut dyn Progress) -> Result<Profile> {
+        let mut transform = Transform::new(src, dst, intent)?;
+        if options.black_point_compensation {
+            transform = transform.with_black_point_compensation();
+        }
+        let (Some(s), Some(d)) = (transform.source(), transform.destination()) else {
+            return Err("device links of lookup table, and pipeline, transforms are not supported".into());
+        };
+        let (scale, offset) = (transform.pcs_scale(), transform.pcs_offset());
+        let scale = [[scale[0], 0.0, 0.0], [0.0, scale[1], 0.0], [0.0, 0.0, scale[2]]];
+        let linear = mul(scale, s.matrix);
+
+        let a: Vec<TagData> = s.trcs.iter().map(Trc::to_tag_data).collect();
+        let b: Vec<TagData> = d.trcs.iter().map(inverse_trc).collect();
+        let atob = if d.channels() == 3 {
+            let identity = || TagData::Curve(crate::tags::Curve(Vec::new()));
+            let matrix = (mul(inverse(d.matrix), linear), mul_vec(inverse(d.matrix), offset));
+            let clut = ClutBuilder::new(s.channels(), 3, 2).fill_with_progress(|v| [v, &[0.0; 3][v.len()..]].concat(), progress)?;
+            lut_atob(a, clut, vec![identity(), identity(), identity()], Some(matrix), b)?
+        } else {
+            // gray destination, with its luminance a linear function of the source's linear values
+            let white_y = d.matrix[1][0];
+            let clut = ClutBuilder::new(s.channels(), 1, 2)
+                .fill_with_progress(|v| vec![(v.iter().zip(linear[1]).map(|(v, m)| v * m).sum::<f64>() + offset[1]) / white_y], progress)?;
+            lut_atob(a, clut, Vec::new(), None, b)?
+        };
+
+        let mut link = Profile::new([4, 3, 0], Class::DeviceLink);
+        link.colorspace = src.colorspace;
+        link.pcs = dst.colorspace;
+        link.rendering_intent = intent;
+        let description = |profile: &Profile| profile.description(None).unwrap_or_else(|| String::from("unnamed profile"));
+        let name = format!("{} to {}", description(src), description(dst));
+        Ok(link
+            .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(&name)))
+            .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us("No copyright, use freely")))
+            .with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob))
+            .with_tag(TagSignature::ProfileSequenceDescTag, TagData::ProfileSequenceDesc(ProfileSequenceDesc(vec![sequence(src), sequence(dst)]))))
+    }
+}
+
+fn sequence(profile: &Profile) -> ProfileDescription {
+    let technology = match profile.tag_data(&TagSignature::TechnologyTag) {
+        Some(TagData::Technology(t)) => *t,
+        _ => TechnologySignature::Unknown,
+    };
+    ProfileDescription::new(profile.manufacturer.as_deref().unwrap_or_default(), profile.device.as_deref().unwrap_or_default(), technology)
+        .with_model_description(&profile.description(None).unwrap_or_else(|| String::from("unnamed profile")))
+}
+
+// Inverse tone response curve, as a gamma function for gamma curves, or as a sampled curve.
+fn inverse_trc(trc: &Trc) -> TagData {
+    match trc {
+        Trc::Parametric(ParametricCurve::ExponentGamma { g }) => TagData::ParametricCurve(ParametricCurve::ExponentGamma { g: 1.0 / g }),
+        _ => {
+            let n = INVERSE_CURVE_ENTRIES;
+            TagData::Curve(crate::tags::Curve(
+                (0..n).map(|i| (trc.eval_inverse(i as f64 / (n - 1) as f64) * 65535.0).round() as u16).collect(),
+            ))
+        }
+    }
+}
+
+#[test]
+fn test_device_link() {
+    use crate::profile::standards;
+    let link = Profile::device_link(&standards::display_p3(), &standards::srgb(), RenderingIntent::Perceptual).unwrap();
+    assert!(link.missing_tags().is_empty());
+    let buf = link.to_buffer().unwrap();
+    let parsed = Profile::from_buffer(&buf).unwrap();
+    assert!(matches!(parsed.class, Class::DeviceLink));
+    let atob = parsed.get_tag(&TagSignature::AToB0Tag).unwrap();
+    let TagData::LutAToB(data) = atob.data() else { panic!("expected a lutAtoBType tag") };
+    // offsets of all five elements, each within the tag
+    let offsets: Vec<u32> = data[4..24].chunks(4).map(|c| u32::from_be_bytes(c.try_into().unwrap())).collect();
+    assert!(offsets.iter().all(|&o| o >= 32 && (o as usize) < data.len() + 8), "{offsets:?}");
+
+    let gray = Profile::device_link(&standards::srgb(), &standards::gray_gamma22(), RenderingIntent::Perceptual).unwrap();
+    assert!(gray.to_buffer().is_ok());
+    assert!(Profile::device_link(&Profile::default(), &standards::srgb(), RenderingIntent::Perceptual).is_err());
+
+    // the matrix offsets are zero, unless black point compensation maps black to a lighter black
+    let matrix_offsets = |link: &Profile| -> Vec<u8> {
+        let Some(TagData::LutAToB(data)) = link.tag_data(&TagSignature::AToB0Tag) else { panic!("expected a lutAtoBType tag") };
+        let matrix = u32::from_be_bytes(data[8..12].try_into().unwrap()) as usize - 8;
+        data[matrix + 36..matrix + 48].to_vec()
+    };
+    let mut lighter = standards::srgb();
+    for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+        lighter = lighter.with_tag(signature, TagData::ParametricCurve(ParametricCurve::IEC61966_3 { g: 2.2, a: 1.0, b: 0.0, c: 0.02 }));
+    }
+    let options = LinkOptions { black_point_compensation: true };
+    assert!(matrix_offsets(&link).iter().all(|&b| b == 0));
+    let bpc = Profile::device_link_with_options(&standards::srgb(), &lighter, RenderingIntent::Perceptual, &options).unwrap();
+    assert!(matrix_offsets(&bpc).iter().any(|&b| b != 0));
+}
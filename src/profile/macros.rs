@@ -0,0 +1,156 @@
+/*!
+  The [profile!](crate::profile!) macro, a compact notation for profiles, mainly intended for
+  test fixtures and examples.
+*/
+
+/// Creates a version 4.3 [Profile](crate::profile::Profile), from a list of header fields and tags.
+///
+/// The profile class comes first, followed by any of:
+///
+/// - header fields: `version: [4, 3, 0]`, `colorspace: RGB`, `pcs: XYZ`, and `intent: Perceptual`;
+/// - text tags: `desc` and `cprt`, as US English multi-localized strings;
+/// - XYZ tags: `wtpt`, `bkpt`, `rXYZ`, `gXYZ`, and `bXYZ`, and a `chad` matrix of nine values;
+/// - tone response curves: `rTRC`, `gTRC`, `bTRC`, and `kTRC`, with `trc` setting all three RGB
+///   curves, given as `gamma(2.2)`, `srgb`, or `curve(vec![..])` for a sampled curve;
+/// - any other tag, as `tag(Signature): data`, with a `TagSignature` variant and a `TagData` value.
+///
+/// ```
+/// let profile = cmx::profile! {
+///     class: Display,
+///     colorspace: Gray,
+///     pcs: XYZ,
+///     desc: "Gray Gamma 2.2",
+///     wtpt: cmx::illuminants::PCS_ILLUMINANT,
+///     kTRC: gamma(2.2),
+/// };
+/// assert_eq!(profile.tag_count(), 3);
+/// ```
+#[macro_export]
+macro_rules! profile {
+    (class: $class:ident $(, $($rest:tt)*)?) => {{
+        let profile = $crate::profile::Profile::new([4, 3, 0], $crate::profile::Class::$class);
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+
+    (@fields $p:expr; ) => { $p };
+    (@fields $p:expr; version: $v:expr $(, $($rest:tt)*)?) => {{
+        let mut profile = $p;
+        profile.version = $v;
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+    (@fields $p:expr; colorspace: $space:ident $(, $($rest:tt)*)?) => {{
+        let mut profile = $p;
+        profile.colorspace = Some($crate::profile::ColorSpace::new($crate::signatures::colorspace::ColorSpaceSignature::$space));
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+    (@fields $p:expr; pcs: $space:ident $(, $($rest:tt)*)?) => {{
+        let mut profile = $p;
+        profile.pcs = Some($crate::profile::ColorSpace::new($crate::signatures::colorspace::ColorSpaceSignature::$space));
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+    (@fields $p:expr; intent: $intent:ident $(, $($rest:tt)*)?) => {{
+        let mut profile = $p;
+        profile.rendering_intent = $crate::profile::RenderingIntent::$intent;
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+    (@fields $p:expr; desc: $text:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, ProfileDescriptionTag, $crate::profile!(@text $text); $($($rest)*)?)
+    };
+    (@fields $p:expr; cprt: $text:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, CopyrightTag, $crate::profile!(@text $text); $($($rest)*)?)
+    };
+    (@fields $p:expr; wtpt: $xyz:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, MediaWhitePointTag, $crate::profile!(@xyz $xyz); $($($rest)*)?)
+    };
+    (@fields $p:expr; bkpt: $xyz:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, MediaBlackPointTag, $crate::profile!(@xyz $xyz); $($($rest)*)?)
+    };
+    (@fields $p:expr; rXYZ: $xyz:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, RedMatrixColumnTag, $crate::profile!(@xyz $xyz); $($($rest)*)?)
+    };
+    (@fields $p:expr; gXYZ: $xyz:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, GreenMatrixColumnTag, $crate::profile!(@xyz $xyz); $($($rest)*)?)
+    };
+    (@fields $p:expr; bXYZ: $xyz:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, BlueMatrixColumnTag, $crate::profile!(@xyz $xyz); $($($rest)*)?)
+    };
+    (@fields $p:expr; chad: $m:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, ChromaticAdaptationTag,
+            $crate::tags::TagData::S15Fixed16Array(<[f32; 9]>::from($m).to_vec()); $($($rest)*)?)
+    };
+    (@fields $p:expr; trc: $kind:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {{
+        let profile = $p
+            .with_tag($crate::signatures::tag::TagSignature::RedTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?))
+            .with_tag($crate::signatures::tag::TagSignature::GreenTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?))
+            .with_tag($crate::signatures::tag::TagSignature::BlueTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?));
+        $crate::profile!(@fields profile; $($($rest)*)?)
+    }};
+    (@fields $p:expr; rTRC: $kind:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, RedTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?); $($($rest)*)?)
+    };
+    (@fields $p:expr; gTRC: $kind:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, GreenTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?); $($($rest)*)?)
+    };
+    (@fields $p:expr; bTRC: $kind:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, BlueTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?); $($($rest)*)?)
+    };
+    (@fields $p:expr; kTRC: $kind:ident $(($($arg:tt)*))? $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, GrayTRCTag, $crate::profile!(@trc $kind $(($($arg)*))?); $($($rest)*)?)
+    };
+    (@fields $p:expr; tag($sig:ident): $data:expr $(, $($rest:tt)*)?) => {
+        $crate::profile!(@tag $p, $sig, $data; $($($rest)*)?)
+    };
+
+    (@tag $p:expr, $sig:ident, $data:expr; $($rest:tt)*) => {{
+        let profile = $p.with_tag($crate::signatures::tag::TagSignature::$sig, $data);
+        $crate::profile!(@fields profile; $($rest)*)
+    }};
+    (@text $text:expr) => {
+        $crate::tags::TagData::MultiLocalizedUnicode($crate::tags::multi_localized_unicode::MultiLocalizedUnicode::en_us($text))
+    };
+    (@xyz $xyz:expr) => {
+        $crate::tags::TagData::XYZ($crate::tags::XYZ(vec![<[f64; 3]>::from($xyz)]))
+    };
+    (@trc gamma($g:expr)) => {
+        $crate::tags::TagData::ParametricCurve($crate::tags::parametric_curve::ParametricCurve::ExponentGamma { g: $g as f32 })
+    };
+    (@trc srgb) => {
+        $crate::tags::TagData::ParametricCurve($crate::tags::parametric_curve::ParametricCurve::IEC61966_2_1 {
+            g: 2.4,
+            a: (1.0 / 1.055) as f32,
+            b: (0.055 / 1.055) as f32,
+            c: (1.0 / 12.92) as f32,
+            d: 0.04045,
+        })
+    };
+    (@trc curve($v:expr)) => {
+        $crate::tags::TagData::Curve($crate::tags::Curve(Vec::<u16>::from($v)))
+    };
+}
+
+#[test]
+fn test_profile_macro() {
+    use crate::profile::standards;
+    let srgb = crate::profile! {
+        class: Display,
+        colorspace: RGB,
+        pcs: XYZ,
+        desc: "sRGB",
+        cprt: "No copyright, use freely",
+        wtpt: crate::illuminants::PCS_ILLUMINANT,
+    };
+    let diff = srgb.diff(&standards::srgb());
+    assert!(diff.header.iter().all(|d| ["date_time", "pcs_illuminant"].contains(&d.field.as_str())), "{}", diff);
+    assert!(diff.removed.is_empty() && diff.changed.is_empty());
+
+    let gray = crate::profile! {
+        class: Display,
+        version: [2, 1, 0],
+        colorspace: Gray,
+        intent: AbsoluteColorimetric,
+        kTRC: curve(vec![0, 0x8000, 0xFFFF]),
+        tag(LuminanceTag): crate::tags::TagData::XYZ(crate::tags::XYZ(vec![[0.0, 100.0, 0.0]])),
+    };
+    assert_eq!(gray.version, [2, 1, 0]);
+    assert_eq!(gray.tag_count(), 2);
+}
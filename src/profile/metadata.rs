@@ -0,0 +1,66 @@
+/*!
+  Profile metadata, stored as name/value pairs in the dictionary of the metadata ('meta') tag, for
+  information such as the serial number of a device, or the software used to create a profile.
+
+  The `keys` module lists names in common use, as defined by the colord metadata specification, which
+  are also read by other color management tools.
+*/
+
+use std::collections::BTreeMap;
+
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::TagData;
+
+pub mod keys {
+    /// Name of the software which created the profile.
+    pub const CMF_PRODUCT: &str = "CMF_PRODUCT";
+    /// Name of the executable which created the profile.
+    pub const CMF_BINARY: &str = "CMF_BINARY";
+    /// Version of the software which created the profile.
+    pub const CMF_VERSION: &str = "CMF_VERSION";
+    /// Source of the profile data, such as `calib`, `edid`, or `standard`.
+    pub const DATA_SOURCE: &str = "DATA_source";
+    /// Serial number of the display, from its EDID.
+    pub const EDID_SERIAL: &str = "EDID_serial";
+    /// Model name of the display, from its EDID.
+    pub const EDID_MODEL: &str = "EDID_model";
+    /// Manufacturer name of the display, from its EDID.
+    pub const EDID_MANUFACTURER: &str = "EDID_mnft";
+    /// MD5 hash of the EDID of the display.
+    pub const EDID_MD5: &str = "EDID_md5";
+    /// License of the profile, as an SPDX identifier or URL.
+    pub const LICENSE: &str = "License";
+}
+
+impl Profile {
+    /// The entries of the metadata tag with a value, empty if the profile has no metadata tag.
+    pub fn metadata(&self) -> BTreeMap<String, String> {
+        match self.tag_data(&TagSignature::MetaDataTag) {
+            Some(TagData::Dict(d)) => d.iter().filter_map(|(k, v)| Some((k.to_owned(), v?.to_owned()))).collect(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Sets a metadata entry, adding the metadata tag if not present.
+    pub fn with_metadata(self, key: &str, value: &str) -> Self {
+        self.with_dict(TagSignature::MetaDataTag, |d| {
+            d.insert(key, value);
+        })
+    }
+}
+
+#[test]
+fn test_metadata() {
+    let profile = crate::profile::standards::srgb();
+    assert!(profile.metadata().is_empty());
+    let profile = profile
+        .with_metadata(keys::CMF_PRODUCT, "cmx")
+        .with_metadata(keys::EDID_SERIAL, "ABC123")
+        .with_metadata(keys::CMF_PRODUCT, "cmx-cli");
+    let parsed = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let metadata = parsed.metadata();
+    assert_eq!(metadata.len(), 2);
+    assert_eq!(metadata[keys::CMF_PRODUCT], "cmx-cli");
+    assert_eq!(metadata[keys::EDID_SERIAL], "ABC123");
+}
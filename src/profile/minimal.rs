@@ -0,0 +1,84 @@
+/*!
+  Minimal version 2 matrix/TRC display profiles, for embedding in images on the web, where the size
+  of a profile adds to the size of every image.
+
+  A minimal profile has only the tags required for a version 2 display profile: a description, a
+  copyright, the media white point, the colorants, and the tone response curves. The three tone
+  response curves are identical sampled curves, written once, and shared by the three tags; with
+  the default of 32 curve entries, the minimal sRGB profile is about 500 bytes.
+*/
+
+use crate::common::Result;
+use crate::illuminants::{D65_XY, PCS_ILLUMINANT};
+use crate::profile::summary::estimate_gamma;
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, Curve, TagData};
+
+#[derive(Debug, Clone)]
+pub struct MinimalOptions {
+    pub description: String,
+    /// Copyright text; the tag is required by the specification, but some applications, which only
+    /// use the colorants and curves, accept profiles without it.
+    pub copyright: Option<String>,
+    /// Number of entries of the tone response curve: with a single entry, the curve is the power
+    /// function best fitting the transfer function.
+    pub curve_entries: usize,
+}
+
+impl Default for MinimalOptions {
+    fn default() -> Self {
+        Self { description: String::from("sRGB"), copyright: Some(String::from("CC0")), curve_entries: 32 }
+    }
+}
+
+/// Minimal sRGB profile.
+pub fn minimal_srgb(options: &MinimalOptions) -> Result<Profile> {
+    let srgb = |v: f64| if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+    minimal_from_rgb_space([[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]], D65_XY, srgb, options)
+}
+
+/// Minimal profile for an RGB color space, from the xy chromaticities of its primaries, and white
+/// point, and its transfer function, from encoded values to linear values, both in the range from
+/// 0.0 to 1.0.
+pub fn minimal_from_rgb_space(primaries: [[f64; 2]; 3], white: [f64; 2], trc: impl Fn(f64) -> f64, options: &MinimalOptions) -> Result<Profile> {
+    let mut curve = Curve::default();
+    match options.curve_entries {
+        0 => return Err("a tone response curve needs at least one entry".into()),
+        1 => curve.set_gamma(estimate_gamma(&trc)),
+        n => curve.set_table_from_fn(n, &trc),
+    }
+    let text = |s: &str| TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(s));
+
+    let mut profile = Profile::new([4, 3, 0], Class::Display);
+    profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::RGB));
+    profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+    profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+    profile = profile.with_tag(TagSignature::ProfileDescriptionTag, text(&options.description));
+    if let Some(copyright) = &options.copyright {
+        profile = profile.with_tag(TagSignature::CopyrightTag, text(copyright));
+    }
+    let [red, green, blue] = primaries;
+    let mut profile = profile.with_primaries(red, green, blue, white).to_version(2, 1)?.profile;
+    for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+        profile = profile.with_tag(signature, TagData::Curve(curve.clone()));
+    }
+    Ok(profile)
+}
+
+#[test]
+fn test_minimal_srgb() {
+    use crate::profile::{compare, standards, RenderingIntent};
+    let profile = minimal_srgb(&MinimalOptions::default()).unwrap();
+    let icc = profile.to_buffer().unwrap();
+    assert!(icc.len() <= 520, "{}", icc.len());
+    let parsed = Profile::from_buffer(&icc).unwrap();
+    assert_eq!(parsed.version[0], 2);
+    assert_eq!(parsed.tag_count(), 9);
+    let comparison = compare(&parsed, &standards::srgb(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert!(comparison.delta_e2000.max < 1.0, "{:?}", comparison.delta_e2000);
+
+    let gamma = MinimalOptions { curve_entries: 1, copyright: None, ..Default::default() };
+    let small = minimal_srgb(&gamma).unwrap().to_buffer().unwrap();
+    assert!(small.len() < icc.len() - 60);
+}
@@ -0,0 +1,109 @@
+/*!
+  Size optimization of profiles, e.g. for profiles embedded in images on the web.
+
+  [Profile::to_buffer] already writes a single copy of tags with identical encoded data. The
+  optimizer makes more tags identical, by changing the sampled curves ('curv' tags):
+
+  - curves which sample the identity function are replaced by an empty curve, the identity;
+  - curves which differ from an earlier curve, with the same number of entries, by no more than a
+    given tolerance, are replaced by a copy of that curve, to share its data, as is common for the
+    red, green, and blue tone response curves of profiles created from measurements.
+
+  With a tolerance of zero, only exact identity curves are replaced, which does not change the
+  transforms of a profile. The padding of the tag data to four byte boundaries, including that of
+  the last tag, is required by the specification, and is kept.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::{Curve, Tag, TagData};
+
+#[derive(Debug)]
+pub struct Optimization {
+    pub profile: Profile,
+    /// Encoded sizes of the profile, before and after the optimization.
+    pub size: (usize, usize),
+    /// Curves replaced by the identity.
+    pub simplified: Vec<TagSignature>,
+    /// Curves replaced by a copy of another curve, as pairs of the replaced, and the copied, tags.
+    pub shared: Vec<(TagSignature, TagSignature)>,
+}
+
+impl Optimization {
+    pub fn bytes_saved(&self) -> usize {
+        self.size.0.saturating_sub(self.size.1)
+    }
+}
+
+impl Profile {
+    /// Optimizes the encoded size of the profile, with the largest difference of curve entries
+    /// allowed for sharing curves, in units of 1/65535.
+    pub fn optimize(mut self, tolerance: u16) -> Result<Optimization> {
+        let before = self.to_buffer()?.len();
+        let (mut simplified, mut shared) = (Vec::new(), Vec::new());
+        for i in 0..self.tags.len() {
+            let TagData::Curve(Curve(entries)) = self.tags[i].data() else { continue };
+            let signature = self.tags[i].signature().clone();
+            if entries.len() >= 2 && is_identity(entries) {
+                self.tags[i] = Tag::new(signature.clone(), TagData::Curve(Curve(Vec::new())));
+                simplified.push(signature);
+                continue;
+            }
+            let similar = self.tags[..i].iter().find(|t| match t.data() {
+                TagData::Curve(Curve(other)) => other != entries && other.len() == entries.len() && max_difference(other, entries) <= tolerance,
+                _ => false,
+            });
+            if let Some(tag) = similar {
+                let (copied, data) = (tag.signature().clone(), TagData::Curve(Curve(entries_of(tag).to_vec())));
+                self.tags[i] = Tag::new(signature.clone(), data);
+                shared.push((signature, copied));
+            }
+        }
+        let after = self.to_buffer()?.len();
+        Ok(Optimization { profile: self, size: (before, after), simplified, shared })
+    }
+}
+
+fn entries_of(tag: &Tag) -> &[u16] {
+    match tag.data() {
+        TagData::Curve(Curve(entries)) => entries,
+        _ => &[],
+    }
+}
+
+fn is_identity(entries: &[u16]) -> bool {
+    let n = entries.len() - 1;
+    entries.iter().enumerate().all(|(i, &v)| v as usize == (i * 65535 + n / 2) / n)
+}
+
+fn max_difference(a: &[u16], b: &[u16]) -> u16 {
+    a.iter().zip(b).map(|(&a, &b)| a.abs_diff(b)).max().unwrap_or(0)
+}
+
+#[test]
+fn test_optimize() {
+    use crate::profile::standards;
+    let ramp: Vec<u16> = (0..256u32).map(|i| ((i * 65535 + 127) / 255) as u16).collect();
+    let gamma: Vec<u16> = (0..256).map(|i| (65535.0 * (i as f64 / 255.0).powf(2.2)).round() as u16).collect();
+    let mut blue = gamma.clone();
+    blue[100] += 1;
+    let profile = || {
+        standards::srgb()
+            .with_tag(TagSignature::RedTRCTag, TagData::Curve(Curve(gamma.clone())))
+            .with_tag(TagSignature::GreenTRCTag, TagData::Curve(Curve(ramp.clone())))
+            .with_tag(TagSignature::BlueTRCTag, TagData::Curve(Curve(blue.clone())))
+    };
+
+    let lossless = profile().optimize(0).unwrap();
+    assert_eq!(lossless.simplified, [TagSignature::GreenTRCTag]);
+    assert!(lossless.shared.is_empty());
+    assert_eq!(lossless.bytes_saved(), 2 * 256);
+
+    let optimized = profile().optimize(1).unwrap();
+    assert_eq!(optimized.shared, [(TagSignature::BlueTRCTag, TagSignature::RedTRCTag)]);
+    assert_eq!(optimized.bytes_saved(), 4 * 256 + 12);
+    let icc = optimized.profile.to_buffer().unwrap();
+    assert_eq!(icc.len(), optimized.size.1);
+    assert!(Profile::from_buffer(&icc).is_ok());
+}
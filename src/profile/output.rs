@@ -0,0 +1,286 @@
+/*!
+  CMYK printer profiles from characterization data: pairs of CMYK values, as printed, and the CIELAB
+  values measured on the print, such as the patches of an IT8.7/4, or ECI2002, chart.
+
+  The 'A2B' tables are sampled from a local linear regression of the measurements, weighted by their
+  inverse distance, in CMYK space. The 'B2A' tables are calculated by inverting the colorimetric
+  'A2B1' table with a damped Gauss–Newton search, for the black amount of the black generation
+  settings, and within the total ink limit; colors outside the gamut are clipped to the closest
+  printable color. All colorimetric values are relative to the paper white, the CMYK value 0, 0, 0,
+  0, which should be measured.
+
+  The perceptual, and saturation, tables only map the lightness range of the PCS to the lightness
+  range of the printer, from its darkest color to the paper white; no other gamut mapping is done.
+*/
+
+use crate::common::Result;
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::abstract_lab::{decode, encode};
+use crate::color::delta_e::cie76;
+use crate::profile::fit::{lab_to_xyz, solve, DeltaEStats};
+use crate::profile::{Class, ColorSpace, Profile};
+use crate::progress::{NoProgress, Progress};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{
+    lut::{lut_atob, lut_btoa, Clut, ClutBuilder},
+    multi_localized_unicode::MultiLocalizedUnicode,
+    Curve, TagData, XYZ,
+};
+
+// Color difference, in ΔE*ab units, of the closest printable color, above which a color is out of gamut.
+const GAMUT_THRESHOLD: f64 = 2.0;
+// Weight of differences with the black generation's black amount, in the inversion, in ΔE*ab units
+// for a black amount difference of 1.0.
+const BLACK_WEIGHT: f64 = 10.0;
+const ITERATIONS: usize = 30;
+
+/// Black generation: the black amount increases linearly, from zero at the start darkness, to the
+/// maximum black amount at the darkest color, where darkness is 1 - L* / 100.
+#[derive(Debug, Clone, Copy)]
+pub struct BlackGeneration {
+    pub start: f64,
+    pub max_black: f64,
+}
+
+impl Default for BlackGeneration {
+    fn default() -> Self {
+        Self { start: 0.4, max_black: 1.0 }
+    }
+}
+
+impl BlackGeneration {
+    fn black(&self, lightness: f64) -> f64 {
+        let darkness = 1.0 - lightness / 100.0;
+        self.max_black * ((darkness - self.start) / (1.0 - self.start)).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputProfileOptions {
+    pub description: String,
+    /// Number of grid points of the CMYK lookup tables, in the 'A2B' tags.
+    pub grid_points: usize,
+    /// Number of grid points of the CIELAB lookup tables, in the 'B2A', and gamut, tags.
+    pub lab_grid_points: usize,
+    /// Largest sum of the four ink amounts, such as 3.0 for 300%.
+    pub ink_limit: f64,
+    pub black_generation: BlackGeneration,
+}
+
+impl Default for OutputProfileOptions {
+    fn default() -> Self {
+        Self { description: String::from("Output profile"), grid_points: 9, lab_grid_points: 17, ink_limit: 3.0, black_generation: BlackGeneration::default() }
+    }
+}
+
+#[derive(Debug)]
+pub struct OutputProfile {
+    pub profile: Profile,
+    /// Color differences of the patches, between their measured values, and the values of the 'A2B1' table.
+    pub delta_e: DeltaEStats,
+}
+
+impl OutputProfile {
+    /// Output profile from pairs of CMYK values, in the range from 0.0 to 1.0, and measured CIELAB
+    /// values, for the D50 illuminant.
+    pub fn from_characterization(patches: &[([f64; 4], [f64; 3])], options: &OutputProfileOptions) -> Result<OutputProfile> {
+        Self::from_characterization_with_progress(patches, options, &mut NoProgress)
+    }
+
+    /// Output profile, as [OutputProfile::from_characterization], reporting the progress of sampling
+    /// each of its color lookup tables in turn, and stopping with an error if the operation is
+    /// cancelled.
+    pub fn from_characterization_with_progress(patches: &[([f64; 4], [f64; 3])], options: &OutputProfileOptions, progress: &mut dyn Progress) -> Result<OutputProfile> {
+        if patches.len() < 5 {
+            return Err("at least five patches are required".into());
+        }
+        if !(2..=255).contains(&options.grid_points) || !(2..=255).contains(&options.lab_grid_points) {
+            return Err("the number of grid points should be from 2 to 255".into());
+        }
+        let paper = patches.iter().find(|(cmyk, _)| cmyk.iter().all(|&v| v == 0.0)).ok_or("characterization data without a paper white patch")?;
+        let paper = lab_to_xyz(paper.1);
+        // media relative CIELAB values, scaling XYZ values by the ratio of the PCS illuminant and the paper white
+        let relative = |lab: [f64; 3]| {
+            let xyz = lab_to_xyz(lab);
+            xyz_to_lab([0, 1, 2].map(|i| xyz[i] * PCS_ILLUMINANT[i] / paper[i]))
+        };
+        let patches: Vec<([f64; 4], [f64; 3])> = patches.iter().map(|(cmyk, lab)| (*cmyk, relative(*lab))).collect();
+
+        let (cmyk_grid, lab_grid) = (ClutBuilder::new(4, 3, options.grid_points), ClutBuilder::new(3, 4, options.lab_grid_points));
+        let colorimetric = cmyk_grid.fill_with_progress(|cmyk| encode(regression(&patches, cmyk)), progress)?;
+        let forward = |cmyk: &[f64]| decode(&colorimetric.eval(cmyk));
+        let delta_e = DeltaEStats::new(&patches.iter().map(|(cmyk, lab)| cie76(forward(cmyk), *lab)).collect::<Vec<_>>());
+        let black_lightness = patches.iter().map(|(_, lab)| lab[0]).fold(100.0, f64::min);
+        let compress = |l: f64| black_lightness + l * (100.0 - black_lightness) / 100.0;
+        let expand = |l: f64| ((l - black_lightness) * 100.0 / (100.0 - black_lightness)).max(0.0);
+
+        let invert = |lab: [f64; 3]| {
+            let k = options.black_generation.black(lab[0]);
+            let start = patches.iter().min_by(|a, b| cie76(a.1, lab).total_cmp(&cie76(b.1, lab))).map_or([0.0; 4], |p| p.0);
+            let start = limit([start[0], start[1], start[2], k], options.ink_limit);
+            inverse(&forward, lab, k, start, options.ink_limit)
+        };
+        // the colorimetric inversion, with a fifth output flagging colors out of gamut, for the gamut tag
+        let inverted = ClutBuilder::new(3, 5, options.lab_grid_points).fill_with_progress(|v| {
+            let (cmyk, de) = invert(decode(v));
+            [&cmyk[..], &[if de > GAMUT_THRESHOLD { 1.0 } else { 0.0 }]].concat()
+        }, progress)?;
+        let btoa_colorimetric = Clut { inputs: 3, outputs: 4, grid_points: options.lab_grid_points, data: inverted.data.chunks(5).flat_map(|v| &v[..4]).copied().collect() };
+        let btoa_perceptual = lab_grid.fill_with_progress(|v| {
+            let [l, a, b] = decode(v);
+            invert([compress(l), a, b]).0.to_vec()
+        }, progress)?;
+        let atob_perceptual = cmyk_grid.fill_with_progress(|cmyk| {
+            let [l, a, b] = forward(cmyk);
+            encode([expand(l), a, b])
+        }, progress)?;
+        let n = options.lab_grid_points;
+        let gamut = Clut { inputs: 3, outputs: 1, grid_points: n, data: inverted.data.chunks(5).map(|v| v[4]).collect() };
+
+        let curves = |n: usize| (0..n).map(|_| TagData::Curve(Curve(Vec::new()))).collect::<Vec<_>>();
+        let atob = |clut: Clut| -> Result<TagData> { Ok(TagData::LutAToB(lut_atob(curves(4), clut, Vec::new(), None, curves(3))?)) };
+        let btoa = |clut: Clut| -> Result<TagData> {
+            let outputs = clut.outputs;
+            Ok(TagData::LutBToA(lut_btoa(curves(3), None, Vec::new(), clut, curves(outputs))?))
+        };
+
+        let mut profile = Profile::new([4, 3, 0], Class::Output);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::CMYK));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::Lab));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        profile = profile
+            .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(&options.description)))
+            .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us("No copyright, use freely")))
+            .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![paper])))
+            .with_tag(TagSignature::AToB0Tag, atob(atob_perceptual.clone())?)
+            .with_tag(TagSignature::AToB1Tag, atob(colorimetric)?)
+            .with_tag(TagSignature::AToB2Tag, atob(atob_perceptual)?)
+            .with_tag(TagSignature::BToA0Tag, btoa(btoa_perceptual.clone())?)
+            .with_tag(TagSignature::BToA1Tag, btoa(btoa_colorimetric)?)
+            .with_tag(TagSignature::BToA2Tag, btoa(btoa_perceptual)?)
+            .with_tag(TagSignature::GamutTag, btoa(gamut)?);
+        Ok(OutputProfile { profile, delta_e })
+    }
+}
+
+// CIELAB value at a CMYK value, from an affine function fitted to the patches, weighted by their
+// inverse distance to the CMYK value.
+fn regression(patches: &[([f64; 4], [f64; 3])], cmyk: &[f64]) -> [f64; 3] {
+    let mut ata = vec![vec![0.0; 5]; 5];
+    let mut atb = [vec![0.0; 5], vec![0.0; 5], vec![0.0; 5]];
+    for (p, lab) in patches {
+        let d2: f64 = (0..4).map(|i| (p[i] - cmyk[i]).powi(2)).sum();
+        let w = 1.0 / (d2 + 1E-4).powi(2);
+        let x = [1.0, p[0] - cmyk[0], p[1] - cmyk[1], p[2] - cmyk[2], p[3] - cmyk[3]];
+        for i in 0..5 {
+            for j in 0..5 {
+                ata[i][j] += w * x[i] * x[j];
+            }
+            (0..3).for_each(|c| atb[c][i] += w * x[i] * lab[c]);
+        }
+    }
+    // a small ridge term, for CMYK values with patches in only a few directions
+    let scale = ata[0][0] * 1E-6;
+    (1..5).for_each(|i| ata[i][i] += scale);
+    // the constant term of the affine function, centered at the CMYK value, is its CIELAB value
+    [0, 1, 2].map(|c| solve(ata.clone(), atb[c].clone()).map_or(0.0, |x| x[0]))
+}
+
+// Scales down the CMY amounts, and if needed the black amount, to the ink limit.
+fn limit([c, m, y, k]: [f64; 4], ink_limit: f64) -> [f64; 4] {
+    let k = k.clamp(0.0, 1.0).min(ink_limit);
+    let cmy = [c, m, y].map(|v| v.clamp(0.0, 1.0));
+    let sum: f64 = cmy.iter().sum();
+    let f = if sum > ink_limit - k { (ink_limit - k) / sum } else { 1.0 };
+    [cmy[0] * f, cmy[1] * f, cmy[2] * f, k]
+}
+
+// CMYK value with the smallest color difference with a CIELAB value, and a black amount close to
+// the black generation's black amount, with its color difference.
+fn inverse(forward: &impl Fn(&[f64]) -> [f64; 3], lab: [f64; 3], black: f64, start: [f64; 4], ink_limit: f64) -> ([f64; 4], f64) {
+    let residuals = |x: &[f64; 4]| {
+        let v = forward(x);
+        [v[0] - lab[0], v[1] - lab[1], v[2] - lab[2], BLACK_WEIGHT * (x[3] - black)]
+    };
+    let cost = |r: &[f64; 4]| r.iter().map(|v| v * v).sum::<f64>();
+    let mut x = start;
+    let mut r = residuals(&x);
+    let mut damping = 1E-3;
+    for _ in 0..ITERATIONS {
+        // numerical Jacobian, with columns for the four ink amounts
+        let h = 1E-3;
+        let jacobian: Vec<[f64; 4]> = (0..4)
+            .map(|j| {
+                let mut xh = x;
+                xh[j] = if x[j] + h <= 1.0 { x[j] + h } else { x[j] - h };
+                let rh = residuals(&xh);
+                let step = xh[j] - x[j];
+                [0, 1, 2, 3].map(|i| (rh[i] - r[i]) / step)
+            })
+            .collect();
+        let mut jtj = vec![vec![0.0; 4]; 4];
+        let mut jtr = vec![0.0; 4];
+        for a in 0..4 {
+            for b in 0..4 {
+                jtj[a][b] = (0..4).map(|i| jacobian[a][i] * jacobian[b][i]).sum();
+            }
+            jtj[a][a] *= 1.0 + damping;
+            jtr[a] = -(0..4).map(|i| jacobian[a][i] * r[i]).sum::<f64>();
+        }
+        let Some(step) = solve(jtj, jtr) else { break };
+        let candidate = limit([0, 1, 2, 3].map(|i| x[i] + step[i]), ink_limit);
+        let rc = residuals(&candidate);
+        if cost(&rc) < cost(&r) {
+            (x, r) = (candidate, rc);
+            damping = (damping * 0.3).max(1E-6);
+        } else {
+            damping *= 10.0;
+        }
+    }
+    (x, (r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sqrt())
+}
+
+#[test]
+fn test_output_profile() {
+    // subtractive model, with the transmission of each ink for X, Y, and Z
+    let inks = [[0.55, 0.4, 0.1], [0.4, 0.6, 0.9], [0.9, 0.85, 0.15], [0.05, 0.05, 0.05]];
+    let paper = [0.93, 0.95, 0.85];
+    let print = |cmyk: [f64; 4]| {
+        let xyz = [0, 1, 2].map(|i| paper[i] * (0..4).map(|j| 1.0 - cmyk[j] * (1.0 - inks[j][i])).product::<f64>());
+        xyz_to_lab(xyz)
+    };
+    let mut patches = Vec::new();
+    for i in 0..256 {
+        let cmyk = [i / 64, i / 16 % 4, i / 4 % 4, i % 4].map(|v| v as f64 / 3.0);
+        patches.push((cmyk, print(cmyk)));
+    }
+    let options = OutputProfileOptions { grid_points: 7, lab_grid_points: 9, ink_limit: 2.8, ..Default::default() };
+    let output = OutputProfile::from_characterization(&patches, &options).unwrap();
+    assert!(output.delta_e.max < 0.5, "{:?}", output.delta_e);
+    assert!(output.profile.missing_tags().is_empty());
+    assert!(Profile::from_buffer(&output.profile.to_buffer().unwrap()).is_ok());
+
+    // round trip of an in-gamut color, relative to the paper white, through the colorimetric tables
+    let colorimetric = ClutBuilder::new(4, 3, 7).fill(|cmyk| encode(regression(&patches, cmyk))).unwrap();
+    let forward = |cmyk: &[f64]| decode(&colorimetric.eval(cmyk));
+    let lab = forward(&[0.3, 0.2, 0.5, 0.1]);
+    let k = options.black_generation.black(lab[0]);
+    let (cmyk, de) = inverse(&forward, lab, k, [0.5, 0.5, 0.5, k], options.ink_limit);
+    assert!(de < 0.5, "{cmyk:?} {de}");
+    assert!(cmyk.iter().sum::<f64>() <= options.ink_limit + 1E-9);
+    assert!(OutputProfile::from_characterization(&patches[1..3], &options).is_err());
+
+    // cancelled while sampling the second table, the colorimetric inversion
+    use crate::progress::{Cancellable, CancellationToken};
+    let token = CancellationToken::new();
+    let (cancel, mut tables) = (token.clone(), 0);
+    let mut receiver = Cancellable::new(|done: usize, _: usize| {
+        tables += (done == 0) as usize;
+        if tables == 2 && done == 2 {
+            cancel.cancel();
+        }
+    }, token.clone());
+    assert!(OutputProfile::from_characterization_with_progress(&patches, &options, &mut receiver).is_err());
+    assert!(token.is_cancelled());
+}
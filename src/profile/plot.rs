@@ -0,0 +1,131 @@
+/*!
+  SVG plots of the tone response curves of a profile, and of its gamut triangle in the CIE 1931 xy
+  chromaticity diagram, together with the spectral locus, and the sRGB gamut for reference.
+
+  The plots are written with a minimal internal SVG writer, without external dependencies, and are
+  only available with the `plot` feature.
+*/
+
+use std::fmt::Write;
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::transform::MatrixTrc;
+
+/// Size of each of the two plot panels, in pixels, and the margin around them.
+const SIZE: f64 = 300.0;
+const MARGIN: f64 = 30.0;
+
+/// CIE 1931 2º chromaticity coordinates of the spectral locus, from 380 to 700nm, in steps of 10nm.
+const SPECTRAL_LOCUS: [[f64; 2]; 33] = [
+    [0.1741, 0.0050], [0.1738, 0.0049], [0.1733, 0.0048], [0.1726, 0.0048], [0.1714, 0.0051], [0.1689, 0.0069],
+    [0.1644, 0.0109], [0.1566, 0.0177], [0.1440, 0.0297], [0.1241, 0.0578], [0.0913, 0.1327], [0.0454, 0.2950],
+    [0.0082, 0.5384], [0.0139, 0.7502], [0.0743, 0.8338], [0.1547, 0.8059], [0.2296, 0.7543], [0.3016, 0.6923],
+    [0.3731, 0.6245], [0.4441, 0.5547], [0.5125, 0.4866], [0.5752, 0.4242], [0.6270, 0.3725], [0.6658, 0.3340],
+    [0.6915, 0.3083], [0.7079, 0.2920], [0.7190, 0.2809], [0.7260, 0.2740], [0.7300, 0.2700], [0.7320, 0.2680],
+    [0.7334, 0.2666], [0.7344, 0.2656], [0.7347, 0.2653],
+];
+
+const SRGB_PRIMARIES: [[f64; 2]; 3] = [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]];
+
+/// Minimal SVG writer, with panels mapping data coordinates to pixels, with the y-axis pointing up.
+struct Svg {
+    body: String,
+    width: f64,
+    height: f64,
+}
+
+/// Plot area, with its pixel offset, and the data range shown on its axes.
+struct Panel {
+    left: f64,
+    top: f64,
+    x_max: f64,
+    y_max: f64,
+}
+
+impl Panel {
+    fn point(&self, [x, y]: [f64; 2]) -> (f64, f64) {
+        (self.left + x / self.x_max * SIZE, self.top + SIZE - y / self.y_max * SIZE)
+    }
+}
+
+impl Svg {
+    fn new(width: f64, height: f64) -> Self {
+        Self { body: String::new(), width, height }
+    }
+
+    fn polyline(&mut self, panel: &Panel, points: impl IntoIterator<Item = [f64; 2]>, style: &str, closed: bool) {
+        let points: Vec<String> = points.into_iter().map(|p| panel.point(p)).map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        let element = if closed { "polygon" } else { "polyline" };
+        writeln!(self.body, r#"<{} points="{}" fill="none" {}/>"#, element, points.join(" "), style).unwrap();
+    }
+
+    fn text(&mut self, x: f64, y: f64, text: &str) {
+        let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        writeln!(self.body, r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="12">{}</text>"#, x, y, text).unwrap();
+    }
+
+    /// Frame of a panel, with a title, and labels for the maximum values of the axes.
+    fn frame(&mut self, panel: &Panel, title: &str) {
+        self.polyline(panel, [[0.0, 0.0], [panel.x_max, 0.0], [panel.x_max, panel.y_max], [0.0, panel.y_max]], r#"stroke="gray""#, true);
+        self.text(panel.left, panel.top - 8.0, title);
+        self.text(panel.left - 4.0, panel.top + SIZE + 14.0, "0");
+        self.text(panel.left + SIZE - 8.0, panel.top + SIZE + 14.0, &format!("{}", panel.x_max));
+        self.text(panel.left - 24.0, panel.top + 4.0, &format!("{}", panel.y_max));
+    }
+
+    fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+             <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+impl Profile {
+    /// SVG image with a plot of the tone response curves, and a plot of the gamut triangle in the
+    /// xy chromaticity diagram, for matrix/TRC and gray TRC profiles.
+    pub fn plot_svg(&self) -> Result<String> {
+        let matrix_trc = MatrixTrc::from_profile(self)?;
+        let summary = self.summary();
+        let mut svg = Svg::new(3.0 * MARGIN + 2.0 * SIZE, 2.0 * MARGIN + SIZE);
+
+        let trc = Panel { left: MARGIN, top: MARGIN, x_max: 1.0, y_max: 1.0 };
+        svg.frame(&trc, "tone response curves");
+        let colors: &[&str] = if matrix_trc.channels() == 1 { &["black"] } else { &["red", "green", "blue"] };
+        for (curve, color) in matrix_trc.trcs.iter().zip(colors) {
+            let points = (0..=100).map(|i| i as f64 / 100.0).map(|x| [x, curve.eval(x).clamp(0.0, 1.0)]);
+            svg.polyline(&trc, points, &format!(r#"stroke="{}""#, color), false);
+        }
+
+        let xy = Panel { left: 2.0 * MARGIN + SIZE, top: MARGIN, x_max: 0.8, y_max: 0.9 };
+        svg.frame(&xy, "xy chromaticity");
+        svg.polyline(&xy, SPECTRAL_LOCUS, r#"stroke="black""#, true);
+        svg.polyline(&xy, SRGB_PRIMARIES, r#"stroke="gray" stroke-dasharray="4 3""#, true);
+        if let Some(primaries) = summary.primaries {
+            svg.polyline(&xy, primaries, r#"stroke="black" stroke-width="2""#, true);
+        }
+        if let Some(white) = summary.white_point {
+            let (x, y) = xy.point(white);
+            writeln!(svg.body, r#"<circle cx="{:.1}" cy="{:.1}" r="3" fill="black"/>"#, x, y).unwrap();
+        }
+        if let Some(description) = &summary.description {
+            svg.text(xy.left + 0.45 * SIZE, xy.top + 20.0, description);
+        }
+        Ok(svg.finish())
+    }
+}
+
+#[test]
+fn test_plot_svg() {
+    use crate::profile::standards;
+    let svg = standards::display_p3().plot_svg().unwrap();
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<polyline").count(), 3);
+    assert_eq!(svg.matches("<polygon").count(), 5);
+    assert!(svg.contains("<circle"));
+    assert!(crate::profile::Profile::default().plot_svg().is_err());
+}
@@ -0,0 +1,235 @@
+/*!
+  PostScript Level 2 color space arrays (CSA), and color rendering dictionaries (CRD), for legacy RIP
+  workflows, as PostScript text, or as the 'ps2s', and 'psd0' to 'psd3', tags of version 2 profiles.
+
+  Matrix/TRC, and, for color space arrays, gray TRC, profiles give a `CIEBasedABC`, or `CIEBasedA`,
+  color space with the tone response curves as decode procedures, and the colorant matrix; their
+  color rendering dictionary encodes XYZ values with the inverse matrix and tone response curves.
+  The curves are sampled, and interpolated in the PostScript procedures.
+
+  Profiles with lookup tables, or pipelines, evaluated as in the [transform](crate::transform)
+  module, give a `CIEBasedDEF`, or `CIEBasedDEFG`, color space for three, or four, device channels,
+  with a table of CIELAB values sampled on a grid of device values, and a color rendering dictionary
+  with a `RenderTable` of device values, for three, or four, device channels, sampled on a grid of
+  CIELAB values.
+*/
+
+use crate::adaptation::inverse;
+use crate::common::{to_hex, Result};
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::fit::lab_to_xyz;
+use crate::profile::{Profile, RenderingIntent};
+use crate::signatures::tag::TagSignature;
+use crate::tags::{Data, TagData};
+use crate::transform::{DeviceTransform, MatrixTrc};
+
+// Number of samples of the tone response curves, and of their inverse.
+const CURVE_ENTRIES: usize = 256;
+const INVERSE_CURVE_ENTRIES: usize = 1024;
+
+// Grid points of the tables of color space arrays, for three, and four, device channels, and of
+// color rendering dictionaries.
+const DEF_GRID_POINTS: usize = 17;
+const DEFG_GRID_POINTS: usize = 9;
+const RENDER_GRID_POINTS: usize = 33;
+
+impl Profile {
+    /// PostScript color space array, converting device values to CIE XYZ values, for the rendering
+    /// intent in the profile header if the profile has lookup tables.
+    pub fn postscript_csa(&self) -> Result<String> {
+        let transform = DeviceTransform::new(self, self.rendering_intent, true)?;
+        let Some(model) = transform.matrix_trc() else {
+            return table_csa(&transform);
+        };
+        let white = numbers(&PCS_ILLUMINANT);
+        if model.channels() == 1 {
+            let [x, y, z] = PCS_ILLUMINANT;
+            return Ok(format!(
+                "[/CIEBasedA <<\n  /DecodeA {}\n  /MatrixA [{}]\n  /WhitePoint [{white}]\n>>]\n",
+                procedure(|v| model.trcs[0].eval(v), CURVE_ENTRIES),
+                numbers(&[x, y, z])
+            ));
+        }
+        let decode: Vec<String> = model.trcs.iter().map(|trc| procedure(|v| trc.eval(v), CURVE_ENTRIES)).collect();
+        Ok(format!(
+            "[/CIEBasedABC <<\n  /DecodeABC [\n  {}\n  ]\n  /MatrixABC [{}]\n  /WhitePoint [{white}]\n>>]\n",
+            decode.join("\n  "),
+            numbers(&columns(model.matrix))
+        ))
+    }
+
+    /// PostScript color rendering dictionary, of type 1, converting CIE XYZ values to device values,
+    /// for a rendering intent: all intents scale the white point of the source color space to the
+    /// PCS illuminant, apart from the absolute colorimetric intent. Matrix/TRC profiles should have
+    /// three channels, and profiles with lookup tables, or pipelines, three, or four, channels.
+    pub fn postscript_crd(&self, intent: RenderingIntent) -> Result<String> {
+        let transform = DeviceTransform::new(self, intent, false)?;
+        let (encode, render_table) = match transform.matrix_trc() {
+            Some(model) if model.channels() == 3 => {
+                let encode: Vec<String> = model.trcs.iter().map(|trc| procedure(|v| trc.eval_inverse(v), INVERSE_CURVE_ENTRIES)).collect();
+                let encode = format!("/MatrixABC [{}]\n  /EncodeABC [\n  {}\n  ]\n  /RangeABC [0 1 0 1 0 1]", numbers(&columns(inverse(model.matrix))), encode.join("\n  "));
+                (encode, String::new())
+            }
+            Some(_) => return Err("color rendering dictionaries are only supported for RGB matrix/TRC profiles".into()),
+            None => table_crd(&transform)?,
+        };
+        // transform procedures get the source and destination white and black points, which
+        // are arrays [X Y Z P Q R], and a P, Q, or R value
+        let transform = |i: usize| match intent {
+            RenderingIntent::AbsoluteColorimetric => "{exch pop exch pop exch pop exch pop}".to_owned(),
+            _ => format!("{{4 index {i} get div 2 index {i} get mul exch pop exch pop exch pop exch pop}}"),
+        };
+        Ok(format!(
+            "<<\n  /ColorRenderingType 1\n  /WhitePoint [{}]\n  /MatrixPQR [1 0 0 0 1 0 0 0 1]\n  /RangePQR [-0.5 2 -0.5 2 -0.5 2]\n  \
+             /TransformPQR [{} {} {}]\n  {encode}\n{render_table}>>\n",
+            numbers(&PCS_ILLUMINANT),
+            transform(3),
+            transform(4),
+            transform(5),
+        ))
+    }
+
+    /// Adds the color space array as a 'ps2s' tag, and, for profiles with a color rendering
+    /// dictionary, the dictionaries for the four rendering intents as 'psd0' to 'psd3' tags; these
+    /// tags are only defined for version 2 profiles.
+    pub fn with_postscript_tags(self) -> Result<Self> {
+        let text = |s: String| TagData::Data(Data { flag: 0, data: s.into_bytes() });
+        let csa = self.postscript_csa()?;
+        let mut profile = self.with_tag(TagSignature::Ps2CSATag, text(csa));
+        let rendering = DeviceTransform::new(&profile, RenderingIntent::Perceptual, false)
+            .is_ok_and(|t| t.matrix_trc().map_or(matches!(t.channels(), 3 | 4), |m| m.channels() == 3));
+        if rendering {
+            let intents = [
+                (TagSignature::Ps2CRD0Tag, RenderingIntent::Perceptual),
+                (TagSignature::Ps2CRD1Tag, RenderingIntent::MediaRelativeColorimetric),
+                (TagSignature::Ps2CRD2Tag, RenderingIntent::Saturation),
+                (TagSignature::Ps2CRD3Tag, RenderingIntent::AbsoluteColorimetric),
+            ];
+            for (signature, intent) in intents {
+                let crd = profile.postscript_crd(intent)?;
+                profile = profile.with_tag(signature, text(crd));
+            }
+        }
+        Ok(profile)
+    }
+}
+
+// CIEBasedDEF, or CIEBasedDEFG, color space array, with a table of CIELAB values, decoded to XYZ
+// values as a CIEBasedABC CIELAB color space.
+fn table_csa(transform: &DeviceTransform) -> Result<String> {
+    let (family, n) = match transform.channels() {
+        3 => ("DEF", DEF_GRID_POINTS),
+        4 => ("DEFG", DEFG_GRID_POINTS),
+        _ => return Err("color space arrays of lookup tables are only supported for three, and four, channels".into()),
+    };
+    let strings = table_strings(transform.channels(), n, |device| {
+        let [l, a, b] = xyz_to_lab(transform.to_xyz(device).ok_or("color space arrays need a transform to the PCS")?);
+        Ok(vec![byte(l / 100.0), byte((a + 128.0) / 255.0), byte((b + 128.0) / 255.0)])
+    })?;
+    let table = match family {
+        "DEF" => format!("{n} {n} {n} [\n  {}\n  ]", strings.join("\n  ")),
+        _ => {
+            let rows: Vec<String> = strings.chunks(n).map(|row| format!("[\n  {}\n  ]", row.join("\n  "))).collect();
+            format!("{n} {n} {n} {n} [\n  {}\n  ]", rows.join("\n  "))
+        }
+    };
+    let decode_lmn: Vec<String> = PCS_ILLUMINANT
+        .iter()
+        .map(|w| format!("{{dup 6 29 div ge {{dup dup mul mul}} {{4 29 div sub 108 841 div mul}} ifelse {} mul}}", numbers(&[*w])))
+        .collect();
+    Ok(format!(
+        "[/CIEBased{family} <<\n  /Table [{table}]\n  /RangeABC [0 100 -128 127 -128 127]\n  \
+         /DecodeABC [{{16 add 116 div}} {{500 div}} {{200 div}}]\n  /MatrixABC [1 1 1 1 0 0 0 0 -1]\n  \
+         /DecodeLMN [{}]\n  /WhitePoint [{}]\n>>]\n",
+        decode_lmn.join(" "),
+        numbers(&PCS_ILLUMINANT)
+    ))
+}
+
+// Encoding of XYZ values to CIELAB values, scaled to the range from 0 to 1, and a render table of
+// device values, sampled on a grid of these values, of a color rendering dictionary.
+fn table_crd(transform: &DeviceTransform) -> Result<(String, String)> {
+    let m = transform.channels();
+    if !matches!(m, 3 | 4) {
+        return Err("color rendering dictionaries of lookup tables are only supported for three, and four, channels".into());
+    }
+    let encode_lmn: Vec<String> = PCS_ILLUMINANT
+        .iter()
+        .map(|w| format!("{{{} div dup 216 24389 div gt {{1 3 div exp}} {{841 108 div mul 4 29 div add}} ifelse}}", numbers(&[*w])))
+        .collect();
+    let encode = format!(
+        "/EncodeLMN [{}]\n  /MatrixABC [0 500 0 116 -500 200 0 0 -200]\n  \
+         /EncodeABC [{{16 sub 100 div}} {{128 add 255 div}} {{128 add 255 div}}]\n  /RangeABC [0 1 0 1 0 1]",
+        encode_lmn.join(" ")
+    );
+    let n = RENDER_GRID_POINTS;
+    let strings = table_strings(3, n, |v| {
+        let device = transform.to_device(lab_to_xyz([v[0] * 100.0, v[1] * 255.0 - 128.0, v[2] * 255.0 - 128.0]));
+        Ok(device.ok_or("color rendering dictionaries need a transform from the PCS")?.into_iter().map(byte).collect())
+    })?;
+    let procedures = vec!["{}"; m].join(" ");
+    Ok((encode, format!("  /RenderTable [{n} {n} {n} [\n  {}\n  ] {m} {procedures}]\n", strings.join("\n  "))))
+}
+
+// Hexadecimal strings of a table sampled on a grid of `n` points for each input, with the first input
+// varying slowest, and a string for the values of each row of the last two inputs.
+fn table_strings(inputs: usize, n: usize, f: impl Fn(&[f64]) -> Result<Vec<u8>>) -> Result<Vec<String>> {
+    let mut data = Vec::new();
+    for i in 0..n.pow(inputs as u32) {
+        let v: Vec<f64> = (0..inputs).rev().map(|k| (i / n.pow(k as u32) % n) as f64 / (n - 1) as f64).collect();
+        data.extend(f(&v)?);
+    }
+    Ok(data.chunks(data.len() / n.pow(inputs as u32 - 2)).map(|s| format!("<{}>", to_hex(s))).collect())
+}
+
+// Table value of a value in the range from 0 to 1.
+fn byte(v: f64) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn numbers(v: &[f64]) -> String {
+    v.iter().map(|x| format!("{}", (x * 1E6).round() / 1E6)).collect::<Vec<_>>().join(" ")
+}
+
+// Matrix elements in column order, as used for PostScript matrices.
+fn columns(m: [[f64; 3]; 3]) -> [f64; 9] {
+    [m[0][0], m[1][0], m[2][0], m[0][1], m[1][1], m[2][1], m[0][2], m[1][2], m[2][2]]
+}
+
+// Procedure interpolating a sampled function, for input values clipped to the range from 0 to 1.
+fn procedure(f: impl Fn(f64) -> f64, n: usize) -> String {
+    let last = n - 1;
+    let table: Vec<f64> = (0..n).map(|i| f(i as f64 / last as f64)).collect();
+    format!(
+        "{{0 max 1 min {last} mul dup cvi dup {last} eq {{1 sub}} if exch 1 index sub [{}] dup 3 index get \
+         exch 3 index 1 add get 1 index sub 2 index mul add exch pop exch pop}}",
+        numbers(&table)
+    )
+}
+
+#[test]
+fn test_postscript() {
+    use crate::profile::standards;
+    let csa = standards::srgb().postscript_csa().unwrap();
+    assert!(csa.starts_with("[/CIEBasedABC <<") && csa.contains("/MatrixABC [0.436"));
+    assert!(standards::gray_gamma22().postscript_csa().unwrap().starts_with("[/CIEBasedA <<"));
+    assert!(standards::gray_gamma22().postscript_crd(RenderingIntent::Perceptual).is_err());
+    let crd = standards::srgb().postscript_crd(RenderingIntent::AbsoluteColorimetric).unwrap();
+    assert!(crd.contains("/ColorRenderingType 1") && crd.contains("/TransformPQR [{exch pop"));
+
+    let profile = standards::srgb().with_postscript_tags().unwrap();
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let crd = profile.get_tag(&TagSignature::Ps2CRD1Tag).unwrap();
+    assert!(matches!(crd.data(), TagData::Data(Data { flag: 0, data }) if data.starts_with(b"<<")));
+
+    // lookup tables, with tables of CIELAB values, from black to white for a CIELAB profile
+    let csa = standards::lab_d50().postscript_csa().unwrap();
+    assert!(csa.starts_with("[/CIEBasedDEF <<\n  /Table [17 17 17 [\n  <000000") && csa.contains("ffffff>\n  ]]"), "{csa}");
+    let cmyk = Profile::from_buffer(include_bytes!("../../examples/test_profiles/Generic CMYK Profile.icc")).unwrap();
+    assert!(cmyk.postscript_csa().unwrap().starts_with("[/CIEBasedDEFG <<\n  /Table [9 9 9 9 [\n  [\n  <"));
+    let crd = cmyk.postscript_crd(RenderingIntent::Perceptual).unwrap();
+    assert!(crd.contains("/RenderTable [33 33 33 [") && crd.contains("] 4 {} {} {} {}]"));
+    let profile = cmyk.with_postscript_tags().unwrap();
+    assert!(profile.get_tag(&TagSignature::Ps2CRD3Tag).is_some());
+}
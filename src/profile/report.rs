@@ -0,0 +1,110 @@
+/*!
+  Build reports, recording the inputs used to create a profile, such as primaries, tone response
+  curves, and white points, and the version of this library, for later reconstruction and audits of
+  how a profile was produced.
+
+  A report is written as JSON, next to the profile, with [Profile::write_with_report], which also
+  stores a hash of the report in the profile's metadata tag; [BuildReport::matches] checks if a
+  report belongs to a profile.
+*/
+
+use std::collections::BTreeMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::common::Result;
+use crate::profile::Profile;
+
+/// Metadata key of the build report hash.
+pub const METADATA_KEY: &str = "CMX_build_report";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildReport {
+    pub software: String,
+    pub version: String,
+    pub inputs: BTreeMap<String, Value>,
+}
+
+impl Default for BuildReport {
+    fn default() -> Self {
+        Self {
+            software: env!("CARGO_PKG_NAME").to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            inputs: BTreeMap::new(),
+        }
+    }
+}
+
+impl BuildReport {
+    /// Empty report, for the current version of this library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds, or replaces, a named input, such as `primaries`, or `white_point`.
+    pub fn with_input(mut self, name: &str, value: impl Serialize) -> Result<Self> {
+        self.inputs.insert(name.to_owned(), serde_json::to_value(value)?);
+        Ok(self)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// 64-bit FNV-1a hash of the compact JSON representation of the report, as a hexadecimal string;
+    /// the inputs are ordered by name, so equal reports have equal hashes.
+    pub fn hash(&self) -> Result<String> {
+        let hash = serde_json::to_vec(self)?
+            .iter()
+            .fold(0xcbf29ce484222325u64, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        Ok(format!("{:016x}", hash))
+    }
+
+    /// Checks if the report's hash matches the hash stored in the profile's metadata.
+    pub fn matches(&self, profile: &Profile) -> Result<bool> {
+        Ok(profile.metadata().get(METADATA_KEY) == Some(&self.hash()?))
+    }
+}
+
+impl Profile {
+    /// Stores the hash of a build report in the metadata tag.
+    pub fn with_build_report(self, report: &BuildReport) -> Result<Self> {
+        Ok(self.with_metadata(METADATA_KEY, &report.hash()?))
+    }
+
+    /// Writes the profile, with the hash of the report in its metadata, and the report, as JSON, to
+    /// the same path with a `json` extension.
+    #[cfg(feature = "fs")]
+    pub fn write_with_report(self, path: impl AsRef<Path>, report: &BuildReport) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.with_build_report(report)?.to_buffer()?)?;
+        std::fs::write(path.with_extension("json"), report.to_json()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fs")]
+#[test]
+fn test_build_report() {
+    use crate::illuminants::D65_XY;
+    let report = BuildReport::new()
+        .with_input("primaries", [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]])
+        .unwrap()
+        .with_input("white_point", D65_XY)
+        .unwrap();
+    let path = std::env::temp_dir().join("cmx-build-report.icc");
+    crate::profile::standards::srgb().write_with_report(&path, &report).unwrap();
+
+    let profile = Profile::from_buffer(&std::fs::read(&path).unwrap()).unwrap();
+    let read = BuildReport::from_json(&std::fs::read_to_string(path.with_extension("json")).unwrap()).unwrap();
+    assert_eq!(read, report);
+    assert!(read.matches(&profile).unwrap());
+    assert!(!read.with_input("gamma", 2.2).unwrap().matches(&profile).unwrap());
+}
@@ -0,0 +1,154 @@
+/*!
+  Round trip checks, for users who want to verify that reading, and writing, their profiles with
+  this library is lossless, such as for all profiles of a collection.
+
+  A profile is parsed, and written again, and the written profile is compared with the original.
+  The comparison is structural: header fields are compared by their bytes, except the profile size,
+  and tags are compared by the bytes of their data, by signature. Differences which the
+  specification allows are ignored: the order of the tags, the sharing of identical tag data, the
+  padding of tag data to four byte boundaries, and a recalculated profile ID, if the original ID did
+  not match the profile.
+
+  ```
+  let icc = cmx::profile::standards::srgb().to_buffer().unwrap();
+  let report = cmx::profile::roundtrip_check(&icc);
+  assert!(report.is_lossless(), "{:?}", report);
+  ```
+*/
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::profile::borrowed::{ProfileRef, TagRef};
+use crate::profile::diff::TagDiff;
+use crate::profile::id::has_valid_profile_id;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+
+// Header fields, with their byte ranges; the profile size is not compared.
+const HEADER_FIELDS: [(&str, std::ops::Range<usize>); 22] = [
+    ("cmm", 4..8),
+    ("version", 8..12),
+    ("class", 12..16),
+    ("colorspace", 16..20),
+    ("pcs", 20..24),
+    ("date_time", 24..36),
+    ("signature", 36..40),
+    ("platform", 40..44),
+    ("flags", 44..48),
+    ("manufacturer", 48..52),
+    ("device", 52..56),
+    ("attributes", 56..64),
+    ("rendering_intent", 64..68),
+    ("pcs_illuminant", 68..80),
+    ("creator", 80..84),
+    ("profile_id", 84..100),
+    ("spectral_pcs", 100..104),
+    ("spectral_pcs_wavelength_range", 104..110),
+    ("bi_spectral_pcs_wavelength_range", 110..116),
+    ("mcs", 116..120),
+    ("profile_device_sub_class", 120..124),
+    ("reserved", 124..128),
+];
+
+#[derive(Debug, Default, Serialize)]
+pub struct RoundtripReport {
+    /// Sizes of the original, and the written, profile.
+    pub size: (usize, Option<usize>),
+    /// Reason the profile could not be parsed, or written.
+    pub error: Option<String>,
+    /// Header fields with different bytes.
+    pub header: Vec<&'static str>,
+    /// Tags of the original profile which were not written.
+    pub removed: Vec<TagSignature>,
+    /// Tags which were written with different data; the values are those of the parsed tags.
+    pub changed: Vec<TagDiff>,
+}
+
+impl RoundtripReport {
+    pub fn is_lossless(&self) -> bool {
+        self.error.is_none() && self.header.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Parses, and writes, an encoded profile, and compares the written profile with the original.
+pub fn roundtrip_check(icc: &[u8]) -> RoundtripReport {
+    let mut report = RoundtripReport { size: (icc.len(), None), ..Default::default() };
+    let written = match Profile::from_buffer(icc).and_then(|p| p.to_buffer()) {
+        Ok(written) => written,
+        Err(e) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+    report.size.1 = Some(written.len());
+    let (original, copy) = match (ProfileRef::from_buffer(icc), ProfileRef::from_buffer(&written)) {
+        (Ok(original), Ok(copy)) => (original, copy),
+        (Err(e), _) | (_, Err(e)) => {
+            report.error = Some(e.to_string());
+            return report;
+        }
+    };
+
+    let recalculated_id = !has_valid_profile_id(icc);
+    for (field, range) in HEADER_FIELDS {
+        if icc.get(range.clone()) != written.get(range) && !(field == "profile_id" && recalculated_id) {
+            report.header.push(field);
+        }
+    }
+    for tag in &original.tags {
+        match copy.tag(&tag.signature) {
+            Some(t) if same_data(tag.data, t.data) => {}
+            Some(t) => report.changed.push(tag_diff(tag, t)),
+            None => report.removed.push(tag.signature.clone()),
+        }
+    }
+    report
+}
+
+// Equal tag data, apart from up to three zero bytes of padding.
+fn same_data(a: &[u8], b: &[u8]) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    long.len() - short.len() < 4 && long.starts_with(short) && long[short.len()..].iter().all(|&b| b == 0)
+}
+
+fn tag_diff(original: &TagRef, written: &TagRef) -> TagDiff {
+    let (l, r) = (original.data, written.data);
+    let differing: Vec<usize> = l.iter().zip(r).enumerate().filter(|(_, (a, b))| a != b).map(|(i, _)| i).collect();
+    let value = |t: &TagRef| t.parse().ok().and_then(|t| serde_json::to_value(t).ok()).unwrap_or(Value::Null);
+    let (lv, rv) = (value(original), value(written));
+    TagDiff {
+        signature: original.signature.clone(),
+        length: (Some(l.len()), Some(r.len())),
+        first_difference: differing.first().copied().or_else(|| (l.len() != r.len()).then(|| l.len().min(r.len()))),
+        bytes_changed: differing.len(),
+        value: (lv != rv).then_some((lv, rv)),
+    }
+}
+
+#[test]
+fn test_roundtrip_check() {
+    use crate::profile::standards;
+    let icc = standards::display_p3().to_buffer().unwrap();
+    let report = roundtrip_check(&icc);
+    assert!(report.is_lossless(), "{report:?}");
+    assert_eq!(report.size, (icc.len(), Some(icc.len())));
+
+    // padding of the last tag, included in its length, and a reordered tag table, are not differences
+    let mut padded = icc.clone();
+    let last = 132 + 12 * (u32::from_be_bytes(icc[128..132].try_into().unwrap()) as usize - 1);
+    let length = u32::from_be_bytes(padded[last + 8..last + 12].try_into().unwrap());
+    padded[last + 8..last + 12].copy_from_slice(&length.next_multiple_of(4).to_be_bytes());
+    let (first, second) = (132, 144);
+    let swapped: Vec<u8> = [&padded[second..second + 12], &padded[first..first + 12]].concat();
+    padded[first..first + 24].copy_from_slice(&swapped);
+    assert!(roundtrip_check(&padded).is_lossless(), "{:?}", roundtrip_check(&padded));
+
+    // reserved flag bits are not kept
+    let mut changed = icc.clone();
+    changed[44] = 0xFF;
+    let report = roundtrip_check(&changed);
+    assert!(!report.is_lossless());
+    assert_eq!(report.header, ["flags"]);
+    assert!(roundtrip_check(b"not a profile").error.is_some());
+}
@@ -0,0 +1,95 @@
+/*!
+  Removal of identifying information from profiles, before embedding them in images for
+  distribution.
+
+  By default, scrubbing removes:
+
+  - the creator, device manufacturer, and device model fields of the header;
+  - the creation date, and the calibration date, and date tags;
+  - the device descriptions, Apple's make and model tag, the characterization target data, the
+    metadata, and the profile sequence descriptions, which describe the devices of a device link;
+  - private tags, with signatures not known to this library.
+
+  The description, and copyright, can be replaced, e.g. with the name of the color space; a replaced
+  description also replaces Apple's localized descriptions. The profile ID is recalculated.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+
+#[derive(Debug, Clone, Default)]
+pub struct ScrubOptions {
+    /// New description; the description is kept if none.
+    pub description: Option<String>,
+    /// New copyright text; the copyright is kept if none.
+    pub copyright: Option<String>,
+    /// Keep the creation, and calibration, dates.
+    pub keep_dates: bool,
+    /// Keep the device manufacturer, and model, in the header, and the device tags.
+    pub keep_device: bool,
+    /// Keep private tags.
+    pub keep_vendor_tags: bool,
+}
+
+const DATE_TAGS: [TagSignature; 2] = [TagSignature::CalibrationDateTimeTag, TagSignature::DateTimeTag];
+
+const DEVICE_TAGS: [TagSignature; 6] = [
+    TagSignature::DeviceMfgDescTag,
+    TagSignature::DeviceModelDescTag,
+    TagSignature::MakeAndModelTag,
+    TagSignature::CharTargetTag,
+    TagSignature::MetaDataTag,
+    TagSignature::ProfileSequenceDescTag,
+];
+
+impl Profile {
+    pub fn scrub(mut self, options: &ScrubOptions) -> Result<Self> {
+        self.creator = None;
+        if !options.keep_dates {
+            self.date_time = None;
+            self = self.retain_tags(|s| !DATE_TAGS.contains(s));
+        }
+        if !options.keep_device {
+            self.manufacturer = None;
+            self.device = None;
+            self = self.retain_tags(|s| !DEVICE_TAGS.contains(s));
+        }
+        if !options.keep_vendor_tags {
+            self = self.retain_tags(|s| !matches!(s, TagSignature::VendorTag(_)));
+        }
+        if let Some(description) = &options.description {
+            self = self.with_description(description).without_tag(TagSignature::MultilocalizedDescriptionStringTag);
+        }
+        if let Some(copyright) = &options.copyright {
+            self = self.with_copyright(copyright);
+        }
+        self.with_profile_id()
+    }
+}
+
+#[test]
+fn test_scrub() {
+    use crate::profile::standards;
+    use crate::tags::TagData;
+    let mut profile = standards::srgb()
+        .with_metadata("owner", "Jane Doe")
+        .with_tag(TagSignature::VendorTag(String::from("priv")), TagData::Text(String::from("serial 1234")))
+        .with_make_and_model(|m| {
+            m.set_manufacturer("APP").set_model(1);
+        })
+        .unwrap();
+    profile.creator = Some(String::from("jdoe"));
+    profile.device = Some(String::from("dev1"));
+
+    let options = ScrubOptions { description: Some(String::from("sRGB")), copyright: Some(String::from("CC0")), ..Default::default() };
+    let scrubbed = profile.scrub(&options).unwrap();
+    assert!(scrubbed.creator.is_none() && scrubbed.device.is_none() && scrubbed.date_time.is_none());
+    assert!(scrubbed.metadata().is_empty() && scrubbed.make_and_model().is_none());
+    assert_eq!(scrubbed.tag_count(), standards::srgb().tag_count());
+
+    let icc = scrubbed.to_buffer().unwrap();
+    assert!(crate::profile::id::has_valid_profile_id(&icc));
+    assert!(!String::from_utf8_lossy(&icc).contains("Jane"));
+    assert_eq!(Profile::from_buffer(&icc).unwrap().summary().description.as_deref(), Some("sRGB"));
+}
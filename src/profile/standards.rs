@@ -0,0 +1,249 @@
+/*!
+  Ready made, ICC version 4.3, matrix/TRC display profiles for common RGB color spaces, and a gray
+  display profile, and identity color space profiles for the CIELAB and CIEXYZ D50 profile
+  connection spaces, as used for working spaces of editing applications.
+
+  The colorant tags are calculated from the color space's primaries and white point, and are
+  chromatically adapted to the D50 profile connection space illuminant using the Bradford
+  transform, with the adaptation matrix recorded in the 'chad' tag.
+
+  ```
+  let srgb = cmx::profile::standards::srgb();
+  let icc = srgb.to_buffer().unwrap();
+  std::fs::write(std::env::temp_dir().join("sRGB-cmx.icc"), icc).unwrap();
+  ```
+*/
+
+use crate::profile::{Class, ColorSpace, Profile, RenderingIntent};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{
+    Curve, TagData, XYZ,
+    lut::{lut_atob, lut_btoa, ClutBuilder},
+    multi_localized_unicode::MultiLocalizedUnicode,
+    parametric_curve::ParametricCurve,
+};
+
+use crate::adaptation::{inverse, matrix, mul, mul_vec, Method};
+use crate::illuminants::{xy_to_xyz, D50_XY, D65_XY, PCS_ILLUMINANT as D50};
+
+const COPYRIGHT: &str = "No copyright, use freely";
+
+/// IEC 61966-2-1 sRGB display profile.
+pub fn srgb() -> Profile {
+    rgb_display("sRGB", [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]], D65_XY, srgb_trc())
+}
+
+/// Display P3 profile, with the DCI-P3 primaries, a D65 white point, and the sRGB tone response curve.
+pub fn display_p3() -> Profile {
+    rgb_display("Display P3", [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060]], D65_XY, srgb_trc())
+}
+
+/// Profile compatible with the Adobe RGB (1998) color space, with a gamma of 563/256.
+pub fn adobe_rgb() -> Profile {
+    rgb_display(
+        "Compatible with Adobe RGB (1998)",
+        [[0.64, 0.33], [0.21, 0.71], [0.15, 0.06]],
+        D65_XY,
+        TagData::Curve(Curve(vec![0x0233])),
+    )
+}
+
+/// Recommendation ITU-R BT.2020 profile, using the BT.2020 reference tone response curve.
+pub fn rec2020() -> Profile {
+    let alpha = 1.09929682680944;
+    let beta = 0.018053968510807;
+    rgb_display(
+        "ITU-R BT.2020",
+        [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046]],
+        D65_XY,
+        TagData::ParametricCurve(ParametricCurve::IEC61966_2_1 {
+            g: (1.0 / 0.45) as f32,
+            a: (1.0 / alpha) as f32,
+            b: ((alpha - 1.0) / alpha) as f32,
+            c: (1.0 / 4.5) as f32,
+            d: (4.5 * beta) as f32,
+        }),
+    )
+}
+
+/// ProPhoto (ROMM) RGB profile, with a D50 white point and a gamma of 1.8.
+pub fn prophoto_rgb() -> Profile {
+    rgb_display(
+        "ProPhoto RGB",
+        [[0.7347, 0.2653], [0.1596, 0.8404], [0.0366, 0.0001]],
+        D50_XY,
+        TagData::ParametricCurve(ParametricCurve::ExponentGamma { g: 1.8 }),
+    )
+}
+
+/// Gray display profile, with a gamma of 2.2, and a D65 white point.
+pub fn gray_gamma22() -> Profile {
+    let chad = bradford(xy_to_xyz(D65_XY), D50);
+    header(ColorSpaceSignature::Gray)
+        .with_tag(TagSignature::ProfileDescriptionTag, text("Gray Gamma 2.2"))
+        .with_tag(TagSignature::CopyrightTag, text(COPYRIGHT))
+        .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![D50])))
+        .with_tag(TagSignature::ChromaticAdaptationTag, s15_fixed16_matrix(chad))
+        .with_tag(TagSignature::GrayTRCTag, TagData::ParametricCurve(ParametricCurve::ExponentGamma { g: 2.2 }))
+}
+
+/// CIELAB D50 color space profile, with identity 'A2B0' and 'B2A0' transforms to a Lab profile
+/// connection space.
+pub fn lab_d50() -> Profile {
+    identity_space("Lab D50", ColorSpaceSignature::Lab)
+}
+
+/// CIEXYZ D50 color space profile, with identity 'A2B0' and 'B2A0' transforms to an XYZ profile
+/// connection space.
+pub fn xyz_d50() -> Profile {
+    identity_space("XYZ D50", ColorSpaceSignature::XYZ)
+}
+
+// The lookup tables have identity curves, and a two point grid, which interpolates the identity
+// exactly, as the color space, and the profile connection space, use the same 16-bit encoding.
+fn identity_space(description: &str, space: ColorSpaceSignature) -> Profile {
+    let curves = || vec![TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new()))];
+    let clut = || ClutBuilder::new(3, 3, 2).fill(|v| v.to_vec()).expect("a two point grid fits a lookup table");
+    let atob = lut_atob(curves(), clut(), Vec::new(), None, curves()).expect("a two point grid fits a lookup table");
+    let btoa = lut_btoa(curves(), None, Vec::new(), clut(), curves()).expect("a two point grid fits a lookup table");
+    let mut profile = Profile::new([4, 3, 0], Class::ColorSpace);
+    profile.colorspace = Some(ColorSpace::new(space));
+    profile.pcs = Some(ColorSpace::new(space));
+    profile.pcs_illuminant = Some(D50);
+    profile
+        .with_tag(TagSignature::ProfileDescriptionTag, text(description))
+        .with_tag(TagSignature::CopyrightTag, text(COPYRIGHT))
+        .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![D50])))
+        .with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob))
+        .with_tag(TagSignature::BToA0Tag, TagData::LutBToA(btoa))
+}
+
+fn srgb_trc() -> TagData {
+    TagData::ParametricCurve(ParametricCurve::IEC61966_2_1 {
+        g: 2.4,
+        a: (1.0 / 1.055) as f32,
+        b: (0.055 / 1.055) as f32,
+        c: (1.0 / 12.92) as f32,
+        d: 0.04045,
+    })
+}
+
+fn header(colorspace: ColorSpaceSignature) -> Profile {
+    let mut profile = Profile::new([4, 3, 0], Class::Display);
+    profile.colorspace = Some(ColorSpace::new(colorspace));
+    profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+    profile.pcs_illuminant = Some(D50);
+    profile
+}
+
+impl Profile {
+    /// Sets the colorant tags, and media white point, of a matrix/TRC profile, from the xy
+    /// chromaticities of its red, green, and blue primaries, and its white point. The colorants are
+    /// adapted to the D50 PCS illuminant, with the Bradford transform, which is recorded in the 'chad'
+    /// tag for other white points.
+    pub fn with_primaries(self, red: [f64; 2], green: [f64; 2], blue: [f64; 2], white: [f64; 2]) -> Self {
+        let white = xy_to_xyz(white);
+        let chad = bradford(white, D50);
+        let colorants = mul(chad, rgb_to_xyz([red, green, blue], white));
+        let column = |i: usize| TagData::XYZ(XYZ(vec![[colorants[0][i], colorants[1][i], colorants[2][i]]]));
+        let profile = self
+            .with_tag(TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(vec![D50])))
+            .with_tag(TagSignature::RedMatrixColumnTag, column(0))
+            .with_tag(TagSignature::GreenMatrixColumnTag, column(1))
+            .with_tag(TagSignature::BlueMatrixColumnTag, column(2));
+        if white != xy_to_xyz(D50_XY) {
+            profile.with_tag(TagSignature::ChromaticAdaptationTag, s15_fixed16_matrix(chad))
+        } else {
+            profile.without_tag(TagSignature::ChromaticAdaptationTag)
+        }
+    }
+}
+
+fn rgb_display(description: &str, [red, green, blue]: [[f64; 2]; 3], white: [f64; 2], trc: TagData) -> Profile {
+    let mut profile = header(ColorSpaceSignature::RGB)
+        .with_tag(TagSignature::ProfileDescriptionTag, text(description))
+        .with_tag(TagSignature::CopyrightTag, text(COPYRIGHT))
+        .with_primaries(red, green, blue, white);
+    for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+        profile = profile.with_tag(signature, copy_trc(&trc));
+    }
+    profile
+}
+
+fn copy_trc(trc: &TagData) -> TagData {
+    match trc {
+        TagData::Curve(Curve(v)) => TagData::Curve(Curve(v.clone())),
+        TagData::ParametricCurve(p) => TagData::ParametricCurve(*p),
+        _ => unreachable!("tone response curves are curve or parametric curve tags"),
+    }
+}
+
+fn text(s: &str) -> TagData {
+    TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(s))
+}
+
+fn s15_fixed16_matrix(m: [[f64; 3]; 3]) -> TagData {
+    TagData::s15_fixed16_array(&m)
+}
+
+// Matrix with the XYZ values of the red, green, and blue primaries in its columns, scaled to match the white point.
+fn rgb_to_xyz(primaries: [[f64; 2]; 3], white: [f64; 3]) -> [[f64; 3]; 3] {
+    let p = primaries.map(xy_to_xyz);
+    let m = [[p[0][0], p[1][0], p[2][0]], [p[0][1], p[1][1], p[2][1]], [p[0][2], p[1][2], p[2][2]]];
+    let s = mul_vec(inverse(m), white);
+    m.map(|row| [row[0] * s[0], row[1] * s[1], row[2] * s[2]])
+}
+
+fn bradford(src: [f64; 3], dst: [f64; 3]) -> [[f64; 3]; 3] {
+    matrix(Method::Bradford, src, dst)
+}
+
+#[test]
+fn test_srgb() {
+    let icc = srgb().to_buffer().unwrap();
+    assert_eq!(icc.len() % 4, 0);
+    let srgb = Profile::from_buffer(&icc).unwrap();
+    assert_eq!(srgb.tag_count(), 10);
+    // values of the ICC sRGB v4 profile
+    let red = srgb.get_tag(&TagSignature::RedMatrixColumnTag).unwrap();
+    match red.data() {
+        TagData::XYZ(XYZ(v)) => {
+            for (a, b) in v[0].iter().zip([0.4361, 0.2225, 0.0139]) {
+                assert!((a - b).abs() < 2E-4, "{:?}", v);
+            }
+        }
+        _ => panic!("rXYZ not an XYZ tag"),
+    }
+    // the three tone response curves share their data
+    assert_eq!(&icc[132 + 12 * 7 + 4..132 + 12 * 7 + 12], &icc[132 + 12 * 9 + 4..132 + 12 * 9 + 12]);
+}
+
+#[test]
+fn test_with_primaries() {
+    let profile = header(ColorSpaceSignature::RGB).with_primaries([0.64, 0.33], [0.30, 0.60], [0.15, 0.06], D65_XY);
+    let srgb = srgb();
+    for signature in [TagSignature::RedMatrixColumnTag, TagSignature::MediaWhitePointTag, TagSignature::ChromaticAdaptationTag] {
+        let find = |p: &Profile| p.get_tag(&signature).unwrap().to_be_bytes().unwrap();
+        assert_eq!(find(&profile), find(&srgb));
+    }
+    let d50 = profile.with_primaries([0.7347, 0.2653], [0.1596, 0.8404], [0.0366, 0.0001], D50_XY);
+    assert!(!d50.contains_tag(&TagSignature::ChromaticAdaptationTag));
+}
+
+#[test]
+fn test_identity_spaces() {
+    for (profile, space) in [(lab_d50(), ColorSpaceSignature::Lab), (xyz_d50(), ColorSpaceSignature::XYZ)] {
+        assert!(profile.missing_tags().is_empty());
+        let parsed = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+        assert!(matches!(parsed.class, Class::ColorSpace));
+        assert_eq!(parsed.pcs.as_ref().map(ColorSpace::signature), Some(space));
+        let Some(TagData::LutAToB(data)) = parsed.tag_data(&TagSignature::AToB0Tag) else { panic!("expected a lutAtoBType tag") };
+        // the grid corners map to themselves
+        let clut = u32::from_be_bytes(data[16..20].try_into().unwrap()) as usize - 8;
+        assert_eq!(data[clut..clut + 4], [2, 2, 2, 0]);
+        let entries: Vec<u16> = data[clut + 20..clut + 20 + 48].chunks(2).map(|c| u16::from_be_bytes(c.try_into().unwrap())).collect();
+        for (i, corner) in entries.chunks(3).enumerate() {
+            assert_eq!(corner, [i >> 2 & 1, i >> 1 & 1, i & 1].map(|b| b as u16 * 65535));
+        }
+    }
+}
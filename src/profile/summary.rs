@@ -0,0 +1,143 @@
+/*!
+  Human oriented summaries of profiles, as shown by `cmx info`: the profile class and color spaces,
+  the white point and primaries as chromaticity coordinates, the gamut area relative to sRGB,
+  estimated gamma values of the tone response curves, and the tags in the profile.
+
+  White points and primaries are un-adapted with the inverse of the 'chad' chromatic adaptation
+  matrix, if present, to give the values of the device, instead of the PCS.
+*/
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::adaptation::{inverse, mul_vec};
+use crate::profile::{Class, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature, tagtype::TagTypeSignature};
+use crate::tags::{TagData, XYZ};
+use crate::transform::MatrixTrc;
+
+/// Chromaticity coordinates of the sRGB primaries.
+const SRGB_PRIMARIES: [[f64; 2]; 3] = [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06]];
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub version: [u8; 3],
+    pub class: Class,
+    pub colorspace: Option<ColorSpaceSignature>,
+    pub pcs: Option<ColorSpaceSignature>,
+    pub description: Option<String>,
+    pub white_point: Option<[f64; 2]>,
+    pub primaries: Option<[[f64; 2]; 3]>,
+    /// Area of the triangle spanned by the primaries in the xy chromaticity diagram, relative to sRGB.
+    pub gamut_area: Option<f64>,
+    /// Gamma values of power functions best fitting the tone response curves, in channel order.
+    pub gamma: Vec<f64>,
+    /// Tag signatures, type signatures, and encoded lengths, if the tags could be encoded.
+    pub tags: Vec<(TagSignature, TagTypeSignature, Option<usize>)>,
+}
+
+impl Profile {
+    pub fn summary(&self) -> Summary {
+        let data = |signature: TagSignature| self.tag_data(&signature);
+        let xyz = |signature: TagSignature| match data(signature) {
+            Some(TagData::XYZ(XYZ(v))) => v.first().copied(),
+            _ => None,
+        };
+        let unadapt = data(TagSignature::ChromaticAdaptationTag).and_then(TagData::matrix).map(inverse);
+        let xy = |v: [f64; 3]| {
+            let [x, y, z] = unadapt.map_or(v, |m| mul_vec(m, v));
+            [x / (x + y + z), y / (x + y + z)]
+        };
+        let primaries = match [TagSignature::RedMatrixColumnTag, TagSignature::GreenMatrixColumnTag, TagSignature::BlueMatrixColumnTag].map(xyz) {
+            [Some(r), Some(g), Some(b)] => Some([xy(r), xy(g), xy(b)]),
+            _ => None,
+        };
+        let description = match data(TagSignature::ProfileDescriptionTag) {
+            Some(TagData::MultiLocalizedUnicode(m)) => m.0.first().map(|(_, _, s)| s.clone()),
+            Some(TagData::TextDescription(t)) => Some(t.ascii.clone()),
+            _ => None,
+        };
+        let gamma = MatrixTrc::from_profile(self).map(|m| m.trcs.iter().map(|trc| estimate_gamma(|x| trc.eval(x))).collect()).unwrap_or_default();
+        Summary {
+            version: self.version,
+            class: self.class,
+            colorspace: self.colorspace.as_ref().map(|c| c.signature()),
+            pcs: self.pcs.as_ref().map(|c| c.signature()),
+            description,
+            white_point: xyz(TagSignature::MediaWhitePointTag).map(xy),
+            primaries,
+            gamut_area: primaries.map(|p| area(p) / area(SRGB_PRIMARIES)),
+            gamma,
+            tags: self.iter_tags().map(|(signature, t)| (signature.clone(), t.type_signature(), t.to_be_bytes().ok().map(|b| b.len()))).collect(),
+        }
+    }
+}
+
+/// Least squares fit of `ln(y) = gamma ln(x)`, for inputs from 0.1 to 0.9.
+pub(crate) fn estimate_gamma(f: impl Fn(f64) -> f64) -> f64 {
+    let (mut xy, mut xx) = (0.0, 0.0);
+    for x in (1..10).map(|i| i as f64 / 10.0) {
+        let y = f(x);
+        if y > 0.0 {
+            xy += x.ln() * y.ln();
+            xx += x.ln() * x.ln();
+        }
+    }
+    xy / xx
+}
+
+fn area([[xr, yr], [xg, yg], [xb, yb]]: [[f64; 2]; 3]) -> f64 {
+    ((xg - xr) * (yb - yr) - (xb - xr) * (yg - yr)).abs() / 2.0
+}
+
+fn four_cc(v: u32) -> String {
+    v.to_be_bytes().iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect()
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(description) = &self.description {
+            writeln!(f, "description:  {}", description)?;
+        }
+        writeln!(f, "version:      {}.{}.{}", self.version[0], self.version[1], self.version[2])?;
+        writeln!(f, "class:        {:?}", self.class)?;
+        let space = |s: &Option<ColorSpaceSignature>| s.map_or_else(|| String::from("-"), |s| format!("{:?}", s));
+        writeln!(f, "color space:  {}", space(&self.colorspace))?;
+        writeln!(f, "pcs:          {}", space(&self.pcs))?;
+        if let Some([x, y]) = self.white_point {
+            writeln!(f, "white point:  x={:.4} y={:.4}", x, y)?;
+        }
+        if let Some(primaries) = self.primaries {
+            for (name, [x, y]) in ["red", "green", "blue"].iter().zip(primaries) {
+                writeln!(f, "{:<13} x={:.4} y={:.4}", format!("{}:", name), x, y)?;
+            }
+        }
+        if let Some(area) = self.gamut_area {
+            writeln!(f, "gamut area:   {:.1}% of sRGB", 100.0 * area)?;
+        }
+        if !self.gamma.is_empty() {
+            let gamma: Vec<String> = self.gamma.iter().map(|g| format!("{:.2}", g)).collect();
+            writeln!(f, "gamma:        {}", gamma.join(" "))?;
+        }
+        writeln!(f, "tags:")?;
+        for (signature, type_signature, length) in &self.tags {
+            let length = length.map_or_else(|| String::from("?"), |l| l.to_string());
+            writeln!(f, "  {}  {}  {}", four_cc(signature.to_u32()), four_cc(*type_signature as u32), length)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_summary() {
+    use crate::profile::standards;
+    let summary = standards::display_p3().summary();
+    let [x, y] = summary.white_point.unwrap();
+    assert!((x - 0.3127).abs() < 1E-3 && (y - 0.3290).abs() < 1E-3, "{} {}", x, y);
+    assert!((summary.primaries.unwrap()[1][0] - 0.265).abs() < 1E-3);
+    assert!((summary.gamut_area.unwrap() - 1.36).abs() < 0.01, "{:?}", summary.gamut_area);
+    assert!(summary.gamma.iter().all(|g| (g - 2.1).abs() < 0.1), "{:?}", summary.gamma);
+    assert!(summary.tags.iter().all(|t| t.2.is_some()));
+    assert!(summary.to_string().contains("gamut area"));
+}
@@ -0,0 +1,84 @@
+/*!
+  Synthetic profiles with edge cases, valid according to the ICC specification, but rarely found in
+  practice, for testing profile readers and color management modules.
+
+  All profiles are based on the built-in sRGB profile, unless noted otherwise, and can be written
+  with [Profile::to_buffer].
+*/
+
+use crate::profile::{standards, Profile};
+use crate::signatures::tag::TagSignature;
+use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, Curve, TagData};
+use crate::signatures::tagtype::TagTypeSignature;
+
+/// Profile with a description, and copyright, without any text records.
+pub fn empty_mluc() -> Profile {
+    standards::srgb()
+        .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode(Vec::new())))
+        .with_tag(TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode(Vec::new())))
+}
+
+/// Profile with tone response curves with a single entry, which is a gamma value, of 2.2.
+pub fn gamma_curves() -> Profile {
+    let gamma = || TagData::Curve(Curve(vec![(2.2f64 * 256.0).round() as u16]));
+    standards::srgb()
+        .with_tag(TagSignature::RedTRCTag, gamma())
+        .with_tag(TagSignature::GreenTRCTag, gamma())
+        .with_tag(TagSignature::BlueTRCTag, gamma())
+}
+
+/// Profile with identity tone response curves, without entries.
+pub fn identity_curves() -> Profile {
+    let identity = || TagData::Curve(Curve(Vec::new()));
+    standards::srgb()
+        .with_tag(TagSignature::RedTRCTag, identity())
+        .with_tag(TagSignature::GreenTRCTag, identity())
+        .with_tag(TagSignature::BlueTRCTag, identity())
+}
+
+/// Profile with a private tag without data, only its type signature and reserved field.
+pub fn empty_tag() -> Profile {
+    standards::srgb().with_tag(TagSignature::VendorTag("zero".to_owned()), TagData::Custom(*b"ui08", Vec::new()))
+}
+
+/// Encoded profile with a tag with a length of zero bytes in the tag table, which readers should
+/// reject, or skip; not representable as a [Profile].
+pub fn zero_length_tag() -> Vec<u8> {
+    let mut buf = empty_tag().to_buffer().unwrap();
+    let n = u32::from_be_bytes(buf[128..132].try_into().unwrap()) as usize;
+    for i in 0..n {
+        let entry = 132 + 12 * i;
+        if &buf[entry..entry + 4] == b"zero" {
+            buf[entry + 8..entry + 12].copy_from_slice(&0u32.to_be_bytes());
+        }
+    }
+    buf
+}
+
+/// Profile with `n` private tags in addition to the sRGB tags, each with a unique signature, and
+/// different data, so none of the tags share their data.
+pub fn many_tags(n: usize) -> Profile {
+    const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut profile = standards::srgb();
+    for i in 0..n.min(36usize.pow(3)) {
+        let sig = [b'z', CHARS[i / 1296], CHARS[i / 36 % 36], CHARS[i % 36]];
+        profile.tags.push(crate::tags::Tag::new(
+            TagSignature::VendorTag(String::from_utf8(sig.to_vec()).unwrap()),
+            TagData::UInt32Array(vec![i as u32]),
+        ));
+    }
+    profile
+}
+
+#[test]
+fn test_synthetic_profiles() {
+    for profile in [empty_mluc(), gamma_curves(), identity_curves(), empty_tag(), many_tags(1000)] {
+        let buf = profile.to_buffer().unwrap();
+        let parsed = Profile::from_buffer(&buf).unwrap();
+        assert_eq!(parsed.tag_count(), profile.tag_count());
+        assert_eq!(parsed.to_buffer().unwrap(), buf);
+    }
+    assert!(Profile::from_buffer(&zero_length_tag()).is_err());
+    let trc = gamma_curves().trc_table(3);
+    assert!((trc.rows[1].1[0] - 0.5f64.powf(2.2)).abs() < 1E-3);
+}
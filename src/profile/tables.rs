@@ -0,0 +1,117 @@
+/*!
+  Tone response curves and colorant matrices of a profile as tables, for analysis in spreadsheets.
+*/
+
+use std::fmt::Write;
+
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::TagData;
+
+const TRC_TAGS: [(TagSignature, &str); 4] = [
+    (TagSignature::RedTRCTag, "rTRC"),
+    (TagSignature::GreenTRCTag, "gTRC"),
+    (TagSignature::BlueTRCTag, "bTRC"),
+    (TagSignature::GrayTRCTag, "kTRC"),
+];
+
+const XYZ_TAGS: [(TagSignature, &str); 5] = [
+    (TagSignature::RedMatrixColumnTag, "rXYZ"),
+    (TagSignature::GreenMatrixColumnTag, "gXYZ"),
+    (TagSignature::BlueMatrixColumnTag, "bXYZ"),
+    (TagSignature::MediaWhitePointTag, "wtpt"),
+    (TagSignature::MediaBlackPointTag, "bkpt"),
+];
+
+/// A table with a header row, and rows with a label and a number of values.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Table {
+    pub header: Vec<String>,
+    pub rows: Vec<(String, Vec<f64>)>,
+}
+
+impl Table {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = self.header.join(",");
+        csv.push('\n');
+        for (label, values) in &self.rows {
+            csv.push_str(label);
+            for v in values {
+                write!(csv, ",{}", v).unwrap();
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl Profile {
+    /// The tone response curves of the profile, sampled at `n` equally spaced input values, with a
+    /// column for each of the curves present.
+    pub fn trc_table(&self, n: usize) -> Table {
+        let curves: Vec<(&str, &TagData)> = TRC_TAGS
+            .iter()
+            .filter_map(|(sig, name)| self.tag_data(sig).map(|data| (*name, data)))
+            .filter(|(_, data)| matches!(data, TagData::Curve(_) | TagData::ParametricCurve(_)))
+            .collect();
+        let mut table = Table { header: vec!["input".to_owned()], rows: Vec::new() };
+        if curves.is_empty() {
+            return table;
+        }
+        table.header.extend(curves.iter().map(|(name, _)| name.to_string()));
+        for i in 0..n {
+            let x = if n > 1 { i as f64 / (n - 1) as f64 } else { 0.0 };
+            let values = curves
+                .iter()
+                .map(|(_, data)| match data {
+                    TagData::Curve(c) => c.value(x as f32) as f64,
+                    TagData::ParametricCurve(p) => p.value(x as f32) as f64,
+                    _ => unreachable!(),
+                })
+                .collect();
+            table.rows.push((x.to_string(), values));
+        }
+        table
+    }
+
+    /// The colorant matrix columns, and the media white and black points, of the profile, as rows of
+    /// XYZ values, followed by the rows of the chromatic adaptation matrix, if present.
+    pub fn matrix_table(&self) -> Table {
+        let mut table = Table { header: ["tag", "X", "Y", "Z"].map(String::from).to_vec(), rows: Vec::new() };
+        for (sig, name) in XYZ_TAGS {
+            if let Some(TagData::XYZ(xyz)) = self.tag_data(&sig) {
+                if let Some(v) = xyz.0.first() {
+                    table.rows.push((name.to_owned(), v.to_vec()));
+                }
+            }
+        }
+        if let Some(TagData::S15Fixed16Array(m)) =
+            self.tag_data(&TagSignature::ChromaticAdaptationTag)
+        {
+            for (i, row) in m.chunks_exact(3).take(3).enumerate() {
+                table.rows.push((format!("chad{}", i + 1), row.iter().map(|&v| v as f64).collect()));
+            }
+        }
+        table
+    }
+}
+
+#[test]
+fn test_tables() {
+    let srgb = crate::profile::standards::srgb();
+    let trc = srgb.trc_table(5);
+    assert_eq!(trc.header, ["input", "rTRC", "gTRC", "bTRC"]);
+    assert_eq!(trc.rows.len(), 5);
+    assert!(trc.rows[0].1[0].abs() < 1E-6);
+    assert!((trc.rows[2].1[0] - 0.214).abs() < 1E-3);
+    assert!((trc.rows[4].1[0] - 1.0).abs() < 1E-6);
+    assert!(trc.to_csv().starts_with("input,rTRC,gTRC,bTRC\n0,0,0,0\n"));
+
+    let matrix = srgb.matrix_table();
+    let labels: Vec<&str> = matrix.rows.iter().map(|(label, _)| label.as_str()).collect();
+    assert_eq!(labels, ["rXYZ", "gXYZ", "bXYZ", "wtpt", "chad1", "chad2", "chad3"]);
+}
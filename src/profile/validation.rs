@@ -0,0 +1,104 @@
+/*!
+  Tag validation hooks, run when a profile is written, to enforce policies for the content of
+  generated profiles, such as a minimum number of curve entries, or non-empty descriptions.
+
+  Hooks are registered for a tag signature, and run on the data of that tag, if present; a profile
+  is only written if all its tags pass, with the error listing all failing tags otherwise.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+use crate::signatures::tag::TagSignature;
+use crate::tags::TagData;
+
+type Hook = Box<dyn Fn(&TagData) -> std::result::Result<(), String> + Send + Sync>;
+
+#[derive(Default)]
+pub struct TagValidators {
+    hooks: Vec<(TagSignature, Hook)>,
+}
+
+impl TagValidators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a validation hook for a tag; a tag can have more than one hook.
+    pub fn with(
+        mut self,
+        signature: TagSignature,
+        hook: impl Fn(&TagData) -> std::result::Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.push((signature, Box::new(hook)));
+        self
+    }
+
+    /// Runs the hooks on the tags of a profile, returning a message for each failed validation.
+    pub fn validate(&self, profile: &Profile) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (signature, hook) in &self.hooks {
+            if let Some(tag) = profile.get_tag(signature) {
+                if let Err(e) = hook(tag.data()) {
+                    errors.push(format!("{:?}: {}", signature, e));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl std::fmt::Debug for TagValidators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.hooks.iter().map(|(s, _)| s)).finish()
+    }
+}
+
+/// Hook for curve tags, requiring sampled curves to have at least `n` entries; parametric curves pass.
+pub fn min_curve_entries(n: usize) -> impl Fn(&TagData) -> std::result::Result<(), String> + Send + Sync {
+    move |data| match data {
+        TagData::Curve(c) if c.0.len() < n => Err(format!("curve with {} entries, at least {} required", c.0.len(), n)),
+        _ => Ok(()),
+    }
+}
+
+/// Hook for text tags, requiring a non-empty text, in all languages of localized text.
+pub fn non_empty_text() -> impl Fn(&TagData) -> std::result::Result<(), String> + Send + Sync {
+    |data| {
+        let empty = match data {
+            TagData::Text(s) => s.trim().is_empty(),
+            TagData::TextDescription(t) => t.ascii.trim().is_empty(),
+            TagData::MultiLocalizedUnicode(m) => m.0.is_empty() || m.0.iter().any(|(_, _, s)| s.trim().is_empty()),
+            _ => false,
+        };
+        if empty { Err("empty text".to_owned()) } else { Ok(()) }
+    }
+}
+
+impl Profile {
+    /// Writes the profile, as [Profile::to_buffer], after validating its tags with the given hooks.
+    pub fn to_buffer_validated(&self, validators: &TagValidators) -> Result<Vec<u8>> {
+        let errors = validators.validate(self);
+        if !errors.is_empty() {
+            return Err(format!("tag validation failed: {}", errors.join("; ")).into());
+        }
+        Ok(self.to_buffer()?)
+    }
+}
+
+#[test]
+fn test_tag_validators() {
+    use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, Curve};
+    let validators = TagValidators::new()
+        .with(TagSignature::ProfileDescriptionTag, non_empty_text())
+        .with(TagSignature::RedTRCTag, min_curve_entries(256))
+        .with(TagSignature::GrayTRCTag, min_curve_entries(256));
+    let srgb = crate::profile::standards::srgb();
+    assert!(srgb.to_buffer_validated(&validators).is_ok());
+
+    let profile = srgb
+        .with_tag(TagSignature::ProfileDescriptionTag, TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(" ")))
+        .with_tag(TagSignature::RedTRCTag, TagData::Curve(Curve(vec![0, 65535])));
+    assert_eq!(validators.validate(&profile).len(), 2);
+    let e = profile.to_buffer_validated(&validators).unwrap_err().to_string();
+    assert!(e.contains("RedTRCTag: curve with 2 entries"), "{e}");
+}
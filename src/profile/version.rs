@@ -0,0 +1,190 @@
+/*!
+  Conversion of profiles between ICC version 2, and version 4.
+
+  Besides the version in the header, the conversion changes:
+
+  - the text tags: version 4 profiles use the multiLocalizedUnicodeType for the description, the
+    copyright, and the device and viewing conditions descriptions, while version 2 profiles use the
+    textDescriptionType, and, for the copyright, the textType;
+  - parametric curves, which version 2 profiles do not support, and which are sampled;
+  - the media white point of display, and input, profiles: version 4 profiles have the PCS illuminant
+    as their media white point, with the chromatic adaptation to the PCS in a 'chad' tag, while
+    version 2 profiles have the white point of the device, and no 'chad' tag.
+
+  Tags with types which can not be represented in the target version, such as lookup tables of the
+  lutAtoBType in version 2 profiles, are removed, and reported.
+*/
+
+use crate::adaptation::{inverse, matrix, mul_vec, Method};
+use crate::common::Result;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::{Class, Profile};
+use crate::signatures::tag::TagSignature;
+use crate::tags::{multi_localized_unicode::MultiLocalizedUnicode, text_description::TextDescription, Curve, Tag, TagData, XYZ};
+
+// Number of entries of sampled parametric curves.
+const CURVE_ENTRIES: usize = 1024;
+
+/// A profile converted to another version, and the tags which were removed, as they can not be
+/// represented in that version.
+#[derive(Debug)]
+pub struct VersionConversion {
+    pub profile: Profile,
+    pub removed: Vec<TagSignature>,
+}
+
+impl Profile {
+    /// Converts a profile to version 2, or version 4, with the given minor version.
+    pub fn to_version(mut self, major: u8, minor: u8) -> Result<VersionConversion> {
+        if major != 2 && major != 4 {
+            return Err(format!("conversion to version {major} is not supported").into());
+        }
+        if minor > 15 {
+            return Err("minor version should be in the range from 0 to 15".into());
+        }
+        let adapt_white = matches!(self.class, Class::Display | Class::Input);
+        let chad = self.tag_data(&TagSignature::ChromaticAdaptationTag).and_then(TagData::matrix);
+        let mut removed = Vec::new();
+        let mut tags = Vec::with_capacity(self.tags.len());
+        for tag in std::mem::take(&mut self.tags) {
+            let signature = tag.signature().clone();
+            if major == 2 && signature == TagSignature::ChromaticAdaptationTag {
+                // the adaptation is included in the media white point of version 2 profiles
+                continue;
+            }
+            let converted = if major == 4 { to_v4(tag) } else { to_v2(tag, chad, adapt_white) };
+            match converted {
+                Some(tag) => tags.push(tag),
+                None => removed.push(signature),
+            }
+        }
+        if major == 4 && adapt_white && chad.is_none() {
+            let white = tags.iter().find(|t| t.signature() == &TagSignature::MediaWhitePointTag).and_then(|t| match t.data() {
+                TagData::XYZ(XYZ(v)) => v.first().copied(),
+                _ => None,
+            });
+            if let Some(white) = white.filter(|w| w.iter().zip(PCS_ILLUMINANT).any(|(a, b)| (a - b).abs() > 1E-4)) {
+                let chad = matrix(Method::Bradford, white, PCS_ILLUMINANT);
+                tags.push(Tag::new(TagSignature::ChromaticAdaptationTag, TagData::s15_fixed16_array(&chad)));
+                let wtpt = tags.iter_mut().find(|t| t.signature() == &TagSignature::MediaWhitePointTag).map(|t| t.data_mut());
+                if let Some(TagData::XYZ(XYZ(v))) = wtpt {
+                    v[0] = PCS_ILLUMINANT;
+                }
+            }
+        }
+        self.tags = tags;
+        self.version = [major, minor, 0];
+        self.attributes.version = major;
+        self.profile_id = None;
+        Ok(VersionConversion { profile: self, removed })
+    }
+}
+
+fn description(text: &str) -> MultiLocalizedUnicode {
+    MultiLocalizedUnicode::en_us(text)
+}
+
+fn to_v4(tag: Tag) -> Option<Tag> {
+    let signature = tag.signature().clone();
+    let data = match tag.data() {
+        TagData::TextDescription(t) => TagData::MultiLocalizedUnicode(description(if t.unicode.is_empty() { &t.ascii } else { &t.unicode })),
+        TagData::Text(t) => TagData::MultiLocalizedUnicode(description(t)),
+        TagData::Float16Array(_)
+        | TagData::Float32Array(_)
+        | TagData::Float64Array(_)
+        | TagData::SparseMatrixArray(_)
+        | TagData::TagStruct(_)
+        | TagData::Utf8(_)
+        | TagData::Utf16(_)
+        | TagData::Utf8Zip(_)
+        | TagData::SpectralViewingConditions(_)
+        | TagData::SpectralDataInfo(_)
+        | TagData::EmbeddedHeigthImage(_)
+        | TagData::EmbeddedNormalImage(_)
+        | TagData::GamutBoundaryDescription(_) => return None,
+        _ => return Some(tag),
+    };
+    Some(Tag::new(signature, data))
+}
+
+fn to_v2(tag: Tag, chad: Option<[[f64; 3]; 3]>, adapt_white: bool) -> Option<Tag> {
+    let signature = tag.signature().clone();
+    let text = |m: &MultiLocalizedUnicode| m.0.first().map(|(_, _, s)| s.clone()).unwrap_or_default();
+    let data = match (&signature, tag.data()) {
+        // Apple's localized description is a multiLocalizedUnicodeType in version 2 profiles too
+        (TagSignature::MultilocalizedDescriptionStringTag, _) => return Some(tag),
+        (TagSignature::CopyrightTag, TagData::MultiLocalizedUnicode(m)) => TagData::Text(text(m)),
+        (_, TagData::MultiLocalizedUnicode(m)) => TagData::TextDescription(TextDescription::new(&text(m))),
+        (_, TagData::ParametricCurve(p)) => {
+            TagData::Curve(Curve((0..CURVE_ENTRIES).map(|i| (p.eval(i as f64 / (CURVE_ENTRIES - 1) as f64).clamp(0.0, 1.0) * 65535.0).round() as u16).collect()))
+        }
+        (TagSignature::MediaWhitePointTag, TagData::XYZ(XYZ(v))) if adapt_white && chad.is_some() => {
+            let white = chad.map(|m| mul_vec(inverse(m), v.first().copied().unwrap_or(PCS_ILLUMINANT)));
+            TagData::XYZ(XYZ(vec![white.unwrap_or(PCS_ILLUMINANT)]))
+        }
+        (_, data) if v2_type(data) => return Some(tag),
+        _ => return None,
+    };
+    Some(Tag::new(signature, data))
+}
+
+// Tag types of version 2 profiles, including Apple's private types, and unknown types.
+fn v2_type(data: &TagData) -> bool {
+    matches!(
+        data,
+        TagData::Chromaticity(_)
+            | TagData::ColorantOrder(_)
+            | TagData::ColorantTable(_)
+            | TagData::Curve(_)
+            | TagData::Custom(..)
+            | TagData::Data(_)
+            | TagData::DateTime(_)
+            | TagData::Lut8(_)
+            | TagData::MakeAndModel(_)
+            | TagData::Measurement(_)
+            | TagData::NamedColor2(_)
+            | TagData::NativeDisplayInfo(_)
+            | TagData::ProfileSequenceDesc(_)
+            | TagData::S15Fixed16Array(_)
+            | TagData::Signature(_)
+            | TagData::Technology(_)
+            | TagData::Text(_)
+            | TagData::TextDescription(_)
+            | TagData::U16Fixed16Array(_)
+            | TagData::UInt8Array(_)
+            | TagData::UInt16Array(_)
+            | TagData::UInt32Array(_)
+            | TagData::UInt64Array(_)
+            | TagData::Vcgp(_)
+            | TagData::Vcgt(_)
+            | TagData::ViewingConditions(_)
+            | TagData::XYZ(_)
+    )
+}
+
+#[test]
+fn test_to_version() {
+    use crate::profile::standards;
+    let srgb = standards::srgb();
+    let v2 = standards::srgb().to_version(2, 1).unwrap();
+    assert!(v2.removed.is_empty());
+    let v2 = v2.profile;
+    assert_eq!(v2.version, [2, 1, 0]);
+    assert!(matches!(v2.tag_data(&TagSignature::ProfileDescriptionTag), Some(TagData::TextDescription(_))));
+    assert!(matches!(v2.tag_data(&TagSignature::CopyrightTag), Some(TagData::Text(_))));
+    assert!(matches!(v2.tag_data(&TagSignature::RedTRCTag), Some(TagData::Curve(_))));
+    assert!(v2.tag_data(&TagSignature::ChromaticAdaptationTag).is_none());
+    let Some(TagData::XYZ(XYZ(white))) = v2.tag_data(&TagSignature::MediaWhitePointTag) else { panic!("no white point") };
+    assert!((white[0][0] - 0.9505).abs() < 1E-3 && (white[0][2] - 1.089).abs() < 1E-3);
+
+    // and back, with the D65 white point adapted to the PCS illuminant again
+    let v4 = Profile::from_buffer(&v2.to_buffer().unwrap()).unwrap().to_version(4, 3).unwrap().profile;
+    assert!(matches!(v4.tag_data(&TagSignature::ProfileDescriptionTag), Some(TagData::MultiLocalizedUnicode(_))));
+    let (Some(TagData::S15Fixed16Array(a)), Some(TagData::S15Fixed16Array(b))) =
+        (v4.tag_data(&TagSignature::ChromaticAdaptationTag), srgb.tag_data(&TagSignature::ChromaticAdaptationTag)) else { panic!("no chad tag") };
+    assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1E-3));
+    assert!(crate::profile::compare(&srgb, &v4, crate::profile::RenderingIntent::Perceptual).unwrap().delta_e2000.max < 0.1);
+
+    let lut = Profile::new([4, 3, 0], Class::Abstract).with_tag(TagSignature::AToB0Tag, TagData::LutAToB(Vec::new()));
+    assert_eq!(lut.to_version(2, 4).unwrap().removed, [TagSignature::AToB0Tag]);
+}
@@ -0,0 +1,149 @@
+/*!
+  Progress reporting for long running operations.
+
+  Operations which process a known number of items, such as sampling a color lookup table, or parsing a
+  directory of profiles, accept a `&mut dyn Progress` argument, and report the number of completed items,
+  and the total number of items, as they go. Closures taking `(done, total)` implement `Progress`
+  directly, so a frontend can use for example:
+
+  ```
+  use cmx::progress::{Progress, Counter};
+
+  let mut bar = |done: usize, total: usize| println!("{}/{}", done, total);
+  let mut counter = Counter::new(&mut bar, 3);
+  for _ in 0..3 {
+      counter.tick();
+  }
+  ```
+
+  Operations can be cancelled, for example from a GUI thread, through a [CancellationToken]: a
+  progress receiver wrapped in a [Cancellable] reports the cancellation, and operations stop with an
+  error at the next completed item.
+*/
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::common::Result;
+
+pub trait Progress {
+    /// Called with the number of completed items, and the total number of items of an operation.
+    fn update(&mut self, done: usize, total: usize);
+
+    /// Checked by operations after each completed item; operations stop if it returns true.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(usize, usize)> Progress for F {
+    fn update(&mut self, done: usize, total: usize) {
+        self(done, total)
+    }
+}
+
+/// Progress receiver for callers which are not interested in progress updates.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn update(&mut self, _done: usize, _total: usize) {}
+}
+
+/// A flag shared between threads, to cancel one or more operations.
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A progress receiver, which cancels operations when its token is cancelled.
+pub struct Cancellable<P> {
+    pub receiver: P,
+    pub token: CancellationToken,
+}
+
+impl<P: Progress> Cancellable<P> {
+    pub fn new(receiver: P, token: CancellationToken) -> Self {
+        Self { receiver, token }
+    }
+}
+
+impl<P: Progress> Progress for Cancellable<P> {
+    fn update(&mut self, done: usize, total: usize) {
+        self.receiver.update(done, total)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.receiver.is_cancelled()
+    }
+}
+
+/// Keeps track of the number of completed items, and forwards each step to a `Progress` receiver.
+pub struct Counter<'a> {
+    receiver: &'a mut dyn Progress,
+    done: usize,
+    total: usize,
+}
+
+impl<'a> Counter<'a> {
+    /// Creates a counter for an operation of `total` items, and reports the start of the operation.
+    pub fn new(receiver: &'a mut dyn Progress, total: usize) -> Self {
+        receiver.update(0, total);
+        Self { receiver, done: 0, total }
+    }
+
+    /// Marks one item as completed.
+    pub fn tick(&mut self) {
+        self.advance(1)
+    }
+
+    /// Marks `n` items as completed.
+    pub fn advance(&mut self, n: usize) {
+        self.done = (self.done + n).min(self.total);
+        self.receiver.update(self.done, self.total);
+    }
+
+    /// Marks one item as completed, failing if the operation has been cancelled.
+    pub fn step(&mut self) -> Result<()> {
+        self.tick();
+        if self.receiver.is_cancelled() {
+            return Err("operation cancelled".into());
+        }
+        Ok(())
+    }
+
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+#[test]
+fn test_cancellation() {
+    let token = CancellationToken::new();
+    let mut receiver = Cancellable::new(NoProgress, token.clone());
+    let mut counter = Counter::new(&mut receiver, 10);
+    let result: Result<()> = (0..10).try_for_each(|i| {
+        if i == 3 {
+            token.cancel();
+        }
+        counter.step()
+    });
+    assert!(result.is_err());
+    assert_eq!(counter.done(), 4);
+}
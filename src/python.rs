@@ -0,0 +1,189 @@
+/*!
+  Python bindings, with the `python` feature, for reading, inspecting, and creating profiles from
+  Python, e.g. in notebooks with measurement data. Build and install the `cmx` Python module with
+  [maturin](https://www.maturin.rs), using the settings in `pyproject.toml`:
+
+  ```text
+  maturin develop --release
+  ```
+
+  ```python
+  import cmx
+  p3 = cmx.Profile.standard("display_p3")
+  print(p3.description, p3.colorspace, p3.tags())
+  custom = cmx.Profile.rgb("My Display", [[0.68, 0.32], [0.265, 0.69], [0.15, 0.06]], [0.3127, 0.329], 2.2)
+  custom.write("my_display.icc")
+  ```
+*/
+
+// the error conversions generated by pyo3 for methods returning a PyResult trigger this lint
+#![allow(clippy::useless_conversion)]
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::error::Error;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::dump::DumpOptions;
+use crate::profile::{standards, Class, ColorSpace, Profile};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{parametric_curve::ParametricCurve, TagData};
+
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(e) => e.into(),
+            e => PyValueError::new_err(e.to_string()),
+        }
+    }
+}
+
+fn value_error(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn four_cc(v: u32) -> String {
+    v.to_be_bytes().iter().map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' }).collect()
+}
+
+#[pyclass(name = "Profile", module = "cmx")]
+pub struct PyProfile(pub Profile);
+
+#[pymethods]
+impl PyProfile {
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self(Profile::from_buffer(data)?))
+    }
+
+    #[staticmethod]
+    fn read(path: &str) -> PyResult<Self> {
+        Ok(Self(Profile::from_file(path)?))
+    }
+
+    /// One of the standard profiles: "srgb", "display_p3", "adobe_rgb", "rec2020", "prophoto_rgb",
+    /// or "gray_gamma22".
+    #[staticmethod]
+    fn standard(name: &str) -> PyResult<Self> {
+        let profile = match name {
+            "srgb" => standards::srgb(),
+            "display_p3" => standards::display_p3(),
+            "adobe_rgb" => standards::adobe_rgb(),
+            "rec2020" => standards::rec2020(),
+            "prophoto_rgb" => standards::prophoto_rgb(),
+            "gray_gamma22" => standards::gray_gamma22(),
+            _ => return Err(value_error(format!("unknown standard profile {}", name))),
+        };
+        Ok(Self(profile))
+    }
+
+    /// Version 4 RGB display profile, from the xy chromaticities of its primaries and white point,
+    /// and the gamma of its tone response curves.
+    #[staticmethod]
+    fn rgb(description: &str, primaries: [[f64; 2]; 3], white_point: [f64; 2], gamma: f32) -> Self {
+        let mut profile = Profile::new([4, 3, 0], Class::Display);
+        profile.colorspace = Some(ColorSpace::new(ColorSpaceSignature::RGB));
+        profile.pcs = Some(ColorSpace::new(ColorSpaceSignature::XYZ));
+        profile.pcs_illuminant = Some(PCS_ILLUMINANT);
+        let [red, green, blue] = primaries;
+        let mut profile = profile
+            .with_description(description)
+            .with_primaries(red, green, blue, white_point);
+        for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+            profile = profile.with_tag(signature, TagData::ParametricCurve(ParametricCurve::ExponentGamma { g: gamma }));
+        }
+        Self(profile)
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new_bound(py, &self.0.to_buffer()?))
+    }
+
+    fn write(&self, path: &str) -> PyResult<()> {
+        Ok(self.0.to_file(path)?)
+    }
+
+    /// Profile as a dictionary, as written by `cmx dump`, with the given number of samples added to
+    /// parametric curves.
+    #[pyo3(signature = (curve_samples = 0))]
+    fn to_dict(&self, py: Python<'_>, curve_samples: usize) -> PyResult<PyObject> {
+        let json = self.0.to_json(DumpOptions { curve_samples, ..Default::default() }).map_err(value_error)?;
+        Ok(py.import_bound("json")?.call_method1("loads", (json.to_string(),))?.unbind())
+    }
+
+    /// Sets the description, with the tag type of the profile version.
+    fn set_description(&mut self, description: &str) {
+        self.0 = std::mem::take(&mut self.0).with_description(description);
+    }
+
+    /// Signatures of the tags in the profile, as four character codes.
+    fn tags(&self) -> Vec<String> {
+        self.0.iter_tags().map(|(s, _)| four_cc(s.to_u32())).collect()
+    }
+
+    #[getter]
+    fn version(&self) -> (u8, u8, u8) {
+        let [major, minor, bugfix] = self.0.version;
+        (major, minor, bugfix)
+    }
+
+    #[getter]
+    fn profile_class(&self) -> String {
+        format!("{:?}", self.0.class)
+    }
+
+    #[getter]
+    fn colorspace(&self) -> Option<String> {
+        self.0.colorspace.as_ref().map(|c| format!("{:?}", c.signature()))
+    }
+
+    #[getter]
+    fn pcs(&self) -> Option<String> {
+        self.0.pcs.as_ref().map(|c| format!("{:?}", c.signature()))
+    }
+
+    #[getter]
+    fn rendering_intent(&self) -> String {
+        self.0.rendering_intent.to_string()
+    }
+
+    #[getter]
+    fn description(&self) -> Option<String> {
+        self.0.summary().description
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<cmx.Profile {:?} {}>", self.0.class, self.description().unwrap_or_default())
+    }
+}
+
+#[pymodule]
+fn cmx(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyProfile>()
+}
+
+#[test]
+fn test_python() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let srgb = PyProfile::standard("srgb").unwrap();
+        let bytes = srgb.to_bytes(py).unwrap();
+        let profile = PyProfile::from_bytes(bytes.as_bytes()).unwrap();
+        assert_eq!(profile.description().as_deref(), Some("sRGB"));
+        assert_eq!(profile.colorspace().as_deref(), Some("RGB"));
+        let dict = profile.to_dict(py, 0).unwrap();
+        assert_eq!(dict.bind(py).get_item("class").unwrap().extract::<String>().unwrap(), "Display");
+
+        let custom = PyProfile::rgb("Custom", [[0.68, 0.32], [0.265, 0.69], [0.15, 0.06]], [0.3127, 0.329], 2.2);
+        assert_eq!(custom.tags().len(), 9);
+        assert!(PyProfile::standard("unknown").is_err());
+
+        // a version 2 profile gets a textDescriptionType description
+        let mut v2 = PyProfile::standard("srgb").unwrap();
+        v2.0.version = [2, 4, 0];
+        v2.set_description("Version 2");
+        assert!(matches!(v2.0.tag_data(&TagSignature::ProfileDescriptionTag), Some(TagData::TextDescription(_))));
+        assert_eq!(v2.description().as_deref(), Some("Version 2"));
+    });
+}
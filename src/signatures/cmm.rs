@@ -1,5 +1,5 @@
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub enum  CmmSignature {
     Adobe            ,  /* 'ADBE' */
     Agfa             ,  /* 'ACMS' */
@@ -67,7 +67,42 @@ impl CmmSignature {
             0x7669766F => Some(Self::Vivo),
             0x57544720 => Some(Self::WareToGo),
             0x7a633030 => Some(Self::Zoran),
-            n @ _  => Some(Self::Unknown(std::str::from_utf8(&n.to_be_bytes()).unwrap().to_string())),
+            n => Some(Self::Unknown(String::from_utf8_lossy(&n.to_be_bytes()).into_owned())),
         }
     }
-}
\ No newline at end of file
+
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            Self::Adobe => 0x41444245,
+            Self::Agfa => 0x41434D53,
+            Self::Apple => 0x6170706C,
+            Self::ColorGear => 0x43434D53,
+            Self::ColorGearLite => 0x5543434D,
+            Self::ColorGearC => 0x55434D53,
+            Self::EFI => 0x45464920,
+            Self::ExactScan => 0x45584143,
+            Self::FujiFilm => 0x46462020,
+            Self::HarlequinRIP => 0x48434d4d,
+            Self::ArgyllCMS => 0x6172676C,
+            Self::Lino => 0x4c696e6f,
+            Self::LogoSync => 0x44676f53,
+            Self::Heidelberg => 0x48444d20,
+            Self::LittleCMS => 0x6C636D73,
+            Self::Kodak => 0x4b434d53,
+            Self::KonicaMinolta => 0x4d434d44,
+            Self::WindowsCMS => 0x57435320,
+            Self::Mutoh => 0x5349474E,
+            Self::OnyxGraphics => 0x4f4e5958,
+            Self::RefIccMAX => 0x52494343,
+            Self::DemoIccMAX => 0x44494d58,
+            Self::RolfGierling => 0x52474d53,
+            Self::SampleICC => 0x53494343,
+            Self::Toshiba => 0x54434D4D,
+            Self::TheImagingFactory => 0x33324254,
+            Self::Vivo => 0x7669766F,
+            Self::WareToGo => 0x57544720,
+            Self::Zoran => 0x7a633030,
+            Self::Unknown(s) => u32::from_be_bytes(crate::common::signature_to_be_bytes(&Some(s.clone()))),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+
+use serde::Serialize;
+
+/// Colorimetric intent image states, of the 'ciis' tag, for the colorimetric intent transforms of
+/// input profiles.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize)]
+pub enum ColorimetricIntentImageState {
+    SceneColorimetryEstimates              = 0x73636F65,  /* 'scoe' */
+    SceneAppearanceEstimates               = 0x73617065,  /* 'sape' */
+    FocalPlaneColorimetryEstimates         = 0x66706365,  /* 'fpce' */
+    ReflectionHardcopyOriginalColorimetry  = 0x72686F63,  /* 'rhoc' */
+    ReflectionPrintOutputColorimetry       = 0x72706F63,  /* 'rpoc' */
+}
+
+impl ColorimetricIntentImageState {
+    pub fn from_u32(v: u32) -> Option<Self> {
+        [
+            Self::SceneColorimetryEstimates,
+            Self::SceneAppearanceEstimates,
+            Self::FocalPlaneColorimetryEstimates,
+            Self::ReflectionHardcopyOriginalColorimetry,
+            Self::ReflectionPrintOutputColorimetry,
+        ]
+        .into_iter()
+        .find(|&s| s as u32 == v)
+    }
+}
@@ -1,5 +1,6 @@
 pub mod cmm;
 pub mod colorspace;
+pub mod image_state;
 pub mod tag;
 pub mod tagtype;
-pub mod technology;
\ No newline at end of file
+pub mod technology;
@@ -1,7 +1,7 @@
 //use num_derive::FromPrimitive;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Clone, Debug, Serialize)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum TagSignature {
     VendorTag(String),
     AToB0Tag                          ,  /* 'A2B0' */ 
@@ -44,6 +44,7 @@ pub enum TagSignature {
     CharTargetTag                     ,  /* 'targ' */ 
     ChromaticAdaptationTag            ,  /* 'chad' */
     ChromaticityTag                   ,  /* 'chrm' */
+    CicpTag                           ,  /* 'cicp' */
     ColorEncodingParamsTag            ,  /* 'cept' */
     ColorSpaceNameTag                 ,  /* 'csnm' */
     ColorantInfoTag                   ,  /* 'clin' */
@@ -188,6 +189,7 @@ impl TagSignature {
             0x74617267 => Self::CharTargetTag,
             0x63686164 => Self::ChromaticAdaptationTag,
             0x6368726D => Self::ChromaticityTag,
+            0x63696370 => Self::CicpTag,
             0x63657074 => Self::ColorEncodingParamsTag,
             0x63736e6d => Self::ColorSpaceNameTag,
             0x636c696e => Self::ColorantInfoTag,
@@ -223,7 +225,7 @@ impl TagSignature {
             0x6b545243 => Self::GrayTRCTag,
             0x6758595A => Self::GreenMatrixColumnTag,
             0x67545243 => Self::GreenTRCTag,
-            0x6C756d69 => Self::LuminanceTag,
+            0x6C756D69 => Self::LuminanceTag,
             0x6D647620 => Self::MaterialDefaultValuesTag,
             0x6d637461 => Self::MaterialTypeArrayTag,
             0x4d324130 => Self::MToA0Tag,
@@ -239,8 +241,8 @@ impl TagSignature {
             0x626B7074 => Self::MediaBlackPointTag,
             0x77747074 => Self::MediaWhitePointTag,
             0x6D657461 => Self::MetaDataTag,
-            0x6E636f6C => Self::NamedColorTag,
-            0x6e6d636C => Self::NamedColorV5Tag,
+            0x6E636F6C => Self::NamedColorTag,
+            0x6E6D636C => Self::NamedColorV5Tag,
             0x6E636C32 => Self::NamedColor2Tag,
             0x72657370 => Self::OutputResponseTag,
             0x72696730 => Self::PerceptualRenderingIntentGamutTag,
@@ -287,10 +289,153 @@ impl TagSignature {
             // ArgyllCMS
             0x61727473 => Self::AbsToRelTransSpaceTag, // https://www.argyllcms.com/doc/ArgyllCMS_arts_tag.html
 
-            _ => Self::VendorTag(std::str::from_utf8(&sig.to_be_bytes()).unwrap().to_owned())
+            _ => Self::VendorTag(String::from_utf8_lossy(&sig.to_be_bytes()).into_owned())
 
         }
     }
+
+    pub fn to_u32(&self) -> u32 {
+        match self {
+            Self::AToB0Tag => 0x41324230,
+            Self::AToB1Tag => 0x41324231,
+            Self::AToB2Tag => 0x41324232,
+            Self::AToB3Tag => 0x41324233,
+            Self::AToM0Tag => 0x41324D30,
+            Self::BlueMatrixColumnTag => 0x6258595A,
+            Self::BlueTRCTag => 0x62545243,
+            Self::BrdfColorimetricParameter0Tag => 0x62637030,
+            Self::BrdfColorimetricParameter1Tag => 0x62637031,
+            Self::BrdfColorimetricParameter2Tag => 0x62637032,
+            Self::BrdfColorimetricParameter3Tag => 0x62637033,
+            Self::BrdfSpectralParameter0Tag => 0x62737030,
+            Self::BrdfSpectralParameter1Tag => 0x62737031,
+            Self::BrdfSpectralParameter2Tag => 0x62737032,
+            Self::BrdfSpectralParameter3Tag => 0x62737033,
+            Self::BRDFAToB0Tag => 0x62414230,
+            Self::BRDFAToB1Tag => 0x62414231,
+            Self::BRDFAToB2Tag => 0x62414232,
+            Self::BRDFAToB3Tag => 0x62414233,
+            Self::BRDFDToB0Tag => 0x62444230,
+            Self::BRDFDToB1Tag => 0x62444231,
+            Self::BRDFDToB2Tag => 0x62444232,
+            Self::BRDFDToB3Tag => 0x62444233,
+            Self::BRDFMToB0Tag => 0x624D4230,
+            Self::BRDFMToB1Tag => 0x624D4231,
+            Self::BRDFMToB2Tag => 0x624D4232,
+            Self::BRDFMToB3Tag => 0x624D4233,
+            Self::BRDFMToS0Tag => 0x624D5330,
+            Self::BRDFMToS1Tag => 0x624D5331,
+            Self::BRDFMToS2Tag => 0x624D5332,
+            Self::BRDFMToS3Tag => 0x624D5333,
+            Self::BToA0Tag => 0x42324130,
+            Self::BToA1Tag => 0x42324131,
+            Self::BToA2Tag => 0x42324132,
+            Self::BToA3Tag => 0x42324133,
+            Self::CalibrationDateTimeTag => 0x63616C74,
+            Self::CharTargetTag => 0x74617267,
+            Self::ChromaticAdaptationTag => 0x63686164,
+            Self::ChromaticityTag => 0x6368726D,
+            Self::CicpTag => 0x63696370,
+            Self::ColorEncodingParamsTag => 0x63657074,
+            Self::ColorSpaceNameTag => 0x63736E6D,
+            Self::ColorantInfoTag => 0x636C696E,
+            Self::ColorantInfoOutTag => 0x636C696F,
+            Self::ColorantOrderTag => 0x636C726F,
+            Self::ColorantOrderOutTag => 0x636C6F6F,
+            Self::ColorantTableTag => 0x636C7274,
+            Self::ColorantTableOutTag => 0x636C6F74,
+            Self::ColorimetricIntentImageStateTag => 0x63696973,
+            Self::CopyrightTag => 0x63707274,
+            Self::CrdInfoTag => 0x63726469,
+            Self::CustomToStandardPccTag => 0x63327370,
+            Self::CxFTag => 0x43784620,
+            Self::DataTag => 0x64617461,
+            Self::DateTimeTag => 0x6474696D,
+            Self::DeviceMediaWhitePointTag => 0x646D7770,
+            Self::DeviceMfgDescTag => 0x646D6E64,
+            Self::DeviceModelDescTag => 0x646D6464,
+            Self::DeviceSettingsTag => 0x64657673,
+            Self::DToB0Tag => 0x44324230,
+            Self::DToB1Tag => 0x44324231,
+            Self::DToB2Tag => 0x44324232,
+            Self::DToB3Tag => 0x44324233,
+            Self::BToD0Tag => 0x42324430,
+            Self::BToD1Tag => 0x42324431,
+            Self::BToD2Tag => 0x42324432,
+            Self::BToD3Tag => 0x42324433,
+            Self::GamutTag => 0x67616D74,
+            Self::GamutBoundaryDescription0Tag => 0x67626430,
+            Self::GamutBoundaryDescription1Tag => 0x67626431,
+            Self::GamutBoundaryDescription2Tag => 0x67626432,
+            Self::GamutBoundaryDescription3Tag => 0x67626433,
+            Self::GrayTRCTag => 0x6B545243,
+            Self::GreenMatrixColumnTag => 0x6758595A,
+            Self::GreenTRCTag => 0x67545243,
+            Self::LuminanceTag => 0x6C756D69,
+            Self::MaterialDefaultValuesTag => 0x6D647620,
+            Self::MaterialTypeArrayTag => 0x6D637461,
+            Self::MToA0Tag => 0x4D324130,
+            Self::MToB0Tag => 0x4D324230,
+            Self::MToB1Tag => 0x4D324231,
+            Self::MToB2Tag => 0x4D324232,
+            Self::MToB3Tag => 0x4D324233,
+            Self::MToS0Tag => 0x4D325330,
+            Self::MToS1Tag => 0x4D325331,
+            Self::MToS2Tag => 0x4D325332,
+            Self::MToS3Tag => 0x4D325333,
+            Self::MeasurementTag => 0x6D656173,
+            Self::MediaBlackPointTag => 0x626B7074,
+            Self::MediaWhitePointTag => 0x77747074,
+            Self::MetaDataTag => 0x6D657461,
+            Self::NamedColorTag => 0x6E636F6C,
+            Self::NamedColorV5Tag => 0x6E6D636C,
+            Self::NamedColor2Tag => 0x6E636C32,
+            Self::OutputResponseTag => 0x72657370,
+            Self::PerceptualRenderingIntentGamutTag => 0x72696730,
+            Self::Preview0Tag => 0x70726530,
+            Self::Preview1Tag => 0x70726531,
+            Self::Preview2Tag => 0x70726532,
+            Self::PrintConditionTag => 0x7074636E,
+            Self::ProfileDescriptionTag => 0x64657363,
+            Self::ProfileSequenceDescTag => 0x70736571,
+            Self::ProfileSequceIdTag => 0x70736964,
+            Self::Ps2CRD0Tag => 0x70736430,
+            Self::Ps2CRD1Tag => 0x70736431,
+            Self::Ps2CRD2Tag => 0x70736432,
+            Self::Ps2CRD3Tag => 0x70736433,
+            Self::Ps2CSATag => 0x70733273,
+            Self::Ps2RenderingIntentTag => 0x70733269,
+            Self::RedMatrixColumnTag => 0x7258595A,
+            Self::RedTRCTag => 0x72545243,
+            Self::ReferenceNameTag => 0x72666E6D,
+            Self::SaturationRenderingIntentGamutTag => 0x72696732,
+            Self::ScreeningDescTag => 0x73637264,
+            Self::ScreeningTag => 0x7363726E,
+            Self::SpectralDataInfoTag => 0x7364696E,
+            Self::SpectralWhitePointTag => 0x73777074,
+            Self::SpectralViewingConditionsTag => 0x7376636E,
+            Self::StandardToCustomPccTag => 0x73326370,
+            Self::SurfaceMapTag => 0x736D6170,
+            Self::TechnologyTag => 0x74656368,
+            Self::UcrBgTag => 0x62666420,
+            Self::ViewingCondDescTag => 0x76756564,
+            Self::ViewingConditionsTag => 0x76696577,
+            Self::EmbeddedV5ProfileTag => 0x49434335,
+            Self::MakeAndModelTag => 0x6D6D6F64,
+            Self::MultilocalizedDescriptionStringTag => 0x6473636D,
+            Self::NativeDisplayInfoTag => 0x6E64696E,
+            Self::VcgpTag => 0x76636770,
+            Self::VcgtTag => 0x76636774,
+            Self::AbsToRelTransSpaceTag => 0x61727473,
+            Self::VendorTag(s) => {
+                let mut sig = [b' '; 4];
+                for (c, b) in sig.iter_mut().zip(s.bytes()) {
+                    *c = b;
+                }
+                u32::from_be_bytes(sig)
+            }
+        }
+    }
 }
 
 #[test]
@@ -5,6 +5,7 @@ use serde::Serialize;
 pub enum TagTypeSignature {
     UndefinedType                  = 0x00000000,
     ChromaticityType               = 0x6368726D,  /* 'chrm' */
+    CicpType                       = 0x63696370,  /* 'cicp' */
     ColorantOrderType              = 0x636C726F,  /* 'clro' */
     ColorantTableType              = 0x636C7274,  /* 'clrt' */
     CrdInfoType                    = 0x63726469,  /* 'crdi' Removed in V4 */
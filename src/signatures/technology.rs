@@ -2,8 +2,9 @@
 use num_derive::FromPrimitive;
 use serde::Serialize;
 
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Default)]
 pub enum TechnologySignature {
+    #[default]
     Unknown                        = 0x00000000, 
     DigitalCamera                  = 0x6463616D,  /* 'dcam' */
     FilmScanner                    = 0x6673636E,  /* 'fscn' */
@@ -32,9 +33,3 @@ pub enum TechnologySignature {
     DigitalMotionPictureCamera     = 0x646D7063,  /* 'dmpc' */
     DigitalCinemaProjector         = 0x64636A70,  /* 'dcpj' */
 }
-
-impl Default for TechnologySignature {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
\ No newline at end of file
@@ -0,0 +1,277 @@
+/*!
+  Profiles of the displays of the operating system, as set by the user in the display settings.
+
+  Available with the `system` feature, on macOS, from ColorSync, through the color space of the
+  display in Core Graphics, on Windows, from the profile file associated with the display device by
+  the Windows color system, and on Linux, and the BSDs, only from the `_ICC_PROFILE` atom of the
+  root window of the X11 display, as set by colord, and other color management daemons, following the
+  ICC Profiles in X Specification. Display `0` is the main display; on X11, the other displays use
+  the `_ICC_PROFILE_1`, `_ICC_PROFILE_2`, ..., atoms.
+
+  On Linux, colord is not queried over D-Bus, and Wayland compositors are not supported; in a Wayland
+  session, the profile is only found if the compositor, or the color management daemon, sets the atom
+  on the Xwayland root window, and otherwise an error is returned.
+*/
+
+use crate::common::Result;
+use crate::profile::Profile;
+
+/// Profile of a display, with the main display at index `0`; returns an error if there is no such
+/// display, or if no profile is set for it.
+pub fn display_profile(display_index: usize) -> Result<Profile> {
+    let data = platform::display_profile_data(display_index)?;
+    Ok(Profile::from_buffer(&data)?)
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    use crate::common::Result;
+
+    // maximum number of displays queried
+    const MAX_DISPLAYS: usize = 32;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGGetActiveDisplayList(max_displays: u32, active_displays: *mut u32, display_count: *mut u32) -> i32;
+        fn CGDisplayCopyColorSpace(display: u32) -> *const c_void;
+        fn CGColorSpaceCopyICCData(space: *const c_void) -> *const c_void;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFDataGetLength(data: *const c_void) -> isize;
+        fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    pub(super) fn display_profile_data(display_index: usize) -> Result<Vec<u8>> {
+        let mut displays = [0u32; MAX_DISPLAYS];
+        let mut count = 0u32;
+        // SAFETY: the display list has room for MAX_DISPLAYS displays, and the color space, and
+        // its ICC data, are released after copying the data
+        unsafe {
+            if CGGetActiveDisplayList(MAX_DISPLAYS as u32, displays.as_mut_ptr(), &mut count) != 0 {
+                return Err("the active displays could not be listed".into());
+            }
+            if display_index >= count as usize {
+                return Err(format!("display {display_index} not found, of {count} active displays").into());
+            }
+            let space = CGDisplayCopyColorSpace(displays[display_index]);
+            if space.is_null() {
+                return Err(format!("display {display_index} has no color space").into());
+            }
+            let icc = CGColorSpaceCopyICCData(space);
+            CFRelease(space);
+            if icc.is_null() {
+                return Err(format!("the color space of display {display_index} has no ICC profile").into());
+            }
+            let data = std::slice::from_raw_parts(CFDataGetBytePtr(icc), CFDataGetLength(icc) as usize).to_vec();
+            CFRelease(icc);
+            Ok(data)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::{c_void, OsString};
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::ptr;
+
+    use crate::common::Result;
+
+    const DISPLAY_DEVICE_ATTACHED_TO_DESKTOP: u32 = 0x1;
+    const MAX_PATH: usize = 260;
+
+    #[repr(C)]
+    struct DisplayDevice {
+        cb: u32,
+        device_name: [u16; 32],
+        device_string: [u16; 128],
+        state_flags: u32,
+        device_id: [u16; 128],
+        device_key: [u16; 128],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn EnumDisplayDevicesW(device: *const u16, dev_num: u32, display_device: *mut DisplayDevice, flags: u32) -> i32;
+    }
+
+    #[link(name = "gdi32")]
+    extern "system" {
+        fn CreateDCW(driver: *const u16, device: *const u16, port: *const u16, init_data: *const c_void) -> *mut c_void;
+        fn DeleteDC(hdc: *mut c_void) -> i32;
+        fn GetICMProfileW(hdc: *mut c_void, buf_size: *mut u32, filename: *mut u16) -> i32;
+    }
+
+    pub(super) fn display_profile_data(display_index: usize) -> Result<Vec<u8>> {
+        let device_name = attached_display(display_index)?;
+        let driver: Vec<u16> = std::ffi::OsStr::new("DISPLAY").encode_wide().chain([0]).collect();
+        let mut filename = [0u16; MAX_PATH];
+        let mut size = MAX_PATH as u32;
+        // SAFETY: the driver, and device, names are null terminated, the filename buffer has room for
+        // `size` characters, and the device context is deleted after use
+        let found = unsafe {
+            let hdc = CreateDCW(driver.as_ptr(), device_name.as_ptr(), ptr::null(), ptr::null());
+            if hdc.is_null() {
+                return Err(format!("no device context for display {display_index}").into());
+            }
+            let found = GetICMProfileW(hdc, &mut size, filename.as_mut_ptr());
+            DeleteDC(hdc);
+            found
+        };
+        if found == 0 {
+            return Err(format!("no profile is associated with display {display_index}").into());
+        }
+        let len = filename.iter().position(|&c| c == 0).unwrap_or(MAX_PATH);
+        Ok(std::fs::read(OsString::from_wide(&filename[..len]))?)
+    }
+
+    // Null terminated device name of the display at an index, counting only the display devices
+    // attached to the desktop.
+    fn attached_display(display_index: usize) -> Result<[u16; 32]> {
+        let mut attached = 0;
+        for dev_num in 0.. {
+            let mut device = DisplayDevice { cb: std::mem::size_of::<DisplayDevice>() as u32, device_name: [0; 32], device_string: [0; 128], state_flags: 0, device_id: [0; 128], device_key: [0; 128] };
+            // SAFETY: the size of the display device structure is set in its `cb` field
+            if unsafe { EnumDisplayDevicesW(ptr::null(), dev_num, &mut device, 0) } == 0 {
+                break;
+            }
+            if device.state_flags & DISPLAY_DEVICE_ATTACHED_TO_DESKTOP != 0 {
+                if attached == display_index {
+                    return Ok(device.device_name);
+                }
+                attached += 1;
+            }
+        }
+        Err(format!("display {display_index} not found, of {attached} attached displays").into())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+mod platform {
+    use std::ffi::{c_char, c_int, c_long, c_uchar, c_ulong, c_void, CString};
+    use std::ptr;
+
+    use crate::common::Result;
+
+    // largest profile read, in 32 bit units, as used by X11 for property lengths
+    const MAX_LENGTH: c_long = 0x0fff_ffff;
+    const SUCCESS: c_int = 0;
+    const ANY_PROPERTY_TYPE: c_ulong = 0;
+
+    #[link(name = "X11")]
+    extern "C" {
+        fn XOpenDisplay(name: *const c_char) -> *mut c_void;
+        fn XCloseDisplay(display: *mut c_void) -> c_int;
+        fn XDefaultRootWindow(display: *mut c_void) -> c_ulong;
+        fn XInternAtom(display: *mut c_void, name: *const c_char, only_if_exists: c_int) -> c_ulong;
+        #[allow(clippy::too_many_arguments)]
+        fn XGetWindowProperty(
+            display: *mut c_void,
+            window: c_ulong,
+            property: c_ulong,
+            long_offset: c_long,
+            long_length: c_long,
+            delete: c_int,
+            req_type: c_ulong,
+            actual_type: *mut c_ulong,
+            actual_format: *mut c_int,
+            n_items: *mut c_ulong,
+            bytes_after: *mut c_ulong,
+            prop: *mut *mut c_uchar,
+        ) -> c_int;
+        fn XFree(data: *mut c_void) -> c_int;
+    }
+
+    pub(super) fn display_profile_data(display_index: usize) -> Result<Vec<u8>> {
+        let name = CString::new(super::atom_name(display_index))?;
+        // SAFETY: the display connection is closed, and the property data freed, after use, and the
+        // property data has `n_items` bytes for a format of 8 bits
+        unsafe {
+            let display = XOpenDisplay(ptr::null());
+            if display.is_null() {
+                return Err("no X11 display".into());
+            }
+            let data = property(display, &name);
+            XCloseDisplay(display);
+            data.ok_or_else(|| format!("no profile is set for display {display_index}").into())
+        }
+    }
+
+    unsafe fn property(display: *mut c_void, name: &CString) -> Option<Vec<u8>> {
+        let atom = XInternAtom(display, name.as_ptr(), 1);
+        if atom == 0 {
+            return None;
+        }
+        let (mut actual_type, mut actual_format, mut n_items, mut bytes_after) = (0, 0, 0, 0);
+        let mut prop: *mut c_uchar = ptr::null_mut();
+        let status = XGetWindowProperty(
+            display,
+            XDefaultRootWindow(display),
+            atom,
+            0,
+            MAX_LENGTH,
+            0,
+            ANY_PROPERTY_TYPE,
+            &mut actual_type,
+            &mut actual_format,
+            &mut n_items,
+            &mut bytes_after,
+            &mut prop,
+        );
+        if status != SUCCESS || prop.is_null() {
+            return None;
+        }
+        let data = super::atom_data(actual_format, bytes_after, std::slice::from_raw_parts(prop, n_items as usize));
+        XFree(prop.cast());
+        data
+    }
+}
+
+/// Name of the X11 atom with the profile of a display, following the ICC Profiles in X Specification.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+fn atom_name(display_index: usize) -> String {
+    match display_index {
+        0 => "_ICC_PROFILE".to_string(),
+        i => format!("_ICC_PROFILE_{i}"),
+    }
+}
+
+/// Profile data of an `_ICC_PROFILE` atom, with the items of the property as read from X11; the profile
+/// is stored as 8 bit items, and is not used if it is empty, or if it was only partly read.
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+fn atom_data(format: std::ffi::c_int, bytes_after: std::ffi::c_ulong, items: &[u8]) -> Option<Vec<u8>> {
+    (format == 8 && bytes_after == 0 && !items.is_empty()).then(|| items.to_vec())
+}
+
+#[cfg(not(any(target_os = "macos", windows, target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd")))]
+mod platform {
+    use crate::common::Result;
+
+    pub(super) fn display_profile_data(_display_index: usize) -> Result<Vec<u8>> {
+        Err("display profiles are not supported on this operating system".into())
+    }
+}
+
+#[test]
+fn test_display_profile() {
+    // no display has this index, also without a display server
+    assert!(display_profile(1000).is_err());
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "dragonfly", target_os = "netbsd", target_os = "openbsd"))]
+#[test]
+fn test_atom_data() {
+    assert_eq!(atom_name(0), "_ICC_PROFILE");
+    assert_eq!(atom_name(2), "_ICC_PROFILE_2");
+    let profile = crate::profile::standards::srgb().to_buffer().unwrap();
+    let data = atom_data(8, 0, &profile).unwrap();
+    assert!(Profile::from_buffer(&data).is_ok());
+    assert!(atom_data(32, 0, &profile).is_none());
+    assert!(atom_data(8, 4, &profile).is_none());
+    assert!(atom_data(8, 0, &[]).is_none());
+}
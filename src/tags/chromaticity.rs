@@ -3,9 +3,48 @@ use serde::Serialize;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
 
-#[derive(Debug, Serialize)]
+/// Phosphor or colorant type, and the CIE xy chromaticity coordinates of each channel, of the 'chrm'
+/// tag.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Chromaticity((Primaries, Vec<[f32;2]>));
 impl Chromaticity {
+    pub fn new(primaries: Primaries, chromaticities: Vec<[f32;2]>) -> Self {
+        Self((primaries, chromaticities))
+    }
+
+    /// Chromaticity of a standard phosphor or colorant type, with its red, green, and blue
+    /// chromaticity coordinates.
+    pub fn from_primaries(primaries: Primaries) -> Self {
+        Self((primaries, primaries.chromaticities().map(Vec::from).unwrap_or_default()))
+    }
+
+    pub fn primaries(&self) -> Primaries {
+        self.0.0
+    }
+
+    /// Chromaticity coordinates, one for each channel.
+    pub fn chromaticities(&self) -> &[[f32;2]] {
+        &self.0.1
+    }
+
+    pub fn channels(&self) -> usize {
+        self.0.1.len()
+    }
+
+    /// Sets the phosphor or colorant type, and, for a standard type, the chromaticity coordinates
+    /// of its channels.
+    pub fn set_primaries(&mut self, primaries: Primaries) {
+        self.0.0 = primaries;
+        if let Some(chromaticities) = primaries.chromaticities() {
+            self.0.1 = chromaticities.to_vec();
+        }
+    }
+
+    /// Sets the chromaticity coordinates of the channels, for a colorant type with absolute values.
+    pub fn set_chromaticities(&mut self, chromaticities: Vec<[f32;2]>) {
+        self.0 = (Primaries::Absolute, chromaticities);
+    }
+
     pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
         let channels = read_be_u16(buf)? as usize;
         let primaries = FromPrimitive::from_u16(read_be_u16(buf)?).unwrap_or_default();
@@ -17,19 +56,50 @@ impl Chromaticity {
         }
         Ok(Chromaticity((primaries, chromaticities)))
     }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let (primaries, chromaticities) = &self.0;
+        let mut v = Vec::with_capacity(4 + 8 * chromaticities.len());
+        v.extend((chromaticities.len() as u16).to_be_bytes());
+        v.extend((*primaries as u16).to_be_bytes());
+        for [x, y] in chromaticities {
+            v.extend(u16fixed16_to_be_bytes(*x as f64));
+            v.extend(u16fixed16_to_be_bytes(*y as f64));
+        }
+        v
+    }
 }
 
-#[derive(Debug, Serialize, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, FromPrimitive, Default)]
 pub enum Primaries {
-    Absolute = 0x0000,
-    ITU      = 0x0001,
-    SMPTE    = 0x0002,
-    EBU      = 0x0003,
+    #[default]
+    Absolute = 0x0000, // values given by the chromaticity coordinates
+    ITU      = 0x0001, // ITU-R BT.709-2
+    SMPTE    = 0x0002, // SMPTE RP145
+    EBU      = 0x0003, // EBU Tech. 3213-E
     P22      = 0x0004,
 }
 
-impl Default for Primaries {
-    fn default() -> Self {
-        Self::Absolute
+impl Primaries {
+    /// Red, green, and blue chromaticity coordinates of the standard phosphor and colorant types.
+    pub fn chromaticities(&self) -> Option<[[f32;2];3]> {
+        match self {
+            Self::Absolute => None,
+            Self::ITU => Some([[0.640, 0.330], [0.300, 0.600], [0.150, 0.060]]),
+            Self::SMPTE => Some([[0.630, 0.340], [0.310, 0.595], [0.155, 0.070]]),
+            Self::EBU => Some([[0.640, 0.330], [0.290, 0.600], [0.150, 0.060]]),
+            Self::P22 => Some([[0.625, 0.340], [0.280, 0.605], [0.155, 0.070]]),
+        }
     }
 }
+
+#[test]
+fn test_chromaticity() {
+    let mut c = Chromaticity::from_primaries(Primaries::EBU);
+    assert_eq!((c.channels(), c.chromaticities()[1]), (3, [0.29, 0.60]));
+    c.set_chromaticities(vec![[0.7, 0.3], [0.2, 0.7]]);
+    let c = Chromaticity::try_new(&mut c.to_be_bytes().as_slice()).unwrap();
+    assert_eq!(c.primaries(), Primaries::Absolute);
+    assert_eq!(c.channels(), 2);
+    assert!((c.chromaticities()[1][1] - 0.7).abs() < 1E-4);
+}
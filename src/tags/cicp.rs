@@ -0,0 +1,111 @@
+use crate::common::*;
+use serde::Serialize;
+
+/// Coding-independent code points, as defined in ITU-T H.273, identifying the color primaries,
+/// transfer characteristics, and matrix coefficients of video, and HDR, color encodings.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct Cicp {
+    pub color_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coefficients: u8,
+    pub video_full_range: bool,
+}
+
+/// Color primaries code points.
+pub mod primaries {
+    pub const BT709: u8 = 1;
+    pub const UNSPECIFIED: u8 = 2;
+    pub const BT470BG: u8 = 5;
+    pub const BT601: u8 = 6;
+    pub const BT2020: u8 = 9;
+    pub const XYZ: u8 = 10;
+    pub const DCI_P3: u8 = 11;
+    pub const DISPLAY_P3: u8 = 12;
+}
+
+/// Transfer characteristics code points.
+pub mod transfer {
+    pub const BT709: u8 = 1;
+    pub const UNSPECIFIED: u8 = 2;
+    pub const BT601: u8 = 6;
+    pub const LINEAR: u8 = 8;
+    pub const SRGB: u8 = 13;
+    pub const BT2020_10BIT: u8 = 14;
+    pub const BT2020_12BIT: u8 = 15;
+    pub const PQ: u8 = 16;
+    pub const SMPTE428: u8 = 17;
+    pub const HLG: u8 = 18;
+}
+
+/// Matrix coefficients code points; RGB encodings, as used for ICC profiles, use `IDENTITY`.
+pub mod matrix {
+    pub const IDENTITY: u8 = 0;
+    pub const BT709: u8 = 1;
+    pub const UNSPECIFIED: u8 = 2;
+    pub const BT601: u8 = 6;
+    pub const BT2020_NCL: u8 = 9;
+    pub const BT2020_CL: u8 = 10;
+    pub const ICTCP: u8 = 14;
+}
+
+impl Default for Cicp {
+    fn default() -> Self {
+        Self::new(primaries::UNSPECIFIED, transfer::UNSPECIFIED, matrix::UNSPECIFIED, true)
+    }
+}
+
+impl Cicp {
+    pub fn new(color_primaries: u8, transfer_characteristics: u8, matrix_coefficients: u8, video_full_range: bool) -> Self {
+        Self { color_primaries, transfer_characteristics, matrix_coefficients, video_full_range }
+    }
+
+    pub fn srgb() -> Self {
+        Self::new(primaries::BT709, transfer::SRGB, matrix::IDENTITY, true)
+    }
+
+    pub fn display_p3() -> Self {
+        Self::new(primaries::DISPLAY_P3, transfer::SRGB, matrix::IDENTITY, true)
+    }
+
+    /// BT.2100 RGB with the perceptual quantizer, as used for HDR10.
+    pub fn hdr10() -> Self {
+        Self::new(primaries::BT2020, transfer::PQ, matrix::IDENTITY, true)
+    }
+
+    /// BT.2100 RGB with the hybrid log-gamma transfer function.
+    pub fn hlg() -> Self {
+        Self::new(primaries::BT2020, transfer::HLG, matrix::IDENTITY, true)
+    }
+
+    /// High dynamic range encodings, with a PQ or HLG transfer function.
+    pub fn is_hdr(&self) -> bool {
+        matches!(self.transfer_characteristics, transfer::PQ | transfer::HLG)
+    }
+
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        if buf.len() < 4 {
+            return Err("cicp tag data too short".into());
+        }
+        Ok(Self {
+            color_primaries: read_u8(buf)?,
+            transfer_characteristics: read_u8(buf)?,
+            matrix_coefficients: read_u8(buf)?,
+            video_full_range: read_u8(buf)? != 0,
+        })
+    }
+
+    pub fn to_be_bytes(self) -> [u8;4] {
+        [self.color_primaries, self.transfer_characteristics, self.matrix_coefficients, self.video_full_range as u8]
+    }
+}
+
+#[test]
+fn test_cicp() {
+    let profile = crate::profile::standards::rec2020().with_cicp(|c| *c = Cicp::hdr10());
+    let profile = crate::profile::Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    assert_eq!(profile.cicp(), Some(Cicp::hdr10()));
+    assert!(profile.cicp().unwrap().is_hdr());
+    let profile = profile.with_cicp(|c| c.transfer_characteristics = transfer::HLG);
+    assert_eq!(profile.cicp(), Some(Cicp::hlg()));
+    assert_eq!(crate::profile::standards::srgb().with_cicp(|_| ()).cicp(), Some(Cicp::default()));
+}
@@ -0,0 +1,69 @@
+use crate::common::*;
+use serde::Serialize;
+use super::named_color2::NamedColorPcs;
+
+/// A colorant, with decoded PCS values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colorant<'a> {
+    pub name: &'a str,
+    pub pcs: [f64; 3],
+}
+
+/// Names, and PCS values, of the colorants of a device, as used in the 'clrt' and 'clot' tags, with
+/// the PCS values encoded as for named colors.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ColorantTable {
+    pub colorants: Vec<(String, [u16; 3])>,
+}
+
+impl ColorantTable {
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let count = read_be_u32(buf)? as usize;
+        let mut colorants = Vec::with_capacity(count.min(buf.len() / 38));
+        for _ in 0..count {
+            let name = read_ascii_string(buf, 32)?;
+            let pcs = [read_be_u16(buf)?, read_be_u16(buf)?, read_be_u16(buf)?];
+            colorants.push((name, pcs));
+        }
+        Ok(Self { colorants })
+    }
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a colorant, with its PCS values in the given encoding.
+    pub fn with_colorant(mut self, name: &str, pcs: NamedColorPcs, values: [f64; 3]) -> Self {
+        self.colorants.push((name.to_owned(), pcs.encode(values)));
+        self
+    }
+
+    /// Iterates over the colorants, with their PCS values decoded using the given encoding.
+    pub fn colorants(&self, pcs: NamedColorPcs) -> impl Iterator<Item = Colorant<'_>> {
+        self.colorants.iter().map(move |(name, values)| Colorant { name, pcs: pcs.decode(values) })
+    }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(4 + 38 * self.colorants.len());
+        v.extend((self.colorants.len() as u32).to_be_bytes());
+        for (name, pcs) in &self.colorants {
+            v.extend(ascii_to_be_bytes(name, 32));
+            v.extend(pcs.iter().flat_map(|c| c.to_be_bytes()));
+        }
+        v
+    }
+}
+
+#[test]
+fn test_colorant_table() {
+    let clrt = ColorantTable::new()
+        .with_colorant("Cyan", NamedColorPcs::Lab, [55.0, -37.0, -50.0])
+        .with_colorant("Orange", NamedColorPcs::Lab, [65.0, 50.0, 70.0]);
+    let bytes = clrt.to_be_bytes();
+    assert_eq!(bytes.len(), 4 + 2 * 38);
+    let parsed = ColorantTable::try_new(&mut bytes.as_slice()).unwrap();
+    assert_eq!(parsed, clrt);
+    let colorants: Vec<Colorant> = parsed.colorants(NamedColorPcs::Lab).collect();
+    assert_eq!(colorants[1].name, "Orange");
+    assert!(colorants[1].pcs.iter().zip([65.0, 50.0, 70.0]).all(|(a, b)| (a - b).abs() < 0.01));
+}
@@ -0,0 +1,135 @@
+/*!
+  Least-squares fitting of parametric curves to sampled curves, such as measured tone response
+  curves, or the channels of a video card gamma table, for compact version 4 profiles.
+
+  The IEC 61966-2.1 (type 3) function, a power function with a linear segment near black, is fitted
+  first; the seven parameter (type 4) function, which adds offsets for both segments, is only used
+  if it reduces the RMS error by at least a 16-bit step.
+*/
+
+use crate::common::Result;
+use crate::tags::{parametric_curve::ParametricCurve, Curve};
+
+#[derive(Debug, Clone, Copy)]
+pub struct CurveFit {
+    pub curve: ParametricCurve,
+    /// Root mean square of the differences with the table entries, in the range from 0.0 to 1.0.
+    pub rms_error: f64,
+    /// Largest absolute difference with the table entries.
+    pub max_error: f64,
+}
+
+impl Curve {
+    /// Fits a type 3, or type 4, parametric curve to the entries of the curve; identity, and gamma,
+    /// curves are converted exactly.
+    pub fn fit_parametric(&self) -> Result<CurveFit> {
+        if let Some(curve) = ParametricCurve::from_curve(self) {
+            return Ok(CurveFit { curve, rms_error: 0.0, max_error: 0.0 });
+        }
+        let n = self.0.len();
+        let samples: Vec<(f64, f64)> = self.0.iter().enumerate().map(|(i, &v)| (i as f64 / (n - 1) as f64, v as f64 / 65535.0)).collect();
+        fit_samples(&samples)
+    }
+}
+
+/// Fits a type 3, or type 4, parametric curve to pairs of input and output values, sorted by input
+/// value, such as the values of a measured ramp.
+pub fn fit_samples(samples: &[(f64, f64)]) -> Result<CurveFit> {
+    let n = samples.len();
+    if n < 2 {
+        return Err("at least two samples are required".into());
+    }
+    // initial gamma, from a fit of the logarithms of the samples above the black region
+    let logs: Vec<(f64, f64)> = samples.iter().filter(|(x, y)| *x > 0.1 && *y > 0.0).map(|(x, y)| (x.ln(), y.ln())).collect();
+    if logs.is_empty() {
+        return Err("curve without increasing values, can not be fitted".into());
+    }
+    let g = logs.iter().map(|(lx, ly)| lx * ly).sum::<f64>() / logs.iter().map(|(lx, _)| lx * lx).sum::<f64>();
+    let slope = samples[1].1 / samples[1].0;
+    // the parameters are fitted to at most 256 of the samples, the errors are for all samples
+    let subset: Vec<(f64, f64)> = samples.iter().step_by(n.div_ceil(256)).copied().collect();
+
+    let type3 = |p: &[f64]| ParametricCurve::IEC61966_2_1 { g: p[0] as f32, a: p[1] as f32, b: p[2] as f32, c: p[3] as f32, d: p[4] as f32 };
+    let p3 = minimize(|p| sum_of_squares(&type3(p), &subset), vec![g, 1.0, 0.0, slope, 0.0]);
+    let fit3 = fit(type3(&p3), samples);
+
+    let type4 = |p: &[f64]| ParametricCurve::SevenParameter {
+        g: p[0] as f32, a: p[1] as f32, b: p[2] as f32, c: p[3] as f32, d: p[4] as f32, e: p[5] as f32, f: p[6] as f32,
+    };
+    let p4 = minimize(|p| sum_of_squares(&type4(p), &subset), [p3, vec![0.0, 0.0]].concat());
+    let fit4 = fit(type4(&p4), samples);
+    Ok(if fit4.rms_error < fit3.rms_error - 1.0 / 65535.0 { fit4 } else { fit3 })
+}
+
+fn sum_of_squares(curve: &ParametricCurve, samples: &[(f64, f64)]) -> f64 {
+    let s: f64 = samples.iter().map(|&(x, y)| (curve.eval(x) - y).powi(2)).sum();
+    if s.is_finite() { s } else { f64::MAX }
+}
+
+fn fit(curve: ParametricCurve, samples: &[(f64, f64)]) -> CurveFit {
+    let errors = samples.iter().map(|&(x, y)| (curve.eval(x) - y).abs());
+    let max_error = errors.clone().fold(0.0, f64::max);
+    let rms_error = (errors.map(|e| e * e).sum::<f64>() / samples.len() as f64).sqrt();
+    CurveFit { curve, rms_error, max_error }
+}
+
+// Nelder-Mead simplex minimization, restarted from the best point found, to escape early collapse
+// of the simplex.
+pub(crate) fn minimize(f: impl Fn(&[f64]) -> f64, x0: Vec<f64>) -> Vec<f64> {
+    let n = x0.len();
+    let mut best = x0;
+    for step in [0.1, 0.02, 0.005] {
+        let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+            .map(|i| {
+                let mut x = best.clone();
+                if i > 0 {
+                    x[i - 1] += step;
+                }
+                let v = f(&x);
+                (x, v)
+            })
+            .collect();
+        for _ in 0..200 * n {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+            let centroid: Vec<f64> = (0..n).map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>() / n as f64).collect();
+            let towards = |t: f64| -> Vec<f64> { centroid.iter().zip(&simplex[n].0).map(|(c, w)| c + t * (w - c)).collect() };
+            let reflected = towards(-1.0);
+            let fr = f(&reflected);
+            if fr < simplex[0].1 {
+                let expanded = towards(-2.0);
+                let fe = f(&expanded);
+                simplex[n] = if fe < fr { (expanded, fe) } else { (reflected, fr) };
+            } else if fr < simplex[n - 1].1 {
+                simplex[n] = (reflected, fr);
+            } else {
+                let contracted = towards(0.5);
+                let fc = f(&contracted);
+                if fc < simplex[n].1 {
+                    simplex[n] = (contracted, fc);
+                } else {
+                    let x0 = simplex[0].0.clone();
+                    for (x, v) in simplex.iter_mut().skip(1) {
+                        *x = x.iter().zip(&x0).map(|(a, b)| b + 0.5 * (a - b)).collect();
+                        *v = f(x);
+                    }
+                }
+            }
+        }
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        best = simplex.swap_remove(0).0;
+    }
+    best
+}
+
+#[test]
+fn test_fit_parametric() {
+    let srgb = ParametricCurve::IEC61966_2_1 { g: 2.4, a: 1.0 / 1.055, b: 0.055 / 1.055, c: 1.0 / 12.92, d: 0.04045 };
+    let fit = srgb.to_curve(256).fit_parametric().unwrap();
+    assert!(fit.rms_error < 1E-4 && fit.max_error < 5E-4, "{fit:?}");
+    assert!((fit.curve.eval(0.5) - srgb.eval(0.5)).abs() < 1E-3);
+
+    let gamma = ParametricCurve::ExponentGamma { g: 1.8 }.to_curve(1024).fit_parametric().unwrap();
+    assert!(gamma.rms_error < 1E-4, "{gamma:?}");
+    assert!(matches!(Curve(vec![0x0200]).fit_parametric().unwrap().curve, ParametricCurve::ExponentGamma { g } if g == 2.0));
+    assert!(Curve(vec![0, 0, 0]).fit_parametric().is_err());
+}
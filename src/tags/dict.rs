@@ -0,0 +1,198 @@
+/*!
+  Dictionary ('dict') tag data: an ordered list of name/value pairs, with optional localized display
+  names and values, such as used for the metadata tag of version 4 profiles.
+
+  Names and values are stored as UTF-16 strings, and the display names and values as embedded 'mluc'
+  tag elements; all at offsets from the start of the tag element. A value with a zero offset is
+  absent, which differs from an empty value.
+*/
+
+use crate::common::*;
+use serde::Serialize;
+
+use super::multi_localized_unicode::MultiLocalizedUnicode;
+
+const MLUC: [u8; 4] = *b"mluc";
+
+#[derive(Debug, Serialize)]
+pub struct DictEntry {
+    pub name: String,
+    pub value: Option<String>,
+    pub display_name: Option<MultiLocalizedUnicode>,
+    pub display_value: Option<MultiLocalizedUnicode>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Dict(pub Vec<DictEntry>);
+
+// Offset and size of an element in a dictionary tag; offsets include the 8 byte tag type signature and
+// reserved field, which are not part of the tag data buffer.
+fn element(buf: &[u8], offset: u32, size: u32) -> Result<Option<&[u8]>> {
+    if offset == 0 {
+        return Ok(None);
+    }
+    let start = (offset as usize).checked_sub(8).ok_or("dict element offset error")?;
+    let end = start.checked_add(size as usize).ok_or("dict element size error")?;
+    Ok(Some(buf.get(start..end).ok_or("dict element size error")?))
+}
+
+fn utf16(b: &[u8]) -> Result<String> {
+    let v = read_vec_u16(&mut &b[..], b.len())?;
+    Ok(String::from_utf16(&v)?)
+}
+
+fn mluc(b: &[u8]) -> Result<MultiLocalizedUnicode> {
+    let mut b = b;
+    if read_vec(&mut b, 4)? != MLUC {
+        return Err("dict display names and values should be mluc tag elements".into());
+    }
+    let _reserved = read_be_u32(&mut b)?;
+    MultiLocalizedUnicode::try_new(&mut b)
+}
+
+impl Dict {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let data = *buf;
+        let n = read_be_u32(buf)? as usize;
+        let record_length = read_be_u32(buf)? as usize;
+        if ![16, 24, 32].contains(&record_length) {
+            return Err("dict record length error".into());
+        }
+        let mut entries = Vec::with_capacity(n.min(buf.len() / record_length));
+        for _ in 0..n {
+            let record = read_vec(buf, record_length)?;
+            let mut r = record.as_slice();
+            let mut fields = [(0u32, 0u32); 4];
+            for f in fields.iter_mut().take(record_length / 8) {
+                *f = (read_be_u32(&mut r)?, read_be_u32(&mut r)?);
+            }
+            let [name, value, display_name, display_value] = fields;
+            entries.push(DictEntry {
+                name: utf16(element(data, name.0, name.1)?.unwrap_or_default())?,
+                value: element(data, value.0, value.1)?.map(utf16).transpose()?,
+                display_name: element(data, display_name.0, display_name.1)?.map(mluc).transpose()?,
+                display_value: element(data, display_value.0, display_value.1)?.map(mluc).transpose()?,
+            });
+        }
+        Ok(Self(entries))
+    }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let record_length: usize = if self.0.iter().any(|e| e.display_value.is_some()) {
+            32
+        } else if self.0.iter().any(|e| e.display_name.is_some()) {
+            24
+        } else {
+            16
+        };
+        let mut records = Vec::with_capacity(8 + record_length * self.0.len());
+        records.extend((self.0.len() as u32).to_be_bytes());
+        records.extend((record_length as u32).to_be_bytes());
+        let mut elements = Vec::new();
+        // elements are 4 byte aligned, at offsets from the start of the tag element
+        let start = 16 + record_length * self.0.len();
+        let mut add = |records: &mut Vec<u8>, element: Option<Vec<u8>>| -> Result<()> {
+            match element {
+                Some(b) => {
+                    records.extend(u32::try_from(start + elements.len())?.to_be_bytes());
+                    records.extend(u32::try_from(b.len())?.to_be_bytes());
+                    elements.extend(b);
+                    elements.resize(elements.len().next_multiple_of(4), 0);
+                }
+                None => records.extend([0u8; 8]),
+            }
+            Ok(())
+        };
+        let text = |s: &str| s.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<u8>>();
+        let mluc = |m: &MultiLocalizedUnicode| -> Result<Vec<u8>> { Ok([&MLUC[..], &[0u8; 4], &m.to_be_bytes()?].concat()) };
+        for e in &self.0 {
+            add(&mut records, Some(text(&e.name)))?;
+            add(&mut records, e.value.as_deref().map(text))?;
+            if record_length >= 24 {
+                add(&mut records, e.display_name.as_ref().map(mluc).transpose()?)?;
+            }
+            if record_length == 32 {
+                add(&mut records, e.display_value.as_ref().map(mluc).transpose()?)?;
+            }
+        }
+        records.extend(elements);
+        Ok(records)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value of the first entry with the given name.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|e| e.name == name).and_then(|e| e.value.as_deref())
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.0.iter().any(|e| e.name == name)
+    }
+
+    /// Sets the value of an entry, returning its previous value; new entries are added at the end.
+    pub fn insert(&mut self, name: &str, value: &str) -> Option<String> {
+        match self.0.iter_mut().find(|e| e.name == name) {
+            Some(e) => e.value.replace(value.to_owned()),
+            None => {
+                self.0.push(DictEntry { name: name.to_owned(), value: Some(value.to_owned()), display_name: None, display_value: None });
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let i = self.0.iter().position(|e| e.name == name)?;
+        self.0.remove(i).value
+    }
+
+    /// Iterates over the names and values of the entries, in the order of the dictionary.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.0.iter().map(|e| (e.name.as_str(), e.value.as_deref()))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|e| e.name.as_str())
+    }
+}
+
+#[test]
+fn test_dict() {
+    let mut dict = Dict::new();
+    assert_eq!(dict.insert("serial", "12345"), None);
+    dict.insert("date", "2024-03-01");
+    dict.0.push(DictEntry { name: "empty".into(), value: None, display_name: None, display_value: Some(MultiLocalizedUnicode::en_us("none")) });
+    assert_eq!(dict.insert("serial", "54321").as_deref(), Some("12345"));
+    let bytes = dict.to_be_bytes().unwrap();
+    assert_eq!(u32::from_be_bytes(bytes[4..8].try_into().unwrap()), 32);
+    let parsed = Dict::try_new(&mut bytes.as_slice()).unwrap();
+    assert_eq!(parsed.keys().collect::<Vec<_>>(), ["serial", "date", "empty"]);
+    assert_eq!(parsed.get("serial"), Some("54321"));
+    assert_eq!(parsed.iter().nth(2), Some(("empty", None)));
+    assert_eq!(parsed.0[2].display_value.as_ref().unwrap().0[0].2, "none");
+    assert_eq!(parsed.to_be_bytes().unwrap(), bytes);
+}
+
+#[test]
+fn test_profile_with_dict() {
+    use crate::signatures::tag::TagSignature;
+    let profile = crate::profile::standards::srgb()
+        .with_dict(TagSignature::MetaDataTag, |d| { d.insert("serial", "1"); })
+        .with_dict(TagSignature::MetaDataTag, |d| { d.insert("version", "2"); });
+    let buf = profile.to_buffer().unwrap();
+    let parsed = crate::profile::Profile::from_buffer(&buf).unwrap();
+    match parsed.tag_data(&TagSignature::MetaDataTag).unwrap() {
+        super::TagData::Dict(d) => assert_eq!(d.iter().collect::<Vec<_>>(), [("serial", Some("1")), ("version", Some("2"))]),
+        _ => panic!("expected a dictionary"),
+    }
+}
@@ -0,0 +1,537 @@
+/*!
+  Encoding of lutAtoBType tags, the multi-dimensional lookup table pipelines of version 4 profiles,
+  with up to five elements: 'A' curves, a color lookup table, 'M' curves, a matrix, and 'B' curves,
+  and of lut16Type tags, their version 2 counterpart.
+
+  Color lookup tables are sampled from a function with [Clut::new], or, with a check of the table
+  dimensions, and, with the `batch` feature, optionally on multiple threads, with a [ClutBuilder].
+
+  The lookup tables of lutAtoBType, lutBtoAType, lut16Type, and lut8Type tags are decoded for
+  evaluation, with their elements in processing order, as a [Lut].
+*/
+
+#[cfg(feature = "batch")]
+use rayon::prelude::*;
+
+use std::ops::Range;
+
+use crate::color::pcs::u16_v4_to_legacy;
+use crate::common::{read_be_u16, read_be_u32, read_s15fixed16, read_u8, read_vec, read_vec_u16, s15fixed16_to_be_bytes, Result};
+use crate::progress::{Counter, NoProgress, Progress};
+use crate::signatures::tagtype::TagTypeSignature;
+use crate::tags::{parametric_curve::ParametricCurve, Curve, TagData};
+use crate::transform::Trc;
+
+// Grid rows sampled in parallel between progress updates.
+const PARALLEL_ROWS: usize = 16;
+
+const CURVE: u32 = TagTypeSignature::CurveType as u32;
+const PARAMETRIC_CURVE: u32 = TagTypeSignature::ParametricCurveType as u32;
+
+/// Color lookup table, with 16 bit output values in the range from 0.0 to 1.0.
+#[derive(Debug, Clone)]
+pub struct Clut {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub grid_points: usize,
+    pub data: Vec<u16>,
+}
+
+impl Clut {
+    /// Samples a function of the input values, in the range from 0.0 to 1.0, at the points of a grid
+    /// with the given number of points for each input channel; output values are clipped. A table
+    /// should have from 1 to 16 input channels, 1 to 255 output channels, and 2 to 255 grid points.
+    pub fn new(inputs: usize, outputs: usize, grid_points: usize, f: impl Fn(&[f64]) -> Vec<f64>) -> Result<Self> {
+        let n = check_dimensions(inputs, outputs, grid_points)?;
+        let mut data = Vec::with_capacity(n * outputs);
+        for i in 0..n {
+            data.extend(sample(&f, i, inputs, outputs, grid_points));
+        }
+        Ok(Self { inputs, outputs, grid_points, data })
+    }
+
+    /// Converts output values in the version 4 16-bit CIELAB encoding to the legacy encoding, as
+    /// required for lut16Type tags with a CIELAB connection space.
+    pub fn with_legacy_lab_encoding(mut self) -> Self {
+        self.data.iter_mut().for_each(|v| *v = u16_v4_to_legacy(*v));
+        self
+    }
+
+    /// Output values for input values in the range from 0.0 to 1.0, by multilinear interpolation.
+    pub fn eval(&self, input: &[f64]) -> Vec<f64> {
+        let n = self.grid_points;
+        let mut base = 0;
+        let mut fractions = Vec::with_capacity(self.inputs);
+        let mut strides = Vec::with_capacity(self.inputs);
+        let mut stride = self.outputs;
+        for i in (0..self.inputs).rev() {
+            let pos = input.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0) * (n - 1) as f64;
+            let j = (pos.floor() as usize).min(n.saturating_sub(2));
+            base += j * stride;
+            fractions.push(pos - j as f64);
+            strides.push(stride);
+            stride *= n;
+        }
+        let mut output = vec![0.0; self.outputs];
+        for corner in 0..1usize << self.inputs {
+            let (mut offset, mut weight) = (base, 1.0);
+            for (k, (f, s)) in fractions.iter().zip(&strides).enumerate() {
+                if corner >> k & 1 == 1 {
+                    offset += s;
+                    weight *= f;
+                } else {
+                    weight *= 1.0 - f;
+                }
+            }
+            if weight > 0.0 {
+                output.iter_mut().zip(&self.data[offset..]).for_each(|(o, &v)| *o += weight * v as f64 / 65535.0);
+            }
+        }
+        output
+    }
+}
+
+// Number of grid points of a color lookup table of a lutAtoBType, or lutBtoAType, tag, which has
+// from 1 to 16 input channels, as given by its 16 grid point fields, 1 to 255 output channels, and
+// 2 to 255 grid points.
+fn check_dimensions(inputs: usize, outputs: usize, grid_points: usize) -> Result<usize> {
+    if !(1..=16).contains(&inputs) || !(1..=255).contains(&outputs) {
+        return Err("a color lookup table should have from 1 to 16 input, and 1 to 255 output, channels".into());
+    }
+    if !(2..=255).contains(&grid_points) {
+        return Err("the number of grid points should be from 2 to 255".into());
+    }
+    Ok(grid_points.checked_pow(inputs as u32).ok_or("color lookup table size error")?)
+}
+
+// Output values at grid point `i`; the first input channel varies slowest.
+fn sample(f: impl Fn(&[f64]) -> Vec<f64>, i: usize, inputs: usize, outputs: usize, grid_points: usize) -> Vec<u16> {
+    let mut point = vec![0.0; inputs];
+    let mut index = i;
+    for v in point.iter_mut().rev() {
+        *v = (index % grid_points) as f64 / (grid_points - 1) as f64;
+        index /= grid_points;
+    }
+    let mut values: Vec<u16> = f(&point).iter().take(outputs).map(|x| (x.clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+    values.resize(outputs, 0);
+    values
+}
+
+/// Samples color lookup tables, checking their dimensions: 1 to 15 input, and output, channels,
+/// and 2 to 255 grid points.
+///
+/// ```
+/// use cmx::tags::lut::ClutBuilder;
+/// let clut = ClutBuilder::new(3, 1, 9).fill(|rgb| vec![rgb.iter().sum::<f64>() / 3.0]).unwrap();
+/// assert_eq!(clut.data.len(), 9 * 9 * 9);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ClutBuilder {
+    inputs: usize,
+    outputs: usize,
+    grid_points: usize,
+    parallel: bool,
+}
+
+impl ClutBuilder {
+    pub fn new(inputs: usize, outputs: usize, grid_points: usize) -> Self {
+        Self { inputs, outputs, grid_points, parallel: false }
+    }
+
+    /// Samples the function on multiple threads.
+    #[cfg(feature = "batch")]
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Samples a function of the input values, in the range from 0.0 to 1.0, at the grid points;
+    /// output values are clipped, and missing output values are zero.
+    pub fn fill(&self, f: impl Fn(&[f64]) -> Vec<f64> + Sync) -> Result<Clut> {
+        self.fill_with_progress(f, &mut NoProgress)
+    }
+
+    /// Samples a function, as [ClutBuilder::fill], reporting a step for each row of the grid, along
+    /// the last input channel, and stopping with an error if the operation is cancelled.
+    pub fn fill_with_progress(&self, f: impl Fn(&[f64]) -> Vec<f64> + Sync, progress: &mut dyn Progress) -> Result<Clut> {
+        let Self { inputs, outputs, grid_points, parallel } = *self;
+        if !(1..=15).contains(&inputs) || !(1..=15).contains(&outputs) {
+            return Err("a color lookup table should have from 1 to 15 input, and output, channels".into());
+        }
+        if !(2..=255).contains(&grid_points) {
+            return Err("the number of grid points should be from 2 to 255".into());
+        }
+        let n = grid_points.checked_pow(inputs as u32)
+            .filter(|n| n.checked_mul(outputs * 2).is_some_and(|size| size <= u32::MAX as usize))
+            .ok_or("color lookup table size exceeds the maximum tag size")?;
+        let rows = n / grid_points;
+        // rows sampled between progress updates, which are sampled in parallel
+        let chunk = if parallel { PARALLEL_ROWS } else { 1 };
+        let mut counter = Counter::new(progress, rows);
+        let mut data = Vec::with_capacity(n * outputs);
+        for start in (0..rows).step_by(chunk) {
+            let points = start * grid_points..(start + chunk).min(rows) * grid_points;
+            data.extend(sample_points(&f, points, inputs, outputs, grid_points, parallel));
+            for _ in start..(start + chunk).min(rows) {
+                counter.step()?;
+            }
+        }
+        Ok(Clut { inputs, outputs, grid_points, data })
+    }
+}
+
+#[cfg(feature = "batch")]
+fn sample_points(f: &(impl Fn(&[f64]) -> Vec<f64> + Sync), points: Range<usize>, inputs: usize, outputs: usize, grid_points: usize, parallel: bool) -> Vec<u16> {
+    if parallel {
+        points.into_par_iter().flat_map_iter(|i| sample(f, i, inputs, outputs, grid_points)).collect()
+    } else {
+        points.flat_map(|i| sample(f, i, inputs, outputs, grid_points)).collect()
+    }
+}
+
+#[cfg(not(feature = "batch"))]
+fn sample_points(f: &impl Fn(&[f64]) -> Vec<f64>, points: Range<usize>, inputs: usize, outputs: usize, grid_points: usize, _parallel: bool) -> Vec<u16> {
+    points.flat_map(|i| sample(f, i, inputs, outputs, grid_points)).collect()
+}
+
+/// Data of a lut16Type tag, without its type signature and reserved field, with an identity matrix.
+/// The input, and output, tables have the same number of entries for each channel; empty tables
+/// are written as identity tables with two entries. A CIELAB connection space uses the legacy
+/// encoding, see [Clut::with_legacy_lab_encoding].
+pub fn lut16(input: &[Vec<u16>], clut: &Clut, output: &[Vec<u16>]) -> Result<Vec<u8>> {
+    if !(1..=15).contains(&clut.inputs) || !(1..=15).contains(&clut.outputs) {
+        return Err("a lut16 table should have from 1 to 15 input, and output, channels".into());
+    }
+    let entries = u32::try_from(clut.grid_points).ok().and_then(|n| n.checked_pow(clut.inputs as u32)).map(|n| n as usize * clut.outputs);
+    if !(2..=255).contains(&clut.grid_points) || entries != Some(clut.data.len()) {
+        return Err("the color lookup table data does not match its dimensions".into());
+    }
+    let tables = |tables: &[Vec<u16>], channels: usize| -> Result<(usize, Vec<u8>)> {
+        if tables.is_empty() {
+            return Ok((2, [0u16, 65535].repeat(channels).iter().flat_map(|v| v.to_be_bytes()).collect()));
+        }
+        let entries = tables[0].len();
+        if tables.len() != channels || !(2..=4096).contains(&entries) || tables.iter().any(|t| t.len() != entries) {
+            return Err("lut16 tables should have from 2 to 4096 entries, for each channel".into());
+        }
+        Ok((entries, tables.iter().flatten().flat_map(|v| v.to_be_bytes()).collect()))
+    };
+    let (input_entries, input) = tables(input, clut.inputs)?;
+    let (output_entries, output) = tables(output, clut.outputs)?;
+
+    let mut v = vec![clut.inputs as u8, clut.outputs as u8, clut.grid_points.try_into()?, 0];
+    for (i, j) in (0..3).flat_map(|i| (0..3).map(move |j| (i, j))) {
+        v.extend(s15fixed16_to_be_bytes(if i == j { 1.0 } else { 0.0 }));
+    }
+    v.extend((input_entries as u16).to_be_bytes());
+    v.extend((output_entries as u16).to_be_bytes());
+    v.extend(input);
+    v.extend(clut.data.iter().flat_map(|v| v.to_be_bytes()));
+    v.extend(output);
+    Ok(v)
+}
+
+/// Data of a lutBtoAType tag, with the elements in processing order; its encoding equals the encoding
+/// of a lutAtoBType tag, with the 'A' and 'B' curves exchanged.
+pub fn lut_btoa(b: Vec<TagData>, matrix: Option<([[f64; 3]; 3], [f64; 3])>, m: Vec<TagData>, clut: Clut, a: Vec<TagData>) -> Result<Vec<u8>> {
+    lut_atob(a, clut, m, matrix, b)
+}
+
+/// Data of a lutAtoBType tag, without its type signature and reserved field, with the elements in
+/// the order B curves, matrix, M curves, color lookup table, and A curves; the matrix is followed by
+/// its offsets.
+pub fn lut_atob(a: Vec<TagData>, clut: Clut, m: Vec<TagData>, matrix: Option<([[f64; 3]; 3], [f64; 3])>, b: Vec<TagData>) -> Result<Vec<u8>> {
+    // offsets are from the start of the tag, including the type signature and reserved field
+    const HEADER: usize = 32;
+    let mut body = Vec::new();
+    let mut offsets = [0u32; 5];
+    let curves = |body: &mut Vec<u8>, curves: &[TagData]| -> Result<()> {
+        for c in curves {
+            body.extend((c.type_signature() as u32).to_be_bytes());
+            body.extend([0u8; 4]);
+            body.extend(c.to_be_bytes()?);
+            body.resize(body.len().next_multiple_of(4), 0);
+        }
+        Ok(())
+    };
+    offsets[0] = HEADER as u32;
+    curves(&mut body, &b)?;
+    if let Some((matrix, offset)) = matrix {
+        offsets[1] = (HEADER + body.len()) as u32;
+        for v in matrix.iter().flatten().chain(&offset) {
+            body.extend(s15fixed16_to_be_bytes(*v));
+        }
+    }
+    if !m.is_empty() {
+        offsets[2] = (HEADER + body.len()) as u32;
+        curves(&mut body, &m)?;
+    }
+    let entries = check_dimensions(clut.inputs, clut.outputs, clut.grid_points)?.checked_mul(clut.outputs);
+    if entries != Some(clut.data.len()) {
+        return Err("the color lookup table data does not match its dimensions".into());
+    }
+    offsets[3] = (HEADER + body.len()) as u32;
+    let mut grid = [0u8; 16];
+    grid[..clut.inputs].fill(clut.grid_points.try_into()?);
+    body.extend(grid);
+    body.extend([2, 0, 0, 0]);
+    body.extend(clut.data.iter().flat_map(|v| v.to_be_bytes()));
+    body.resize(body.len().next_multiple_of(4), 0);
+    offsets[4] = (HEADER + body.len()) as u32;
+    curves(&mut body, &a)?;
+
+    let mut v = vec![clut.inputs as u8, clut.outputs as u8, 0, 0];
+    v.extend(offsets.iter().flat_map(|o| o.to_be_bytes()));
+    v.extend(body);
+    Ok(v)
+}
+
+/// Lookup table of a lutAtoBType, lutBtoAType, lut16Type, or lut8Type tag, decoded for evaluation,
+/// with its elements in processing order, for input, and output, values in the range from 0.0 to 1.0.
+#[derive(Debug, Clone)]
+pub struct Lut {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub elements: Vec<LutElement>,
+    /// CIELAB connection space values use the legacy 16-bit encoding, as in lut16Type tags.
+    pub legacy_lab: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum LutElement {
+    /// A curve for each channel.
+    Curves(Vec<Trc>),
+    /// A 3x3 matrix, and its offsets; output values are clipped.
+    Matrix([[f64; 3]; 3], [f64; 3]),
+    Clut(Clut),
+}
+
+impl Lut {
+    /// Decodes the data of a lookup table tag; lutAtoBType, and lutBtoAType, tags with a different
+    /// number of grid points for each input channel are not supported.
+    pub fn from_tag_data(data: &TagData) -> Result<Self> {
+        match data {
+            TagData::LutAToB(v) => Self::from_lut_ab(v, true),
+            TagData::LutBToA(v) => Self::from_lut_ab(v, false),
+            TagData::Custom(_, v) if data.type_signature() == TagTypeSignature::Lut16Type => Self::from_lut16(v),
+            TagData::Lut8(lut) => {
+                let (inputs, outputs) = (lut.n, lut.m);
+                let to_u16 = |v: &[u8]| v.iter().map(|&x| x as u16 * 257).collect::<Vec<u16>>();
+                let tables = |v: &[u8], channels: usize| v.chunks(256).take(channels).map(|t| Trc::Curve(Curve(to_u16(t)))).collect();
+                let matrix: Vec<f64> = lut.e_mat.iter().map(|&v| v as f64).collect();
+                let clut = Clut { inputs, outputs, grid_points: lut.k, data: to_u16(&lut.multi_lut) };
+                Self::legacy(inputs, outputs, &matrix, tables(&lut.input_lut, inputs), clut, tables(&lut.output_lut, outputs), false)
+            }
+            _ => Err(format!("{:?} tags are not lookup tables", data.type_signature()).into()),
+        }
+    }
+
+    // Elements of lut16Type, and lut8Type, tags: the matrix, which is used for three input channels
+    // only, the input tables, the color lookup table, and the output tables.
+    fn legacy(inputs: usize, outputs: usize, matrix: &[f64], input: Vec<Trc>, clut: Clut, output: Vec<Trc>, legacy_lab: bool) -> Result<Self> {
+        let entries = clut.grid_points.checked_pow(inputs as u32).and_then(|n| n.checked_mul(outputs));
+        if !(1..=15).contains(&inputs) || !(1..=15).contains(&outputs) || clut.grid_points < 2 || entries != Some(clut.data.len()) {
+            return Err("the color lookup table data does not match its dimensions".into());
+        }
+        let mut elements = Vec::with_capacity(4);
+        let matrix: [[f64; 3]; 3] = [0, 1, 2].map(|i| [0, 1, 2].map(|j| matrix.get(3 * i + j).copied().unwrap_or(0.0)));
+        let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        if inputs == 3 && matrix != identity {
+            elements.push(LutElement::Matrix(matrix, [0.0; 3]));
+        }
+        elements.extend([LutElement::Curves(input), LutElement::Clut(clut), LutElement::Curves(output)]);
+        Ok(Self { inputs, outputs, elements, legacy_lab })
+    }
+
+    fn from_lut16(data: &[u8]) -> Result<Self> {
+        let buf = &mut &data[..];
+        let (inputs, outputs, grid_points) = (read_u8(buf)? as usize, read_u8(buf)? as usize, read_u8(buf)? as usize);
+        read_u8(buf)?;
+        let matrix = (0..9).map(|_| read_s15fixed16(buf).map(f64::from)).collect::<Result<Vec<f64>>>()?;
+        let (input_entries, output_entries) = (read_be_u16(buf)? as usize, read_be_u16(buf)? as usize);
+        let tables = |buf: &mut &[u8], channels: usize, entries: usize| -> Result<Vec<Trc>> {
+            (0..channels).map(|_| Ok(Trc::Curve(Curve(read_vec_u16(buf, entries * 2)?)))).collect()
+        };
+        let input = tables(buf, inputs, input_entries)?;
+        let n = grid_points.checked_pow(inputs as u32).and_then(|n| n.checked_mul(outputs * 2)).ok_or("lut16 color lookup table size error")?;
+        let clut = Clut { inputs, outputs, grid_points, data: read_vec_u16(buf, n)? };
+        let output = tables(buf, outputs, output_entries)?;
+        if input.iter().chain(&output).any(|t| matches!(t, Trc::Curve(c) if c.0.len() < 2)) {
+            return Err("lut16 tables should have at least two entries".into());
+        }
+        Self::legacy(inputs, outputs, &matrix, input, clut, output, true)
+    }
+
+    // Elements of a lutAtoBType, or lutBtoAType, tag, at offsets from the start of the tag, which
+    // are zero for absent elements.
+    fn from_lut_ab(data: &[u8], atob: bool) -> Result<Self> {
+        let buf = &mut &data[..];
+        let (inputs, outputs) = (read_u8(buf)? as usize, read_u8(buf)? as usize);
+        if !(1..=15).contains(&inputs) || !(1..=15).contains(&outputs) {
+            return Err("a lookup table should have from 1 to 15 input, and output, channels".into());
+        }
+        read_be_u16(buf)?;
+        let offsets: Vec<usize> = (0..5).map(|_| read_be_u32(buf).map(|o| o as usize)).collect::<Result<_>>()?;
+        let at = |offset: usize| -> Result<Option<&[u8]>> {
+            match offset {
+                0 => Ok(None),
+                o => data.get(o.checked_sub(8).ok_or("invalid lookup table offset")?..).map(Some).ok_or_else(|| "lookup table offset outside of the tag".into()),
+            }
+        };
+        let curves = |offset: usize, channels: usize| -> Result<Option<LutElement>> {
+            let Some(mut buf) = at(offset)? else { return Ok(None) };
+            let mut curves = Vec::with_capacity(channels);
+            for _ in 0..channels {
+                let len = buf.len();
+                let signature = read_be_u32(&mut buf)?;
+                read_be_u32(&mut buf)?;
+                curves.push(match signature {
+                    CURVE => {
+                        let n = read_be_u32(&mut buf)? as usize;
+                        Trc::Curve(Curve(read_vec_u16(&mut buf, n.checked_mul(2).ok_or("lookup table curve size error")?)?))
+                    }
+                    PARAMETRIC_CURVE => Trc::Parametric(ParametricCurve::try_new(&mut buf)?),
+                    _ => return Err("lookup table curves should be curve, or parametric curve, types".into()),
+                });
+                // curves are aligned to four bytes
+                let padding = (len - buf.len()).next_multiple_of(4) - (len - buf.len());
+                buf = buf.get(padding.min(buf.len())..).unwrap_or_default();
+            }
+            Ok(Some(LutElement::Curves(curves)))
+        };
+        let clut = match at(offsets[3])? {
+            None => None,
+            Some(mut buf) => {
+                let grid = read_vec(&mut buf, 16)?;
+                let precision = read_u8(&mut buf)?;
+                read_vec(&mut buf, 3)?;
+                let grid_points = grid[0] as usize;
+                if grid[..inputs.min(16)].iter().any(|&g| g as usize != grid_points) {
+                    return Err("lookup tables with a different number of grid points for each input channel are not supported".into());
+                }
+                if grid_points < 2 {
+                    return Err("lookup tables should have at least two grid points".into());
+                }
+                let n = grid_points.checked_pow(inputs as u32).and_then(|n| n.checked_mul(outputs)).ok_or("lookup table size error")?;
+                let data = match precision {
+                    1 => read_vec(&mut buf, n)?.into_iter().map(|v| v as u16 * 257).collect(),
+                    2 => read_vec_u16(&mut buf, n.checked_mul(2).ok_or("lookup table size error")?)?,
+                    _ => return Err("lookup table with an invalid precision".into()),
+                };
+                Some(LutElement::Clut(Clut { inputs, outputs, grid_points, data }))
+            }
+        };
+        let matrix = match at(offsets[1])? {
+            None => None,
+            Some(mut buf) => {
+                let v = (0..12).map(|_| read_s15fixed16(&mut buf).map(f64::from)).collect::<Result<Vec<f64>>>()?;
+                Some(LutElement::Matrix([0, 1, 2].map(|i| [0, 1, 2].map(|j| v[3 * i + j])), [v[9], v[10], v[11]]))
+            }
+        };
+        // the 'A' side has the input channels for lutAtoBType tags, and the output channels for
+        // lutBtoAType tags; the 'M', and 'B', curves, and the matrix, are on the other side
+        let (a_channels, b_channels) = if atob { (inputs, outputs) } else { (outputs, inputs) };
+        let a = curves(offsets[4], a_channels)?;
+        let m = curves(offsets[2], b_channels)?;
+        let b = curves(offsets[0], b_channels)?.ok_or("lookup table without 'B' curves")?;
+        if clut.is_none() && inputs != outputs {
+            return Err("lookup tables without a color lookup table should have as many input as output channels".into());
+        }
+        if (matrix.is_some() || m.is_some()) && b_channels != 3 {
+            return Err("lookup tables with a matrix should have three channels on the 'B' side".into());
+        }
+        let elements = if atob { [a, clut, m, matrix, Some(b)] } else { [Some(b), matrix, m, clut, a] };
+        Ok(Self { inputs, outputs, elements: elements.into_iter().flatten().collect(), legacy_lab: false })
+    }
+
+    /// Output values for input values in the range from 0.0 to 1.0.
+    pub fn eval(&self, input: &[f64]) -> Vec<f64> {
+        let mut v: Vec<f64> = (0..self.inputs).map(|i| input.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0)).collect();
+        for element in &self.elements {
+            v = match element {
+                LutElement::Curves(curves) => curves.iter().zip(&v).map(|(c, &x)| c.eval(x)).collect(),
+                LutElement::Matrix(m, offset) => (0..3).map(|i| (m[i].iter().zip(&v).map(|(m, x)| m * x).sum::<f64>() + offset[i]).clamp(0.0, 1.0)).collect(),
+                LutElement::Clut(clut) => clut.eval(&v),
+            };
+        }
+        v
+    }
+}
+
+#[test]
+fn test_clut_eval() {
+    let clut = Clut::new(3, 2, 5, |v| vec![v[0] * 0.5 + v[1] * 0.25 + v[2] * 0.25, v[0]]).unwrap();
+    let out = clut.eval(&[0.3, 0.6, 0.9]);
+    assert!((out[0] - 0.525).abs() < 1E-4 && (out[1] - 0.3).abs() < 1E-4, "{out:?}");
+    assert_eq!(clut.eval(&[1.0, 1.0, 1.0]), [1.0, 1.0]);
+    assert!(Clut::new(17, 1, 2, |_| vec![0.0]).is_err());
+    assert!(Clut::new(3, 1, 1, |_| vec![0.0]).is_err());
+    // a table with a single grid point, as a constant
+    let single = Clut { inputs: 2, outputs: 1, grid_points: 1, data: vec![65535] };
+    assert_eq!(single.eval(&[0.5, 0.5]), [1.0]);
+}
+
+#[test]
+fn test_clut_builder() {
+    use crate::progress::{Cancellable, CancellationToken};
+    let f = |v: &[f64]| vec![v[0], v[1] * v[2]];
+    let clut = ClutBuilder::new(3, 2, 5).fill(f).unwrap();
+    // cancelled after the third of 25 grid rows
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    let mut updates = Vec::new();
+    let mut receiver = Cancellable::new(|done: usize, total: usize| {
+        updates.push((done, total));
+        if done == 3 {
+            cancel.cancel();
+        }
+    }, token);
+    assert!(ClutBuilder::new(3, 2, 5).fill_with_progress(f, &mut receiver).is_err());
+    assert_eq!(updates.last(), Some(&(3, 25)));
+    assert_eq!(clut.data, Clut::new(3, 2, 5, f).unwrap().data);
+    #[cfg(feature = "batch")]
+    assert_eq!(ClutBuilder::new(3, 2, 5).with_parallel(true).fill(f).unwrap().data, clut.data);
+    assert!(ClutBuilder::new(16, 3, 2).fill(f).is_err());
+    assert!(ClutBuilder::new(3, 3, 1).fill(f).is_err());
+
+    let lut = lut16(&[], &clut, &vec![vec![0, 32768, 65535]; 2]).unwrap();
+    assert_eq!(lut[..4], [3, 2, 5, 0]);
+    assert_eq!(lut[40..44], [0, 2, 0, 3]);
+    assert_eq!(lut.len(), 44 + 3 * 2 * 2 + 125 * 2 * 2 + 2 * 3 * 2);
+    assert!(lut16(&[vec![0, 65535]], &clut, &[]).is_err());
+    assert!(lut16(&[], &Clut { data: clut.data[1..].to_vec(), ..clut.clone() }, &[]).is_err());
+    assert!(lut16(&[], &Clut { inputs: 16, grid_points: 2, data: vec![0; 2 << 16], ..clut.clone() }, &[]).is_err());
+    let legacy = Clut::new(1, 1, 2, |v| vec![v[0]]).unwrap().with_legacy_lab_encoding();
+    assert_eq!(legacy.data, [0, 0xFF00]);
+}
+
+#[test]
+fn test_lut_decoding() {
+    let curves = |n: usize| (0..n).map(|_| TagData::Curve(Curve(vec![512]))).collect::<Vec<_>>();
+    let clut = Clut::new(3, 2, 5, |v| vec![v[0] * 0.5 + v[1] * 0.25 + v[2] * 0.25, v[0]]).unwrap();
+    let atob = Lut::from_tag_data(&TagData::LutAToB(lut_atob(curves(3), clut.clone(), Vec::new(), None, curves(2)).unwrap())).unwrap();
+    assert_eq!((atob.inputs, atob.outputs, atob.elements.len()), (3, 2, 3));
+    // squared inputs, and outputs, around the color lookup table
+    let out = atob.eval(&[0.5, 0.5, 0.5]);
+    let expected = clut.eval(&[0.25; 3]).iter().map(|v| v * v).collect::<Vec<f64>>();
+    assert!(out.iter().zip(&expected).all(|(a, b)| (a - b).abs() < 1E-9), "{out:?}");
+
+    let matrix = ([[0.5, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], [0.0, 0.0, 0.25]);
+    let btoa = lut_btoa(curves(3), Some(matrix), curves(3), Clut::new(3, 1, 2, |v| vec![v[2]]).unwrap(), curves(1)).unwrap();
+    let btoa = Lut::from_tag_data(&TagData::LutBToA(btoa)).unwrap();
+    assert_eq!((btoa.inputs, btoa.outputs), (3, 1));
+    assert!((btoa.eval(&[1.0, 1.0, 0.5])[0] - (0.25f64 + 0.25).powi(2).powi(2)).abs() < 1E-4, "{:?}", btoa.eval(&[1.0, 1.0, 0.5]));
+
+    // more than 16 input channels, or fewer than two grid points
+    let wide = Clut { inputs: 17, outputs: 1, grid_points: 2, data: vec![0; 1 << 17] };
+    assert!(lut_atob(curves(17), wide, Vec::new(), None, curves(1)).is_err());
+    let single = Clut { inputs: 3, outputs: 2, grid_points: 1, data: vec![0; 2] };
+    assert!(lut_atob(curves(3), single, Vec::new(), None, curves(2)).is_err());
+    assert!(lut_atob(curves(3), Clut { data: Vec::new(), ..clut.clone() }, Vec::new(), None, curves(2)).is_err());
+
+    let lut16 = lut16(&[], &clut, &[]).unwrap();
+    let lut16 = Lut::from_tag_data(&TagData::Custom(*b"mft2", lut16)).unwrap();
+    assert!(lut16.legacy_lab && lut16.eval(&[0.3, 0.6, 0.9]).iter().zip(clut.eval(&[0.3, 0.6, 0.9])).all(|(a, b)| (a - b).abs() < 1E-5));
+    assert!(Lut::from_tag_data(&TagData::LutAToB(Vec::new())).is_err());
+    assert!(Lut::from_tag_data(&TagData::Text(String::new())).is_err());
+}
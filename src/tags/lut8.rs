@@ -19,10 +19,11 @@ impl Lut8 {
         let k = read_u8(buf)? as usize;
         let _ = read_u8(buf)?; // padding
         let e_mat = read_s15fixed16_array(buf,36.into())?;
-        let input_lut = read_vec(buf, (n*256).into())?;
+        let input_lut = read_vec(buf, n*256 )?;
         let n_i32 = (n as i32).try_into()?;
-        let multi_lut = read_vec(buf, (k.pow(n_i32)*m).into())?;
-        let output_lut = read_vec(buf, (m*256).into())?;
+        let clut_size = k.checked_pow(n_i32).and_then(|s| s.checked_mul(m)).ok_or("Lut8 color lookup table size error")?;
+        let multi_lut = read_vec(buf, clut_size)?;
+        let output_lut = read_vec(buf, m*256 )?;
         Ok(Lut8 {
             n,
             m,
@@ -33,4 +34,15 @@ impl Lut8 {
             multi_lut,
         })
     }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = vec![self.n as u8, self.m as u8, self.k as u8, 0];
+        for &e in &self.e_mat {
+            v.extend(s15fixed16_to_be_bytes(e as f64));
+        }
+        v.extend(&self.input_lut);
+        v.extend(&self.multi_lut);
+        v.extend(&self.output_lut);
+        v
+    }
 }
\ No newline at end of file
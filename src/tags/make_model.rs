@@ -4,7 +4,9 @@ use serde::Serialize;
 
 // DEPRECATED_IN_MAC_OS_X_VERSION_10_6_AND_LATER
 
-#[derive(Debug, Serialize)]
+/// Apple's make and model ('mmod') tag, identifying the display of a display profile by the
+/// manufacturer ID, and product code, of its EDID, and its serial number and manufacturing date.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct MakeAndModel {
     manufacturer: u32,
     model: u32,
@@ -29,5 +31,84 @@ impl MakeAndModel {
         date,
         })
     }
+
+    /// Three letter EDID manufacturer ID, such as "APP", or "SAM", if set.
+    pub fn manufacturer(&self) -> Option<String> {
+        let id = self.manufacturer;
+        let letters = [10, 5, 0].map(|shift| (id >> shift) & 0x1F);
+        (id != 0 && id <= 0x7FFF && letters.iter().all(|l| (1..=26).contains(l)))
+            .then(|| letters.iter().map(|&l| char::from(b'@' + l as u8)).collect())
+    }
+
+    pub fn model(&self) -> u32 {
+        self.model
+    }
+
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    pub fn date(&self) -> u32 {
+        self.date
+    }
+
+    /// Sets the three letter EDID manufacturer ID; an ID which is not made of three letters from
+    /// A to Z is rejected by [MakeAndModel::validate].
+    pub fn set_manufacturer(&mut self, id: &str) -> &mut Self {
+        let letters: Vec<u32> = id.bytes().map(|b| b.wrapping_sub(b'@') as u32).collect();
+        self.manufacturer = match letters[..] {
+            [a, b, c] if letters.iter().all(|l| (1..=26).contains(l)) => a << 10 | b << 5 | c,
+            _ => u32::MAX,
+        };
+        self
+    }
+
+    /// Sets the EDID product code.
+    pub fn set_model(&mut self, model: u32) -> &mut Self {
+        self.model = model;
+        self
+    }
+
+    pub fn set_serial(&mut self, serial: u32) -> &mut Self {
+        self.serial = serial;
+        self
+    }
+
+    /// Sets the manufacturing date, in seconds since 1904-01-01, as in Apple's vendor profiles.
+    pub fn set_date(&mut self, date: u32) -> &mut Self {
+        self.date = date;
+        self
+    }
+
+    /// Checks for a valid manufacturer ID, and a model.
+    pub fn validate(&self) -> Result<()> {
+        if self.manufacturer().is_none() {
+            return Err("make and model without a valid three letter manufacturer ID".into());
+        }
+        if self.model == 0 {
+            return Err("make and model without a model".into());
+        }
+        Ok(())
+    }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(32);
+        for f in [self.manufacturer, self.model, self.serial, self.date, 0, 0, 0, 0] {
+            v.extend(f.to_be_bytes());
+        }
+        v
+    }
 }
 
+
+#[test]
+fn test_make_and_model() {
+    let mut mmod = MakeAndModel::default();
+    mmod.set_manufacturer("APP").set_model(0xAE31).set_serial(12345);
+    assert!(mmod.validate().is_ok());
+    let parsed = MakeAndModel::try_new(&mut mmod.to_be_bytes().as_slice()).unwrap();
+    assert_eq!(parsed, mmod);
+    assert_eq!((parsed.manufacturer.to_be_bytes(), parsed.manufacturer().unwrap()), ([0, 0, 0x06, 0x10], "APP".to_owned()));
+    assert!(mmod.set_manufacturer("apple").validate().is_err());
+    assert!(MakeAndModel::default().set_manufacturer("SAM").validate().is_err());
+}
@@ -4,7 +4,9 @@ use serde::Serialize;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
 
-#[derive(Debug, Serialize)]
+/// Measurement conditions of the 'meas' tag: the standard observer, the XYZ values of the measurement
+/// backing, the measurement geometry and flare, and the standard illuminant.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct Measurement {
     pub standard_observer: StandardObserver,
     pub xyz: [f64;3],
@@ -24,12 +26,23 @@ impl Measurement {
             illuminant: FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default(),
         })
     }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(28);
+        v.extend((self.standard_observer as u32).to_be_bytes());
+        v.extend(xyz_to_be_bytes(Some(self.xyz)));
+        v.extend((self.geometry as u32).to_be_bytes());
+        v.extend((self.flare as u32).to_be_bytes());
+        v.extend((self.illuminant as u32).to_be_bytes());
+        v
+    }
 }
 
 
 
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Default)]
 pub enum StandardIlluminant {
+    #[default]
     Unknown                 = 0x00000000,
     D50                     = 0x00000001,
     D65                     = 0x00000002,
@@ -57,47 +70,25 @@ pub enum StandardIlluminant {
     F12                     = 0x00000016,
 }
 
-impl Default for StandardIlluminant {
-    fn default() -> Self {
-        Self::Unknown
-    }
-} 
-
-
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Default)]
 pub enum StandardObserver {
+    #[default]
     Unknown                   = 0x00000000,  /* Unknown observer */
     Cie1931TwoDegrees         = 0x00000001,  /* 1931 two degrees */
     Cie1964TenDegrees         = 0x00000002,  /* 1961 ten degrees */
 }
 
-impl Default for StandardObserver {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Default)]
 pub enum Geometry {
+    #[default]
     Unknown                   = 0x00000000,  /* Unknown geometry */
     Normal45                  = 0x00000001,  /* 0/45, 45/0 */
     NormalDiffuse             = 0x00000002,  /* 0/d or d/0 */
 }
 
-impl Default for Geometry {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
-#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize)]
+#[derive(FromPrimitive, PartialEq, Clone, Copy, Debug, Serialize, Default)]
 pub enum Flare {
+    #[default]
     Flare0                            = 0x00000000,  /* 0% flare */
-    Flare100                          = 0x00000001,  /* 100% flare */
+    Flare100                          = 0x00010000,  /* 100% flare, as u16Fixed16Number */
 }
-
-impl Default for Flare {
-    fn default() -> Self {
-        Self::Flare0
-    }
-}
\ No newline at end of file
@@ -1,12 +1,22 @@
 
 pub mod chromaticity;
+pub mod cicp;
+pub mod colorant_table;
+pub mod curve_fit;
+pub mod dict;
+pub mod lut;
 pub mod lut8;
 pub mod make_model;
 pub mod measurement;
 pub mod multi_localized_unicode;
+pub mod multi_process_elements;
 pub mod named_color2;
 pub mod native_display_info;
 pub mod parametric_curve;
+pub mod profile_sequence_desc;
+pub mod sparse_matrix_array;
+pub mod spectral_data_info;
+pub mod spectral_viewing_conditions;
 pub mod text_description;
 pub mod vcgt;
 pub mod vcgp;
@@ -24,7 +34,20 @@ pub struct Tag {
 }
 
 impl Tag {
+    pub fn new(tag_signature: TagSignature, data: TagData) -> Self {
+        Self {
+            tag_signature,
+            type_signature: data.type_signature(),
+            data,
+        }
+    }
+
     pub fn try_new(tag_signature: TagSignature, buf: &mut &[u8]) -> Result<Self> {
+        Self::try_new_with_limit(tag_signature, buf, crate::profile::MAX_PROFILE_SIZE)
+    }
+
+    // Parses a tag, as [Tag::try_new], with compressed data decompressed to at most `max_size` bytes.
+    pub(crate) fn try_new_with_limit(tag_signature: TagSignature, buf: &mut &[u8], max_size: usize) -> Result<Self> {
         let t = read_be_u32(buf)?;
         let type_signature = match FromPrimitive::from_u32(t) {
             Some(c) => c,
@@ -34,23 +57,77 @@ impl Tag {
             }
         };
         let _reserved = read_be_u32(buf)?;
-        Ok(Self {
-            tag_signature: tag_signature.clone(),
-            type_signature,
-            data: TagData::try_new(tag_signature, type_signature, buf)?,
-        })
+        let mut data = TagData::try_new_with_limit(tag_signature.clone(), type_signature, buf, max_size)?;
+        // keep the type signature of unregistered types, such as private types of vendor tags
+        if let TagData::Custom(raw, _) = &mut data {
+            *raw = t.to_be_bytes();
+        }
+        Ok(Self { tag_signature, type_signature, data })
+    }
+
+    /// Parses a tag from its hexadecimal encoded form, as written by [Tag::to_be_bytes].
+    pub fn from_hex(tag_signature: TagSignature, hex: &str) -> Result<Self> {
+        Self::try_new(tag_signature, &mut from_hex(hex)?.as_slice())
+    }
+
+    pub fn signature(&self) -> &TagSignature {
+        &self.tag_signature
+    }
+
+    pub fn type_signature(&self) -> TagTypeSignature {
+        self.type_signature
+    }
+
+    pub fn data(&self) -> &TagData {
+        &self.data
+    }
+
+    // Mutable access to the data, which should keep its type, as the type signature is not updated.
+    pub(crate) fn data_mut(&mut self) -> &mut TagData {
+        &mut self.data
+    }
+
+    // Tag type signature, reserved field, and tag data, as stored in an ICC profile
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let mut v = Vec::new();
+        match &self.data {
+            TagData::Custom(raw, _) => v.extend(raw),
+            _ => v.extend((self.type_signature as u32).to_be_bytes()),
+        }
+        v.extend([0u8; 4]);
+        v.extend(self.data.to_be_bytes()?);
+        Ok(v)
     }
 }
 
+// Tags are read from a JSON, or other serde, representation in their encoded form, as a hexadecimal
+// string in a `raw` field, as included in profile dumps with the raw tags option.
+impl<'de> serde::Deserialize<'de> for Tag {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct RawTag {
+            tag_signature: TagSignature,
+            raw: Option<String>,
+        }
+        let t = RawTag::deserialize(deserializer)?;
+        let raw = t.raw.ok_or_else(|| serde::de::Error::custom(format!("tag {:?} without raw data", t.tag_signature)))?;
+        Tag::from_hex(t.tag_signature, &raw).map_err(serde::de::Error::custom)
+    }
+}
 
 #[derive(Debug, Serialize)]
 pub enum TagData {
     Chromaticity(Chromaticity),
+    Cicp(Cicp), // 'cicp'
     ColorantOrder(ColorantOrder), // 'clro'
+    ColorantTable(ColorantTable), // 'clrt'
     Curve(Curve), // 'data' with flag 1
     Data(Data), // 'data' with flag 1
     DateTime(DateTime), // 'dtim'
-    Dict(Vec<u8>), // 'dict' 
+    Dict(Dict), // 'dict'
     EmbeddedHeigthImage(Vec<u8>), // 'ehim'
     EmbeddedNormalImage(Vec<u8>), // 'enim'
     Float16Array(Vec<half::f16>), // 'fl16'
@@ -63,14 +140,16 @@ pub enum TagData {
     Measurement(Measurement), // 'meas'
     MakeAndModel(MakeAndModel), // 'mmod'
     MultiLocalizedUnicode(MultiLocalizedUnicode), // 'mluc'
-    MultiProcessElements(Vec<u8>), // 'mpet'
+    MultiProcessElements(MultiProcessElements), // 'mpet'
     NativeDisplayInfo(NativeDisplayInfo),
     NamedColor2(NamedColor2), // 'ncl2'
     ParametricCurve(ParametricCurve), // 'para'
+    ProfileSequenceDesc(ProfileSequenceDesc), // 'pseq'
     S15Fixed16Array(Vec<f32>), // 'sf32'
     Signature([u8;4]), // 'sig'
-    SparseMatrixArray(Vec<u8>), // 'smat'
-    SpectralViewingConditions(Vec<u8>), // 'svcn'
+    SparseMatrixArray(SparseMatrixArray), // 'smat'
+    SpectralDataInfo(SpectralDataInfo), // 'sdin'
+    SpectralViewingConditions(SpectralViewingConditions), // 'svcn'
     TagStruct(Vec<u8>), // 'tstr'
     Technology(TechnologySignature), // tag derived type
     Text(String),
@@ -87,32 +166,102 @@ pub enum TagData {
     Vcgp(Vcgp), // 'vcgt'
     ViewingConditions(ViewingConditions),
     XYZ(XYZ), // 'XYZ'
-    Custom(TagTypeSignature, Vec<u8>), // unknown data type
+    Custom([u8; 4], Vec<u8>), // unknown data type, with its type signature as stored
+}
+
+/// Values for fixed point number arrays: slices, and arrays, of numbers, and nested arrays, such as
+/// 3x3 matrices, which are stored in row order.
+pub trait FixedArrayValues {
+    fn to_values(&self) -> Vec<f64>;
 }
 
+impl FixedArrayValues for [f64] {
+    fn to_values(&self) -> Vec<f64> {
+        self.to_vec()
+    }
+}
 
+impl<const N: usize> FixedArrayValues for [f64; N] {
+    fn to_values(&self) -> Vec<f64> {
+        self.to_vec()
+    }
+}
+
+impl<const N: usize, const M: usize> FixedArrayValues for [[f64; N]; M] {
+    fn to_values(&self) -> Vec<f64> {
+        self.iter().flatten().copied().collect()
+    }
+}
 
 impl TagData {
+    /// An s15Fixed16ArrayType tag, such as the 'chad' tag, from numbers, or a matrix.
+    pub fn s15_fixed16_array(values: &(impl FixedArrayValues + ?Sized)) -> Self {
+        Self::S15Fixed16Array(values.to_values().into_iter().map(|v| v as f32).collect())
+    }
+
+    /// A u16Fixed16ArrayType tag, from numbers, or a matrix.
+    pub fn u16_fixed16_array(values: &(impl FixedArrayValues + ?Sized)) -> Self {
+        Self::U16Fixed16Array(values.to_values().into_iter().map(|v| v as f32).collect())
+    }
+
+    /// The four byte value of a signatureType tag, such as the 'tech', and 'ciis', tags.
+    pub fn signature(&self) -> Option<[u8; 4]> {
+        match self {
+            Self::Signature(s) => Some(*s),
+            Self::Technology(t) => Some((*t as u32).to_be_bytes()),
+            _ => None,
+        }
+    }
+
+    /// The values of an s15Fixed16ArrayType, or u16Fixed16ArrayType, tag.
+    pub fn fixed_array(&self) -> Option<Vec<f64>> {
+        match self {
+            Self::S15Fixed16Array(v) | Self::U16Fixed16Array(v) => Some(v.iter().map(|&x| x as f64).collect()),
+            _ => None,
+        }
+    }
+
+    /// The 3x3 matrix of an s15Fixed16ArrayType tag with nine values, such as the 'chad' tag.
+    pub fn matrix(&self) -> Option<[[f64; 3]; 3]> {
+        match self {
+            Self::S15Fixed16Array(v) if v.len() == 9 => Some([0, 1, 2].map(|i| [0, 1, 2].map(|j| v[3 * i + j] as f64))),
+            _ => None,
+        }
+    }
+
     pub fn try_new(tag_signature: TagSignature, type_signature: TagTypeSignature, buf: &mut &[u8]) -> Result<Self> {
+        Self::try_new_with_limit(tag_signature, type_signature, buf, crate::profile::MAX_PROFILE_SIZE)
+    }
+
+    // Parses tag data, as [TagData::try_new], with the text of zipUTF8Type tags decompressed to at
+    // most `max_size` bytes.
+    pub(crate) fn try_new_with_limit(tag_signature: TagSignature, type_signature: TagTypeSignature, buf: &mut &[u8], max_size: usize) -> Result<Self> {
         match (tag_signature, type_signature) {
             (_, TagTypeSignature::ChromaticityType) => {
                 Ok(Self::Chromaticity(Chromaticity::try_new(buf)?))
             },
+            (_, TagTypeSignature::CicpType) => {
+                Ok(Self::Cicp(Cicp::try_new(buf)?))
+            },
+            (_, TagTypeSignature::ColorantTableType) => Ok(Self::ColorantTable(ColorantTable::try_new(buf)?)),
             (_, TagTypeSignature::ColorantOrderType) => Ok(Self::ColorantOrder(ColorantOrder(buf.to_owned()))),
             (_, TagTypeSignature::CurveType) => {
                 let n = read_be_u32(buf)? as usize;
-                let mut v: Vec<u16> = Vec::with_capacity(n);
+                let mut v: Vec<u16> = Vec::with_capacity(n.min(buf.len() / 2));
                 for _ in 0..n {
                     v.push(read_be_u16(buf)?);
                 }
                 Ok(Self::Curve(Curve(v)))
             }
             (_, TagTypeSignature::DataType) => {
-                let _n = read_be_u32(buf)? as usize;
-                Ok(Self::Data(Data(buf.to_owned())))
+                let flag = read_be_u32(buf)?;
+                Ok(Self::Data(Data{flag, data: buf.to_owned()}))
             },
             (_, TagTypeSignature::DateTimeType) => {
-                Ok(Self::DateTime(DateTime(read_date_time(buf)?.unwrap())))
+                Ok(Self::DateTime(DateTime(read_date_time(buf)?.ok_or("date time tag without a date")?)))
+            },
+            (_, TagTypeSignature::DictType) => {
+                Ok(Self::Dict(Dict::try_new(buf)?))
             },
             (_, TagTypeSignature::Float16ArrayType)=> {
                 let mut v = Vec::with_capacity(buf.len()/std::mem::size_of::<half::f16>());
@@ -138,6 +287,9 @@ impl TagData {
             (_, TagTypeSignature::Lut8Type) => {
                 Ok(Self::Lut8(Lut8::try_new(buf)?))
             },
+            (_, TagTypeSignature::GamutBoundaryDescType) => Ok(Self::GamutBoundaryDescription(buf.to_owned())),
+            (_, TagTypeSignature::LutAtoBType) => Ok(Self::LutAToB(buf.to_owned())),
+            (_, TagTypeSignature::LutBtoAType) => Ok(Self::LutBToA(buf.to_owned())),
             (_, TagTypeSignature::MakeAndModelType) => {
                 Ok(Self::MakeAndModel(MakeAndModel::try_new(buf)?))
             },
@@ -150,18 +302,35 @@ impl TagData {
             (_, TagTypeSignature::NativeDisplayInfoType) => {
                 Ok(Self::NativeDisplayInfo(NativeDisplayInfo::try_new(buf)?))
             },
+            (_, TagTypeSignature::MultiProcessElementType) => {
+                Ok(Self::MultiProcessElements(MultiProcessElements::try_new(buf)?))
+            },
             (_, TagTypeSignature::NamedColor2Type) => {
-                Ok(Self::NamedColor2(NamedColor2::try_new(buf, 3)?)) // TODO! pcs size???
+                Ok(Self::NamedColor2(NamedColor2::try_new(buf, 3)?)) // PCS values are always XYZ or Lab
             },
             (_, TagTypeSignature::ParametricCurveType) => {
                 Ok(Self::ParametricCurve(ParametricCurve::try_new(buf)?))
             },
+            (_, TagTypeSignature::ProfileSequenceDescType) => {
+                Ok(Self::ProfileSequenceDesc(ProfileSequenceDesc::try_new(buf)?))
+            },
             (_, TagTypeSignature::S15Fixed16ArrayType) => {
                 Ok(Self::S15Fixed16Array(read_s15fixed16_array(buf, None)?))
             },
+            (_, TagTypeSignature::SparseMatrixArrayType) => {
+                Ok(Self::SparseMatrixArray(SparseMatrixArray::try_new(buf)?))
+            },
+            (_, TagTypeSignature::SpectralDataInfoType) => Ok(Self::SpectralDataInfo(SpectralDataInfo::try_new(buf)?)),
+            (_, TagTypeSignature::SpectralViewingConditionsType) => {
+                Ok(Self::SpectralViewingConditions(SpectralViewingConditions::try_new(buf)?))
+            },
             (_, TagTypeSignature::TextType) => {
                 Ok(Self::Text(std::str::from_utf8(buf)?.trim_end_matches(char::from(0)).to_owned()))
             },
+            (_, TagTypeSignature::Utf8TextType) => Ok(Self::Utf8(utf8_strings(buf)?)),
+            (_, TagTypeSignature::ZipUtf8TextType) => {
+                Ok(Self::Utf8Zip(utf8_strings(&inflate(buf, max_size)?)?))
+            },
             (_, TagTypeSignature::TextDescriptionType) => {
                 Ok(Self::TextDescription(TextDescription::try_new(buf)?))
             },
@@ -188,32 +357,257 @@ impl TagData {
                 Ok(Self::Vcgp(Vcgp::try_new(buf)?))
             },
             (TagSignature::TechnologyTag, TagTypeSignature::SignatureType) => {
-                Ok(Self::Technology(FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default()))
+                // unregistered technologies are kept as signatures
+                let v = read_be_u32(buf)?;
+                Ok(FromPrimitive::from_u32(v).map_or(Self::Signature(v.to_be_bytes()), Self::Technology))
             },
-            _  => Ok(Self::Custom(type_signature, buf.to_owned())),
+            (_, TagTypeSignature::SignatureType) => Ok(Self::Signature(read_vec(buf, 4)?.try_into().unwrap())),
+            _  => Ok(Self::Custom((type_signature as u32).to_be_bytes(), buf.to_owned())),
         } 
     }
+
+    pub fn type_signature(&self) -> TagTypeSignature {
+        match self {
+            Self::Chromaticity(_) => TagTypeSignature::ChromaticityType,
+            Self::ColorantTable(_) => TagTypeSignature::ColorantTableType,
+            Self::Cicp(_) => TagTypeSignature::CicpType,
+            Self::ColorantOrder(_) => TagTypeSignature::ColorantOrderType,
+            Self::Curve(_) => TagTypeSignature::CurveType,
+            Self::Data(_) => TagTypeSignature::DataType,
+            Self::DateTime(_) => TagTypeSignature::DateTimeType,
+            Self::Dict(_) => TagTypeSignature::DictType,
+            Self::EmbeddedHeigthImage(_) => TagTypeSignature::EmbeddedHeightImageType,
+            Self::EmbeddedNormalImage(_) => TagTypeSignature::EmbeddedNormalImageType,
+            Self::Float16Array(_) => TagTypeSignature::Float16ArrayType,
+            Self::Float32Array(_) => TagTypeSignature::Float32ArrayType,
+            Self::Float64Array(_) => TagTypeSignature::Float64ArrayType,
+            Self::GamutBoundaryDescription(_) => TagTypeSignature::GamutBoundaryDescType,
+            Self::Lut8(_) => TagTypeSignature::Lut8Type,
+            Self::LutAToB(_) => TagTypeSignature::LutAtoBType,
+            Self::LutBToA(_) => TagTypeSignature::LutBtoAType,
+            Self::Measurement(_) => TagTypeSignature::MeasurementType,
+            Self::MakeAndModel(_) => TagTypeSignature::MakeAndModelType,
+            Self::MultiLocalizedUnicode(_) => TagTypeSignature::MultiLocalizedUnicodeType,
+            Self::MultiProcessElements(_) => TagTypeSignature::MultiProcessElementType,
+            Self::NativeDisplayInfo(_) => TagTypeSignature::NativeDisplayInfoType,
+            Self::NamedColor2(_) => TagTypeSignature::NamedColor2Type,
+            Self::ParametricCurve(_) => TagTypeSignature::ParametricCurveType,
+            Self::ProfileSequenceDesc(_) => TagTypeSignature::ProfileSequenceDescType,
+            Self::S15Fixed16Array(_) => TagTypeSignature::S15Fixed16ArrayType,
+            Self::Signature(_) => TagTypeSignature::SignatureType,
+            Self::SparseMatrixArray(_) => TagTypeSignature::SparseMatrixArrayType,
+            Self::SpectralDataInfo(_) => TagTypeSignature::SpectralDataInfoType,
+            Self::SpectralViewingConditions(_) => TagTypeSignature::SpectralViewingConditionsType,
+            Self::TagStruct(_) => TagTypeSignature::TagStructType,
+            Self::Technology(_) => TagTypeSignature::SignatureType,
+            Self::Text(_) => TagTypeSignature::TextType,
+            Self::TextDescription(_) => TagTypeSignature::TextDescriptionType,
+            Self::U16Fixed16Array(_) => TagTypeSignature::U16Fixed16ArrayType,
+            Self::UInt8Array(_) => TagTypeSignature::UInt8ArrayType,
+            Self::UInt16Array(_) => TagTypeSignature::UInt16ArrayType,
+            Self::UInt32Array(_) => TagTypeSignature::UInt32ArrayType,
+            Self::UInt64Array(_) => TagTypeSignature::UInt64ArrayType,
+            Self::Utf8(_) => TagTypeSignature::Utf8TextType,
+            Self::Utf16(_) => TagTypeSignature::Utf16TextType,
+            Self::Utf8Zip(_) => TagTypeSignature::ZipUtf8TextType,
+            Self::Vcgt(_) => TagTypeSignature::VcgtType,
+            Self::Vcgp(_) => TagTypeSignature::VcgpType,
+            Self::ViewingConditions(_) => TagTypeSignature::ViewingConditionsType,
+            Self::XYZ(_) => TagTypeSignature::XYZArrayType,
+            Self::Custom(t, _) => FromPrimitive::from_u32(u32::from_be_bytes(*t)).unwrap_or(TagTypeSignature::UndefinedType),
+        }
+    }
+
+    // Tag data, without the type signature and reserved field
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        Ok(match self {
+            Self::Chromaticity(c) => c.to_be_bytes(),
+            Self::ColorantTable(c) => c.to_be_bytes(),
+            Self::Cicp(c) => c.to_be_bytes().to_vec(),
+            Self::ColorantOrder(ColorantOrder(v)) => v.clone(),
+            Self::Curve(Curve(v)) => {
+                let mut b = (v.len() as u32).to_be_bytes().to_vec();
+                b.extend(v.iter().flat_map(|x| x.to_be_bytes()));
+                b
+            }
+            Self::Data(Data{flag, data}) => {
+                let mut b = flag.to_be_bytes().to_vec();
+                b.extend(data);
+                b
+            }
+            Self::DateTime(DateTime(dt)) => datetime_to_be_bytes(Some(*dt)).to_vec(),
+            Self::EmbeddedHeigthImage(v) |
+            Self::EmbeddedNormalImage(v) |
+            Self::GamutBoundaryDescription(v) |
+            Self::LutAToB(v) |
+            Self::LutBToA(v) |
+            Self::TagStruct(v) |
+            Self::UInt8Array(v) |
+            Self::Custom(_, v) => v.clone(),
+            Self::Dict(d) => d.to_be_bytes()?,
+            Self::MultiProcessElements(m) => m.to_be_bytes()?,
+            Self::SpectralDataInfo(s) => s.to_be_bytes(),
+            Self::SpectralViewingConditions(s) => s.to_be_bytes()?,
+            Self::Float16Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::Float32Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::Float64Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::Lut8(l) => l.to_be_bytes(),
+            Self::Measurement(m) => m.to_be_bytes(),
+            Self::MakeAndModel(m) => m.to_be_bytes(),
+            Self::MultiLocalizedUnicode(m) => m.to_be_bytes()?,
+            Self::NativeDisplayInfo(n) => n.to_be_bytes(),
+            Self::NamedColor2(n) => n.to_be_bytes(),
+            Self::ParametricCurve(p) => p.to_be_bytes(),
+            Self::ProfileSequenceDesc(p) => p.to_be_bytes()?,
+            Self::SparseMatrixArray(s) => s.to_be_bytes()?,
+            Self::S15Fixed16Array(v) => v.iter().flat_map(|&x| s15fixed16_to_be_bytes(x as f64)).collect(),
+            Self::U16Fixed16Array(v) => v.iter().flat_map(|&x| u16fixed16_to_be_bytes(x as f64)).collect(),
+            Self::Signature(s) => s.to_vec(),
+            Self::Technology(t) => (*t as u32).to_be_bytes().to_vec(),
+            Self::Text(s) => {
+                let mut b = s.as_bytes().to_vec();
+                b.push(0);
+                b
+            }
+            Self::TextDescription(t) => t.to_be_bytes(),
+            Self::UInt16Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::UInt32Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::UInt64Array(v) => v.iter().flat_map(|x| x.to_be_bytes()).collect(),
+            Self::Utf8(v) => v.iter().flat_map(|s| s.bytes().chain([0])).collect(),
+            Self::Utf8Zip(v) => {
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                std::io::Write::write_all(&mut encoder, &v.iter().flat_map(|s| s.bytes().chain([0])).collect::<Vec<u8>>())?;
+                encoder.finish()?
+            }
+            Self::Utf16(_) => {
+                return Err(format!("writing {:?} tags is not supported", self.type_signature()).into())
+            }
+            Self::Vcgt(v) => v.to_be_bytes(),
+            Self::Vcgp(v) => v.to_be_bytes(),
+            Self::ViewingConditions(v) => v.to_be_bytes(),
+            Self::XYZ(XYZ(v)) => v.iter().flat_map(|&xyz| xyz_to_be_bytes(Some(xyz))).collect(),
+        })
+    }
 }
 
 
 
+// Null terminated UTF-8 strings, of utf8Type, and zipUtf8Type, tags.
+fn utf8_strings(buf: &[u8]) -> Result<Vec<String>> {
+    let text = std::str::from_utf8(buf)?;
+    Ok(text.split_terminator('\0').map(str::to_owned).collect())
+}
+
 // Tag Type definitions
 // Simple tag types defined here, complex tag types in separate files
 
 use chromaticity::Chromaticity;
+use colorant_table::ColorantTable;
+
+use cicp::Cicp;
 
 #[derive(Debug, Serialize)]
-pub struct ColorantOrder(Vec<u8>);
+pub struct ColorantOrder(pub Vec<u8>);
+
+
+// Curve entries; an empty curve is the identity, and a single entry a gamma value in u8Fixed8Number encoding
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Curve(pub Vec<u16>);
+
+impl Curve {
+    /// Output value of the curve for an input value in the range from 0.0 to 1.0, with linear
+    /// interpolation between table entries.
+    pub fn value(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self.0.as_slice() {
+            [] => x,
+            [g] => x.powf(*g as f32 / 256.0),
+            table => {
+                let pos = x * (table.len() - 1) as f32;
+                let i = (pos.floor() as usize).min(table.len() - 2);
+                let (y0, y1) = (table[i] as f32, table[i + 1] as f32);
+                (y0 + (y1 - y0) * (pos - i as f32)) / 65535.0
+            }
+        }
+    }
 
+    /// Sets the curve to a power function, encoded as a single u8Fixed8Number entry, in the range
+    /// from 0.0 to 255.996; a gamma of 1.0 is encoded as the identity, without entries.
+    pub fn set_gamma(&mut self, gamma: f64) {
+        let g = (gamma * 256.0).round().clamp(0.0, 65535.0) as u16;
+        self.0 = if g == 256 { Vec::new() } else { vec![g] };
+    }
 
-#[derive(Debug, Serialize)]
-pub struct Curve(Vec<u16>);
+    /// Sets the sampled table of the curve; a single sample, which would be read as a gamma value,
+    /// is stored as a constant table of two entries.
+    pub fn set_table(&mut self, table: &[u16]) {
+        self.0 = match table {
+            [v] => vec![*v, *v],
+            _ => table.to_vec(),
+        };
+    }
+
+    /// Sets the curve to `n` samples of a function, for input, and output, values in the range from
+    /// 0.0 to 1.0; output values are clipped.
+    pub fn set_table_from_fn(&mut self, n: usize, f: impl Fn(f64) -> f64) {
+        let table: Vec<u16> = (0..n).map(|i| (f(i as f64 / (n.max(2) - 1) as f64).clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+        self.set_table(&table);
+    }
+
+    /// The gamma of a power function curve, 1.0 for the identity, or none for a sampled curve.
+    pub fn gamma(&self) -> Option<f64> {
+        match self.0.as_slice() {
+            [] => Some(1.0),
+            [g] => Some(*g as f64 / 256.0),
+            _ => None,
+        }
+    }
+
+    /// The samples of a sampled curve, or an empty slice for a power function curve.
+    pub fn samples(&self) -> &[u16] {
+        match self.0.as_slice() {
+            [] | [_] => &[],
+            table => table,
+        }
+    }
+}
+
+#[test]
+fn test_fixed_arrays() {
+    let m = [[1.0, 0.5, -0.25], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let chad = TagData::s15_fixed16_array(&m);
+    assert_eq!(chad.matrix(), Some(m));
+    assert_eq!(chad.fixed_array().unwrap()[..3], [1.0, 0.5, -0.25]);
+    let values = TagData::u16_fixed16_array(&[0.5, 2.0][..]);
+    assert_eq!((values.fixed_array(), values.matrix()), (Some(vec![0.5, 2.0]), None));
+    assert!(TagData::Text(String::new()).fixed_array().is_none());
+}
+
+#[test]
+fn test_curve_table() {
+    let mut curve = Curve::default();
+    curve.set_gamma(2.2);
+    assert_eq!(curve.0, [563]);
+    assert!((curve.gamma().unwrap() - 2.2).abs() < 1E-2 && curve.samples().is_empty());
+    curve.set_gamma(1.0);
+    assert_eq!(curve.gamma(), Some(1.0));
+    curve.set_table(&[1000]);
+    assert_eq!((curve.gamma(), curve.samples()), (None, &[1000, 1000][..]));
+    curve.set_table_from_fn(5, |x| x * x);
+    assert_eq!(curve.samples(), [0, 4096, 16384, 36863, 65535]);
+    assert!((curve.value(0.5) - 0.25).abs() < 1E-4);
+}
+
+use dict::Dict;
 
 #[derive(Debug, Serialize)]
-pub struct Data(Vec<u8>);
+pub struct Data {
+    pub flag: u32, // 0: ascii, 1: binary
+    pub data: Vec<u8>,
+}
 
 #[derive(Debug, Serialize)]
-pub struct DateTime(chrono::DateTime<chrono::Utc>);
+pub struct DateTime(pub chrono::DateTime<chrono::Utc>);
 
 #[derive(Debug, Serialize)]
 pub struct Float16Array(Vec<half::f16>);
@@ -232,11 +626,18 @@ use measurement::Measurement;
 
 use multi_localized_unicode::MultiLocalizedUnicode;
 
+use multi_process_elements::MultiProcessElements;
+
 use named_color2::NamedColor2;
 
 use native_display_info::NativeDisplayInfo;
 
 use parametric_curve::ParametricCurve;
+
+use profile_sequence_desc::ProfileSequenceDesc;
+use sparse_matrix_array::SparseMatrixArray;
+use spectral_data_info::SpectralDataInfo;
+use spectral_viewing_conditions::SpectralViewingConditions;
 #[derive(Debug, Serialize)]
 pub struct Text(String);
 
@@ -249,7 +650,20 @@ use vcgp::Vcgp;
 use viewing_conditions::ViewingConditions;
 
 #[derive(Debug, Serialize)]
-pub struct XYZ(Vec<[f64;3]>);
+pub struct XYZ(pub Vec<[f64;3]>);
 
 
 
+
+#[test]
+fn test_zip_utf8_limit() {
+    use crate::profile::{ParseOptions, Profile};
+    let text = TagData::Utf8Zip(vec!["a".repeat(1000)]);
+    let bytes = Tag::new(TagSignature::CharTargetTag, text).to_be_bytes().unwrap();
+    assert!(Tag::try_new(TagSignature::CharTargetTag, &mut &bytes[..]).is_ok());
+    assert!(Tag::try_new_with_limit(TagSignature::CharTargetTag, &mut &bytes[..], 1000).is_err());
+    // the maximum profile size of the parse options also limits the decompressed text
+    let icc = Profile::default().with_tag(TagSignature::CharTargetTag, TagData::Utf8Zip(vec!["a".repeat(4000)])).to_buffer().unwrap();
+    assert!(Profile::from_buffer(&icc).is_ok());
+    assert!(Profile::from_buffer_with_options(&icc, &ParseOptions { max_profile_size: icc.len(), ..Default::default() }).is_err());
+}
@@ -4,12 +4,18 @@ use isolang::Language;
 use isocountry::CountryCode;
 
 #[derive(Debug, Serialize)]
-pub struct MultiLocalizedUnicode(Vec<(Option<CountryCode>, Language, String)>);
+pub struct MultiLocalizedUnicode(pub Vec<(Option<CountryCode>, Language, String)>);
 
 impl MultiLocalizedUnicode {
+    /// A single, US English, text record.
+    pub fn en_us(text: &str) -> Self {
+        Self(vec![(Some(CountryCode::USA), Language::Eng, text.to_owned())])
+    }
+
     pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
         let n = read_be_u32(buf)? as usize;
-        let mut pos = Vec::with_capacity(n);
+        let records_end = n.checked_mul(12).and_then(|r| r.checked_add(16)).ok_or("Multilocalized record count error")?;
+        let mut pos = Vec::with_capacity(n.min(buf.len() / 12));
         let twelve = read_be_u32(buf)?;
         if twelve != 12 { return Err("Incorrect multilocalized record length".into())}
         for _ in 0..n {
@@ -18,7 +24,7 @@ impl MultiLocalizedUnicode {
             if country == "FU" {country = String::from("FR")}; // found in Generic CMYK Profile MacOS
             if country == "PO" {country = String::from("PT")}; // found in Generic CMYK Profile
             let length = read_be_u32(buf)? as usize;
-            let start = (read_be_u32(buf)? - (16 + 12 * n as u32)) as usize;
+            let start = (read_be_u32(buf)? as usize).checked_sub(records_end).ok_or("Multilocalized string offset error")?;
             pos.push((lang, country, start, length));
         }
         let data = read_vec_u16(buf, buf.len())?;
@@ -26,11 +32,33 @@ impl MultiLocalizedUnicode {
         for (lang, country, start, length) in pos {
             mlu.push((
                 CountryCode::for_alpha2_caseless(country.as_str()).ok(),
-                Language::from_639_1(lang.as_str()).unwrap(),
-                String::from_utf16(&data[start/2..start/2+length/2])?  
+                Language::from_639_1(lang.as_str()).ok_or("Unknown multilocalized language code")?,
+                String::from_utf16(data.get(start/2..start/2+length/2).ok_or("Multilocalized string length error")?)?
             ));
         }
 
         Ok(Self(mlu))
     }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let n = self.0.len();
+        let mut records = Vec::with_capacity(8 + 12 * n);
+        let mut strings: Vec<u8> = Vec::new();
+        records.extend((n as u32).to_be_bytes());
+        records.extend(12u32.to_be_bytes());
+        for (country, language, text) in &self.0 {
+            let lang = language.to_639_1().ok_or("language without a two letter code in multi localized unicode")?;
+            records.extend(lang.as_bytes());
+            match country {
+                Some(c) => records.extend(c.alpha2().as_bytes()),
+                None => records.extend([0, 0]),
+            }
+            let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            records.extend((utf16.len() as u32).to_be_bytes());
+            records.extend((16 + 12 * n as u32 + strings.len() as u32).to_be_bytes());
+            strings.extend(utf16);
+        }
+        records.extend(strings);
+        Ok(records)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,383 @@
+/*!
+  Multi process element pipelines, the multiProcessElementsType used by the floating point 'D2Bx'
+  and 'B2Dx' tags: a sequence of curve set, matrix, and color lookup table elements, operating on
+  32-bit floating point values.
+
+  Curves are segmented curves, with formula and sampled segments; other element types, such as
+  the iccMAX calculator element, are kept as undecoded data, and can not be evaluated.
+*/
+
+use crate::common::*;
+use serde::Serialize;
+
+const CURVE_SET: u32 = 0x63767374; // 'cvst'
+const MATRIX: u32 = 0x6d617466; // 'matf'
+const CLUT: u32 = 0x636c7574; // 'clut'
+const SEGMENTED_CURVE: u32 = 0x63757266; // 'curf'
+const FORMULA_SEGMENT: u32 = 0x70617266; // 'parf'
+const SAMPLED_SEGMENT: u32 = 0x73616d66; // 'samf'
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MultiProcessElements {
+    pub inputs: u16,
+    pub outputs: u16,
+    pub elements: Vec<ProcessElement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ProcessElement {
+    CurveSet(Vec<SegmentedCurve>),
+    /// Matrix, with a row of input coefficients for each output channel, and an offset for each
+    /// output channel.
+    Matrix { inputs: u16, outputs: u16, matrix: Vec<f32>, offsets: Vec<f32> },
+    /// Color lookup table, with the number of grid points of each input channel, and the output
+    /// values, with the first input channel varying slowest.
+    Clut { grid_points: Vec<u8>, outputs: u16, values: Vec<f32> },
+    /// Element of another type, with its signature, input and output channels, and its data after
+    /// the channel counts.
+    Other { signature: u32, inputs: u16, outputs: u16, data: Vec<u8> },
+}
+
+/// Curve with segments between breakpoints: the first segment applies up to, and including, the
+/// first breakpoint, the last segment above the last breakpoint.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SegmentedCurve {
+    pub breakpoints: Vec<f32>,
+    pub segments: Vec<CurveSegment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum CurveSegment {
+    /// Formula segment, with function type 0: (a·x + b)^γ + c, 1: a·log10(b·x^γ + c) + d, 2: a·b^(c·x + d) + e,
+    /// or 3: a·(b·x + c)^γ + d, with the parameters γ, a, b, c, d, or, for type 2, a, b, c, d, e.
+    Formula { function: u16, parameters: Vec<f32> },
+    /// Sampled segment, with values equally spaced over the segment, excluding its start, which has
+    /// the value of the previous segment at its end.
+    Sampled(Vec<f32>),
+}
+
+fn read_f32s(buf: &mut &[u8], n: usize) -> Result<Vec<f32>> {
+    if buf.len() / 4 < n {
+        return Err("unexpected end of data".into());
+    }
+    (0..n).map(|_| read_be_f32(buf)).collect()
+}
+
+// Data at an offset, and with a size, from a position table entry, relative to the start of `base`.
+fn position(base: &[u8], offset: usize, size: usize) -> Result<&[u8]> {
+    base.get(offset..offset.checked_add(size).ok_or("invalid position")?).ok_or_else(|| "position outside of the data".into())
+}
+
+impl SegmentedCurve {
+    fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        if read_be_u32(buf)? != SEGMENTED_CURVE {
+            return Err("expected a segmented curve".into());
+        }
+        read_be_u32(buf)?;
+        let n = read_be_u16(buf)? as usize;
+        read_be_u16(buf)?;
+        let breakpoints = read_f32s(buf, n.saturating_sub(1))?;
+        let mut segments = Vec::with_capacity(n.min(buf.len() / 12));
+        for _ in 0..n {
+            let signature = read_be_u32(buf)?;
+            read_be_u32(buf)?;
+            segments.push(match signature {
+                FORMULA_SEGMENT => {
+                    let function = read_be_u16(buf)?;
+                    read_be_u16(buf)?;
+                    let count = match function {
+                        0 => 4,
+                        1..=3 => 5,
+                        _ => return Err(format!("unknown formula segment function type {}", function).into()),
+                    };
+                    CurveSegment::Formula { function, parameters: read_f32s(buf, count)? }
+                }
+                SAMPLED_SEGMENT => {
+                    let count = read_be_u32(buf)? as usize;
+                    CurveSegment::Sampled(read_f32s(buf, count)?)
+                }
+                _ => return Err("unknown curve segment type".into()),
+            });
+        }
+        Ok(Self { breakpoints, segments })
+    }
+
+    fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = SEGMENTED_CURVE.to_be_bytes().to_vec();
+        v.extend([0; 4]);
+        v.extend((self.segments.len() as u16).to_be_bytes());
+        v.extend([0; 2]);
+        v.extend(self.breakpoints.iter().flat_map(|b| b.to_be_bytes()));
+        for segment in &self.segments {
+            match segment {
+                CurveSegment::Formula { function, parameters } => {
+                    v.extend(FORMULA_SEGMENT.to_be_bytes());
+                    v.extend([0; 4]);
+                    v.extend(function.to_be_bytes());
+                    v.extend([0; 2]);
+                    v.extend(parameters.iter().flat_map(|p| p.to_be_bytes()));
+                }
+                CurveSegment::Sampled(values) => {
+                    v.extend(SAMPLED_SEGMENT.to_be_bytes());
+                    v.extend([0; 4]);
+                    v.extend((values.len() as u32).to_be_bytes());
+                    v.extend(values.iter().flat_map(|p| p.to_be_bytes()));
+                }
+            }
+        }
+        v
+    }
+
+    pub fn eval(&self, x: f32) -> f32 {
+        if self.segments.is_empty() {
+            return f32::NAN;
+        }
+        let i = self.breakpoints.partition_point(|&b| b < x).min(self.segments.len().saturating_sub(1));
+        self.eval_segment(i, x)
+    }
+
+    fn eval_segment(&self, i: usize, x: f32) -> f32 {
+        match &self.segments[i] {
+            CurveSegment::Formula { function, parameters } => {
+                let p = |i: usize| parameters.get(i).copied().unwrap_or(0.0);
+                match function {
+                    0 => (p(1) * x + p(2)).powf(p(0)) + p(3),
+                    1 => p(1) * (p(2) * x.powf(p(0)) + p(3)).log10() + p(4),
+                    2 => p(0) * p(1).powf(p(2) * x + p(3)) + p(4),
+                    _ => p(1) * (p(2) * x + p(3)).powf(p(0)) + p(4),
+                }
+            }
+            CurveSegment::Sampled(values) => {
+                // sampled segments are not allowed as first segment, and need breakpoints at both ends
+                let (Some(&start), Some(&end)) = (i.checked_sub(1).and_then(|j| self.breakpoints.get(j)), self.breakpoints.get(i)) else {
+                    return f32::NAN;
+                };
+                if values.is_empty() {
+                    return f32::NAN;
+                }
+                let first = self.eval_segment(i - 1, start);
+                let pos = ((x - start) / (end - start)).clamp(0.0, 1.0) * values.len() as f32;
+                let j = (pos.floor() as usize).min(values.len() - 1);
+                let y0 = if j == 0 { first } else { values[j - 1] };
+                y0 + (values[j] - y0) * (pos - j as f32)
+            }
+        }
+    }
+}
+
+impl ProcessElement {
+    fn try_new(data: &[u8]) -> Result<Self> {
+        let buf = &mut &data[..];
+        let signature = read_be_u32(buf)?;
+        read_be_u32(buf)?;
+        let inputs = read_be_u16(buf)?;
+        let outputs = read_be_u16(buf)?;
+        match signature {
+            CURVE_SET => {
+                let mut curves = Vec::with_capacity(inputs as usize);
+                for _ in 0..inputs {
+                    let offset = read_be_u32(buf)? as usize;
+                    let size = read_be_u32(buf)? as usize;
+                    curves.push(SegmentedCurve::try_new(&mut position(data, offset, size)?)?);
+                }
+                Ok(Self::CurveSet(curves))
+            }
+            MATRIX => {
+                let matrix = read_f32s(buf, inputs as usize * outputs as usize)?;
+                let offsets = read_f32s(buf, outputs as usize)?;
+                Ok(Self::Matrix { inputs, outputs, matrix, offsets })
+            }
+            CLUT => {
+                let grid = read_vec(buf, 16)?;
+                let grid_points = grid[..(inputs as usize).min(16)].to_vec();
+                let n = grid_points.iter().try_fold(outputs as usize, |n, &g| n.checked_mul(g as usize)).ok_or("CLUT size error")?;
+                let values = read_f32s(buf, n)?;
+                Ok(Self::Clut { grid_points, outputs, values })
+            }
+            _ => Ok(Self::Other { signature, inputs, outputs, data: buf.to_vec() }),
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        match self {
+            Self::CurveSet(c) => c.len(),
+            Self::Matrix { inputs, .. } | Self::Other { inputs, .. } => *inputs as usize,
+            Self::Clut { grid_points, .. } => grid_points.len(),
+        }
+    }
+
+    pub fn outputs(&self) -> usize {
+        match self {
+            Self::CurveSet(c) => c.len(),
+            Self::Matrix { outputs, .. } | Self::Clut { outputs, .. } | Self::Other { outputs, .. } => *outputs as usize,
+        }
+    }
+
+    fn to_be_bytes(&self) -> Vec<u8> {
+        let signature = match self {
+            Self::CurveSet(_) => CURVE_SET,
+            Self::Matrix { .. } => MATRIX,
+            Self::Clut { .. } => CLUT,
+            Self::Other { signature, .. } => *signature,
+        };
+        let mut v = signature.to_be_bytes().to_vec();
+        v.extend([0; 4]);
+        v.extend((self.inputs() as u16).to_be_bytes());
+        v.extend((self.outputs() as u16).to_be_bytes());
+        match self {
+            Self::CurveSet(curves) => {
+                let curves: Vec<Vec<u8>> = curves.iter().map(SegmentedCurve::to_be_bytes).collect();
+                let mut offset = v.len() + 8 * curves.len();
+                for c in &curves {
+                    v.extend((offset as u32).to_be_bytes());
+                    v.extend((c.len() as u32).to_be_bytes());
+                    offset += c.len();
+                }
+                curves.iter().for_each(|c| v.extend(c));
+            }
+            Self::Matrix { matrix, offsets, .. } => v.extend(matrix.iter().chain(offsets).flat_map(|x| x.to_be_bytes())),
+            Self::Clut { grid_points, values, .. } => {
+                let mut grid = [0u8; 16];
+                grid[..grid_points.len()].copy_from_slice(grid_points);
+                v.extend(grid);
+                v.extend(values.iter().flat_map(|x| x.to_be_bytes()));
+            }
+            Self::Other { data, .. } => v.extend(data),
+        }
+        v
+    }
+
+    /// Applies the element to input values; elements of other types give NaN values.
+    pub fn eval(&self, input: &[f32]) -> Vec<f32> {
+        match self {
+            Self::CurveSet(curves) => curves.iter().zip(input).map(|(c, &x)| c.eval(x)).collect(),
+            Self::Matrix { inputs, matrix, offsets, .. } => offsets
+                .iter()
+                .enumerate()
+                .map(|(i, o)| o + matrix.get(i * *inputs as usize..).unwrap_or_default().iter().zip(input).map(|(m, x)| m * x).sum::<f32>())
+                .collect(),
+            Self::Clut { grid_points, outputs, values } => {
+                let outputs = *outputs as usize;
+                let mut output = vec![0.0; outputs];
+                // multilinear interpolation, over the corners of the grid cell
+                let mut cell = Vec::with_capacity(grid_points.len());
+                let mut stride = outputs;
+                for (i, &g) in grid_points.iter().enumerate().rev() {
+                    let n = (g as usize).max(2);
+                    let pos = input.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0) * (n - 1) as f32;
+                    let j = (pos.floor() as usize).min(n - 2);
+                    cell.push((j * stride, stride, pos - j as f32));
+                    stride *= g as usize;
+                }
+                for corner in 0..1usize << cell.len() {
+                    let (mut offset, mut weight) = (0, 1.0);
+                    for (k, &(base, stride, f)) in cell.iter().enumerate() {
+                        offset += base;
+                        if corner >> k & 1 == 1 {
+                            offset += stride;
+                            weight *= f;
+                        } else {
+                            weight *= 1.0 - f;
+                        }
+                    }
+                    if weight > 0.0 {
+                        if let Some(v) = values.get(offset..offset + outputs) {
+                            output.iter_mut().zip(v).for_each(|(o, v)| *o += weight * v);
+                        }
+                    }
+                }
+                output
+            }
+            Self::Other { outputs, .. } => vec![f32::NAN; *outputs as usize],
+        }
+    }
+}
+
+impl MultiProcessElements {
+    pub fn new(inputs: u16, outputs: u16) -> Self {
+        Self { inputs, outputs, elements: Vec::new() }
+    }
+
+    pub fn with_element(mut self, element: ProcessElement) -> Self {
+        self.elements.push(element);
+        self
+    }
+
+    /// Parses the tag data, without the type signature and reserved bytes; the offsets in its
+    /// position table are from the start of the tag.
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let data: &[u8] = buf;
+        let inputs = read_be_u16(buf)?;
+        let outputs = read_be_u16(buf)?;
+        let n = read_be_u32(buf)? as usize;
+        let mut elements = Vec::with_capacity(n.min(buf.len() / 8));
+        for _ in 0..n {
+            let offset = (read_be_u32(buf)? as usize).checked_sub(8).ok_or("invalid element offset")?;
+            let size = read_be_u32(buf)? as usize;
+            elements.push(ProcessElement::try_new(position(data, offset, size)?)?);
+        }
+        Ok(Self { inputs, outputs, elements })
+    }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let mut channels = self.inputs as usize;
+        for e in &self.elements {
+            if e.inputs() != channels {
+                return Err("the number of input channels of a process element does not match the previous element".into());
+            }
+            channels = e.outputs();
+        }
+        if channels != self.outputs as usize {
+            return Err("the number of output channels of the last process element does not match the pipeline".into());
+        }
+        let mut v = self.inputs.to_be_bytes().to_vec();
+        v.extend(self.outputs.to_be_bytes());
+        v.extend((self.elements.len() as u32).to_be_bytes());
+        let elements: Vec<Vec<u8>> = self.elements.iter().map(ProcessElement::to_be_bytes).collect();
+        // offsets are from the start of the tag, including its type signature and reserved bytes
+        let mut offset = 8 + v.len() + 8 * elements.len();
+        for e in &elements {
+            v.extend((offset as u32).to_be_bytes());
+            v.extend((e.len() as u32).to_be_bytes());
+            offset += e.len();
+        }
+        elements.iter().for_each(|e| v.extend(e));
+        Ok(v)
+    }
+
+    /// Applies the elements of the pipeline, in sequence, to input values.
+    pub fn eval(&self, input: &[f32]) -> Vec<f32> {
+        self.elements.iter().fold(input.to_vec(), |v, e| e.eval(&v))
+    }
+}
+
+#[test]
+fn test_multi_process_elements() {
+    let gamma = SegmentedCurve {
+        breakpoints: vec![0.0, 1.0, 2.0],
+        segments: vec![
+            CurveSegment::Formula { function: 0, parameters: vec![1.0, 1.0, 0.0, 0.0] },
+            CurveSegment::Formula { function: 0, parameters: vec![2.0, 1.0, 0.0, 0.0] },
+            CurveSegment::Sampled(vec![1.5, 2.0]),
+            CurveSegment::Formula { function: 0, parameters: vec![1.0, 0.0, 0.0, 2.0] },
+        ],
+    };
+    assert_eq!(gamma.eval(0.5), 0.25);
+    assert_eq!(gamma.eval(-1.0), -1.0);
+    // sampled segment, starting at the end value 1.0 of the previous segment
+    assert_eq!(gamma.eval(1.25), 1.25);
+    assert_eq!(gamma.eval(1.5), 1.5);
+    assert_eq!(gamma.eval(3.0), 2.0);
+
+    let mpe = MultiProcessElements::new(3, 3)
+        .with_element(ProcessElement::CurveSet(vec![gamma.clone(), gamma.clone(), gamma]))
+        .with_element(ProcessElement::Matrix { inputs: 3, outputs: 3, matrix: vec![1.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 2.0], offsets: vec![0.0, 0.1, 0.0] })
+        .with_element(ProcessElement::Clut { grid_points: vec![2, 2, 2], outputs: 3, values: (0..8).flat_map(|i| [(i >> 2) as f32, (i >> 1 & 1) as f32, (i & 1) as f32]).collect() });
+    let out = mpe.eval(&[0.5, 0.5, 0.5]);
+    assert!(out.iter().zip([0.25, 0.225, 0.5]).all(|(a, b)| (a - b).abs() < 1E-6), "{out:?}");
+
+    let bytes = mpe.to_be_bytes().unwrap();
+    assert_eq!(MultiProcessElements::try_new(&mut bytes.as_slice()).unwrap(), mpe);
+    assert!(MultiProcessElements::new(3, 1).with_element(ProcessElement::CurveSet(Vec::new())).to_be_bytes().is_err());
+    assert!(MultiProcessElements::try_new(&mut &bytes[..20]).is_err());
+}
@@ -1,7 +1,52 @@
+use crate::color::pcs;
 use crate::common::*;
+use crate::profile::Profile;
+use crate::signatures::colorspace::ColorSpaceSignature;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+/// Encoding of the PCS values of named colors, which follows the PCS of the profile: XYZ values as
+/// u1Fixed15 numbers, or CIELAB values in the legacy 16-bit encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamedColorPcs {
+    XYZ,
+    Lab,
+}
+
+impl NamedColorPcs {
+    pub fn from_profile(profile: &Profile) -> Option<Self> {
+        match profile.pcs.as_ref()?.signature() {
+            ColorSpaceSignature::XYZ => Some(Self::XYZ),
+            ColorSpaceSignature::Lab => Some(Self::Lab),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, v: [f64; 3]) -> [u16; 3] {
+        match self {
+            Self::XYZ => pcs::xyz_to_u16(v),
+            Self::Lab => pcs::lab_to_u16_legacy(v),
+        }
+    }
+
+    /// Decoded PCS values; missing values are zero.
+    pub fn decode(&self, v: &[u16]) -> [f64; 3] {
+        let v = [0, 1, 2].map(|i| v.get(i).copied().unwrap_or(0));
+        match self {
+            Self::XYZ => pcs::u16_to_xyz(v),
+            Self::Lab => pcs::u16_legacy_to_lab(v),
+        }
+    }
+}
+
+/// A named color, with decoded PCS values, and device coordinates in the range from 0.0 to 1.0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedColor<'a> {
+    pub name: &'a str,
+    pub pcs: [f64; 3],
+    pub device: Vec<f64>,
+}
+
+#[derive(Debug, Default, Serialize)]
 pub struct NamedColor2 {
     pub flags: u32,
     pub prefix: String,
@@ -16,7 +61,7 @@ impl NamedColor2{
         let device_coordinates= read_be_u32(buf)? as usize;
         let prefix = read_ascii_string(buf, 32)?;
         let suffix = read_ascii_string(buf, 32)?;
-        let mut colors = Vec::with_capacity(count);
+        let mut colors = Vec::with_capacity(count.min(buf.len() / 32));
         for _ in 0..count {
             let root = read_ascii_string(buf, 32)?;
             let pcs = read_vec_u16(buf, 2*dim_pcs)?;
@@ -30,4 +75,65 @@ impl NamedColor2{
             colors,
         })
     }
+
+    /// An empty named color list, with the prefix and suffix added to each color name.
+    pub fn new(prefix: &str, suffix: &str) -> Self {
+        Self { prefix: prefix.to_owned(), suffix: suffix.to_owned(), ..Default::default() }
+    }
+
+    /// Adds a color, with its PCS values in the given encoding, and its device coordinates, in the range
+    /// from 0.0 to 1.0; all colors should have the same number of device coordinates.
+    pub fn with_color(mut self, name: &str, pcs: NamedColorPcs, values: [f64; 3], device: &[f64]) -> Self {
+        let device = device.iter().map(|&d| (d.clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+        self.colors.push((name.to_owned(), pcs.encode(values).to_vec(), device));
+        self
+    }
+
+    /// Iterates over the colors, with their PCS values decoded using the given encoding.
+    pub fn colors(&self, pcs: NamedColorPcs) -> impl Iterator<Item = NamedColor<'_>> {
+        self.colors.iter().map(move |(name, values, device)| NamedColor {
+            name,
+            pcs: pcs.decode(values),
+            device: device.iter().map(|&d| d as f64 / 65535.0).collect(),
+        })
+    }
+
+    /// Full name of a color, including the prefix and suffix.
+    pub fn full_name(&self, name: &str) -> String {
+        format!("{}{}{}", self.prefix, name, self.suffix)
+    }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let device_coordinates = self.colors.first().map_or(0, |(_, _, device)| device.len());
+        let mut v = Vec::new();
+        v.extend(self.flags.to_be_bytes());
+        v.extend((self.colors.len() as u32).to_be_bytes());
+        v.extend((device_coordinates as u32).to_be_bytes());
+        v.extend(ascii_to_be_bytes(&self.prefix, 32));
+        v.extend(ascii_to_be_bytes(&self.suffix, 32));
+        for (root, pcs, device) in &self.colors {
+            v.extend(ascii_to_be_bytes(root, 32));
+            v.extend(pcs.iter().flat_map(|c| c.to_be_bytes()));
+            v.extend(device.iter().flat_map(|c| c.to_be_bytes()));
+        }
+        v
+    }
+}
+
+#[test]
+fn test_named_color2() {
+    let ncl2 = NamedColor2::new("Acme ", " C")
+        .with_color("Red", NamedColorPcs::Lab, [50.0, 70.0, 40.0], &[0.0, 1.0, 1.0, 0.0])
+        .with_color("Blue", NamedColorPcs::Lab, [30.0, 20.0, -60.0], &[1.0, 0.8, 0.0, 0.0]);
+    let bytes = ncl2.to_be_bytes();
+    assert_eq!(bytes.len(), 76 + 2 * (32 + 6 + 8));
+    let parsed = NamedColor2::try_new(&mut bytes.as_slice(), 3).unwrap();
+    let colors: Vec<NamedColor> = parsed.colors(NamedColorPcs::Lab).collect();
+    assert_eq!(colors[1].name, "Blue");
+    assert_eq!(parsed.full_name(colors[1].name), "Acme Blue C");
+    for (a, b) in colors[1].pcs.iter().zip([30.0, 20.0, -60.0]) {
+        assert!((a - b).abs() < 0.01);
+    }
+    assert_eq!(colors[0].device, [0.0, 1.0, 1.0, 0.0]);
+    assert_eq!(NamedColorPcs::Lab.encode([100.0, 127.0, 0.0]), [0xFF00, 0xFF00, 0x8000]);
 }
@@ -1,7 +1,16 @@
 use crate::common::*;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+/// Apple's native display information ('ndin') tag, with the native chromaticities, and tone
+/// response, of a display, before calibration.
+///
+/// The tag is not documented; its layout, after the tag type and reserved fields, is: the size of the
+/// data, including the size field, as a uInt32Number; the CIE xy chromaticities of the red, green,
+/// and blue primaries, and of the white point, and the red, green, and blue gamma values, as
+/// s15Fixed16Numbers; the number of channels, the number of entries per channel, and the size of
+/// an entry, in bytes, of an optional gamma table, as uInt16Numbers; and the gamma table, with the
+/// entries of each channel in sequence.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct NativeDisplayInfo{
     pub red_phosphor: [f32;2],
     pub green_phosphor: [f32;2],
@@ -20,6 +29,48 @@ pub struct NativeDisplayInfo{
 }
 
 impl NativeDisplayInfo{
+    /// Native display information with the chromaticities of the primaries, and the white point,
+    /// and a gamma value for each channel, without a gamma table.
+    pub fn new(primaries: [[f32;2];3], white_point: [f32;2], gamma: [f32;3]) -> Self {
+        Self {
+            red_phosphor: primaries[0],
+            green_phosphor: primaries[1],
+            blue_phosphor: primaries[2],
+            white_point,
+            red_gamma_value: gamma[0],
+            green_gamma_value: gamma[1],
+            blue_gamma_value: gamma[2],
+            ..Default::default()
+        }
+    }
+
+    pub fn primaries(&self) -> [[f32;2];3] {
+        [self.red_phosphor, self.green_phosphor, self.blue_phosphor]
+    }
+
+    pub fn gamma(&self) -> [f32;3] {
+        [self.red_gamma_value, self.green_gamma_value, self.blue_gamma_value]
+    }
+
+    /// Sets the gamma table, with the same number of 16 bit entries for each channel, in the range
+    /// from 0.0 to 1.0.
+    pub fn set_gamma_table(&mut self, channels: &[Vec<f64>]) {
+        let entries = channels.iter().flatten().map(|&v| (v.clamp(0.0, 1.0) * 65535.0).round() as u16).collect();
+        self.gamma_channels = zero_as_none(channels.len() as u16);
+        self.gamma_data = Some(Lut::Bit16(entries));
+    }
+
+    /// Entries of the gamma table for a channel, scaled to the range from 0.0 to 1.0, if present.
+    pub fn gamma_table(&self, channel: usize) -> Option<Vec<f64>> {
+        let channels = self.gamma_channels? as usize;
+        let values: Vec<f64> = match self.gamma_data.as_ref()? {
+            Lut::Bit8(d) => d.iter().map(|&v| v as f64 / 255.0).collect(),
+            Lut::Bit16(d) => d.iter().map(|&v| v as f64 / 65535.0).collect(),
+        };
+        let n = values.len() / channels;
+        (channel < channels).then(|| values[channel * n..(channel + 1) * n].to_vec())
+    }
+
     pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
         let _size = read_be_u32(buf)?;
         let red_phosphor = [read_s15fixed16(buf)?, read_s15fixed16(buf)?];
@@ -55,4 +106,41 @@ impl NativeDisplayInfo{
             gamma_data,
         })
     }
-}
\ No newline at end of file
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        for value in self.red_phosphor.iter()
+            .chain(&self.green_phosphor)
+            .chain(&self.blue_phosphor)
+            .chain(&self.white_point)
+            .chain([self.red_gamma_value, self.green_gamma_value, self.blue_gamma_value].iter())
+        {
+            v.extend(s15fixed16_to_be_bytes(*value as f64));
+        }
+        let channels = self.gamma_channels.unwrap_or(0);
+        let (entries, entry_size, data): (usize, u16, Vec<u8>) = match &self.gamma_data {
+            None => (0, 0, Vec::new()),
+            Some(Lut::Bit8(d)) => (d.len(), 1, d.clone()),
+            Some(Lut::Bit16(d)) => (d.len(), 2, d.iter().flat_map(|x| x.to_be_bytes()).collect()),
+        };
+        v.extend(channels.to_be_bytes());
+        v.extend((entries.checked_div(channels as usize).unwrap_or(0) as u16).to_be_bytes());
+        v.extend(entry_size.to_be_bytes());
+        v.extend(data);
+        // the size field includes itself
+        let mut ndin = (v.len() as u32 + 4).to_be_bytes().to_vec();
+        ndin.extend(v);
+        ndin
+    }
+}
+#[test]
+fn test_native_display_info() {
+    let mut ndin = NativeDisplayInfo::new([[0.68, 0.32], [0.265, 0.69], [0.15, 0.06]], [0.3127, 0.329], [2.2, 2.2, 2.2]);
+    ndin.set_gamma_table(&[vec![0.0, 0.5, 1.0], vec![0.0, 0.4, 1.0], vec![0.0, 0.6, 1.0]]);
+    let bytes = ndin.to_be_bytes();
+    let parsed = NativeDisplayInfo::try_new(&mut bytes.as_slice()).unwrap();
+    assert_eq!(parsed.gamma_channels, Some(3));
+    assert!((parsed.primaries()[1][1] - 0.69).abs() < 1E-4 && (parsed.gamma()[2] - 2.2).abs() < 1E-4);
+    assert!((parsed.gamma_table(1).unwrap()[1] - 0.4).abs() < 1E-4);
+    assert_eq!(parsed.gamma_table(3), None);
+}
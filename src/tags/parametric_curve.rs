@@ -1,8 +1,9 @@
 
 use crate::common::*;
+use crate::tags::Curve;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Clone, Copy)]
 pub enum ParametricCurve {
     ExponentGamma{g: f32},
     CIE122{g: f32, a: f32, b:f32},
@@ -49,43 +50,105 @@ impl ParametricCurve{
         }
     }
 
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let (function_type, parameters): (u16, Vec<f32>) = match *self {
+            Self::ExponentGamma{g} => (0, vec![g]),
+            Self::CIE122{g, a, b} => (1, vec![g, a, b]),
+            Self::IEC61966_3{g, a, b, c} => (2, vec![g, a, b, c]),
+            Self::IEC61966_2_1{g, a, b, c, d} => (3, vec![g, a, b, c, d]),
+            Self::SevenParameter{g, a, b, c, d, e, f} => (4, vec![g, a, b, c, d, e, f]),
+        };
+        let mut v = Vec::with_capacity(4 + 4 * parameters.len());
+        v.extend(function_type.to_be_bytes());
+        v.extend([0, 0]);
+        for p in parameters {
+            v.extend(s15fixed16_to_be_bytes(p as f64));
+        }
+        v
+    }
+
+    /// Output value for an input value in the range from 0.0 to 1.0, or NaN for other input values.
     pub fn value(&self, x: f32) -> f32 {
-        if x<0.0 || x>1.0 { 
+        if !(0.0..=1.0).contains(&x) {
             f32::NAN
         } else {
-            match *self {
-                Self::ExponentGamma{g} => x.powf(g),
-                Self::CIE122{g,a,b} => {
-                    if x>= -b/a {
-                        (a*x + b).powf(g)
-                    } else {
-                        0.0
-                    }
-                }
-                Self::IEC61966_3{g,a,b, c} => {
-                    if x>= -b/a {
-                        (a*x + b).powf(g) + c
-                    } else {
-                       c 
-                    }
-                }
-                Self::IEC61966_2_1{g,a,b, c, d} => {
-                    if x>= d {
-                        (a*x + b).powf(g)
-                    } else {
-                        c*x
-                    }
-                }
-                Self::SevenParameter{g,a,b, c, d, e, f} => {
-                    if x>= d {
-                        (a*x + b).powf(g) + e
-                    } else {
-                        c*x + f
-                    }
-                }
+            self.eval(x as f64) as f32
+        }
+    }
+
+    /// Output value for an input value, both clipped to the range from 0.0 to 1.0, as required by
+    /// the ICC specification.
+    pub fn eval(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        let p = |v: f32| v as f64;
+        let y = match *self {
+            Self::ExponentGamma { g } => x.powf(p(g)),
+            Self::CIE122 { g, a, b } => {
+                if x >= -p(b) / p(a) { (p(a) * x + p(b)).powf(p(g)) } else { 0.0 }
+            }
+            Self::IEC61966_3 { g, a, b, c } => {
+                if x >= -p(b) / p(a) { (p(a) * x + p(b)).powf(p(g)) + p(c) } else { p(c) }
+            }
+            Self::IEC61966_2_1 { g, a, b, c, d } => {
+                if x >= p(d) { (p(a) * x + p(b)).powf(p(g)) } else { p(c) * x }
             }
+            Self::SevenParameter { g, a, b, c, d, e, f } => {
+                if x >= p(d) { (p(a) * x + p(b)).powf(p(g)) + p(e) } else { p(c) * x + p(f) }
+            }
+        };
+        y.clamp(0.0, 1.0)
+    }
 
+    /// Smallest input value with an output value of at least `y`, for the monotonically increasing
+    /// curves used as tone response curves; 1.0 if the curve does not reach `y`.
+    pub fn eval_inverse(&self, y: f64) -> f64 {
+        let (mut lo, mut hi) = (0.0, 1.0);
+        if self.eval(lo) >= y {
+            return lo;
+        }
+        // bisection, as the inverse of the two segment functions has no closed form for all parameters
+        for _ in 0..64 {
+            let mid = (lo + hi) / 2.0;
+            if self.eval(mid) >= y { hi = mid } else { lo = mid }
+        }
+        hi
+    }
+
+    /// Sampled curve with `n` entries, at equally spaced input values; at least two entries are used.
+    pub fn to_curve(&self, n: usize) -> Curve {
+        let n = n.max(2);
+        Curve((0..n).map(|i| (self.eval(i as f64 / (n - 1) as f64) * 65535.0).round() as u16).collect())
+    }
+
+    /// Parametric equivalent of a sampled curve, for the identity, and gamma, curves; none for tables.
+    pub fn from_curve(curve: &Curve) -> Option<Self> {
+        match curve.0.as_slice() {
+            [] => Some(Self::ExponentGamma { g: 1.0 }),
+            [g] => Some(Self::ExponentGamma { g: *g as f32 / 256.0 }),
+            _ => None,
         }
     }
 }
 
+#[test]
+fn test_eval_inverse() {
+    let curves = [
+        ParametricCurve::ExponentGamma { g: 2.2 },
+        ParametricCurve::CIE122 { g: 2.0, a: 1.2, b: -0.2 },
+        ParametricCurve::IEC61966_3 { g: 2.0, a: 1.2, b: -0.2, c: 0.05 },
+        ParametricCurve::IEC61966_2_1 { g: 2.4, a: 1.0 / 1.055, b: 0.055 / 1.055, c: 1.0 / 12.92, d: 0.04045 },
+        ParametricCurve::SevenParameter { g: 2.4, a: 0.9, b: 0.1, c: 0.1, d: 0.1, e: -0.01, f: 0.001 },
+    ];
+    for curve in curves {
+        for i in 2..10 {
+            let x = i as f64 / 10.0;
+            assert!((curve.eval_inverse(curve.eval(x)) - x).abs() < 1E-9, "{curve:?} {x}");
+        }
+        let table = curve.to_curve(256);
+        assert_eq!(table.0.len(), 256);
+        assert!((table.value(0.3) as f64 - curve.eval(0.3)).abs() < 1E-3);
+    }
+    assert!((curves[3].eval(0.5) - 0.2140).abs() < 1E-4);
+    assert!(curves[0].value(1.5).is_nan());
+    assert!(matches!(ParametricCurve::from_curve(&Curve(vec![0x0233])), Some(ParametricCurve::ExponentGamma { g }) if g == 563.0 / 256.0));
+}
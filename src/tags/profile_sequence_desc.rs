@@ -0,0 +1,147 @@
+use crate::common::*;
+use crate::signatures::{tagtype::TagTypeSignature, technology::TechnologySignature};
+use num::FromPrimitive;
+use serde::Serialize;
+
+use super::{multi_localized_unicode::MultiLocalizedUnicode, text_description::TextDescription, TagData};
+
+// Description of a profile in a device link, or abstract profile, chain. The manufacturer and model
+// descriptions are embedded 'desc' (version 2), or 'mluc' (version 4), tag elements.
+#[derive(Debug, Serialize)]
+pub struct ProfileDescription {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub attributes: u64,
+    pub technology: TechnologySignature,
+    pub manufacturer_description: TagData,
+    pub model_description: TagData,
+}
+
+impl ProfileDescription {
+    pub fn new(manufacturer: &str, model: &str, technology: TechnologySignature) -> Self {
+        Self {
+            manufacturer: (!manufacturer.is_empty()).then(|| manufacturer.to_owned()),
+            model: (!model.is_empty()).then(|| model.to_owned()),
+            attributes: 0,
+            technology,
+            manufacturer_description: TagData::MultiLocalizedUnicode(MultiLocalizedUnicode(Vec::new())),
+            model_description: TagData::MultiLocalizedUnicode(MultiLocalizedUnicode(Vec::new())),
+        }
+    }
+
+    pub fn with_attributes(mut self, attributes: u64) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn with_manufacturer_description(mut self, description: &str) -> Self {
+        self.manufacturer_description = TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(description));
+        self
+    }
+
+    pub fn with_model_description(mut self, description: &str) -> Self {
+        self.model_description = TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::en_us(description));
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProfileSequenceDesc(pub Vec<ProfileDescription>);
+
+// Reads an embedded description tag element; its length follows from its content, as it is not stored.
+fn read_description(buf: &mut &[u8]) -> Result<TagData> {
+    let type_signature = read_be_u32(buf)?;
+    let _reserved = read_be_u32(buf)?;
+    match FromPrimitive::from_u32(type_signature) {
+        Some(TagTypeSignature::TextDescriptionType) => Ok(TagData::TextDescription(TextDescription::try_new(buf)?)),
+        Some(TagTypeSignature::MultiLocalizedUnicodeType) => {
+            // records, followed by the strings, at offsets from the start of the tag element
+            let mut records = *buf;
+            let n = read_be_u32(&mut records)? as usize;
+            let _record_size = read_be_u32(&mut records)?;
+            let mut end = n.checked_mul(12).and_then(|r| r.checked_add(8)).ok_or("pseq description length error")?;
+            for _ in 0..n {
+                let _lang_country = read_be_u32(&mut records)?;
+                let length = read_be_u32(&mut records)? as usize;
+                let offset = (read_be_u32(&mut records)? as usize).checked_sub(8).ok_or("pseq description offset error")?;
+                end = end.max(offset.checked_add(length).ok_or("pseq description length error")?);
+            }
+            let mut data = buf.get(..end).ok_or("pseq description length error")?;
+            *buf = &buf[end..];
+            Ok(TagData::MultiLocalizedUnicode(MultiLocalizedUnicode::try_new(&mut data)?))
+        }
+        _ => Err("unsupported pseq description type".into()),
+    }
+}
+
+impl ProfileSequenceDesc {
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let n = read_be_u32(buf)? as usize;
+        let mut v = Vec::with_capacity(n.min(buf.len() / 20));
+        for _ in 0..n {
+            if buf.len() < 20 {
+                return Err("pseq data too short".into());
+            }
+            v.push(ProfileDescription {
+                manufacturer: read_signature(buf)?,
+                model: read_signature(buf)?,
+                attributes: read_be_u64(buf)?,
+                technology: FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default(),
+                manufacturer_description: read_description(buf)?,
+                model_description: read_description(buf)?,
+            });
+        }
+        Ok(Self(v))
+    }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let mut v = (self.0.len() as u32).to_be_bytes().to_vec();
+        for p in &self.0 {
+            v.extend(signature_to_be_bytes(&p.manufacturer));
+            v.extend(signature_to_be_bytes(&p.model));
+            v.extend(p.attributes.to_be_bytes());
+            v.extend((p.technology as u32).to_be_bytes());
+            for d in [&p.manufacturer_description, &p.model_description] {
+                if !matches!(d, TagData::TextDescription(_) | TagData::MultiLocalizedUnicode(_)) {
+                    return Err("pseq descriptions should be 'desc' or 'mluc' tag data".into());
+                }
+                v.extend((d.type_signature() as u32).to_be_bytes());
+                v.extend([0u8; 4]);
+                v.extend(d.to_be_bytes()?);
+            }
+        }
+        Ok(v)
+    }
+}
+
+#[test]
+fn test_profile_sequence_desc() {
+    let pseq = ProfileSequenceDesc(vec![
+        ProfileDescription::new("APPL", "", TechnologySignature::AMDisplay)
+            .with_manufacturer_description("Apple")
+            .with_model_description("Studio Display"),
+        ProfileDescription {
+            manufacturer: None,
+            model: None,
+            attributes: 1,
+            technology: TechnologySignature::InkJetPrinter,
+            manufacturer_description: TagData::TextDescription(TextDescription {
+                ascii: "Acme".into(),
+                unicode_language_code: 0,
+                unicode: String::new(),
+                scriptcode_code: 0,
+                scriptcode: String::new(),
+            }),
+            model_description: TagData::MultiLocalizedUnicode(MultiLocalizedUnicode(Vec::new())),
+        },
+    ]);
+    let bytes = pseq.to_be_bytes().unwrap();
+    let parsed = ProfileSequenceDesc::try_new(&mut bytes.as_slice()).unwrap();
+    assert_eq!(parsed.to_be_bytes().unwrap(), bytes);
+    assert_eq!(parsed.0[0].manufacturer.as_deref(), Some("APPL"));
+    assert_eq!(parsed.0[1].technology, TechnologySignature::InkJetPrinter);
+    match &parsed.0[0].model_description {
+        TagData::MultiLocalizedUnicode(m) => assert_eq!(m.0[0].2, "Studio Display"),
+        _ => panic!("expected a multi localized unicode description"),
+    }
+}
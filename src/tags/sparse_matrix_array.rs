@@ -0,0 +1,202 @@
+/*!
+  Sparse matrix arrays ('smat', ICC.2), used for bi-spectral reflectance data of fluorescent
+  materials, with a matrix of emission (rows) by excitation (columns) wavelengths for each
+  reflectance sample.
+
+  Each matrix is stored in a fixed size block of `channels` values of the array's encoding type,
+  holding the number of rows and columns, the compressed sparse row indices, the column indices of
+  the non-zero entries, and their values, with any remaining space zero filled.
+*/
+
+use crate::common::*;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SparseMatrixEncoding {
+    UInt8 = 1,
+    UInt16 = 2,
+    Float16 = 3,
+    Float32 = 4,
+}
+
+impl SparseMatrixEncoding {
+    pub fn from_u16(v: u16) -> Option<Self> {
+        match v {
+            1 => Some(Self::UInt8),
+            2 => Some(Self::UInt16),
+            3 => Some(Self::Float16),
+            4 => Some(Self::Float32),
+            _ => None,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::UInt8 => 1,
+            Self::UInt16 | Self::Float16 => 2,
+            Self::Float32 => 4,
+        }
+    }
+
+    fn read(&self, buf: &mut &[u8]) -> Result<f32> {
+        Ok(match self {
+            Self::UInt8 => read_u8(buf)? as f32 / 255.0,
+            Self::UInt16 => read_be_u16(buf)? as f32 / 65535.0,
+            Self::Float16 => read_be_f16(buf)?.to_f32(),
+            Self::Float32 => read_be_f32(buf)?,
+        })
+    }
+
+    fn write(&self, v: f32, out: &mut Vec<u8>) {
+        match self {
+            Self::UInt8 => out.push((v.clamp(0.0, 1.0) * 255.0).round() as u8),
+            Self::UInt16 => out.extend(((v.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes()),
+            Self::Float16 => out.extend(half::f16::from_f32(v).to_be_bytes()),
+            Self::Float32 => out.extend(v.to_be_bytes()),
+        }
+    }
+}
+
+/// A matrix in compressed sparse row format: the non-zero entries of row `r` are at the positions
+/// `row_start[r]..row_start[r+1]` in `columns` and `values`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SparseMatrix {
+    pub rows: u16,
+    pub cols: u16,
+    pub row_start: Vec<u16>,
+    pub columns: Vec<u16>,
+    pub values: Vec<f32>,
+}
+
+impl SparseMatrix {
+    /// Creates a sparse matrix from a dense matrix, in row major order, omitting entries with an
+    /// absolute value not larger than `threshold`.
+    pub fn from_dense(rows: u16, cols: u16, dense: &[f32], threshold: f32) -> Result<Self> {
+        if dense.len() != rows as usize * cols as usize {
+            return Err("dense matrix size does not match its dimensions".into());
+        }
+        let mut m = Self { rows, cols, row_start: vec![0], columns: Vec::new(), values: Vec::new() };
+        for r in 0..rows as usize {
+            for (c, &v) in dense[r * cols as usize..(r + 1) * cols as usize].iter().enumerate() {
+                if v.abs() > threshold {
+                    m.columns.push(c as u16);
+                    m.values.push(v);
+                }
+            }
+            m.row_start.push(m.columns.len().try_into().map_err(|_| "too many sparse matrix entries")?);
+        }
+        Ok(m)
+    }
+
+    pub fn to_dense(&self) -> Vec<f32> {
+        let mut dense = vec![0.0; self.rows as usize * self.cols as usize];
+        for r in 0..self.rows as usize {
+            for i in self.row_start[r] as usize..self.row_start[r + 1] as usize {
+                dense[r * self.cols as usize + self.columns[i] as usize] = self.values[i];
+            }
+        }
+        dense
+    }
+
+    pub fn get(&self, row: u16, col: u16) -> f32 {
+        let (r, c) = (row as usize, col);
+        match self.row_start.get(r..r + 2) {
+            Some(&[start, end]) => (start as usize..end as usize)
+                .find(|&i| self.columns[i] == c)
+                .map_or(0.0, |i| self.values[i]),
+            _ => 0.0,
+        }
+    }
+
+    // Number of bytes used to store the matrix, without padding.
+    fn encoded_len(&self, encoding: SparseMatrixEncoding) -> usize {
+        4 + 2 * self.row_start.len() + (2 + encoding.size()) * self.values.len()
+    }
+
+    fn read(buf: &[u8], encoding: SparseMatrixEncoding) -> Result<Self> {
+        let mut buf = buf;
+        let rows = read_be_u16(&mut buf)?;
+        let cols = read_be_u16(&mut buf)?;
+        let row_start = read_vec_u16(&mut buf, 2 * (rows as usize + 1))?;
+        let n = *row_start.last().unwrap() as usize;
+        if row_start.windows(2).any(|w| w[0] > w[1]) || row_start[0] != 0 {
+            return Err("sparse matrix row index error".into());
+        }
+        let columns = read_vec_u16(&mut buf, 2 * n)?;
+        if columns.iter().any(|&c| c >= cols) {
+            return Err("sparse matrix column index error".into());
+        }
+        let values = (0..n).map(|_| encoding.read(&mut buf)).collect::<Result<_>>()?;
+        Ok(Self { rows, cols, row_start, columns, values })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SparseMatrixArray {
+    pub encoding: SparseMatrixEncoding,
+    /// Size of the storage block of each matrix, in number of values of the encoding type.
+    pub channels: u16,
+    pub matrices: Vec<SparseMatrix>,
+}
+
+impl SparseMatrixArray {
+    /// Creates an array with the smallest storage block size which fits all the matrices.
+    pub fn new(encoding: SparseMatrixEncoding, matrices: Vec<SparseMatrix>) -> Result<Self> {
+        let len = matrices.iter().map(|m| m.encoded_len(encoding)).max().unwrap_or(0);
+        let channels = len.div_ceil(encoding.size()).try_into().map_err(|_| "sparse matrix too large")?;
+        Ok(Self { encoding, channels, matrices })
+    }
+
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let channels = read_be_u16(buf)?;
+        let encoding = SparseMatrixEncoding::from_u16(read_be_u16(buf)?).ok_or("unknown sparse matrix encoding")?;
+        let n = read_be_u32(buf)? as usize;
+        let block = channels as usize * encoding.size();
+        let mut matrices = Vec::with_capacity(n.min(buf.len() / block.max(1)));
+        for _ in 0..n {
+            matrices.push(SparseMatrix::read(&read_vec(buf, block)?, encoding)?);
+        }
+        Ok(Self { encoding, channels, matrices })
+    }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        let block = self.channels as usize * self.encoding.size();
+        let mut v = Vec::with_capacity(8 + block * self.matrices.len());
+        v.extend(self.channels.to_be_bytes());
+        v.extend((self.encoding as u16).to_be_bytes());
+        v.extend((self.matrices.len() as u32).to_be_bytes());
+        for m in &self.matrices {
+            if m.encoded_len(self.encoding) > block {
+                return Err("sparse matrix does not fit in its storage block".into());
+            }
+            let start = v.len();
+            v.extend(m.rows.to_be_bytes());
+            v.extend(m.cols.to_be_bytes());
+            m.row_start.iter().chain(&m.columns).for_each(|i| v.extend(i.to_be_bytes()));
+            m.values.iter().for_each(|&x| self.encoding.write(x, &mut v));
+            v.resize(start + block, 0);
+        }
+        Ok(v)
+    }
+}
+
+#[test]
+fn test_sparse_matrix_array() {
+    // a fluorescent sample: reflection on the diagonal, and emission at longer wavelengths
+    let dense = [
+        0.8, 0.0, 0.0, 0.0,
+        0.1, 0.7, 0.0, 0.0,
+        0.0, 0.2, 0.9, 0.0,
+        0.0, 0.0, 0.0, 0.9,
+    ];
+    let m = SparseMatrix::from_dense(4, 4, &dense, 0.0).unwrap();
+    assert_eq!(m.values.len(), 6);
+    assert_eq!(m.get(2, 1), 0.2);
+    assert_eq!(m.to_dense(), dense);
+
+    let identity = SparseMatrix::from_dense(4, 4, &[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0], 0.0).unwrap();
+    let smat = SparseMatrixArray::new(SparseMatrixEncoding::Float32, vec![m, identity]).unwrap();
+    let bytes = smat.to_be_bytes().unwrap();
+    assert_eq!(bytes.len(), 8 + 2 * smat.channels as usize * 4);
+    assert_eq!(SparseMatrixArray::try_new(&mut bytes.as_slice()).unwrap(), smat);
+}
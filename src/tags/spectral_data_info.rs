@@ -0,0 +1,45 @@
+/*!
+  Spectral data info, the 'sdin' tag of version 5 (iccMAX) profiles, describing the spectral values
+  of a profile: their type, with the number of channels, and their wavelength ranges.
+*/
+
+use crate::common::*;
+use crate::profile::{SpectralColorSpace, WavelengthRange};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SpectralDataInfo {
+    pub space: SpectralColorSpace,
+    pub range: WavelengthRange,
+    /// Wavelength range of the excitation, for bi-spectral values.
+    pub bi_spectral_range: WavelengthRange,
+}
+
+impl SpectralDataInfo {
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        Ok(Self {
+            space: SpectralColorSpace::read(buf)?.unwrap_or(SpectralColorSpace::None),
+            range: WavelengthRange::try_new(buf)?,
+            bi_spectral_range: WavelengthRange::try_new(buf)?,
+        })
+    }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = self.space.to_be_bytes().to_vec();
+        v.extend(self.range.to_be_bytes());
+        v.extend(self.bi_spectral_range.to_be_bytes());
+        v
+    }
+}
+
+#[test]
+fn test_spectral_data_info() {
+    let sdin = SpectralDataInfo {
+        space: SpectralColorSpace::Reflectance(36),
+        range: WavelengthRange::new(380.0, 730.0, 36),
+        bi_spectral_range: WavelengthRange::default(),
+    };
+    let bytes = sdin.to_be_bytes();
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(SpectralDataInfo::try_new(&mut bytes.as_slice()).unwrap(), sdin);
+}
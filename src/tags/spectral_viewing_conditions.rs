@@ -0,0 +1,100 @@
+/*!
+  Spectral viewing conditions, the 'svcn' tag of version 5 (iccMAX) profiles: the colorimetric
+  observer, and the illuminant, used to calculate the colorimetric PCS values from spectral values,
+  with their spectra when custom, and the XYZ values of the illuminant and the surround.
+
+  Wavelength ranges use the iccMAX spectralRange encoding: a start and end wavelength, as half
+  precision floating point numbers, and the number of steps, followed by two reserved bytes.
+*/
+
+use crate::common::*;
+use crate::profile::WavelengthRange;
+use crate::tags::measurement::{StandardIlluminant, StandardObserver};
+use num::FromPrimitive;
+use serde::Serialize;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct SpectralViewingConditions {
+    /// Standard observer, or `Unknown` for a custom observer, with its color matching functions.
+    pub observer: StandardObserver,
+    pub observer_range: WavelengthRange,
+    /// Custom observer color matching functions, with the x̄, ȳ, and z̄ values of each wavelength step.
+    pub observer_functions: Vec<f32>,
+    pub illuminant: StandardIlluminant,
+    /// Correlated color temperature, for black body and daylight illuminants, in Kelvin.
+    pub color_temperature: f32,
+    pub illuminant_range: WavelengthRange,
+    pub illuminant_spectrum: Vec<f32>,
+    pub xyz_illuminant: [f32; 3],
+    pub xyz_surround: [f32; 3],
+}
+
+fn read_f32s(buf: &mut &[u8], n: usize) -> Result<Vec<f32>> {
+    (0..n).map(|_| read_be_f32(buf)).collect()
+}
+
+fn range_to_be_bytes(range: &WavelengthRange) -> Vec<u8> {
+    let mut v = range.to_be_bytes().to_vec();
+    v.extend([0, 0]);
+    v
+}
+
+impl SpectralViewingConditions {
+    pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
+        let observer = FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default();
+        let observer_range = WavelengthRange::try_new(buf)?;
+        read_be_u16(buf)?;
+        let observer_functions = read_f32s(buf, 3 * observer_range.steps())?;
+        let illuminant = FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default();
+        let color_temperature = read_be_f32(buf)?;
+        let illuminant_range = WavelengthRange::try_new(buf)?;
+        read_be_u16(buf)?;
+        let illuminant_spectrum = read_f32s(buf, illuminant_range.steps())?;
+        let xyz = read_f32s(buf, 6)?;
+        Ok(Self {
+            observer,
+            observer_range,
+            observer_functions,
+            illuminant,
+            color_temperature,
+            illuminant_range,
+            illuminant_spectrum,
+            xyz_illuminant: [xyz[0], xyz[1], xyz[2]],
+            xyz_surround: [xyz[3], xyz[4], xyz[5]],
+        })
+    }
+
+    pub fn to_be_bytes(&self) -> Result<Vec<u8>> {
+        if self.observer_functions.len() != 3 * self.observer_range.steps() || self.illuminant_spectrum.len() != self.illuminant_range.steps() {
+            return Err("the number of spectral values does not match the number of wavelength steps".into());
+        }
+        let mut v = Vec::new();
+        v.extend((self.observer as u32).to_be_bytes());
+        v.extend(range_to_be_bytes(&self.observer_range));
+        v.extend(self.observer_functions.iter().flat_map(|x| x.to_be_bytes()));
+        v.extend((self.illuminant as u32).to_be_bytes());
+        v.extend(self.color_temperature.to_be_bytes());
+        v.extend(range_to_be_bytes(&self.illuminant_range));
+        v.extend(self.illuminant_spectrum.iter().flat_map(|x| x.to_be_bytes()));
+        v.extend(self.xyz_illuminant.iter().chain(&self.xyz_surround).flat_map(|x| x.to_be_bytes()));
+        Ok(v)
+    }
+}
+
+#[test]
+fn test_spectral_viewing_conditions() {
+    let svcn = SpectralViewingConditions {
+        observer: StandardObserver::Cie1931TwoDegrees,
+        illuminant: StandardIlluminant::D50,
+        color_temperature: 5000.0,
+        illuminant_range: WavelengthRange::new(380.0, 780.0, 3),
+        illuminant_spectrum: vec![24.5, 102.3, 63.6],
+        xyz_illuminant: crate::illuminants::PCS_ILLUMINANT.map(|v| v as f32),
+        ..Default::default()
+    };
+    let bytes = svcn.to_be_bytes().unwrap();
+    assert_eq!(bytes.len(), 4 + 8 + 4 + 4 + 8 + 12 + 24);
+    assert_eq!(SpectralViewingConditions::try_new(&mut bytes.as_slice()).unwrap(), svcn);
+    assert_eq!(svcn.illuminant_range.wavelengths(), [380.0, 580.0, 780.0]);
+    assert!(SpectralViewingConditions { illuminant_spectrum: Vec::new(), ..svcn }.to_be_bytes().is_err());
+}
@@ -4,7 +4,7 @@ use num::Zero;
 
 // DEPRECATED_IN_MAC_OS_X_VERSION_10_6_AND_LATER
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(default)]
 pub struct TextDescription{
     pub ascii: String,
@@ -19,15 +19,54 @@ pub struct TextDescription{
 }
 
 impl TextDescription {
+    /// A description with an ASCII text, in which other characters are replaced by question marks,
+    /// and the full text as its Unicode description.
+    pub fn new(text: &str) -> Self {
+        let ascii = text.chars().map(|c| if c.is_ascii() && c != '\0' { c } else { '?' }).collect();
+        Self { ascii, unicode: text.to_owned(), ..Default::default() }
+    }
+
+    /// Sets the Unicode description, and its language code, such as `u32::from_be_bytes(*b"enUS")`,
+    /// or zero if not specified.
+    pub fn with_unicode(mut self, language_code: u32, text: &str) -> Self {
+        self.unicode_language_code = language_code;
+        self.unicode = text.to_owned();
+        self
+    }
+
+    /// Sets the ScriptCode description, read by older Macintosh applications, with its script code,
+    /// such as 0 for the Roman script. The text is stored in the Mac OS Roman encoding, with other
+    /// characters replaced by question marks, and is truncated to 66 characters, to fit its field.
+    pub fn with_scriptcode(mut self, code: u16, text: &str) -> Self {
+        self.scriptcode_code = code;
+        self.scriptcode = from_mac_roman(&to_mac_roman(text));
+        self
+    }
+
+    /// The Unicode description, or, if empty, the ASCII description.
+    pub fn text(&self) -> &str {
+        if self.unicode.is_empty() { &self.ascii } else { &self.unicode }
+    }
+
     pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
         let n = read_be_u32(buf)? as usize;
         let ascii = read_ascii_string(buf, n)?;
         let unicode_language_code = read_be_u32(buf)?;
         let m = read_be_u32(buf)? as usize;
         let unicode = read_unicode_string(buf, m)?;
-        let scriptcode_code = read_be_u16(buf)?;
-        let l = read_u8(buf)? as usize;
-        let scriptcode= read_ascii_string(buf, l)?;
+        // the ScriptCode string is stored in a fixed 67 byte field, which some writers truncate, or
+        // leave out; a ScriptCode which does not fit in the tag is read as empty
+        let (scriptcode_code, scriptcode) = if buf.len() < 3 {
+            (0, String::new())
+        } else {
+            let code = read_be_u16(buf)?;
+            let l = read_u8(buf)? as usize;
+            let field = read_vec(buf, buf.len().min(67))?;
+            match field.get(..l.min(67)) {
+                Some(text) => (code, from_mac_roman(text).trim_end_matches(char::from(0)).to_owned()),
+                None => (code, String::new()),
+            }
+        };
         Ok(TextDescription{
             ascii,
             unicode_language_code,
@@ -36,4 +75,78 @@ impl TextDescription {
             scriptcode
         })
     }
-}
\ No newline at end of file
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        v.extend((self.ascii.len() as u32 + 1).to_be_bytes());
+        v.extend(self.ascii.as_bytes());
+        v.push(0);
+        v.extend(self.unicode_language_code.to_be_bytes());
+        if self.unicode.is_empty() {
+            v.extend(0u32.to_be_bytes());
+        } else {
+            let unicode: Vec<u16> = self.unicode.encode_utf16().chain(std::iter::once(0)).collect();
+            v.extend((unicode.len() as u32).to_be_bytes());
+            v.extend(unicode.iter().flat_map(|c| c.to_be_bytes()));
+        }
+        v.extend(self.scriptcode_code.to_be_bytes());
+        let mut scriptcode = to_mac_roman(&self.scriptcode);
+        v.push(if scriptcode.is_empty() { 0 } else { scriptcode.len() as u8 + 1 });
+        scriptcode.resize(67, 0);
+        v.extend(scriptcode);
+        v
+    }
+}
+
+// Characters of the Mac OS Roman encoding from 0x80 to 0xFF; with the Roman script the ScriptCode
+// field is decoded exactly, and other scripts are kept unchanged, as the mapping is one to one.
+const MAC_ROMAN: [char; 128] = [
+    'Ä', 'Å', 'Ç', 'É', 'Ñ', 'Ö', 'Ü', 'á', 'à', 'â', 'ä', 'ã', 'å', 'ç', 'é', 'è',
+    'ê', 'ë', 'í', 'ì', 'î', 'ï', 'ñ', 'ó', 'ò', 'ô', 'ö', 'õ', 'ú', 'ù', 'û', 'ü',
+    '†', '°', '¢', '£', '§', '•', '¶', 'ß', '®', '©', '™', '´', '¨', '≠', 'Æ', 'Ø',
+    '∞', '±', '≤', '≥', '¥', 'µ', '∂', '∑', '∏', 'π', '∫', 'ª', 'º', 'Ω', 'æ', 'ø',
+    '¿', '¡', '¬', '√', 'ƒ', '≈', '∆', '«', '»', '…', '\u{a0}', 'À', 'Ã', 'Õ', 'Œ', 'œ',
+    '–', '—', '“', '”', '‘', '’', '÷', '◊', 'ÿ', 'Ÿ', '⁄', '€', '‹', '›', 'ﬁ', 'ﬂ',
+    '‡', '·', '‚', '„', '‰', 'Â', 'Ê', 'Á', 'Ë', 'È', 'Í', 'Î', 'Ï', 'Ì', 'Ó', 'Ô',
+    '\u{f8ff}', 'Ò', 'Ú', 'Û', 'Ù', 'ı', 'ˆ', '˜', '¯', '˘', '˙', '˚', '¸', '˝', '˛', 'ˇ',
+];
+
+fn from_mac_roman(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| if b < 0x80 { b as char } else { MAC_ROMAN[b as usize - 0x80] }).collect()
+}
+
+// Mac OS Roman encoded text, of at most 66 bytes, without null characters.
+fn to_mac_roman(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|&c| c != '\0')
+        .map(|c| if c.is_ascii() { c as u8 } else { MAC_ROMAN.iter().position(|&m| m == c).map_or(b'?', |i| i as u8 + 0x80) })
+        .take(66)
+        .collect()
+}
+
+#[test]
+fn test_text_description() {
+    let description = TextDescription::new("Café").with_unicode(u32::from_be_bytes(*b"frFR"), "Café crème").with_scriptcode(0, "Café™ 日本");
+    assert_eq!(description.ascii, "Caf?");
+    assert_eq!(description.scriptcode, "Café™ ??");
+    let v = description.to_be_bytes();
+    let parsed = TextDescription::try_new(&mut v.as_slice()).unwrap();
+    assert_eq!((parsed.unicode_language_code, parsed.text()), (u32::from_be_bytes(*b"frFR"), "Café crème"));
+    assert_eq!((parsed.scriptcode_code, parsed.scriptcode.as_str()), (0, "Café™ ??"));
+    assert_eq!(parsed.to_be_bytes(), v);
+    // the ScriptCode field has a fixed size
+    assert_eq!(v.len(), 4 + 5 + 4 + 4 + 2 * 11 + 2 + 1 + 67);
+}
+#[test]
+fn test_truncated_text_description() {
+    let v = TextDescription::new("sRGB").with_scriptcode(0, "sRGB IEC61966-2.1").to_be_bytes();
+    let header = 4 + 5 + 4 + 4 + 2 * 5;
+    // ScriptCode field shortened, but still holding its text
+    let parsed = TextDescription::try_new(&mut &v[..header + 3 + 20]).unwrap();
+    assert_eq!((parsed.text(), parsed.scriptcode.as_str()), ("sRGB", "sRGB IEC61966-2.1"));
+    // ScriptCode text cut off, or the ScriptCode left out
+    for len in [header + 3 + 10, header + 3, header + 1, header] {
+        let parsed = TextDescription::try_new(&mut &v[..len]).unwrap();
+        assert_eq!((parsed.text(), parsed.scriptcode.as_str()), ("sRGB", ""));
+    }
+}
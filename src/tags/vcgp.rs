@@ -13,4 +13,8 @@ impl Vcgp {
             tbd: read_vec(buf, buf.len())?
         })
     }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.tbd.clone()
+    }
 }
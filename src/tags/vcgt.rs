@@ -40,7 +40,7 @@ impl VcgtTable {
 }
 
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 pub struct VcgtFormula {
     pub red_gamma: f32,
     pub red_min: f32,
@@ -76,7 +76,128 @@ impl Vcgt {
         match vcgt_type {
             0 => Ok(Self::Table(VcgtTable::try_new(buf)?)),
             1 => Ok(Self::Formula(VcgtFormula::try_new(buf)?)),
-            _ => todo!(),
+            _ => Err("unknown vcgt type".into()),
+        }
+    }
+
+    /// Formula type gamma table, with the gamma, minimum, and maximum output values, in the range
+    /// from 0.0 to 1.0, for the red, green, and blue channels.
+    pub fn formula(red: [f32; 3], green: [f32; 3], blue: [f32; 3]) -> Self {
+        Self::Formula(VcgtFormula {
+            red_gamma: red[0],
+            red_min: red[1],
+            red_max: red[2],
+            green_gamma: green[0],
+            green_min: green[1],
+            green_max: green[2],
+            blue_gamma: blue[0],
+            blue_min: blue[1],
+            blue_max: blue[2],
+        })
+    }
+
+    /// Identity gamma table.
+    pub fn identity() -> Self {
+        Self::formula([1.0, 0.0, 1.0], [1.0, 0.0, 1.0], [1.0, 0.0, 1.0])
+    }
+
+    /// Table type gamma table, with 16-bit entries, and one, or three, channels of equal length.
+    pub fn table(channels: &[Vec<u16>]) -> Result<Self> {
+        let n = channels.first().map_or(0, Vec::len);
+        if !(channels.len() == 1 || channels.len() == 3) || n < 2 || channels.iter().any(|c| c.len() != n) {
+            return Err("vcgt tables should have one or three channels, with equal numbers of entries".into());
         }
+        Ok(Self::Table(VcgtTable {
+            channels: channels.len() as u16,
+            entry_count: n.try_into().map_err(|_| "too many vcgt table entries")?,
+            data: Lut::Bit16(channels.concat()),
+        }))
     }
+
+    /// Output value of a channel, with 0 for red, 1 for green, and 2 for blue, for an input value in the
+    /// range from 0.0 to 1.0; tables are linearly interpolated, and single channel tables apply to all
+    /// channels.
+    pub fn value(&self, channel: usize, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            Self::Formula(f) => {
+                let (gamma, min, max) = match channel {
+                    0 => (f.red_gamma, f.red_min, f.red_max),
+                    1 => (f.green_gamma, f.green_min, f.green_max),
+                    _ => (f.blue_gamma, f.blue_min, f.blue_max),
+                };
+                min as f64 + (max - min) as f64 * x.powf(gamma as f64)
+            }
+            Self::Table(t) => {
+                let n = t.entry_count as usize;
+                let start = if t.channels > 1 { channel.min(t.channels as usize - 1) * n } else { 0 };
+                let entry = |i: usize| match &t.data {
+                    Lut::Bit8(d) => d.get(start + i).map_or(0.0, |&v| v as f64 / 255.0),
+                    Lut::Bit16(d) => d.get(start + i).map_or(0.0, |&v| v as f64 / 65535.0),
+                };
+                if n < 2 {
+                    return x;
+                }
+                let pos = x * (n - 1) as f64;
+                let i = (pos.floor() as usize).min(n - 2);
+                entry(i) + (entry(i + 1) - entry(i)) * (pos - i as f64)
+            }
+        }
+    }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::new();
+        match self {
+            Self::Table(t) => {
+                v.extend(0u32.to_be_bytes());
+                v.extend(t.channels.to_be_bytes());
+                v.extend(t.entry_count.to_be_bytes());
+                match &t.data {
+                    Lut::Bit8(d) => {
+                        v.extend(1u16.to_be_bytes());
+                        v.extend(d);
+                    }
+                    Lut::Bit16(d) => {
+                        v.extend(2u16.to_be_bytes());
+                        v.extend(d.iter().flat_map(|x| x.to_be_bytes()));
+                    }
+                }
+            }
+            Self::Formula(f) => {
+                v.extend(1u32.to_be_bytes());
+                for p in [
+                    f.red_gamma, f.red_min, f.red_max,
+                    f.green_gamma, f.green_min, f.green_max,
+                    f.blue_gamma, f.blue_min, f.blue_max,
+                ] {
+                    v.extend(s15fixed16_to_be_bytes(p as f64));
+                }
+            }
+        }
+        v
+    }
+}
+
+#[test]
+fn test_vcgt() {
+    let formula = Vcgt::formula([1.0, 0.0, 0.9], [2.0, 0.0, 1.0], [1.0, 0.1, 1.0]);
+    assert!((formula.value(0, 1.0) - 0.9).abs() < 1E-6);
+    assert!((formula.value(1, 0.5) - 0.25).abs() < 1E-6);
+    assert!((formula.value(2, 0.0) - 0.1).abs() < 1E-6);
+    let bytes = formula.to_be_bytes();
+    let parsed = Vcgt::try_new(&mut bytes.as_slice()).unwrap();
+    assert!((parsed.value(0, 1.0) - 0.9).abs() < 1E-4);
+
+    let ramp: Vec<u16> = (0..=255).map(|i| i * 257).collect();
+    let half: Vec<u16> = ramp.iter().map(|v| v / 2).collect();
+    assert!(Vcgt::table(&[ramp.clone(), half.clone()]).is_err());
+    let table = Vcgt::table(&[ramp.clone(), half, ramp]).unwrap();
+    let bytes = table.to_be_bytes();
+    let parsed = Vcgt::try_new(&mut bytes.as_slice()).unwrap();
+    assert!((parsed.value(1, 1.0) - 0.5).abs() < 1E-4);
+    assert!((parsed.value(2, 0.5) - 0.5).abs() < 1E-6);
+    assert!(Vcgt::try_new(&mut &[0, 0, 0, 2][..]).is_err());
+
+    let profile = crate::profile::standards::srgb().with_vcgt(|v| *v = parsed);
+    assert!((profile.vcgt().unwrap().value(1, 1.0) - 0.5).abs() < 1E-4);
 }
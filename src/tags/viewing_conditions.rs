@@ -6,7 +6,9 @@ use num::FromPrimitive;
 
 // DEPRECATED_IN_MAC_OS_X_VERSION_10_6_AND_LATER
 
-#[derive(Debug, Serialize)]
+/// Viewing conditions of the 'view' tag: the absolute XYZ values, in cd/m², of the illuminant and
+/// the surround, and the type of illuminant.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct ViewingConditions {
     pub xyz_illuminant: [f64;3],
     pub xyz_surround: [f64;3],
@@ -14,6 +16,10 @@ pub struct ViewingConditions {
 }
 
 impl ViewingConditions {
+    pub fn new(xyz_illuminant: [f64;3], xyz_surround: [f64;3], illuminant: StandardIlluminant) -> Self {
+        Self { xyz_illuminant, xyz_surround, illuminant }
+    }
+
     pub fn try_new(buf: &mut &[u8]) -> Result<Self> {
         Ok(ViewingConditions{
             xyz_illuminant: read_xyz(buf)?.unwrap_or([0.0, 0.0, 0.0]),
@@ -21,4 +27,12 @@ impl ViewingConditions {
             illuminant: FromPrimitive::from_u32(read_be_u32(buf)?).unwrap_or_default(),
         })
     }
+
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let mut v = Vec::with_capacity(28);
+        v.extend(xyz_to_be_bytes(Some(self.xyz_illuminant)));
+        v.extend(xyz_to_be_bytes(Some(self.xyz_surround)));
+        v.extend((self.illuminant as u32).to_be_bytes());
+        v
+    }
 }
@@ -0,0 +1,770 @@
+/*!
+  Color transforms between profiles, through the XYZ profile connection space, for matrix/TRC RGB
+  profiles, gray TRC profiles, profiles with 'A2Bx', and 'B2Ax', lookup tables, and profiles with
+  floating point multi process element 'D2Bx' and 'B2Dx' tags; a [PipelineTransform] combines the
+  pipelines of two profiles directly.
+
+  Device values are in the range from 0.0 to 1.0; PCS XYZ values are relative to the PCS illuminant,
+  with a luminance Y of 1.0 for the media white point. For the absolute colorimetric intent, XYZ
+  values are scaled by the ratio of the media white points of the two profiles. Lookup tables encode
+  their PCS values in the 16-bit encodings of the [pcs](crate::color::pcs) module, with the legacy
+  CIELAB encoding for lut16Type tags, and CIELAB values are converted to XYZ values.
+
+  The transform of each profile is selected as in the ICC specification: a 'D2Bx', or 'B2Dx',
+  pipeline for the rendering intent, an 'A2Bx', or 'B2Ax', lookup table for the intent, the
+  perceptual pipeline, or lookup table, and finally the matrix/TRC tags; see [TransformPath::select],
+  and [DeviceTransform].
+*/
+
+use crate::adaptation::{inverse, mul_vec};
+use crate::color::pcs::{lab_to_normalized, lab_to_normalized_legacy, normalized_legacy_to_lab, normalized_to_lab, normalized_to_xyz, xyz_to_normalized};
+use crate::common::Result;
+use crate::gamut::xyz_to_lab;
+use crate::illuminants::PCS_ILLUMINANT;
+use crate::profile::fit::lab_to_xyz;
+use crate::profile::{Class, Profile, RenderingIntent};
+use crate::signatures::{colorspace::ColorSpaceSignature, tag::TagSignature};
+use crate::tags::{lut::Lut, multi_process_elements::MultiProcessElements, parametric_curve::ParametricCurve, Curve, TagData, XYZ};
+
+/// Tone response curve of a device channel.
+#[derive(Debug, Clone)]
+pub enum Trc {
+    Curve(Curve),
+    Parametric(ParametricCurve),
+}
+
+impl Trc {
+    pub(crate) fn from_tag_data(data: &TagData) -> Option<Self> {
+        match data {
+            TagData::Curve(c) => Some(Trc::Curve(c.clone())),
+            TagData::ParametricCurve(p) => Some(Trc::Parametric(*p)),
+            _ => None,
+        }
+    }
+
+    pub fn to_tag_data(&self) -> TagData {
+        match self {
+            Trc::Curve(c) => TagData::Curve(c.clone()),
+            Trc::Parametric(p) => TagData::ParametricCurve(*p),
+        }
+    }
+
+    pub fn eval(&self, x: f64) -> f64 {
+        match self {
+            Trc::Curve(c) => c.value(x as f32) as f64,
+            Trc::Parametric(p) => p.eval(x),
+        }
+    }
+
+    /// Smallest input value with an output value of at least `y`, for increasing curves.
+    pub fn eval_inverse(&self, y: f64) -> f64 {
+        match self {
+            Trc::Parametric(p) => p.eval_inverse(y),
+            Trc::Curve(curve) => {
+                let (mut lo, mut hi) = (0.0f64, 1.0f64);
+                if curve.value(0.0) as f64 >= y {
+                    return 0.0;
+                }
+                for _ in 0..40 {
+                    let mid = (lo + hi) / 2.0;
+                    if curve.value(mid as f32) as f64 >= y { hi = mid } else { lo = mid }
+                }
+                hi
+            }
+        }
+    }
+}
+
+/// Device model of a matrix/TRC, or gray TRC, profile: the tone response curves, and the matrix
+/// with the colorants, or the PCS illuminant for gray profiles, in its columns.
+#[derive(Debug, Clone)]
+pub struct MatrixTrc {
+    pub trcs: Vec<Trc>,
+    pub matrix: [[f64; 3]; 3],
+    pub white_point: [f64; 3],
+}
+
+impl MatrixTrc {
+    pub fn from_profile(profile: &Profile) -> Result<Self> {
+        let data = |signature: TagSignature| profile.tag_data(&signature);
+        let trc = |signature: TagSignature| {
+            data(signature.clone()).and_then(Trc::from_tag_data).ok_or_else(|| format!("profile without a {:?} curve", signature))
+        };
+        let white_point = media_white_point(profile);
+        if data(TagSignature::RedMatrixColumnTag).is_some() {
+            let mut matrix = [[0.0; 3]; 3];
+            for (i, signature) in [TagSignature::RedMatrixColumnTag, TagSignature::GreenMatrixColumnTag, TagSignature::BlueMatrixColumnTag]
+                .into_iter()
+                .enumerate()
+            {
+                let Some(TagData::XYZ(XYZ(v))) = data(signature.clone()) else {
+                    return Err(format!("profile without a {:?} colorant", signature).into());
+                };
+                let column = v.first().ok_or("empty colorant tag")?;
+                (0..3).for_each(|j| matrix[j][i] = column[j]);
+            }
+            let trcs = vec![trc(TagSignature::RedTRCTag)?, trc(TagSignature::GreenTRCTag)?, trc(TagSignature::BlueTRCTag)?];
+            Ok(Self { trcs, matrix, white_point })
+        } else if data(TagSignature::GrayTRCTag).is_some() {
+            let [x, y, z] = PCS_ILLUMINANT;
+            let matrix = [[x, 0.0, 0.0], [y, 0.0, 0.0], [z, 0.0, 0.0]];
+            Ok(Self { trcs: vec![trc(TagSignature::GrayTRCTag)?], matrix, white_point })
+        } else {
+            Err("only matrix/TRC, and gray TRC, profiles are supported".into())
+        }
+    }
+
+    pub fn channels(&self) -> usize {
+        self.trcs.len()
+    }
+
+    /// Black point, for black point compensation: the PCS XYZ values of the device black, made
+    /// neutral by keeping only its lightness, or zero if its lightness L* exceeds 50.
+    pub fn black_point(&self) -> [f64; 3] {
+        neutral_black(self.to_xyz(&vec![0.0; self.channels()]))
+    }
+
+    /// Linear device values, after the tone response curves, padded with zeros to three channels.
+    pub fn linear(&self, device: &[f64]) -> [f64; 3] {
+        let mut v = [0.0; 3];
+        self.trcs.iter().zip(device).zip(&mut v).for_each(|((trc, &x), v)| *v = trc.eval(x));
+        v
+    }
+
+    pub fn to_xyz(&self, device: &[f64]) -> [f64; 3] {
+        mul_vec(self.matrix, self.linear(device))
+    }
+
+    /// Device values for PCS XYZ values, clipped to the range from 0.0 to 1.0; gray values are
+    /// calculated from the luminance Y.
+    pub fn from_xyz(&self, xyz: [f64; 3]) -> Vec<f64> {
+        let linear = if self.channels() == 1 { [xyz[1] / self.matrix[1][0], 0.0, 0.0] } else { mul_vec(inverse(self.matrix), xyz) };
+        self.trcs.iter().zip(linear).map(|(trc, v)| trc.eval_inverse(v.clamp(0.0, 1.0))).collect()
+    }
+}
+
+/// Transform of a profile, to or from the profile connection space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformPath {
+    /// A floating point multi process element 'D2Bx', or 'B2Dx', tag.
+    Pipeline(TagSignature),
+    /// A lookup table 'A2Bx', or 'B2Ax', tag.
+    Lut(TagSignature),
+    /// The colorant, and tone response curve, tags of a matrix/TRC, or gray TRC, profile.
+    MatrixTrc,
+}
+
+impl TransformPath {
+    /// Transform of a profile for a rendering intent, from device values to PCS values, or, if not
+    /// `to_pcs`, from PCS values to device values, in the order of preference of the ICC
+    /// specification. The absolute colorimetric intent uses the media relative lookup tables.
+    pub fn select(profile: &Profile, intent: RenderingIntent, to_pcs: bool) -> Option<Self> {
+        use TagSignature::*;
+        let (pipelines, luts) = if to_pcs {
+            ([DToB0Tag, DToB1Tag, DToB2Tag, DToB3Tag], [AToB0Tag, AToB1Tag, AToB2Tag, AToB1Tag])
+        } else {
+            ([BToD0Tag, BToD1Tag, BToD2Tag, BToD3Tag], [BToA0Tag, BToA1Tag, BToA2Tag, BToA1Tag])
+        };
+        let i = intent as usize;
+        let candidates = [Self::Pipeline(pipelines[i].clone()), Self::Lut(luts[i].clone()), Self::Pipeline(pipelines[0].clone()), Self::Lut(luts[0].clone())];
+        candidates
+            .into_iter()
+            .find(|path| match path {
+                Self::Pipeline(signature) => matches!(profile.tag_data(signature), Some(TagData::MultiProcessElements(_))),
+                Self::Lut(signature) => profile.contains_tag(signature),
+                Self::MatrixTrc => false,
+            })
+            .or_else(|| MatrixTrc::from_profile(profile).ok().map(|_| Self::MatrixTrc))
+    }
+}
+
+/// Transform path preferred by a profile, and the path used, which is the matrix/TRC path for lookup
+/// tables which can not be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSelection {
+    pub preferred: TransformPath,
+    pub used: TransformPath,
+}
+
+// Model of a device, in one direction, for lookup tables, and pipelines.
+#[derive(Debug, Clone)]
+enum Model {
+    MatrixTrc(MatrixTrc),
+    Lut(Lut),
+    Pipeline(MultiProcessElements),
+}
+
+/// Transform of a single profile, from device values to PCS XYZ values, or from PCS XYZ values to
+/// device values, through the path selected by [TransformPath::select], in the direction given when
+/// it is created; CIELAB connection space values are converted to XYZ values. A lookup table which can not be decoded, such as a table
+/// with a different number of grid points for each channel, is replaced by the matrix/TRC tags of
+/// the profile, if present, as recorded in its [PathSelection].
+#[derive(Debug, Clone)]
+pub struct DeviceTransform {
+    model: Model,
+    // CIELAB connection space
+    lab: bool,
+    // device values of the darkest color
+    black: Vec<f64>,
+    // direction, from device values to PCS values, or the reverse
+    to_pcs: bool,
+    selection: PathSelection,
+}
+
+impl DeviceTransform {
+    pub fn new(profile: &Profile, intent: RenderingIntent, to_pcs: bool) -> Result<Self> {
+        if matches!(profile.class, Class::DeviceLink) {
+            return Err("device link profiles do not transform to, or from, the profile connection space".into());
+        }
+        let preferred = TransformPath::select(profile, intent, to_pcs)
+            .ok_or("only matrix/TRC, gray TRC, lookup table, and multi process element profiles are supported")?;
+        let (model, used) = match &preferred {
+            TransformPath::Pipeline(signature) => {
+                let Some(TagData::MultiProcessElements(m)) = profile.tag_data(signature) else {
+                    return Err(format!("profile without a {:?} multi process element tag", signature).into());
+                };
+                (Model::Pipeline(m.clone()), preferred.clone())
+            }
+            TransformPath::Lut(signature) => {
+                let data = profile.tag_data(signature).ok_or_else(|| format!("profile without a {:?} tag", signature))?;
+                match (Lut::from_tag_data(data), MatrixTrc::from_profile(profile)) {
+                    (Ok(lut), _) => (Model::Lut(lut), preferred.clone()),
+                    (Err(_), Ok(model)) => (Model::MatrixTrc(model), TransformPath::MatrixTrc),
+                    (Err(e), Err(_)) => return Err(format!("the {:?} lookup table can not be used: {}", signature, e).into()),
+                }
+            }
+            TransformPath::MatrixTrc => (Model::MatrixTrc(MatrixTrc::from_profile(profile)?), TransformPath::MatrixTrc),
+        };
+        let (inputs, outputs) = match &model {
+            Model::MatrixTrc(m) => (m.channels(), 3),
+            Model::Lut(lut) if to_pcs => (lut.inputs, lut.outputs),
+            Model::Lut(lut) => (lut.outputs, lut.inputs),
+            Model::Pipeline(m) if to_pcs => (m.inputs as usize, m.outputs as usize),
+            Model::Pipeline(m) => (m.outputs as usize, m.inputs as usize),
+        };
+        if outputs != 3 {
+            return Err("transforms with a PCS of three channels are supported only".into());
+        }
+        let lab = profile.pcs.map(|p| p.signature()) == Some(ColorSpaceSignature::Lab);
+        // subtractive devices have their darkest color at the largest device values
+        let subtractive = matches!(profile.colorspace.map(|c| c.signature()), Some(ColorSpaceSignature::CMY | ColorSpaceSignature::CMYK));
+        let black = vec![if subtractive && !matches!(model, Model::MatrixTrc(_)) { 1.0 } else { 0.0 }; inputs];
+        Ok(Self { model, lab, black, to_pcs, selection: PathSelection { preferred, used } })
+    }
+
+    // Transform of the 'gamt' tag of a profile, from PCS XYZ values to a single value which is zero
+    // for colors within the gamut of the device, if the profile has a gamut tag which can be decoded.
+    fn gamut(profile: &Profile) -> Option<Self> {
+        let lut = Lut::from_tag_data(profile.tag_data(&TagSignature::GamutTag)?).ok().filter(|lut| lut.inputs == 3 && lut.outputs == 1)?;
+        let lab = profile.pcs.map(|p| p.signature()) == Some(ColorSpaceSignature::Lab);
+        let path = TransformPath::Lut(TagSignature::GamutTag);
+        Some(Self { model: Model::Lut(lut), lab, black: vec![0.0], to_pcs: false, selection: PathSelection { preferred: path.clone(), used: path } })
+    }
+
+    pub fn selection(&self) -> &PathSelection {
+        &self.selection
+    }
+
+    /// Model of the device, for matrix/TRC transforms.
+    pub fn matrix_trc(&self) -> Option<&MatrixTrc> {
+        match &self.model {
+            Model::MatrixTrc(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Number of device channels.
+    pub fn channels(&self) -> usize {
+        self.black.len()
+    }
+
+    /// Whether the transform is from device values to PCS values, or, if not, from PCS values to
+    /// device values.
+    pub fn is_to_pcs(&self) -> bool {
+        self.to_pcs
+    }
+
+    /// PCS XYZ values of device values, or `None` for a transform from the PCS, or if the number of
+    /// device values differs from the number of device channels.
+    pub fn to_xyz(&self, device: &[f64]) -> Option<[f64; 3]> {
+        (self.to_pcs && device.len() == self.channels()).then(|| self.xyz(device))
+    }
+
+    /// Device values of PCS XYZ values, in the range from 0.0 to 1.0 for matrix/TRC, and lookup
+    /// table, transforms, or `None` for a transform to the PCS.
+    pub fn to_device(&self, xyz: [f64; 3]) -> Option<Vec<f64>> {
+        (!self.to_pcs).then(|| self.device(xyz))
+    }
+
+    /// Black point, for black point compensation, of a transform to the PCS: the PCS XYZ values of
+    /// the device black, made neutral by keeping only its lightness, or zero if its lightness L*
+    /// exceeds 50. The device black has the largest device values for CMY, and CMYK, lookup tables,
+    /// and pipelines, and zero device values otherwise. Returns `None` for a transform from the PCS.
+    pub fn black_point(&self) -> Option<[f64; 3]> {
+        self.to_xyz(&self.black).map(neutral_black)
+    }
+
+    // PCS XYZ values of device values, for a transform to the PCS, with missing output values as
+    // zero.
+    fn xyz(&self, device: &[f64]) -> [f64; 3] {
+        let v = |v: &[f64]| [0, 1, 2].map(|i| v.get(i).copied().unwrap_or(0.0));
+        match &self.model {
+            Model::MatrixTrc(m) => m.to_xyz(device),
+            Model::Lut(lut) => {
+                let v = v(&lut.eval(device));
+                match (self.lab, lut.legacy_lab) {
+                    (false, _) => normalized_to_xyz(v),
+                    (true, false) => lab_to_xyz(normalized_to_lab(v)),
+                    (true, true) => lab_to_xyz(normalized_legacy_to_lab(v)),
+                }
+            }
+            Model::Pipeline(m) => {
+                let input: Vec<f32> = device.iter().map(|&v| v as f32).collect();
+                let v = v(&m.eval(&input).into_iter().map(f64::from).collect::<Vec<_>>());
+                if self.lab { lab_to_xyz(v) } else { v }
+            }
+        }
+    }
+
+    // Device values of PCS XYZ values, for a transform from the PCS.
+    fn device(&self, xyz: [f64; 3]) -> Vec<f64> {
+        match &self.model {
+            Model::MatrixTrc(m) => m.from_xyz(xyz),
+            Model::Lut(lut) => {
+                let v = match (self.lab, lut.legacy_lab) {
+                    (false, _) => xyz_to_normalized(xyz),
+                    (true, false) => lab_to_normalized(xyz_to_lab(xyz)),
+                    (true, true) => lab_to_normalized_legacy(xyz_to_lab(xyz)),
+                };
+                lut.eval(&v)
+            }
+            Model::Pipeline(m) => {
+                let v = if self.lab { xyz_to_lab(xyz) } else { xyz };
+                m.eval(&v.map(|v| v as f32)).into_iter().map(f64::from).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Transform {
+    src: DeviceTransform,
+    dst: DeviceTransform,
+    // the transform of the destination to the PCS, for gamut checks, and its black point; optional,
+    // as a destination only needs a transform from the PCS, such as a profile with only 'B2Ax' tags
+    round_trip: Option<DeviceTransform>,
+    // the gamut tag of the destination, for gamut checks
+    gamut: Option<DeviceTransform>,
+    // scaling of PCS values, for the absolute colorimetric intent, and black point compensation
+    scale: [f64; 3],
+    // offset of PCS values, for black point compensation
+    offset: [f64; 3],
+    intent: RenderingIntent,
+    selection: [PathSelection; 2],
+}
+
+impl Transform {
+    /// Transform between two profiles, through the transform of each profile selected by
+    /// [TransformPath::select]: a 'D2Bx', or 'B2Dx', pipeline, an 'A2Bx', or 'B2Ax', lookup table,
+    /// or the matrix/TRC tags, with the PCS values of both profiles converted to XYZ values. For the
+    /// perceptual, and saturation, intents, the [PERCEPTUAL_BLACK] of version 4 lookup tables, and
+    /// pipelines, is mapped to the zero black of other profiles, and the reverse.
+    pub fn new(src: &Profile, dst: &Profile, intent: RenderingIntent) -> Result<Self> {
+        let (src_white, dst_white) = (media_white_point(src), media_white_point(dst));
+        let round_trip = DeviceTransform::new(dst, intent, true).ok();
+        let gamut = DeviceTransform::gamut(dst);
+        let (src_transform, dst_transform) = (DeviceTransform::new(src, intent, true)?, DeviceTransform::new(dst, intent, false)?);
+        let (src_black, dst_black) = (perceptual_black(src, &src_transform, intent), perceptual_black(dst, &dst_transform, intent));
+        let selection = [src_transform.selection.clone(), dst_transform.selection.clone()];
+        let mut transform = Self { src: src_transform, dst: dst_transform, round_trip, gamut, scale: [1.0; 3], offset: [0.0; 3], intent, selection };
+        if intent == RenderingIntent::AbsoluteColorimetric {
+            transform.scale = [0, 1, 2].map(|i| src_white[i] / dst_white[i]);
+        } else if src_black != dst_black {
+            transform.map_black(src_black, dst_black);
+        }
+        Ok(transform)
+    }
+
+    // Maps a source black point to a destination black point, by a linear scaling of the PCS XYZ
+    // values which keeps the PCS illuminant.
+    fn map_black(&mut self, src: [f64; 3], dst: [f64; 3]) {
+        for i in 0..3 {
+            let s = (PCS_ILLUMINANT[i] - dst[i]) / (PCS_ILLUMINANT[i] - src[i]);
+            self.offset[i] = dst[i] - src[i] * s;
+            self.scale[i] = s;
+        }
+    }
+
+    /// Black point compensation, as defined by Adobe, and the ICC: the black point of the source
+    /// profile is mapped to the black point of the destination profile, by a linear scaling of the
+    /// PCS XYZ values, which keeps the PCS illuminant, to keep shadow detail when converting to a
+    /// device with a lighter black. It is not applied for the absolute colorimetric intent. A
+    /// destination without a transform to the PCS has a black point of zero.
+    pub fn with_black_point_compensation(mut self) -> Self {
+        if self.intent == RenderingIntent::AbsoluteColorimetric {
+            return self;
+        }
+        let src = neutral_black(self.src.xyz(&self.src.black));
+        let dst = match (self.dst.matrix_trc(), &self.round_trip) {
+            (Some(m), _) => m.black_point(),
+            (None, Some(round_trip)) => neutral_black(round_trip.xyz(&round_trip.black)),
+            (None, None) => [0.0; 3],
+        };
+        self.map_black(src, dst);
+        self
+    }
+
+    /// Transform paths of the source, and destination, profiles.
+    pub fn selection(&self) -> &[PathSelection; 2] {
+        &self.selection
+    }
+
+    /// Model of the source device, for matrix/TRC transforms.
+    pub fn source(&self) -> Option<&MatrixTrc> {
+        self.src.matrix_trc()
+    }
+
+    /// Model of the destination device, for matrix/TRC transforms.
+    pub fn destination(&self) -> Option<&MatrixTrc> {
+        self.dst.matrix_trc()
+    }
+
+    /// Factors applied to the source PCS XYZ values, to get the destination PCS XYZ values, before
+    /// adding the [Transform::pcs_offset].
+    pub fn pcs_scale(&self) -> [f64; 3] {
+        self.scale
+    }
+
+    /// Offsets added to the scaled source PCS XYZ values, for black point compensation.
+    pub fn pcs_offset(&self) -> [f64; 3] {
+        self.offset
+    }
+
+    /// PCS XYZ values of source device values.
+    pub fn to_pcs(&self, device: &[f64]) -> [f64; 3] {
+        let xyz = self.src.xyz(device);
+        [0, 1, 2].map(|i| xyz[i] * self.scale[i] + self.offset[i])
+    }
+
+    /// Transforms source device values to destination device values.
+    pub fn apply(&self, device: &[f64]) -> Vec<f64> {
+        self.dst.device(self.to_pcs(device))
+    }
+
+    /// Checks if PCS XYZ values can be reproduced by the destination device, with the 'gamt' gamut
+    /// tag of the destination profile, which should give a value below one half, or, without a gamut
+    /// tag, by converting the values to destination device values, and back, which should give the
+    /// same values within [GAMUT_TOLERANCE]. Returns `None` if the destination has neither a gamut
+    /// tag, nor a transform to the PCS.
+    pub fn is_pcs_in_gamut(&self, xyz: [f64; 3]) -> Option<bool> {
+        if let Some(gamut) = &self.gamut {
+            return gamut.device(xyz).first().map(|&v| v < 0.5);
+        }
+        let back = match (self.dst.matrix_trc(), &self.round_trip) {
+            (Some(dst), _) => dst.to_xyz(&dst.from_xyz(xyz)),
+            (None, Some(round_trip)) => round_trip.xyz(&self.dst.device(xyz)),
+            (None, None) => return None,
+        };
+        Some(xyz.iter().zip(back).all(|(a, b)| (a - b).abs() <= GAMUT_TOLERANCE))
+    }
+
+    /// Checks if the color of source device values can be reproduced by the destination device, or
+    /// `None` if no gamut check is possible, as for [Transform::is_pcs_in_gamut].
+    pub fn is_in_gamut(&self, device: &[f64]) -> Option<bool> {
+        self.is_pcs_in_gamut(self.to_pcs(device))
+    }
+
+    /// Gamut check for a buffer of source device values, such as the pixels of an image, with the
+    /// values of each color in sequence; a trailing incomplete color is ignored. Returns `None` if no
+    /// gamut check is possible, or if the source has no device channels.
+    pub fn gamut_mask(&self, values: &[f64]) -> Option<Vec<bool>> {
+        if self.src.channels() == 0 {
+            return None;
+        }
+        values.chunks_exact(self.src.channels()).map(|device| self.is_in_gamut(device)).collect()
+    }
+}
+
+const DTOB: [TagSignature; 4] = [TagSignature::DToB0Tag, TagSignature::DToB1Tag, TagSignature::DToB2Tag, TagSignature::DToB3Tag];
+const BTOD: [TagSignature; 4] = [TagSignature::BToD0Tag, TagSignature::BToD1Tag, TagSignature::BToD2Tag, TagSignature::BToD3Tag];
+
+// Media white point of a profile, or the PCS illuminant.
+fn media_white_point(profile: &Profile) -> [f64; 3] {
+    match profile.tag_data(&TagSignature::MediaWhitePointTag) {
+        Some(TagData::XYZ(XYZ(v))) if !v.is_empty() => v[0],
+        _ => PCS_ILLUMINANT,
+    }
+}
+
+// Neutral black point, with the lightness of PCS XYZ values, or zero if its lightness L* exceeds 50.
+fn neutral_black(xyz: [f64; 3]) -> [f64; 3] {
+    let [l, _, _] = xyz_to_lab(xyz);
+    if l > 50.0 {
+        [0.0; 3]
+    } else {
+        lab_to_xyz([l, 0.0, 0.0])
+    }
+}
+
+/// Transform through the floating point multi process element pipelines of a source profile's
+/// 'D2Bx' tag, and a destination profile's 'B2Dx' tag, for the rendering intent, or for the
+/// perceptual intent if not present; both profiles should have the same PCS.
+#[derive(Debug, Clone)]
+pub struct PipelineTransform {
+    to_pcs: MultiProcessElements,
+    from_pcs: MultiProcessElements,
+}
+
+impl PipelineTransform {
+    pub fn new(src: &Profile, dst: &Profile, intent: RenderingIntent) -> Result<Self> {
+        if src.pcs.map(|p| p.signature()) != dst.pcs.map(|p| p.signature()) {
+            return Err("profiles with different profile connection spaces".into());
+        }
+        let pipeline = |profile: &Profile, signatures: [TagSignature; 4]| -> Result<MultiProcessElements> {
+            let tag = |signature: &TagSignature| profile.tag_data(signature);
+            match tag(&signatures[intent as usize]).or_else(|| tag(&signatures[0])) {
+                Some(TagData::MultiProcessElements(m)) => Ok(m.clone()),
+                _ => Err(format!("profile without a {:?} multi process element tag", signatures[intent as usize]).into()),
+            }
+        };
+        let to_pcs = pipeline(src, DTOB)?;
+        let from_pcs = pipeline(dst, BTOD)?;
+        if to_pcs.outputs != from_pcs.inputs {
+            return Err("the pipelines of the profiles have a different number of PCS channels".into());
+        }
+        Ok(Self { to_pcs, from_pcs })
+    }
+
+    /// PCS values of source device values.
+    pub fn to_pcs(&self, device: &[f64]) -> Vec<f64> {
+        let input: Vec<f32> = device.iter().map(|&v| v as f32).collect();
+        self.to_pcs.eval(&input).into_iter().map(f64::from).collect()
+    }
+
+    /// Destination device values of PCS values.
+    pub fn to_device(&self, pcs: &[f64]) -> Vec<f64> {
+        let input: Vec<f32> = pcs.iter().map(|&v| v as f32).collect();
+        self.from_pcs.eval(&input).into_iter().map(f64::from).collect()
+    }
+
+    /// Transforms source device values to destination device values.
+    pub fn apply(&self, device: &[f64]) -> Vec<f64> {
+        self.to_device(&self.to_pcs(device))
+    }
+}
+
+/// Black point of the perceptual, and saturation, intents of version 4 profiles with lookup tables.
+pub const PERCEPTUAL_BLACK: [f64; 3] = [0.00336, 0.0034731, 0.00287];
+
+/// Black point of a profile for a rendering intent, as PCS XYZ values, for the transform to the PCS
+/// used by a [Transform]: the [PERCEPTUAL_BLACK] of the perceptual reference medium gamut for the
+/// lookup tables, or pipelines, of version 4 profiles, for the perceptual, and saturation, intents,
+/// and the [DeviceTransform::black_point] otherwise.
+pub fn black_point(profile: &Profile, intent: RenderingIntent) -> Result<[f64; 3]> {
+    let perceptual = matches!(intent, RenderingIntent::Perceptual | RenderingIntent::Saturation);
+    let transform = DeviceTransform::new(profile, intent, true)?;
+    match transform.selection().used {
+        TransformPath::Lut(_) | TransformPath::Pipeline(_) if profile.version[0] >= 4 && perceptual => Ok(PERCEPTUAL_BLACK),
+        _ => Ok(neutral_black(transform.xyz(&transform.black))),
+    }
+}
+
+// Black of the PCS values of a profile, for the perceptual, and saturation, intents: the
+// perceptual reference medium black for the lookup tables, and pipelines, of version 4 profiles,
+// and zero otherwise, as used to map the perceptual reference medium gamut of version 4 profiles to
+// the PCS values of other profiles.
+fn perceptual_black(profile: &Profile, transform: &DeviceTransform, intent: RenderingIntent) -> [f64; 3] {
+    let perceptual = matches!(intent, RenderingIntent::Perceptual | RenderingIntent::Saturation);
+    match transform.selection.used {
+        TransformPath::Lut(_) | TransformPath::Pipeline(_) if profile.version[0] >= 4 && perceptual => PERCEPTUAL_BLACK,
+        _ => [0.0; 3],
+    }
+}
+
+/// Largest difference of XYZ values, relative to a white luminance of 1.0, in a round trip through
+/// the destination device values, for colors within the destination gamut.
+pub const GAMUT_TOLERANCE: f64 = 1E-3;
+
+#[test]
+fn test_transform() {
+    use crate::profile::standards;
+    let t = Transform::new(&standards::srgb(), &standards::srgb(), RenderingIntent::Perceptual).unwrap();
+    for rgb in [[0.0, 0.0, 0.0], [1.0, 1.0, 1.0], [0.2, 0.5, 0.8]] {
+        assert!(t.apply(&rgb).iter().zip(rgb).all(|(a, b)| (a - b).abs() < 1E-4), "{rgb:?}");
+    }
+    // white maps to white, and gray to gray, between gray and RGB profiles
+    let t = Transform::new(&standards::gray_gamma22(), &standards::adobe_rgb(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert!(t.apply(&[1.0]).iter().all(|v| (v - 1.0).abs() < 1E-3), "{:?}", t.apply(&[1.0]));
+    let rgb = t.apply(&[0.5]);
+    assert!(rgb.iter().all(|v| (v - rgb[0]).abs() < 1E-3 && (v - 0.5).abs() < 0.01), "{rgb:?}");
+
+    // saturated Display P3 green is clipped in sRGB
+    let t = Transform::new(&standards::display_p3(), &standards::srgb(), RenderingIntent::Perceptual).unwrap();
+    let rgb = t.apply(&[0.0, 1.0, 0.0]);
+    assert!(rgb[0] == 0.0 && rgb[1] == 1.0, "{rgb:?}");
+
+    let mask = t.gamut_mask(&[0.0, 1.0, 0.0, 0.5, 0.5, 0.5, 1.0, 1.0]);
+    assert_eq!(mask, Some(vec![false, true]));
+    assert_eq!(t.is_in_gamut(&[1.0, 1.0, 1.0]), Some(true));
+    assert!(Transform::new(&crate::profile::Profile::default(), &standards::srgb(), RenderingIntent::Perceptual).is_err());
+}
+
+#[test]
+fn test_transform_selection() {
+    use crate::profile::standards;
+    let srgb = standards::srgb();
+    assert_eq!(TransformPath::select(&srgb, RenderingIntent::Perceptual, true), Some(TransformPath::MatrixTrc));
+    let lut = standards::srgb().with_tag(TagSignature::AToB0Tag, TagData::LutAToB(Vec::new()));
+    assert_eq!(TransformPath::select(&lut, RenderingIntent::Saturation, true), Some(TransformPath::Lut(TagSignature::AToB0Tag)));
+    assert_eq!(TransformPath::select(&lut, RenderingIntent::Saturation, false), Some(TransformPath::MatrixTrc));
+    assert_eq!(TransformPath::select(&standards::lab_d50(), RenderingIntent::AbsoluteColorimetric, false), Some(TransformPath::Lut(TagSignature::BToA0Tag)));
+    assert!(TransformPath::select(&Profile::default(), RenderingIntent::Perceptual, true).is_none());
+
+    // a lookup table which can not be decoded is replaced by the matrix/TRC tags
+    let t = Transform::new(&lut, &srgb, RenderingIntent::Perceptual).unwrap();
+    assert_eq!(t.selection()[0], PathSelection { preferred: TransformPath::Lut(TagSignature::AToB0Tag), used: TransformPath::MatrixTrc });
+    assert!(t.apply(&[0.2, 0.5, 0.8]).iter().zip([0.2, 0.5, 0.8]).all(|(a, b)| (a - b).abs() < 1E-4));
+    let t = Transform::new(&srgb, &lut, RenderingIntent::Perceptual).unwrap();
+    assert_eq!(t.selection()[1], PathSelection { preferred: TransformPath::MatrixTrc, used: TransformPath::MatrixTrc });
+    assert!(Transform::new(&Profile::default().with_tag(TagSignature::AToB0Tag, TagData::LutAToB(Vec::new())), &srgb, RenderingIntent::Perceptual).is_err());
+
+    // a lookup table sampled from the matrix/TRC tags, with u1Fixed15Number encoded XYZ values
+    let model = MatrixTrc::from_profile(&srgb).unwrap();
+    let clut = crate::tags::lut::ClutBuilder::new(3, 3, 17).fill(|rgb| xyz_to_normalized(model.to_xyz(rgb)).to_vec()).unwrap();
+    let curves = || vec![TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new()))];
+    let atob = crate::tags::lut::lut_atob(curves(), clut, Vec::new(), None, curves()).unwrap();
+    let sampled = Profile::from_buffer(&standards::srgb().with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob)).to_buffer().unwrap()).unwrap();
+    let t = Transform::new(&sampled, &srgb, RenderingIntent::Perceptual).unwrap();
+    assert_eq!(t.selection()[0].used, TransformPath::Lut(TagSignature::AToB0Tag));
+    assert!(t.source().is_none());
+    for rgb in [[0.0, 0.0, 0.0], [1.0, 1.0, 1.0], [0.2, 0.5, 0.8]] {
+        assert!(t.apply(&rgb).iter().zip(rgb).all(|(a, b)| (a - b).abs() < 0.01), "{:?}", t.apply(&rgb));
+    }
+    // CIELAB values, in the version 4 encoding, of an identity Lab profile
+    let t = Transform::new(&standards::lab_d50(), &srgb, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert!(t.apply(&lab_to_normalized([100.0, 0.0, 0.0])).iter().all(|v| (v - 1.0).abs() < 1E-3), "{:?}", t.apply(&lab_to_normalized([100.0, 0.0, 0.0])));
+    let t = Transform::new(&srgb, &standards::lab_d50(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    let lab = normalized_to_lab([0, 1, 2].map(|i| t.apply(&[1.0, 0.0, 0.0])[i]));
+    assert!((lab[0] - 54.3).abs() < 0.5 && lab[1] > 75.0, "{lab:?}");
+
+    // a destination with a lighter black
+    let mut lighter = standards::srgb();
+    for signature in [TagSignature::RedTRCTag, TagSignature::GreenTRCTag, TagSignature::BlueTRCTag] {
+        lighter = lighter.with_tag(signature, TagData::ParametricCurve(ParametricCurve::IEC61966_3 { g: 2.2, a: 1.0, b: 0.0, c: 0.02 }));
+    }
+    let t = Transform::new(&srgb, &lighter, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    let (clipped, compensated) = (t.apply(&[0.05; 3]), t.clone().with_black_point_compensation().apply(&[0.05; 3]));
+    assert!(clipped[0] == 0.0 && compensated[0] > 0.0, "{clipped:?} {compensated:?}");
+    assert!(t.clone().with_black_point_compensation().apply(&[1.0; 3]).iter().all(|v| (v - 1.0).abs() < 1E-2));
+    // the black points map to each other, and are neutral
+    let (src, dst) = (black_point(&srgb, RenderingIntent::Perceptual).unwrap(), black_point(&lighter, RenderingIntent::Perceptual).unwrap());
+    assert_eq!(src, [0.0; 3]);
+    let bpc = t.with_black_point_compensation();
+    assert!(bpc.to_pcs(&[0.0; 3]).iter().zip(dst).all(|(a, b)| (a - b).abs() < 1E-9));
+    assert!((dst[0] / dst[1] - PCS_ILLUMINANT[0]).abs() < 1E-9);
+    assert_eq!(black_point(&crate::profile::standards::lab_d50(), RenderingIntent::Saturation).unwrap(), PERCEPTUAL_BLACK);
+    assert_eq!(black_point(&crate::profile::standards::lab_d50(), RenderingIntent::MediaRelativeColorimetric).unwrap(), [0.0; 3]);
+}
+
+#[test]
+fn test_pipeline_transform() {
+    use crate::profile::Class;
+    use crate::signatures::colorspace::ColorSpaceSignature;
+    use crate::tags::multi_process_elements::ProcessElement;
+    let matrix = |m: Vec<f32>| MultiProcessElements::new(3, 3).with_element(ProcessElement::Matrix { inputs: 3, outputs: 3, matrix: m, offsets: vec![0.0; 3] });
+    let mut profile = Profile::new([5, 0, 0], Class::ColorSpace);
+    profile.pcs = Some(crate::profile::ColorSpace::new(ColorSpaceSignature::XYZ));
+    let profile = profile
+        .with_tag(TagSignature::DToB0Tag, TagData::MultiProcessElements(matrix(vec![0.5, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0])))
+        .with_tag(TagSignature::BToD0Tag, TagData::MultiProcessElements(matrix(vec![2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.5])));
+    let profile = Profile::from_buffer(&profile.to_buffer().unwrap()).unwrap();
+    let t = PipelineTransform::new(&profile, &profile, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert_eq!(t.to_pcs(&[1.0, 1.0, 1.0]), [0.5, 1.0, 2.0]);
+    assert_eq!(t.apply(&[0.25, 0.5, 0.75]), [0.25, 0.5, 0.75]);
+    assert!(PipelineTransform::new(&crate::profile::standards::srgb(), &profile, RenderingIntent::Perceptual).is_err());
+
+    // a transform uses the pipelines, also with matrix/TRC tags, which are ignored
+    let profile = profile.with_tag(TagSignature::RedMatrixColumnTag, TagData::XYZ(XYZ(vec![[1.0, 0.0, 0.0]])));
+    let t = Transform::new(&profile, &profile, RenderingIntent::Saturation).unwrap();
+    assert_eq!(t.selection()[0], PathSelection { preferred: TransformPath::Pipeline(TagSignature::DToB0Tag), used: TransformPath::Pipeline(TagSignature::DToB0Tag) });
+    assert_eq!(t.selection()[1].used, TransformPath::Pipeline(TagSignature::BToD0Tag));
+    assert_eq!(t.to_pcs(&[1.0, 1.0, 1.0]), [0.5, 1.0, 2.0]);
+    assert!(t.apply(&[0.25, 0.5, 0.75]).iter().zip([0.25, 0.5, 0.75]).all(|(a, b)| (a - b).abs() < 1E-6));
+    assert!(t.source().is_none() && t.is_in_gamut(&[0.2, 0.4, 0.6]) == Some(true));
+    // each profile uses its own transform
+    let t = Transform::new(&profile, &crate::profile::standards::srgb(), RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert_eq!(t.selection()[1].used, TransformPath::MatrixTrc);
+    assert_eq!(t.to_pcs(&[1.0, 1.0, 1.0]), [0.5, 1.0, 2.0]);
+}
+
+#[test]
+fn test_perceptual_black() {
+    use crate::profile::standards;
+    // a version 4 perceptual table of sRGB, scaled to the perceptual reference medium black
+    let srgb = standards::srgb();
+    let model = MatrixTrc::from_profile(&srgb).unwrap();
+    let clut = crate::tags::lut::ClutBuilder::new(3, 3, 17)
+        .fill(|rgb| {
+            let xyz = model.to_xyz(rgb);
+            xyz_to_normalized([0, 1, 2].map(|i| PERCEPTUAL_BLACK[i] + xyz[i] * (1.0 - PERCEPTUAL_BLACK[i] / PCS_ILLUMINANT[i]))).to_vec()
+        })
+        .unwrap();
+    let curves = || vec![TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new())), TagData::Curve(Curve(Vec::new()))];
+    let atob = crate::tags::lut::lut_atob(curves(), clut, Vec::new(), None, curves()).unwrap();
+    let mut prmg = standards::srgb().with_tag(TagSignature::AToB0Tag, TagData::LutAToB(atob));
+    prmg.version = [4, 0x30, 0];
+    let t = Transform::new(&prmg, &srgb, RenderingIntent::Perceptual).unwrap();
+    assert_eq!(t.selection()[0].used, TransformPath::Lut(TagSignature::AToB0Tag));
+    assert!(t.apply(&[0.0; 3]).iter().all(|v| v.abs() < 0.01), "{:?}", t.apply(&[0.0; 3]));
+    assert!(t.apply(&[1.0; 3]).iter().all(|v| (v - 1.0).abs() < 0.01), "{:?}", t.apply(&[1.0; 3]));
+    // the colorimetric intents use the tables as they are
+    let t = Transform::new(&prmg, &srgb, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert!(t.apply(&[0.0; 3]).iter().all(|v| *v > 0.01), "{:?}", t.apply(&[0.0; 3]));
+}
+
+#[test]
+fn test_gamut_tag() {
+    use crate::profile::standards;
+    use crate::tags::lut::{lut_btoa, ClutBuilder};
+    // a gamut tag of sRGB, for a CIELAB profile, which has all colors in gamut without it
+    let to_rgb = inverse(MatrixTrc::from_profile(&standards::srgb()).unwrap().matrix);
+    let clut = ClutBuilder::new(3, 1, 33)
+        .fill(|v| {
+            let rgb = mul_vec(to_rgb, lab_to_xyz(normalized_to_lab([v[0], v[1], v[2]])));
+            vec![if rgb.iter().all(|c| (-1E-3..=1.001).contains(c)) { 0.0 } else { 1.0 }]
+        })
+        .unwrap();
+    let curves = |n: usize| (0..n).map(|_| TagData::Curve(Curve(Vec::new()))).collect::<Vec<_>>();
+    let gamt = lut_btoa(curves(3), None, Vec::new(), clut, curves(1)).unwrap();
+    let lab = standards::lab_d50();
+    let t = Transform::new(&standards::display_p3(), &lab, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert_eq!(t.gamut_mask(&[0.0, 1.0, 0.0, 0.5, 0.5, 0.5]), Some(vec![true, true]));
+    let with_gamut = standards::lab_d50().with_tag(TagSignature::GamutTag, TagData::LutBToA(gamt));
+    let t = Transform::new(&standards::display_p3(), &with_gamut, RenderingIntent::MediaRelativeColorimetric).unwrap();
+    assert_eq!(t.gamut_mask(&[0.0, 1.0, 0.0, 0.5, 0.5, 0.5]), Some(vec![false, true]));
+
+    // no gamut check without a gamut tag, or a transform to the PCS
+    let t = Transform::new(&standards::srgb(), &lab.without_tag(TagSignature::AToB0Tag), RenderingIntent::Perceptual).unwrap();
+    assert_eq!(t.is_in_gamut(&[0.5; 3]), None);
+}
+
+#[test]
+fn test_device_transform_direction() {
+    use crate::profile::standards;
+    use crate::tags::lut::{lut_btoa, ClutBuilder};
+    // a gray output table, from PCS values to a single device channel
+    let clut = ClutBuilder::new(3, 1, 9).fill(|v| vec![v[0]]).unwrap();
+    let curves = |n: usize| (0..n).map(|_| TagData::Curve(Curve(Vec::new()))).collect::<Vec<_>>();
+    let btoa = lut_btoa(curves(3), None, Vec::new(), clut, curves(1)).unwrap();
+    let gray = standards::gray_gamma22().with_tag(TagSignature::BToA0Tag, TagData::LutBToA(btoa));
+    let t = DeviceTransform::new(&gray, RenderingIntent::Perceptual, false).unwrap();
+    assert!(!t.is_to_pcs() && t.channels() == 1);
+    assert_eq!((t.to_xyz(&[0.5]), t.black_point()), (None, None));
+    assert_eq!(t.to_device(PCS_ILLUMINANT).map(|v| v.len()), Some(1));
+
+    let t = DeviceTransform::new(&gray, RenderingIntent::Perceptual, true).unwrap();
+    assert!(t.is_to_pcs() && t.to_device(PCS_ILLUMINANT).is_none());
+    assert!(t.to_xyz(&[0.5, 0.5]).is_none());
+    assert!((t.to_xyz(&[1.0]).unwrap()[1] - 1.0).abs() < 1E-6);
+    assert_eq!(t.black_point(), Some([0.0; 3]));
+}